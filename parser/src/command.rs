@@ -4,18 +4,28 @@ use crate::token::Tokenizer;
 use regex::Regex;
 
 pub mod assign;
+pub mod blocked_on;
 pub mod close;
 pub mod concern;
+pub mod defer;
+pub mod escalate;
+pub mod hold;
 pub mod lock;
+pub mod mcp_comment;
 pub mod merge;
+pub mod milestone;
+pub mod needs_repro;
 pub mod nominate;
 pub mod note;
 pub mod ping;
 pub mod prioritize;
+pub mod priority;
 pub mod relabel;
 pub mod second;
 pub mod shortcut;
+pub mod tracked_by;
 pub mod transfer;
+pub mod watch_label;
 
 #[derive(Debug, PartialEq)]
 pub enum Command<'a> {
@@ -32,6 +42,16 @@ pub enum Command<'a> {
     Concern(Result<concern::ConcernCommand, Error<'a>>),
     Transfer(Result<transfer::TransferCommand, Error<'a>>),
     Merge(Result<merge::MergeCommand, Error<'a>>),
+    BlockedOn(Result<blocked_on::BlockedOnCommand, Error<'a>>),
+    WatchLabel(Result<watch_label::WatchLabelCommand, Error<'a>>),
+    Priority(Result<priority::PriorityCommand, Error<'a>>),
+    Hold(Result<hold::HoldCommand, Error<'a>>),
+    Milestone(Result<milestone::MilestoneCommand, Error<'a>>),
+    Escalate(Result<escalate::EscalateCommand, Error<'a>>),
+    McpComment(Result<mcp_comment::McpCommentCommand, Error<'a>>),
+    TrackedBy(Result<tracked_by::TrackedByCommand, Error<'a>>),
+    NeedsRepro(Result<needs_repro::NeedsReproCommand, Error<'a>>),
+    Defer(Result<defer::DeferCommand, Error<'a>>),
 }
 
 #[derive(Debug)]
@@ -65,8 +85,12 @@ where
 }
 
 impl<'a> Input<'a> {
+    /// Parses the commands addressed to any of the `bot` usernames (e.g. `@rustbot`).
     pub fn new(input: &'a str, bot: Vec<&'a str>) -> Input<'a> {
-        let bots: Vec<_> = bot.iter().map(|bot| format!(r"(?:@{bot}\b)")).collect();
+        let bots: Vec<_> = bot
+            .iter()
+            .map(|bot| format!(r"(?:@{}\b)", regex::escape(bot)))
+            .collect();
         let bot_re = Regex::new(&format!(
             r"(?i)(?P<review>\br\?)|{bots}",
             bots = bots.join("|")
@@ -153,6 +177,56 @@ impl<'a> Input<'a> {
             Command::Merge,
             &original_tokenizer,
         ));
+        success.extend(parse_single_command(
+            blocked_on::BlockedOnCommand::parse,
+            Command::BlockedOn,
+            &original_tokenizer,
+        ));
+        success.extend(parse_single_command(
+            watch_label::WatchLabelCommand::parse,
+            Command::WatchLabel,
+            &original_tokenizer,
+        ));
+        success.extend(parse_single_command(
+            priority::PriorityCommand::parse,
+            Command::Priority,
+            &original_tokenizer,
+        ));
+        success.extend(parse_single_command(
+            hold::HoldCommand::parse,
+            Command::Hold,
+            &original_tokenizer,
+        ));
+        success.extend(parse_single_command(
+            milestone::MilestoneCommand::parse,
+            Command::Milestone,
+            &original_tokenizer,
+        ));
+        success.extend(parse_single_command(
+            escalate::EscalateCommand::parse,
+            Command::Escalate,
+            &original_tokenizer,
+        ));
+        success.extend(parse_single_command(
+            mcp_comment::McpCommentCommand::parse,
+            Command::McpComment,
+            &original_tokenizer,
+        ));
+        success.extend(parse_single_command(
+            tracked_by::TrackedByCommand::parse,
+            Command::TrackedBy,
+            &original_tokenizer,
+        ));
+        success.extend(parse_single_command(
+            needs_repro::NeedsReproCommand::parse,
+            Command::NeedsRepro,
+            &original_tokenizer,
+        ));
+        success.extend(parse_single_command(
+            defer::DeferCommand::parse,
+            Command::Defer,
+            &original_tokenizer,
+        ));
 
         assert!(
             success.len() <= 1,
@@ -230,6 +304,16 @@ impl Command<'_> {
             Command::Concern(r) => r.is_ok(),
             Command::Transfer(r) => r.is_ok(),
             Command::Merge(r) => r.is_ok(),
+            Command::BlockedOn(r) => r.is_ok(),
+            Command::WatchLabel(r) => r.is_ok(),
+            Command::Priority(r) => r.is_ok(),
+            Command::Hold(r) => r.is_ok(),
+            Command::Milestone(r) => r.is_ok(),
+            Command::Escalate(r) => r.is_ok(),
+            Command::McpComment(r) => r.is_ok(),
+            Command::TrackedBy(r) => r.is_ok(),
+            Command::NeedsRepro(r) => r.is_ok(),
+            Command::Defer(r) => r.is_ok(),
         }
     }
 
@@ -238,6 +322,37 @@ impl Command<'_> {
     }
 }
 
+impl<'a> Command<'a> {
+    /// The parse error of the command, if any.
+    pub fn error(&self) -> Option<&Error<'a>> {
+        match self {
+            Command::Relabel(r) => r.as_ref().err(),
+            Command::Assign(r) => r.as_ref().err(),
+            Command::Ping(r) => r.as_ref().err(),
+            Command::Nominate(r) => r.as_ref().err(),
+            Command::Prioritize(r) => r.as_ref().err(),
+            Command::Second(r) => r.as_ref().err(),
+            Command::Shortcut(r) => r.as_ref().err(),
+            Command::Lock(r) => r.as_ref().err(),
+            Command::Close(r) => r.as_ref().err(),
+            Command::Note(r) => r.as_ref().err(),
+            Command::Concern(r) => r.as_ref().err(),
+            Command::Transfer(r) => r.as_ref().err(),
+            Command::Merge(r) => r.as_ref().err(),
+            Command::BlockedOn(r) => r.as_ref().err(),
+            Command::WatchLabel(r) => r.as_ref().err(),
+            Command::Priority(r) => r.as_ref().err(),
+            Command::Hold(r) => r.as_ref().err(),
+            Command::Milestone(r) => r.as_ref().err(),
+            Command::Escalate(r) => r.as_ref().err(),
+            Command::McpComment(r) => r.as_ref().err(),
+            Command::TrackedBy(r) => r.as_ref().err(),
+            Command::NeedsRepro(r) => r.as_ref().err(),
+            Command::Defer(r) => r.as_ref().err(),
+        }
+    }
+}
+
 #[test]
 fn errors_outside_command_are_fine() {
     let input = "haha\" unterminated quotes @bot labels +bug. Terminating after the command";
@@ -344,6 +459,83 @@ fn multiname() {
     assert!(input.next().is_none());
 }
 
+#[test]
+fn custom_username() {
+    let input = "@rustbot label +bug. @self-hosted-botanist claim. @Self-Hosted-Bot label +A-docs.";
+    let mut input = Input::new(input, vec!["self-hosted-bot"]);
+    assert_eq!(
+        input.next(),
+        Some(Command::Relabel(Ok(relabel::RelabelCommand::from_deltas(
+            vec![relabel::LabelDelta::Add(relabel::Label("A-docs".into()))]
+        ))))
+    );
+    assert_eq!(input.next(), None);
+}
+
+#[test]
+fn custom_username_is_not_a_pattern() {
+    let input = "@myxbot claim. @my.bot claim.";
+    let mut input = Input::new(input, vec!["my.bot"]);
+    assert!(matches!(input.next(), Some(Command::Assign(Ok(_)))));
+    assert_eq!(input.next(), None);
+}
+
+#[test]
+fn malformed_command_does_not_swallow_following_one() {
+    // The stray quote must not run across lines and turn the following
+    // commands into a label name.
+    let input = "@bot labels \"+bug\n@bot claim\n@bot labels: +T-lang. \"";
+    let mut input = Input::new(input, vec!["bot"]);
+    assert!(matches!(input.next(), Some(Command::Relabel(Err(_)))));
+    assert_eq!(
+        input.next(),
+        Some(Command::Assign(Ok(assign::AssignCommand::Claim)))
+    );
+    assert_eq!(
+        input.next(),
+        Some(Command::Relabel(Ok(relabel::RelabelCommand::from_deltas(
+            vec![relabel::LabelDelta::Add(relabel::Label("T-lang".into()))]
+        ))))
+    );
+    assert_eq!(input.next(), None);
+}
+
+#[test]
+fn malformed_middle_command() {
+    let input = "@bot label +bug.\n@bot assign octocat\n@bot unclaim";
+    let mut input = Input::new(input, vec!["bot"]);
+    assert!(matches!(input.next(), Some(Command::Relabel(Ok(_)))));
+    assert!(matches!(input.next(), Some(Command::Assign(Err(_)))));
+    assert_eq!(
+        input.next(),
+        Some(Command::Assign(Ok(
+            assign::AssignCommand::ReleaseAssignment
+        )))
+    );
+    assert_eq!(input.next(), None);
+}
+
+#[test]
+fn line_commands_consume_their_line() {
+    // The concern title takes the whole line, so the mention inside of it must
+    // not be parsed as another command.
+    let input = "@bot concern we should not @bot claim this\n@bot note summary";
+    let mut input = Input::new(input, vec!["bot"]);
+    assert_eq!(
+        input.next(),
+        Some(Command::Concern(Ok(concern::ConcernCommand::Concern {
+            title: "we should not @bot claim this".to_string()
+        })))
+    );
+    assert_eq!(
+        input.next(),
+        Some(Command::Note(Ok(note::NoteCommand::Summary {
+            title: "summary".to_string()
+        })))
+    );
+    assert_eq!(input.next(), None);
+}
+
 #[test]
 fn review_commands() {
     for (input, name) in [
@@ -519,3 +711,90 @@ fn delegate_with_at() {
         })))
     );
 }
+
+#[test]
+fn blocked_on() {
+    let input = "@bot blocked-on #1234";
+    let mut input = Input::new(input, vec!["bot"]);
+    assert_eq!(
+        input.next(),
+        Some(Command::BlockedOn(Ok(blocked_on::BlockedOnCommand(1234))))
+    );
+}
+
+#[test]
+fn priority() {
+    let input = "@bot priority high";
+    let mut input = Input::new(input, vec!["bot"]);
+    assert_eq!(
+        input.next(),
+        Some(Command::Priority(Ok(priority::PriorityCommand {
+            level: "high".to_string()
+        })))
+    );
+}
+
+#[test]
+fn hold() {
+    let input = "@bot hold";
+    let mut input = Input::new(input, vec!["bot"]);
+    assert_eq!(
+        input.next(),
+        Some(Command::Hold(Ok(hold::HoldCommand::Hold)))
+    );
+}
+
+#[test]
+fn escalate() {
+    let input = r#"@bot escalate T-compiler "miscompilation""#;
+    let mut input = Input::new(input, vec!["bot"]);
+    assert_eq!(
+        input.next(),
+        Some(Command::Escalate(Ok(escalate::EscalateCommand {
+            team: "T-compiler".to_string(),
+            reason: "miscompilation".to_string(),
+        })))
+    );
+}
+
+#[test]
+fn mcp_comment() {
+    let input = "@bot mcp-comment";
+    let mut input = Input::new(input, vec!["bot"]);
+    assert_eq!(
+        input.next(),
+        Some(Command::McpComment(Ok(mcp_comment::McpCommentCommand)))
+    );
+}
+
+#[test]
+fn tracked_by() {
+    let input = "@bot tracked-by #1234";
+    let mut input = Input::new(input, vec!["bot"]);
+    assert_eq!(
+        input.next(),
+        Some(Command::TrackedBy(Ok(tracked_by::TrackedByCommand(1234))))
+    );
+}
+
+#[test]
+fn needs_repro() {
+    let input = "@bot needs-repro";
+    let mut input = Input::new(input, vec!["bot"]);
+    assert_eq!(
+        input.next(),
+        Some(Command::NeedsRepro(Ok(needs_repro::NeedsReproCommand)))
+    );
+}
+
+#[test]
+fn defer() {
+    let input = "@bot defer 3d";
+    let mut input = Input::new(input, vec!["bot"]);
+    assert_eq!(
+        input.next(),
+        Some(Command::Defer(Ok(defer::DeferCommand(
+            std::time::Duration::from_secs(3 * 24 * 60 * 60)
+        ))))
+    );
+}
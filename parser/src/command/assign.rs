@@ -5,7 +5,8 @@
 //! The grammar is as follows:
 //!
 //! ```text
-//! Command: `@bot claim`, `@bot release-assignment`, or `@bot assign @user`.
+//! Command: `@bot claim`, `@bot release-assignment`, `@bot release`, `@bot assign @user`,
+//! `@bot reroll`, or `@bot re-review`.
 //! ```
 
 use crate::error::Error;
@@ -16,7 +17,7 @@ use std::fmt;
 pub enum AssignCommand {
     /// Corresponds to `@bot claim`.
     Claim,
-    /// Corresponds to `@bot release-assignment` or `@bot unclaim`.
+    /// Corresponds to `@bot release-assignment`, `@bot release` or `@bot unclaim`.
     ReleaseAssignment,
     /// Corresponds to `@bot assign @user`.
     AssignUser { username: String },
@@ -24,6 +25,8 @@ pub enum AssignCommand {
     RequestReview { name: String },
     /// Corresponds to `@bot reroll`.
     Reroll,
+    /// Corresponds to `@bot re-review`.
+    ReReview,
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -73,7 +76,9 @@ impl AssignCommand {
             } else {
                 Err(toks.error(ParseError::NoUser))
             }
-        } else if let Some(Token::Word("release-assignment" | "unclaim")) = toks.peek_token()? {
+        } else if let Some(Token::Word("release-assignment" | "release" | "unclaim")) =
+            toks.peek_token()?
+        {
             toks.next_token()?;
             if let Some(Token::Dot | Token::EndOfLine) = toks.peek_token()? {
                 toks.next_token()?;
@@ -87,6 +92,13 @@ impl AssignCommand {
             }
             *input = toks;
             Ok(Some(AssignCommand::Reroll))
+        } else if let Some(Token::Word("re-review")) = toks.peek_token()? {
+            toks.next_token()?;
+            if let Some(Token::Dot | Token::EndOfLine) = toks.peek_token()? {
+                toks.next_token()?;
+            }
+            *input = toks;
+            Ok(Some(AssignCommand::ReReview))
         } else {
             Ok(None)
         }
@@ -154,6 +166,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn re_review() {
+        assert_eq!(parse("re-review"), Ok(Some(AssignCommand::ReReview)));
+        assert_eq!(parse("re-review."), Ok(Some(AssignCommand::ReReview)));
+        assert_eq!(parse("review"), Ok(None));
+    }
+
     fn parse_review<'a>(input: &'a str) -> Result<Option<AssignCommand>, Error<'a>> {
         let mut toks = Tokenizer::new(input);
         Ok(AssignCommand::parse_review(&mut toks)?)
@@ -203,4 +222,13 @@ mod tests {
     fn unclaim() {
         assert_eq!(parse("unclaim"), Ok(Some(AssignCommand::ReleaseAssignment)));
     }
+
+    #[test]
+    fn release() {
+        assert_eq!(parse("release"), Ok(Some(AssignCommand::ReleaseAssignment)));
+        assert_eq!(
+            parse("release-assignment"),
+            Ok(Some(AssignCommand::ReleaseAssignment))
+        );
+    }
 }
@@ -0,0 +1,92 @@
+//! Parses the `@bot blocked-on #123` command.
+
+use crate::error::Error;
+use crate::token::{Token, Tokenizer};
+use std::fmt;
+
+/// Corresponds to `@bot blocked-on #123`, with the number of the blocking issue.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BlockedOnCommand(pub u64);
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum ParseError {
+    MissingIssue,
+    InvalidIssue,
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingIssue => write!(f, "missing the blocking issue"),
+            ParseError::InvalidIssue => write!(f, "the blocking issue should be written as `#123`"),
+        }
+    }
+}
+
+impl BlockedOnCommand {
+    pub fn parse<'a>(input: &mut Tokenizer<'a>) -> Result<Option<Self>, Error<'a>> {
+        let mut toks = input.clone();
+        if !matches!(toks.peek_token()?, Some(Token::Word("blocked-on"))) {
+            return Ok(None);
+        }
+        toks.next_token()?;
+
+        let issue = match toks.next_token()? {
+            Some(Token::Word(issue)) => issue,
+            _ => return Err(toks.error(ParseError::MissingIssue)),
+        };
+        let Some(number) = issue.strip_prefix('#').and_then(|n| n.parse().ok()) else {
+            return Err(toks.error(ParseError::InvalidIssue));
+        };
+
+        if let Some(Token::Dot | Token::EndOfLine) = toks.peek_token()? {
+            toks.next_token()?;
+        }
+        *input = toks;
+        Ok(Some(BlockedOnCommand(number)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    fn parse(input: &str) -> Result<Option<BlockedOnCommand>, Error<'_>> {
+        let mut toks = Tokenizer::new(input);
+        BlockedOnCommand::parse(&mut toks)
+    }
+
+    #[test]
+    fn blocked_on() {
+        assert_eq!(parse("blocked-on #123"), Ok(Some(BlockedOnCommand(123))));
+        assert_eq!(parse("blocked-on #123."), Ok(Some(BlockedOnCommand(123))));
+    }
+
+    #[test]
+    fn not_blocked_on() {
+        assert_eq!(parse("blocked"), Ok(None));
+    }
+
+    #[test]
+    fn missing_issue() {
+        let err = parse("blocked-on").unwrap_err();
+        assert_eq!(
+            err.source().unwrap().downcast_ref(),
+            Some(&ParseError::MissingIssue)
+        );
+    }
+
+    #[test]
+    fn invalid_issue() {
+        for input in ["blocked-on 123", "blocked-on #abc", "blocked-on #"] {
+            let err = parse(input).unwrap_err();
+            assert_eq!(
+                err.source().unwrap().downcast_ref(),
+                Some(&ParseError::InvalidIssue)
+            );
+        }
+    }
+}
@@ -47,6 +47,7 @@ impl ConcernCommand {
                 ConcernCommand::Concern { title }
             };
 
+            *input = toks;
             Ok(Some(command))
         } else {
             Ok(None)
@@ -0,0 +1,144 @@
+//! Parses the `@bot defer <duration>` command, e.g. `@bot defer 3d`.
+
+use crate::error::Error;
+use crate::token::{Token, Tokenizer};
+use std::fmt;
+use std::time::Duration;
+
+/// Corresponds to `@bot defer <duration>`, with a duration like `30m`, `12h`,
+/// `3d` or `2w`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DeferCommand(pub Duration);
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum ParseError {
+    MissingDuration,
+    InvalidDuration,
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingDuration => write!(f, "missing the duration to defer for"),
+            ParseError::InvalidDuration => write!(
+                f,
+                "the duration should be a number followed by `m`, `h`, `d` or `w`, e.g. `3d`"
+            ),
+        }
+    }
+}
+
+impl DeferCommand {
+    pub fn parse<'a>(input: &mut Tokenizer<'a>) -> Result<Option<Self>, Error<'a>> {
+        let mut toks = input.clone();
+        if !matches!(toks.peek_token()?, Some(Token::Word("defer"))) {
+            return Ok(None);
+        }
+        toks.next_token()?;
+
+        let duration = match toks.next_token()? {
+            Some(Token::Word(duration)) => duration,
+            _ => return Err(toks.error(ParseError::MissingDuration)),
+        };
+        let Some(duration) = parse_duration(duration) else {
+            return Err(toks.error(ParseError::InvalidDuration));
+        };
+
+        if let Some(Token::Dot | Token::EndOfLine) = toks.peek_token()? {
+            toks.next_token()?;
+        }
+        *input = toks;
+        Ok(Some(DeferCommand(duration)))
+    }
+}
+
+/// Parses a non-zero duration like `30m`, `12h`, `3d` or `2w`.
+pub fn parse_duration(input: &str) -> Option<Duration> {
+    let unit = input.chars().last()?;
+    let amount: u64 = input[..input.len() - unit.len_utf8()].parse().ok()?;
+    let unit_secs = match unit.to_ascii_lowercase() {
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        'w' => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    match amount.checked_mul(unit_secs)? {
+        0 => None,
+        secs => Some(Duration::from_secs(secs)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    fn parse(input: &str) -> Result<Option<DeferCommand>, Error<'_>> {
+        let mut toks = Tokenizer::new(input);
+        DeferCommand::parse(&mut toks)
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("30m"), Some(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_duration("12h"), Some(Duration::from_secs(12 * 3600)));
+        assert_eq!(parse_duration("3d"), Some(Duration::from_secs(3 * 86400)));
+        assert_eq!(parse_duration("3D"), Some(Duration::from_secs(3 * 86400)));
+        assert_eq!(parse_duration("2w"), Some(Duration::from_secs(14 * 86400)));
+    }
+
+    #[test]
+    fn invalid_durations() {
+        for input in [
+            "",
+            "d",
+            "3",
+            "0d",
+            "-3d",
+            "3y",
+            "1.5d",
+            "3dd",
+            "99999999999999999999w",
+        ] {
+            assert_eq!(parse_duration(input), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn defer() {
+        assert_eq!(
+            parse("defer 3d"),
+            Ok(Some(DeferCommand(Duration::from_secs(3 * 86400))))
+        );
+        assert_eq!(
+            parse("defer 12h."),
+            Ok(Some(DeferCommand(Duration::from_secs(12 * 3600))))
+        );
+    }
+
+    #[test]
+    fn not_defer() {
+        assert_eq!(parse("deferred"), Ok(None));
+    }
+
+    #[test]
+    fn missing_duration() {
+        let err = parse("defer").unwrap_err();
+        assert_eq!(
+            err.source().unwrap().downcast_ref(),
+            Some(&ParseError::MissingDuration)
+        );
+    }
+
+    #[test]
+    fn invalid_duration() {
+        let err = parse("defer soon").unwrap_err();
+        assert_eq!(
+            err.source().unwrap().downcast_ref(),
+            Some(&ParseError::InvalidDuration)
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Parses the `@bot escalate <team> "reason"` command.
+
+use crate::error::Error;
+use crate::token::{Token, Tokenizer};
+use std::fmt;
+
+/// Corresponds to `@bot escalate <team> "reason"`.
+#[derive(PartialEq, Eq, Debug)]
+pub struct EscalateCommand {
+    pub team: String,
+    pub reason: String,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum ParseError {
+    MissingTeam,
+    MissingReason,
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingTeam => write!(f, "missing the team to escalate to"),
+            ParseError::MissingReason => {
+                write!(f, "missing the reason of the escalation, in quotes")
+            }
+        }
+    }
+}
+
+impl EscalateCommand {
+    pub fn parse<'a>(input: &mut Tokenizer<'a>) -> Result<Option<Self>, Error<'a>> {
+        let mut toks = input.clone();
+        if !matches!(toks.peek_token()?, Some(Token::Word("escalate"))) {
+            return Ok(None);
+        }
+        toks.next_token()?;
+
+        let team = match toks.next_token()? {
+            Some(Token::Word(team)) => team.to_string(),
+            _ => return Err(toks.error(ParseError::MissingTeam)),
+        };
+        let reason = match toks.next_token()? {
+            Some(Token::Quote(reason)) if !reason.trim().is_empty() => reason.trim().to_string(),
+            _ => return Err(toks.error(ParseError::MissingReason)),
+        };
+
+        if let Some(Token::Dot | Token::EndOfLine) = toks.peek_token()? {
+            toks.next_token()?;
+        }
+        *input = toks;
+        Ok(Some(EscalateCommand { team, reason }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    fn parse(input: &str) -> Result<Option<EscalateCommand>, Error<'_>> {
+        let mut toks = Tokenizer::new(input);
+        EscalateCommand::parse(&mut toks)
+    }
+
+    #[test]
+    fn escalate() {
+        assert_eq!(
+            parse(r#"escalate T-compiler "regression in a popular crate""#),
+            Ok(Some(EscalateCommand {
+                team: "T-compiler".to_string(),
+                reason: "regression in a popular crate".to_string(),
+            }))
+        );
+    }
+
+    #[test]
+    fn not_escalate() {
+        assert_eq!(parse("escalated"), Ok(None));
+    }
+
+    #[test]
+    fn missing_reason() {
+        let err = parse("escalate T-compiler").unwrap_err();
+        assert_eq!(
+            err.source().unwrap().downcast_ref(),
+            Some(&ParseError::MissingReason)
+        );
+        let err = parse("escalate T-compiler because").unwrap_err();
+        assert_eq!(
+            err.source().unwrap().downcast_ref(),
+            Some(&ParseError::MissingReason)
+        );
+    }
+
+    #[test]
+    fn missing_team() {
+        let err = parse("escalate").unwrap_err();
+        assert_eq!(
+            err.source().unwrap().downcast_ref(),
+            Some(&ParseError::MissingTeam)
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! Parses the `@bot hold` and `@bot unhold` commands.
+//!
+//! They pause and resume the approval countdown of a seconded major change.
+
+use crate::error::Error;
+use crate::token::{Token, Tokenizer};
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum HoldCommand {
+    /// Corresponds to `@bot hold`.
+    Hold,
+    /// Corresponds to `@bot unhold`.
+    Unhold,
+}
+
+impl HoldCommand {
+    pub fn parse<'a>(input: &mut Tokenizer<'a>) -> Result<Option<Self>, Error<'a>> {
+        let mut toks = input.clone();
+        let command = match toks.peek_token()? {
+            Some(Token::Word("hold")) => HoldCommand::Hold,
+            Some(Token::Word("unhold")) => HoldCommand::Unhold,
+            _ => return Ok(None),
+        };
+        toks.next_token()?;
+
+        if let Some(Token::Dot | Token::EndOfLine) = toks.peek_token()? {
+            toks.next_token()?;
+        }
+        *input = toks;
+        Ok(Some(command))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Result<Option<HoldCommand>, Error<'_>> {
+        let mut toks = Tokenizer::new(input);
+        HoldCommand::parse(&mut toks)
+    }
+
+    #[test]
+    fn hold() {
+        assert_eq!(parse("hold"), Ok(Some(HoldCommand::Hold)));
+        assert_eq!(parse("hold."), Ok(Some(HoldCommand::Hold)));
+    }
+
+    #[test]
+    fn unhold() {
+        assert_eq!(parse("unhold"), Ok(Some(HoldCommand::Unhold)));
+    }
+
+    #[test]
+    fn other_word() {
+        assert_eq!(parse("holding"), Ok(None));
+    }
+}
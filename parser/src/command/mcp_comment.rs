@@ -0,0 +1,47 @@
+//! Parses the `@bot mcp-comment` command.
+//!
+//! It re-posts the boilerplate comment of a major change proposal.
+
+use crate::error::Error;
+use crate::token::{Token, Tokenizer};
+
+/// Corresponds to `@bot mcp-comment`.
+#[derive(PartialEq, Eq, Debug)]
+pub struct McpCommentCommand;
+
+impl McpCommentCommand {
+    pub fn parse<'a>(input: &mut Tokenizer<'a>) -> Result<Option<Self>, Error<'a>> {
+        let mut toks = input.clone();
+        if !matches!(toks.peek_token()?, Some(Token::Word("mcp-comment"))) {
+            return Ok(None);
+        }
+        toks.next_token()?;
+
+        if let Some(Token::Dot | Token::EndOfLine) = toks.peek_token()? {
+            toks.next_token()?;
+        }
+        *input = toks;
+        Ok(Some(McpCommentCommand))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Result<Option<McpCommentCommand>, Error<'_>> {
+        let mut toks = Tokenizer::new(input);
+        McpCommentCommand::parse(&mut toks)
+    }
+
+    #[test]
+    fn mcp_comment() {
+        assert_eq!(parse("mcp-comment"), Ok(Some(McpCommentCommand)));
+        assert_eq!(parse("mcp-comment."), Ok(Some(McpCommentCommand)));
+    }
+
+    #[test]
+    fn other_word() {
+        assert_eq!(parse("mcp"), Ok(None));
+    }
+}
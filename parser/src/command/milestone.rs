@@ -0,0 +1,87 @@
+//! Parses the `@bot milestone <title>` command.
+
+use crate::error::Error;
+use crate::token::{Token, Tokenizer};
+use std::fmt;
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct MilestoneCommand {
+    pub title: String,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum ParseError {
+    MissingTitle,
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingTitle => write!(f, "missing milestone title"),
+        }
+    }
+}
+
+impl MilestoneCommand {
+    pub fn parse<'a>(input: &mut Tokenizer<'a>) -> Result<Option<Self>, Error<'a>> {
+        let mut toks = input.clone();
+        if !matches!(toks.peek_token()?, Some(Token::Word("milestone"))) {
+            return Ok(None);
+        }
+        toks.next_token()?;
+
+        // Milestone titles are usually versions, which the tokenizer would split
+        // on the dots, so take the rest of the line.
+        let title = toks.take_line()?.trim().trim_matches('"').trim();
+        if title.is_empty() {
+            return Err(toks.error(ParseError::MissingTitle));
+        }
+
+        *input = toks;
+        Ok(Some(MilestoneCommand {
+            title: title.to_string(),
+        }))
+    }
+}
+
+#[cfg(test)]
+fn parse(input: &str) -> Result<Option<MilestoneCommand>, Error<'_>> {
+    let mut toks = Tokenizer::new(input);
+    MilestoneCommand::parse(&mut toks)
+}
+
+#[test]
+fn parse_version() {
+    assert_eq!(
+        parse("milestone 1.90.0"),
+        Ok(Some(MilestoneCommand {
+            title: "1.90.0".to_string()
+        }))
+    );
+    assert_eq!(
+        parse("milestone \"Rust 2027\"\nmore text"),
+        Ok(Some(MilestoneCommand {
+            title: "Rust 2027".to_string()
+        }))
+    );
+}
+
+#[test]
+fn parse_missing_title() {
+    use std::error::Error;
+    assert_eq!(
+        parse("milestone  ")
+            .unwrap_err()
+            .source()
+            .unwrap()
+            .downcast_ref(),
+        Some(&ParseError::MissingTitle),
+    );
+}
+
+#[test]
+fn parse_other_command() {
+    assert_eq!(parse("milestones 1.90.0"), Ok(None));
+}
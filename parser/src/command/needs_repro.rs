@@ -0,0 +1,47 @@
+//! Parses the `@bot needs-repro` command.
+//!
+//! It asks the author of an issue for a reproduction of the problem.
+
+use crate::error::Error;
+use crate::token::{Token, Tokenizer};
+
+/// Corresponds to `@bot needs-repro`.
+#[derive(PartialEq, Eq, Debug)]
+pub struct NeedsReproCommand;
+
+impl NeedsReproCommand {
+    pub fn parse<'a>(input: &mut Tokenizer<'a>) -> Result<Option<Self>, Error<'a>> {
+        let mut toks = input.clone();
+        if !matches!(toks.peek_token()?, Some(Token::Word("needs-repro"))) {
+            return Ok(None);
+        }
+        toks.next_token()?;
+
+        if let Some(Token::Dot | Token::EndOfLine) = toks.peek_token()? {
+            toks.next_token()?;
+        }
+        *input = toks;
+        Ok(Some(NeedsReproCommand))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Result<Option<NeedsReproCommand>, Error<'_>> {
+        let mut toks = Tokenizer::new(input);
+        NeedsReproCommand::parse(&mut toks)
+    }
+
+    #[test]
+    fn needs_repro() {
+        assert_eq!(parse("needs-repro"), Ok(Some(NeedsReproCommand)));
+        assert_eq!(parse("needs-repro."), Ok(Some(NeedsReproCommand)));
+    }
+
+    #[test]
+    fn other_word() {
+        assert_eq!(parse("needs-reproduction"), Ok(None));
+    }
+}
@@ -6,6 +6,7 @@ use std::fmt;
 pub enum NoteCommand {
     Summary { title: String },
     Remove { title: String },
+    Rename { old: String, new: String },
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -27,6 +28,27 @@ impl NoteCommand {
         if let Some(Token::Word("note")) = toks.peek_token()? {
             toks.next_token()?;
 
+            // `note rename "old" "new"`, otherwise `rename` is part of the title
+            if let Some(Token::Word("rename")) = toks.peek_token()? {
+                let mut rename_toks = toks.clone();
+                rename_toks.next_token()?;
+                if let Ok(Some(Token::Quote(old))) = rename_toks.next_token()
+                    && let Ok(Some(Token::Quote(new))) = rename_toks.next_token()
+                    && let Ok(Some(Token::Dot | Token::Semi | Token::EndOfLine)) =
+                        rename_toks.peek_token()
+                {
+                    if old.trim().is_empty() || new.trim().is_empty() {
+                        return Err(rename_toks.error(ParseError::MissingTitle));
+                    }
+                    rename_toks.take_line()?;
+                    *input = rename_toks;
+                    return Ok(Some(NoteCommand::Rename {
+                        old: old.trim().to_string(),
+                        new: new.trim().to_string(),
+                    }));
+                }
+            }
+
             let remove = if let Some(Token::Word("remove")) = toks.peek_token()? {
                 toks.next_token()?;
                 true
@@ -52,9 +74,83 @@ impl NoteCommand {
                     title: title.to_string(),
                 }
             };
+            *input = toks;
             Ok(Some(command))
         } else {
             Ok(None)
         }
     }
 }
+
+#[cfg(test)]
+fn parse(input: &str) -> Result<Option<NoteCommand>, Error<'_>> {
+    let mut toks = Tokenizer::new(input);
+    NoteCommand::parse(&mut toks)
+}
+
+#[test]
+fn parse_summary() {
+    assert_eq!(
+        parse("note \"design decision\""),
+        Ok(Some(NoteCommand::Summary {
+            title: "design decision".to_string()
+        }))
+    );
+    assert_eq!(
+        parse("note remove design"),
+        Ok(Some(NoteCommand::Remove {
+            title: "design".to_string()
+        }))
+    );
+}
+
+#[test]
+fn parse_rename() {
+    assert_eq!(
+        parse("note rename \"design\" \"design decision\""),
+        Ok(Some(NoteCommand::Rename {
+            old: "design".to_string(),
+            new: "design decision".to_string()
+        }))
+    );
+    assert_eq!(
+        parse("note rename \"design\" \"design decision\"."),
+        Ok(Some(NoteCommand::Rename {
+            old: "design".to_string(),
+            new: "design decision".to_string()
+        }))
+    );
+}
+
+#[test]
+fn parse_rename_is_a_summary_otherwise() {
+    // Without exactly two quoted titles, `rename` is just the start of the title
+    assert_eq!(
+        parse("note rename the variables"),
+        Ok(Some(NoteCommand::Summary {
+            title: "rename the variables".to_string()
+        }))
+    );
+    assert_eq!(
+        parse("note rename \"design\""),
+        Ok(Some(NoteCommand::Summary {
+            title: "rename \"design".to_string()
+        }))
+    );
+    assert_eq!(
+        parse("note rename \"a\" \"b\" \"c\""),
+        Ok(Some(NoteCommand::Summary {
+            title: "rename \"a\" \"b\" \"c".to_string()
+        }))
+    );
+}
+
+#[test]
+fn parse_rename_empty_title() {
+    use std::error::Error as _;
+    let err = parse("note rename \"design\" \" \"").unwrap_err();
+    assert_eq!(
+        err.source().unwrap().downcast_ref(),
+        Some(&ParseError::MissingTitle)
+    );
+}
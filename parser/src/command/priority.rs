@@ -0,0 +1,86 @@
+//! Parses the `@bot priority high` command.
+//!
+//! The priority level is mapped to a label by the repository configuration.
+
+use crate::error::Error;
+use crate::token::{Token, Tokenizer};
+use std::fmt;
+
+/// Corresponds to `@bot priority <level>`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PriorityCommand {
+    pub level: String,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum ParseError {
+    MissingLevel,
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingLevel => write!(f, "missing the priority level"),
+        }
+    }
+}
+
+impl PriorityCommand {
+    pub fn parse<'a>(input: &mut Tokenizer<'a>) -> Result<Option<Self>, Error<'a>> {
+        let mut toks = input.clone();
+        if !matches!(toks.peek_token()?, Some(Token::Word("priority"))) {
+            return Ok(None);
+        }
+        toks.next_token()?;
+
+        let level = match toks.next_token()? {
+            Some(Token::Word(level)) => level.to_lowercase(),
+            _ => return Err(toks.error(ParseError::MissingLevel)),
+        };
+
+        if let Some(Token::Dot | Token::EndOfLine) = toks.peek_token()? {
+            toks.next_token()?;
+        }
+        *input = toks;
+        Ok(Some(PriorityCommand { level }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    fn parse(input: &str) -> Result<Option<PriorityCommand>, Error<'_>> {
+        let mut toks = Tokenizer::new(input);
+        PriorityCommand::parse(&mut toks)
+    }
+
+    #[test]
+    fn priority() {
+        let high = PriorityCommand {
+            level: "high".to_string(),
+        };
+        assert_eq!(parse("priority high"), Ok(Some(high)));
+        let critical = PriorityCommand {
+            level: "critical".to_string(),
+        };
+        assert_eq!(parse("priority Critical."), Ok(Some(critical)));
+    }
+
+    #[test]
+    fn not_priority() {
+        assert_eq!(parse("prioritize"), Ok(None));
+    }
+
+    #[test]
+    fn missing_level() {
+        let err = parse("priority").unwrap_err();
+        assert_eq!(
+            err.source().unwrap().downcast_ref(),
+            Some(&ParseError::MissingLevel)
+        );
+    }
+}
@@ -2,6 +2,16 @@
 //!
 //! See <https://forge.rust-lang.org/triagebot/labeling.html#usage> for the
 //! grammar this parser accepts.
+//!
+//! Labels with spaces are quoted, as in `relabel +"needs design review"`.
+//!
+//! An explicitly added label can be followed by a colon and a quoted reason,
+//! as in `relabel +S-blocked: "waiting on RFC 1234"`.
+//!
+//! A `~label` delta toggles the label: it's added if missing, removed otherwise.
+//!
+//! The `--if-open` and `--if-closed` flags only apply the changes when the
+//! issue is in that state, as in `relabel --if-open +needs-triage`.
 
 use crate::error::Error;
 use crate::token::{Token, Tokenizer};
@@ -9,13 +19,43 @@ use crate::token::{Token, Tokenizer};
 use std::error::Error as _;
 use std::fmt;
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct RelabelCommand(pub Vec<LabelDelta>);
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct RelabelCommand {
+    /// The label changes to apply.
+    pub deltas: Vec<LabelDelta>,
+    /// Name of the configured preset to apply, for `relabel preset NAME`.
+    pub preset: Option<String>,
+    /// Only report the label changes, for `relabel --dry-run ...`.
+    pub dry_run: bool,
+    /// Reasons given for the added labels, for `relabel +label: "reason"`.
+    pub reasons: Vec<LabelReason>,
+    /// Number of the issue to copy the labels from, for `relabel copy-from #123`.
+    pub copy_from: Option<u64>,
+    /// Only explain whether the label changes are allowed, for `relabel explain ...`.
+    pub explain: bool,
+    /// Only apply the changes in this state, for `relabel --if-open ...`.
+    pub if_state: Option<StateCondition>,
+}
+
+/// The state an issue must be in for the label changes to be applied.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StateCondition {
+    Open,
+    Closed,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LabelReason {
+    pub label: Label,
+    pub reason: String,
+}
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum LabelDelta {
     Add(Label),
     Remove(Label),
+    /// Adds the label if missing, removes it otherwise.
+    Toggle(Label),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -26,6 +66,7 @@ pub enum ParseError {
     EmptyLabel,
     ExpectedLabelDelta,
     MisleadingTo,
+    ExpectedPresetName,
 }
 
 impl std::error::Error for ParseError {}
@@ -36,6 +77,12 @@ impl fmt::Display for ParseError {
             ParseError::EmptyLabel => write!(f, "empty label"),
             ParseError::ExpectedLabelDelta => write!(f, "a label delta"),
             ParseError::MisleadingTo => write!(f, "forbidden `to`, use `+to`"),
+            ParseError::ExpectedPresetName => {
+                write!(
+                    f,
+                    "a single preset name after `preset`, use `+preset` for the label"
+                )
+            }
         }
     }
 }
@@ -57,8 +104,37 @@ impl std::ops::Deref for Label {
     }
 }
 
+impl std::str::FromStr for LabelDelta {
+    type Err = ParseError;
+
+    /// Parses a single delta, like `+label`, `-label`, `~label` or `label`.
+    fn from_str(delta: &str) -> Result<LabelDelta, ParseError> {
+        if let Some(label) = delta.strip_prefix('+') {
+            Ok(LabelDelta::Add(Label::parse(label)?))
+        } else if let Some(label) = delta.strip_prefix('-') {
+            Ok(LabelDelta::Remove(Label::parse(label)?))
+        } else if let Some(label) = delta.strip_prefix('~') {
+            Ok(LabelDelta::Toggle(Label::parse(label)?))
+        } else {
+            Ok(LabelDelta::Add(Label::parse(delta)?))
+        }
+    }
+}
+
 impl LabelDelta {
     fn parse<'a>(input: &mut Tokenizer<'a>) -> Result<LabelDelta, Error<'a>> {
+        // a quoted label after its prefix, like `+"needs design review"`
+        if let Some(Token::Word(prefix @ ("+" | "-" | "~"))) = input.peek_token()? {
+            let mut quoted = input.clone();
+            quoted.next_token()?;
+            if let Some(Token::Quote(label)) = quoted.next_token()? {
+                *input = quoted;
+                return format!("{prefix}{label}")
+                    .parse()
+                    .map_err(|e| input.error(e));
+            }
+        }
+
         let delta = match input.peek_token()? {
             Some(Token::Word(delta) | Token::Quote(delta)) => {
                 input.next_token()?;
@@ -68,24 +144,12 @@ impl LabelDelta {
                 return Err(input.error(ParseError::ExpectedLabelDelta));
             }
         };
-        if let Some(label) = delta.strip_prefix('+') {
-            Ok(LabelDelta::Add(
-                Label::parse(label).map_err(|e| input.error(e))?,
-            ))
-        } else if let Some(label) = delta.strip_prefix('-') {
-            Ok(LabelDelta::Remove(
-                Label::parse(label).map_err(|e| input.error(e))?,
-            ))
-        } else {
-            Ok(LabelDelta::Add(
-                Label::parse(delta).map_err(|e| input.error(e))?,
-            ))
-        }
+        delta.parse().map_err(|e| input.error(e))
     }
 
     pub fn label(&self) -> &Label {
         match self {
-            LabelDelta::Add(l) | LabelDelta::Remove(l) => l,
+            LabelDelta::Add(l) | LabelDelta::Remove(l) | LabelDelta::Toggle(l) => l,
         }
     }
 }
@@ -102,13 +166,23 @@ fn delta_empty() {
 }
 
 impl RelabelCommand {
+    pub fn from_deltas(deltas: Vec<LabelDelta>) -> RelabelCommand {
+        RelabelCommand {
+            deltas,
+            ..Default::default()
+        }
+    }
+
     /// Parse and validate command tokens
     pub fn parse<'a>(input: &mut Tokenizer<'a>) -> Result<Option<Self>, Error<'a>> {
         let mut toks = input.clone();
 
         toks.eat_token(Token::Word("modify"))?;
 
-        if toks.eat_token(Token::Word("labels"))? || toks.eat_token(Token::Word("label"))? {
+        if toks.eat_token(Token::Word("labels"))?
+            || toks.eat_token(Token::Word("label"))?
+            || toks.eat_token(Token::Word("relabel"))?
+        {
             toks.eat_token(Token::Word("to"))?;
             toks.eat_token(Token::Colon)?;
 
@@ -120,10 +194,94 @@ impl RelabelCommand {
         if let Some(Token::Word("to")) = toks.peek_token()? {
             return Err(toks.error(ParseError::MisleadingTo));
         }
+
+        // `--dry-run`, `--if-open` and `--if-closed`, in any order
+        let mut dry_run = false;
+        let mut if_state = None;
+        loop {
+            if toks.eat_token(Token::Word("--dry-run"))? {
+                dry_run = true;
+            } else if toks.eat_token(Token::Word("--if-open"))? {
+                if_state = Some(StateCondition::Open);
+            } else if toks.eat_token(Token::Word("--if-closed"))? {
+                if_state = Some(StateCondition::Closed);
+            } else {
+                break;
+            }
+        }
+
+        // `explain` followed by deltas, otherwise `explain` is a regular label
+        let mut explain = false;
+        if let Some(Token::Word("explain")) = toks.peek_token()? {
+            let mut explain_toks = toks.clone();
+            explain_toks.next_token()?;
+            if let Some(Token::Word(_) | Token::Quote(_)) = explain_toks.peek_token()? {
+                toks = explain_toks;
+                explain = true;
+            }
+        }
+
+        // `preset NAME` applies a configured set of deltas, the label named
+        // `preset` has to be added explicitly with `+preset`
+        if toks.eat_token(Token::Word("preset"))? {
+            if let Some(Token::Word(name) | Token::Quote(name)) = toks.next_token()?
+                && !name.is_empty()
+                && !name.starts_with(['+', '-', '~'])
+                && let Some(Token::Semi | Token::Dot | Token::EndOfLine) = toks.peek_token()?
+            {
+                toks.next_token()?;
+                *input = toks;
+                return Ok(Some(RelabelCommand {
+                    preset: Some(name.to_string()),
+                    dry_run,
+                    if_state,
+                    ..Default::default()
+                }));
+            }
+            return Err(toks.error(ParseError::ExpectedPresetName));
+        }
+
+        // `copy-from #123` copies the labels of another issue
+        if let Some(Token::Word("copy-from")) = toks.peek_token()? {
+            let mut copy_toks = toks.clone();
+            copy_toks.next_token()?;
+            if let Some(Token::Word(issue)) = copy_toks.next_token()?
+                && let Some(number) = issue.strip_prefix('#').and_then(|n| n.parse().ok())
+                && let Some(Token::Semi | Token::Dot | Token::EndOfLine) = copy_toks.peek_token()?
+            {
+                copy_toks.next_token()?;
+                *input = copy_toks;
+                return Ok(Some(RelabelCommand {
+                    copy_from: Some(number),
+                    dry_run,
+                    if_state,
+                    ..Default::default()
+                }));
+            }
+        }
+
         // start parsing deltas
         let mut deltas = Vec::new();
+        let mut reasons = Vec::new();
         loop {
-            deltas.push(LabelDelta::parse(&mut toks)?);
+            let explicit_add =
+                matches!(toks.peek_token()?, Some(Token::Word(delta)) if delta.starts_with('+'));
+            let delta = LabelDelta::parse(&mut toks)?;
+
+            // optional quoted reason after a `+label:`, the colon keeping a
+            // quoted label after a `+label` from being taken as its reason
+            if explicit_add && let Some(Token::Colon) = toks.peek_token()? {
+                let mut reason_toks = toks.clone();
+                reason_toks.next_token()?;
+                if let Some(Token::Quote(reason)) = reason_toks.next_token()? {
+                    toks = reason_toks;
+                    reasons.push(LabelReason {
+                        label: delta.label().clone(),
+                        reason: reason.to_string(),
+                    });
+                }
+            }
+            deltas.push(delta);
 
             // optional `, and` separator
             toks.eat_token(Token::Comma)?;
@@ -132,7 +290,13 @@ impl RelabelCommand {
             if let Some(Token::Semi | Token::Dot | Token::EndOfLine) = toks.peek_token()? {
                 toks.next_token()?;
                 *input = toks;
-                return Ok(Some(RelabelCommand(deltas)));
+                return Ok(Some(RelabelCommand {
+                    dry_run,
+                    reasons,
+                    explain,
+                    if_state,
+                    ..RelabelCommand::from_deltas(deltas)
+                }));
             }
         }
     }
@@ -141,7 +305,7 @@ impl RelabelCommand {
 #[cfg(test)]
 fn parse<'a>(input: &'a str) -> Result<Option<Vec<LabelDelta>>, Error<'a>> {
     let mut toks = Tokenizer::new(input);
-    Ok(RelabelCommand::parse(&mut toks)?.map(|c| c.0))
+    Ok(RelabelCommand::parse(&mut toks)?.map(|c| c.deltas))
 }
 
 #[test]
@@ -156,6 +320,47 @@ fn parse_simple() {
     );
 }
 
+#[test]
+fn parse_quoted_multi_word_labels() {
+    assert_eq!(
+        parse(r#"label +"needs design review" -"waiting on team" bug"#),
+        Ok(Some(vec![
+            LabelDelta::Add(Label("needs design review".into())),
+            LabelDelta::Remove(Label("waiting on team".into())),
+            LabelDelta::Add(Label("bug".into())),
+        ]))
+    );
+}
+
+#[test]
+fn parse_quoted_multi_word_label_with_reason() {
+    let mut toks = Tokenizer::new(r#"label +"needs design review": "unclear API""#);
+    let cmd = RelabelCommand::parse(&mut toks).unwrap().unwrap();
+    assert_eq!(
+        cmd.deltas,
+        vec![LabelDelta::Add(Label("needs design review".into()))]
+    );
+    assert_eq!(
+        cmd.reasons,
+        vec![LabelReason {
+            label: Label("needs design review".into()),
+            reason: "unclear API".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn parse_quoted_empty_label() {
+    assert_eq!(
+        parse(r#"label +"" bug"#)
+            .unwrap_err()
+            .source()
+            .unwrap()
+            .downcast_ref(),
+        Some(&ParseError::EmptyLabel)
+    );
+}
+
 #[test]
 fn parse_leading_to_label() {
     assert_eq!(
@@ -259,3 +464,297 @@ fn parse_quote() {
         ]))
     );
 }
+
+#[test]
+fn parse_relabel_keyword() {
+    assert_eq!(
+        parse("relabel +T-compiler -T-lang"),
+        Ok(Some(vec![
+            LabelDelta::Add(Label("T-compiler".into())),
+            LabelDelta::Remove(Label("T-lang".into())),
+        ]))
+    );
+}
+
+#[test]
+fn parse_preset() {
+    let mut toks = Tokenizer::new("relabel preset new-bug");
+    assert_eq!(
+        RelabelCommand::parse(&mut toks),
+        Ok(Some(RelabelCommand {
+            deltas: vec![],
+            preset: Some("new-bug".into()),
+            dry_run: false,
+            reasons: vec![],
+            copy_from: None,
+            explain: false,
+            if_state: None,
+        }))
+    );
+
+    let mut toks = Tokenizer::new("label preset \"new bug\". Some text");
+    assert_eq!(
+        RelabelCommand::parse(&mut toks),
+        Ok(Some(RelabelCommand {
+            deltas: vec![],
+            preset: Some("new bug".into()),
+            dry_run: false,
+            reasons: vec![],
+            copy_from: None,
+            explain: false,
+            if_state: None,
+        }))
+    );
+}
+
+#[test]
+fn parse_invalid_preset() {
+    // A missing or invalid name isn't taken as the `preset` label
+    for input in [
+        "label preset",
+        "label preset +bug",
+        "label preset new-bug +bug",
+    ] {
+        assert_eq!(
+            parse(input).unwrap_err().source().unwrap().downcast_ref(),
+            Some(&ParseError::ExpectedPresetName),
+            "{input}"
+        );
+    }
+    assert_eq!(
+        parse("label +preset +bug"),
+        Ok(Some(vec![
+            LabelDelta::Add(Label("preset".into())),
+            LabelDelta::Add(Label("bug".into())),
+        ]))
+    );
+}
+
+#[test]
+fn parse_dry_run() {
+    let mut toks = Tokenizer::new("relabel --dry-run +A-foo -B-bar");
+    assert_eq!(
+        RelabelCommand::parse(&mut toks),
+        Ok(Some(RelabelCommand {
+            deltas: vec![
+                LabelDelta::Add(Label("A-foo".into())),
+                LabelDelta::Remove(Label("B-bar".into())),
+            ],
+            preset: None,
+            dry_run: true,
+            reasons: vec![],
+            copy_from: None,
+            explain: false,
+            if_state: None,
+        }))
+    );
+
+    let mut toks = Tokenizer::new("label --dry-run preset new-bug");
+    assert_eq!(
+        RelabelCommand::parse(&mut toks),
+        Ok(Some(RelabelCommand {
+            deltas: vec![],
+            preset: Some("new-bug".into()),
+            dry_run: true,
+            reasons: vec![],
+            copy_from: None,
+            explain: false,
+            if_state: None,
+        }))
+    );
+
+    // Without the flag, nothing changes
+    let mut toks = Tokenizer::new("relabel +A-foo");
+    assert_eq!(
+        RelabelCommand::parse(&mut toks).map(|c| c.map(|c| c.dry_run)),
+        Ok(Some(false))
+    );
+}
+
+#[test]
+fn parse_label_with_reason() {
+    let mut toks =
+        Tokenizer::new("relabel +S-blocked: \"waiting on RFC 1234\" -S-waiting-on-review");
+    assert_eq!(
+        RelabelCommand::parse(&mut toks),
+        Ok(Some(RelabelCommand {
+            deltas: vec![
+                LabelDelta::Add(Label("S-blocked".into())),
+                LabelDelta::Remove(Label("S-waiting-on-review".into())),
+            ],
+            preset: None,
+            dry_run: false,
+            reasons: vec![LabelReason {
+                label: Label("S-blocked".into()),
+                reason: "waiting on RFC 1234".into(),
+            }],
+            copy_from: None,
+            explain: false,
+            if_state: None,
+        }))
+    );
+}
+
+#[test]
+fn parse_label_without_reason() {
+    let mut toks = Tokenizer::new("relabel +S-blocked -S-waiting-on-review");
+    assert_eq!(
+        RelabelCommand::parse(&mut toks).map(|c| c.map(|c| c.reasons)),
+        Ok(Some(vec![]))
+    );
+
+    // Only labels explicitly added with a `+` can have a reason
+    assert_eq!(
+        parse("relabel S-blocked \"good first issue\" -bug \"E-easy\""),
+        Ok(Some(vec![
+            LabelDelta::Add(Label("S-blocked".into())),
+            LabelDelta::Add(Label("good first issue".into())),
+            LabelDelta::Remove(Label("bug".into())),
+            LabelDelta::Add(Label("E-easy".into())),
+        ]))
+    );
+    assert_eq!(
+        parse("relabel +S-blocked \"+good first issue\""),
+        Ok(Some(vec![
+            LabelDelta::Add(Label("S-blocked".into())),
+            LabelDelta::Add(Label("good first issue".into())),
+        ]))
+    );
+
+    // A quoted label after a `+label` is still a label, not a reason
+    let mut toks = Tokenizer::new("relabel +S-blocked \"good first issue\"");
+    let cmd = RelabelCommand::parse(&mut toks).unwrap().unwrap();
+    assert_eq!(
+        cmd.deltas,
+        vec![
+            LabelDelta::Add(Label("S-blocked".into())),
+            LabelDelta::Add(Label("good first issue".into())),
+        ]
+    );
+    assert_eq!(cmd.reasons, vec![]);
+}
+
+#[test]
+fn parse_explain() {
+    let mut toks = Tokenizer::new("relabel explain +A-foo -B-bar");
+    assert_eq!(
+        RelabelCommand::parse(&mut toks),
+        Ok(Some(RelabelCommand {
+            deltas: vec![
+                LabelDelta::Add(Label("A-foo".into())),
+                LabelDelta::Remove(Label("B-bar".into())),
+            ],
+            explain: true,
+            if_state: None,
+            ..Default::default()
+        }))
+    );
+
+    // Without deltas, `explain` is a regular label
+    assert_eq!(
+        parse("label explain"),
+        Ok(Some(vec![LabelDelta::Add(Label("explain".into()))]))
+    );
+    assert_eq!(
+        parse("label +explain"),
+        Ok(Some(vec![LabelDelta::Add(Label("explain".into()))]))
+    );
+}
+
+#[test]
+fn parse_copy_from() {
+    let mut toks = Tokenizer::new("relabel copy-from #123. Some text");
+    assert_eq!(
+        RelabelCommand::parse(&mut toks),
+        Ok(Some(RelabelCommand {
+            copy_from: Some(123),
+            ..Default::default()
+        }))
+    );
+
+    let mut toks = Tokenizer::new("label --dry-run copy-from #7");
+    assert_eq!(
+        RelabelCommand::parse(&mut toks),
+        Ok(Some(RelabelCommand {
+            copy_from: Some(7),
+            dry_run: true,
+            ..Default::default()
+        }))
+    );
+
+    // Without an issue, `copy-from` is a regular label
+    assert_eq!(
+        parse("label copy-from +bug"),
+        Ok(Some(vec![
+            LabelDelta::Add(Label("copy-from".into())),
+            LabelDelta::Add(Label("bug".into())),
+        ]))
+    );
+}
+
+#[test]
+fn delta_from_str() {
+    assert_eq!("+C-bug".parse(), Ok(LabelDelta::Add(Label("C-bug".into()))));
+    assert_eq!(
+        "-C-bug".parse(),
+        Ok(LabelDelta::Remove(Label("C-bug".into())))
+    );
+    assert_eq!("C-bug".parse(), Ok(LabelDelta::Add(Label("C-bug".into()))));
+    assert_eq!(
+        "~C-bug".parse(),
+        Ok(LabelDelta::Toggle(Label("C-bug".into())))
+    );
+    assert_eq!("+".parse::<LabelDelta>(), Err(ParseError::EmptyLabel));
+    assert_eq!("~".parse::<LabelDelta>(), Err(ParseError::EmptyLabel));
+}
+
+#[test]
+fn parse_toggle() {
+    assert_eq!(
+        parse("relabel ~A-foo +B-bar \"~C-baz qux\""),
+        Ok(Some(vec![
+            LabelDelta::Toggle(Label("A-foo".into())),
+            LabelDelta::Add(Label("B-bar".into())),
+            LabelDelta::Toggle(Label("C-baz qux".into())),
+        ]))
+    );
+}
+
+#[test]
+fn parse_state_condition() {
+    let mut toks = Tokenizer::new("relabel --if-open +needs-triage");
+    assert_eq!(
+        RelabelCommand::parse(&mut toks),
+        Ok(Some(RelabelCommand {
+            if_state: Some(StateCondition::Open),
+            ..RelabelCommand::from_deltas(vec![LabelDelta::Add(Label("needs-triage".into()))])
+        }))
+    );
+
+    let mut toks = Tokenizer::new("label --if-closed --dry-run -I-prioritize");
+    assert_eq!(
+        RelabelCommand::parse(&mut toks),
+        Ok(Some(RelabelCommand {
+            dry_run: true,
+            if_state: Some(StateCondition::Closed),
+            ..RelabelCommand::from_deltas(vec![LabelDelta::Remove(Label("I-prioritize".into()))])
+        }))
+    );
+
+    let mut toks = Tokenizer::new("relabel --if-open preset new-bug");
+    assert_eq!(
+        RelabelCommand::parse(&mut toks),
+        Ok(Some(RelabelCommand {
+            preset: Some("new-bug".into()),
+            if_state: Some(StateCondition::Open),
+            ..Default::default()
+        }))
+    );
+
+    // Without a flag, the changes are unconditional
+    let mut toks = Tokenizer::new("relabel +A-foo");
+    assert_eq!(
+        RelabelCommand::parse(&mut toks).map(|c| c.map(|c| c.if_state)),
+        Ok(Some(None))
+    );
+}
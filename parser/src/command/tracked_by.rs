@@ -0,0 +1,86 @@
+//! Parses the `@bot tracked-by #123` command.
+
+use crate::error::Error;
+use crate::token::{Token, Tokenizer};
+use std::fmt;
+
+/// Corresponds to `@bot tracked-by #123`, with the number of the tracking issue.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TrackedByCommand(pub u64);
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum ParseError {
+    MissingIssue,
+    InvalidIssue,
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingIssue => write!(f, "missing the tracking issue"),
+            ParseError::InvalidIssue => write!(f, "the tracking issue should be written as `#123`"),
+        }
+    }
+}
+
+impl TrackedByCommand {
+    pub fn parse<'a>(input: &mut Tokenizer<'a>) -> Result<Option<Self>, Error<'a>> {
+        let mut toks = input.clone();
+        if !matches!(toks.peek_token()?, Some(Token::Word("tracked-by"))) {
+            return Ok(None);
+        }
+        toks.next_token()?;
+
+        let issue = match toks.next_token()? {
+            Some(Token::Word(issue)) => issue,
+            _ => return Err(toks.error(ParseError::MissingIssue)),
+        };
+        let Some(number) = issue.strip_prefix('#').and_then(|n| n.parse().ok()) else {
+            return Err(toks.error(ParseError::InvalidIssue));
+        };
+
+        if let Some(Token::Dot | Token::EndOfLine) = toks.peek_token()? {
+            toks.next_token()?;
+        }
+        *input = toks;
+        Ok(Some(TrackedByCommand(number)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    fn parse(input: &str) -> Result<Option<TrackedByCommand>, Error<'_>> {
+        let mut toks = Tokenizer::new(input);
+        TrackedByCommand::parse(&mut toks)
+    }
+
+    #[test]
+    fn tracked_by() {
+        assert_eq!(parse("tracked-by #123"), Ok(Some(TrackedByCommand(123))));
+        assert_eq!(parse("tracked-by #123."), Ok(Some(TrackedByCommand(123))));
+    }
+
+    #[test]
+    fn not_tracked_by() {
+        assert_eq!(parse("tracked"), Ok(None));
+    }
+
+    #[test]
+    fn invalid_issue() {
+        let err = parse("tracked-by 123").unwrap_err();
+        assert_eq!(
+            err.source().unwrap().downcast_ref(),
+            Some(&ParseError::InvalidIssue)
+        );
+        let err = parse("tracked-by").unwrap_err();
+        assert_eq!(
+            err.source().unwrap().downcast_ref(),
+            Some(&ParseError::MissingIssue)
+        );
+    }
+}
@@ -0,0 +1,98 @@
+//! Parses the `@bot watch-label LABEL` and `@bot unwatch-label LABEL` commands.
+
+use crate::error::Error;
+use crate::token::{Token, Tokenizer};
+use std::fmt;
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum WatchLabelCommand {
+    /// Corresponds to `@bot watch-label LABEL`.
+    Watch { label: String },
+    /// Corresponds to `@bot unwatch-label LABEL`.
+    Unwatch { label: String },
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum ParseError {
+    MissingLabel,
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingLabel => write!(f, "missing the label to watch"),
+        }
+    }
+}
+
+impl WatchLabelCommand {
+    pub fn parse<'a>(input: &mut Tokenizer<'a>) -> Result<Option<Self>, Error<'a>> {
+        let mut toks = input.clone();
+        let watch = match toks.peek_token()? {
+            Some(Token::Word("watch-label")) => true,
+            Some(Token::Word("unwatch-label")) => false,
+            _ => return Ok(None),
+        };
+        toks.next_token()?;
+
+        let label = match toks.next_token()? {
+            Some(Token::Word(label) | Token::Quote(label)) if !label.is_empty() => {
+                label.to_string()
+            }
+            _ => return Err(toks.error(ParseError::MissingLabel)),
+        };
+
+        if let Some(Token::Dot | Token::EndOfLine) = toks.peek_token()? {
+            toks.next_token()?;
+        }
+        *input = toks;
+
+        Ok(Some(if watch {
+            WatchLabelCommand::Watch { label }
+        } else {
+            WatchLabelCommand::Unwatch { label }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    fn parse(input: &str) -> Result<Option<WatchLabelCommand>, Error<'_>> {
+        let mut toks = Tokenizer::new(input);
+        WatchLabelCommand::parse(&mut toks)
+    }
+
+    #[test]
+    fn watch() {
+        assert_eq!(
+            parse("watch-label S-waiting-on-review"),
+            Ok(Some(WatchLabelCommand::Watch {
+                label: "S-waiting-on-review".to_string()
+            }))
+        );
+    }
+
+    #[test]
+    fn unwatch() {
+        assert_eq!(
+            parse("unwatch-label \"good first issue\"."),
+            Ok(Some(WatchLabelCommand::Unwatch {
+                label: "good first issue".to_string()
+            }))
+        );
+    }
+
+    #[test]
+    fn missing_label() {
+        let err = parse("watch-label").unwrap_err();
+        assert_eq!(
+            err.source().unwrap().downcast_ref(),
+            Some(&ParseError::MissingLabel)
+        );
+    }
+}
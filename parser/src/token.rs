@@ -152,8 +152,10 @@ impl<'a> Tokenizer<'a> {
         loop {
             match self.cur() {
                 Some((_, '"')) => break,
+                // Quoted strings never span multiple lines, otherwise a stray quote
+                // would swallow any commands following it in the comment.
+                Some((_, '\n')) | None => return Err(self.error(ErrorKind::UnterminatedString)),
                 Some(_) => self.advance(),
-                None => return Err(self.error(ErrorKind::UnterminatedString)),
             };
         }
         let body = self.str_from(start);
@@ -204,6 +206,11 @@ impl<'a> Tokenizer<'a> {
         {
             if ch == '"' {
                 let so_far = self.str_from(start);
+                if matches!(so_far, "+" | "-" | "~") {
+                    // A quoted label delta, like `+"needs design review"`: the
+                    // prefix is a word of its own, followed by the quote.
+                    break;
+                }
                 if so_far
                     .strip_prefix('r')
                     .is_some_and(|s| s.chars().all(|v| v == '#' || v == '"'))
@@ -330,6 +337,16 @@ fn tokenize_6() {
     );
 }
 
+#[test]
+fn tokenize_unterminated_string_stops_at_newline() {
+    assert_eq!(
+        tokenize("\"testing\nmore\"")
+            .unwrap_err()
+            .position_and_kind(),
+        (8, ErrorKind::UnterminatedString)
+    );
+}
+
 #[test]
 fn tokenize_7() {
     assert_eq!(
@@ -340,6 +357,20 @@ fn tokenize_7() {
     );
 }
 
+#[test]
+fn tokenize_prefixed_quote() {
+    assert_eq!(
+        tokenize(r#"+"needs design review" -"bug""#).unwrap(),
+        [
+            Token::Word("+"),
+            Token::Quote("needs design review"),
+            Token::Word("-"),
+            Token::Quote("bug"),
+            Token::EndOfLine,
+        ]
+    );
+}
+
 #[test]
 fn tokenize_raw_string_prohibit() {
     assert_eq!(
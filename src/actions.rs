@@ -1,5 +1,5 @@
-use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::BTreeMap;
 use std::sync::{Arc, LazyLock};
 
 use async_trait::async_trait;
@@ -12,6 +12,10 @@ use crate::team_data::TeamClient;
 
 #[async_trait]
 pub trait Action {
+    /// Runs the queries of the agenda, without rendering it.
+    async fn data(&self) -> anyhow::Result<AgendaData>;
+
+    /// Runs the queries of the agenda and renders it with its template.
     async fn call(&self) -> anyhow::Result<String>;
 }
 
@@ -87,6 +91,45 @@ pub struct MCPDetails {
     pub concerns: Option<Vec<(String, String)>>,
 }
 
+/// The structured content of an agenda, from which its template is rendered.
+#[derive(Debug, serde::Serialize)]
+pub struct AgendaData {
+    /// Name of the agenda, which is also the name of its template.
+    pub name: String,
+    pub date: NaiveDate,
+    pub triage_logs: String,
+    /// The lists of issues, in the order of their queries.
+    pub sections: Vec<AgendaSection>,
+    pub counts: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct AgendaSection {
+    pub name: String,
+    pub issues: Vec<IssueDecorator>,
+}
+
+impl AgendaData {
+    fn context(&self) -> Context {
+        let mut context = Context::new();
+        for section in &self.sections {
+            context.insert(&section.name, &section.issues);
+        }
+        for (name, count) in &self.counts {
+            context.insert(name, count);
+        }
+        context.insert("CURRENT_DATE", &self.date);
+        context.insert("triage_logs", &self.triage_logs);
+        context
+    }
+
+    pub fn render(&self) -> String {
+        TEMPLATES
+            .render(&format!("{}.tt", self.name), &self.context())
+            .unwrap()
+    }
+}
+
 pub static TEMPLATES: LazyLock<Tera> = LazyLock::new(|| match Tera::new("templates/*") {
     Ok(t) => t,
     Err(e) => {
@@ -108,6 +151,10 @@ pub fn to_human(d: DateTime<Utc>) -> String {
 #[async_trait]
 impl Action for Step<'_> {
     async fn call(&self) -> anyhow::Result<String> {
+        Ok(self.data().await?.render())
+    }
+
+    async fn data(&self) -> anyhow::Result<AgendaData> {
         let mut gh = GithubClient::new_from_env();
         gh.set_retry_rate_limit(true);
         let team_api = TeamClient::new_from_env();
@@ -124,8 +171,8 @@ impl Action for Step<'_> {
             }
         };
 
-        let mut context = Context::new();
-        let mut results = HashMap::new();
+        let mut sections: Vec<AgendaSection> = Vec::new();
+        let mut counts = BTreeMap::new();
 
         let mut handles: Vec<tokio::task::JoinHandle<anyhow::Result<(String, QueryKind, Vec<_>)>>> =
             Vec::new();
@@ -178,33 +225,80 @@ impl Action for Step<'_> {
         for handle in handles {
             let (name, kind, issues) = handle.await.unwrap()?;
             match kind {
-                QueryKind::List => {
-                    results.entry(name).or_insert(Vec::new()).extend(issues);
-                }
+                QueryKind::List => match sections.iter_mut().find(|s| s.name == name) {
+                    Some(section) => section.issues.extend(issues),
+                    None => sections.push(AgendaSection { name, issues }),
+                },
                 QueryKind::Count => {
-                    let count = issues.len();
-                    let result = if let Some(value) = context.get(&name) {
-                        value.as_u64().unwrap() + count as u64
-                    } else {
-                        count as u64
-                    };
-
-                    context.insert(name, &result);
+                    *counts.entry(name).or_insert(0) += issues.len() as u64;
                 }
             }
         }
 
-        for (name, issues) in &results {
-            context.insert(name, issues);
-        }
-
-        let date = chrono::Utc::now().date_naive();
-        context.insert("CURRENT_DATE", &date);
+        Ok(AgendaData {
+            name: self.name.to_string(),
+            date: today,
+            triage_logs,
+            sections,
+            counts,
+        })
+    }
+}
 
-        context.insert("triage_logs", &triage_logs);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        Ok(TEMPLATES
-            .render(&format!("{}.tt", self.name), &context)
-            .unwrap())
+    #[test]
+    fn agenda_serialization() {
+        let data = AgendaData {
+            name: "types_planning_agenda".to_string(),
+            date: NaiveDate::from_ymd_opt(2025, 2, 10).unwrap(),
+            triage_logs: String::new(),
+            sections: vec![AgendaSection {
+                name: "nominated".to_string(),
+                issues: vec![IssueDecorator {
+                    number: 1234,
+                    title: "ICE in trait solver".to_string(),
+                    html_url: "https://github.com/rust-lang/rust/issues/1234".to_string(),
+                    repo_name: "rust".to_string(),
+                    labels: "T-types, I-types-nominated".to_string(),
+                    author: "lcnr".to_string(),
+                    team: String::new(),
+                    assignees: String::new(),
+                    updated_at_hts: "about 2 days ago".to_string(),
+                    fcp_details: None,
+                    mcp_details: None,
+                    is_blocked: false,
+                }],
+            }],
+            counts: BTreeMap::from([("open_issues".to_string(), 42)]),
+        };
+        assert_eq!(
+            serde_json::to_value(&data).unwrap(),
+            serde_json::json!({
+                "name": "types_planning_agenda",
+                "date": "2025-02-10",
+                "triage_logs": "",
+                "sections": [{
+                    "name": "nominated",
+                    "issues": [{
+                        "number": 1234,
+                        "title": "ICE in trait solver",
+                        "html_url": "https://github.com/rust-lang/rust/issues/1234",
+                        "repo_name": "rust",
+                        "labels": "T-types, I-types-nominated",
+                        "author": "lcnr",
+                        "team": "",
+                        "assignees": "",
+                        "updated_at_hts": "about 2 days ago",
+                        "fcp_details": null,
+                        "mcp_details": null,
+                        "is_blocked": false,
+                    }],
+                }],
+                "counts": { "open_issues": 42 },
+            })
+        );
     }
 }
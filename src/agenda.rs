@@ -1,12 +1,88 @@
 use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+
+use axum::Json;
+use axum::extract::{Path, Query as QueryParams, State};
+use axum::response::{IntoResponse, Response};
+use chrono::{Datelike, NaiveDate, Utc, Weekday};
+use hyper::StatusCode;
 
 use crate::actions::{Action, Query, QueryKind, QueryMap, Step};
+use crate::db::agenda_archive::{ArchivedAgenda, archive_agenda, get_archived_agenda};
 use crate::errors::AppError;
 use crate::github::issue_query::LeastRecentlyReviewedPullRequests;
 use crate::github::issue_query::Query as IssueQuery;
+use crate::handlers::Context;
+
+/// Number of requests to the agenda rejected because of its rate limit.
+pub static AGENDA_RATE_LIMITED: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, serde::Deserialize)]
+pub struct AgendaParams {
+    /// `1` to also archive the agenda for the current ISO week.
+    archive: Option<String>,
+    /// `json` for the structured agenda instead of its rendered text.
+    format: Option<String>,
+}
+
+pub async fn types_planning_http(
+    QueryParams(params): QueryParams<AgendaParams>,
+    State(ctx): State<Arc<Context>>,
+) -> axum::response::Result<Response, AppError> {
+    let data = types_planning().data().await?;
+    let agenda = data.render();
+    if matches!(params.archive.as_deref(), Some("1" | "true")) {
+        let now = Utc::now();
+        let week = now.iso_week();
+        let archived = ArchivedAgenda {
+            content: agenda.clone(),
+            archived_at: now,
+        };
+        archive_agenda(
+            &*ctx.db.get().await,
+            "types",
+            week.year(),
+            week.week(),
+            &archived,
+        )
+        .await?;
+    }
+    if params.format.as_deref() == Some("json") {
+        Ok(Json(data).into_response())
+    } else {
+        Ok(agenda.into_response())
+    }
+}
+
+/// `GET /agenda/{team}/archive/{year}-W{week}`, the agenda archived for that week.
+pub async fn archived_agenda_http(
+    Path((team, week)): Path<(String, String)>,
+    State(ctx): State<Arc<Context>>,
+) -> axum::response::Result<Response, AppError> {
+    let Some((iso_year, iso_week)) = parse_iso_week(&week) else {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            format!("invalid ISO week `{week}`, expected e.g. `2025-W07`"),
+        )
+            .into_response());
+    };
+    match get_archived_agenda(&*ctx.db.get().await, &team, iso_year, iso_week).await? {
+        Some(agenda) => Ok(agenda.content.into_response()),
+        None => Ok((
+            StatusCode::NOT_FOUND,
+            format!("no agenda of `{team}` archived for {week}"),
+        )
+            .into_response()),
+    }
+}
 
-pub async fn types_planning_http() -> axum::response::Result<String, AppError> {
-    Ok(types_planning().call().await?)
+/// Parses an ISO week like `2025-W07` into its year and week number.
+fn parse_iso_week(week: &str) -> Option<(i32, u32)> {
+    let (year, week) = week.split_once("-W")?;
+    let (year, week) = (year.parse().ok()?, week.parse().ok()?);
+    // Rejects the weeks that don't exist, like the 53rd week of most years
+    NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)?;
+    Some((year, week))
 }
 
 pub fn prioritization() -> Box<dyn Action> {
@@ -574,3 +650,13 @@ pub static INDEX: &str = r#"
 </body>
 </html>
 "#;
+
+#[test]
+fn iso_week_parsing() {
+    assert_eq!(parse_iso_week("2025-W07"), Some((2025, 7)));
+    assert_eq!(parse_iso_week("2026-W53"), Some((2026, 53)));
+    assert_eq!(parse_iso_week("2025-W53"), None);
+    assert_eq!(parse_iso_week("2025-W00"), None);
+    assert_eq!(parse_iso_week("2025-07"), None);
+    assert_eq!(parse_iso_week("latest"), None);
+}
@@ -0,0 +1,306 @@
+//! Authenticated HTTP endpoints for maintenance operations.
+//!
+//! They are only available when the `TRIAGEBOT_API_TOKEN` environment variable
+//! is set, and requests must provide that token with `Authorization: Bearer <token>`.
+
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::{Path, Request, State};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use hyper::header::AUTHORIZATION;
+use hyper::{HeaderMap, StatusCode};
+use subtle::ConstantTimeEq;
+use tracing as log;
+
+use crate::config::ConfigurationError;
+use crate::db::disabled_handlers::{get_disabled_handlers, set_handler_disabled};
+use crate::errors::AppError;
+use crate::github::IssueRepository;
+use crate::handlers::close::bulk_close;
+use crate::handlers::milestone_prs::milestone_pr;
+use crate::handlers::pr_tracking::resync_pr;
+use crate::handlers::relabel::bulk_relabel;
+use crate::handlers::{Context, forget_disabled_handlers, is_handler_name};
+use parser::command::relabel::LabelDelta;
+
+/// Rejects the requests without the API token.
+pub async fn require_api_token(req: Request, next: Next) -> Response {
+    let Ok(expected) = std::env::var("TRIAGEBOT_API_TOKEN") else {
+        return (StatusCode::NOT_FOUND, "The API is not enabled.").into_response();
+    };
+    if !is_authorized(req.headers(), &expected) {
+        return (StatusCode::UNAUTHORIZED, "Invalid authorization.").into_response();
+    }
+
+    next.run(req).await
+}
+
+fn is_authorized(headers: &HeaderMap, expected: &str) -> bool {
+    let Some(token) = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    !expected.is_empty() && bool::from(token.as_bytes().ct_eq(expected.as_bytes()))
+}
+
+/// Re-runs the milestone assignment of a merged PR, e.g. when the webhook was missed.
+pub async fn milestone_recheck(
+    Path((owner, repo, pr)): Path<(String, String, u64)>,
+    State(ctx): State<Arc<Context>>,
+) -> axum::response::Result<Response, AppError> {
+    let repo = IssueRepository {
+        organization: owner,
+        repository: repo,
+    };
+    let pr = ctx.github.pull_request(&repo, pr).await?;
+    let outcome = milestone_pr(&ctx, &pr).await?;
+    Ok(Json(outcome).into_response())
+}
+
+#[derive(Debug, serde::Serialize)]
+struct WorkqueueResync {
+    /// The reviewers the PR is assigned to in the workqueue after the resync.
+    reviewers: Vec<String>,
+}
+
+/// Re-synchronizes the workqueue entry of a single PR with its current assignees
+/// on GitHub, without reloading the whole workqueue.
+pub async fn workqueue_resync(
+    Path((owner, repo, pr)): Path<(String, String, u64)>,
+    State(ctx): State<Arc<Context>>,
+) -> axum::response::Result<Response, AppError> {
+    let repo = IssueRepository {
+        organization: owner,
+        repository: repo,
+    };
+    let full_repo_name = repo.full_repo_name();
+    let pr = ctx.github.pull_request(&repo, pr).await?;
+    let Some(reviewers) = resync_pr(&ctx, &full_repo_name, &pr).await else {
+        return Ok((
+            StatusCode::NOT_FOUND,
+            format!("The workqueue of {full_repo_name} is not tracked."),
+        )
+            .into_response());
+    };
+    Ok(Json(WorkqueueResync { reviewers }).into_response())
+}
+
+/// Returns the effective config of a repository, as loaded by triagebot (with the
+/// organization config merged in), along with its enabled handlers.
+///
+/// This is part of the authenticated API as the config of private repositories
+/// isn't public, and its sensitive fields are redacted nonetheless.
+pub async fn config(
+    Path((owner, repo)): Path<(String, String)>,
+    State(ctx): State<Arc<Context>>,
+) -> axum::response::Result<Response, AppError> {
+    let full_name = format!("{owner}/{repo}");
+    let repo = match ctx.github.repository(&full_name).await {
+        Ok(repo) => repo,
+        Err(err)
+            if err
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(|e| e.status() == Some(reqwest::StatusCode::NOT_FOUND)) =>
+        {
+            return Ok((
+                StatusCode::NOT_FOUND,
+                format!("The repository {full_name} doesn't exist."),
+            )
+                .into_response());
+        }
+        Err(err) => return Err(err.into()),
+    };
+    match crate::config::get(&ctx.github, &repo).await {
+        Ok(config) => Ok(Json(config.to_json()?).into_response()),
+        Err(err @ ConfigurationError::Missing) => {
+            Ok((StatusCode::NOT_FOUND, err.to_string()).into_response())
+        }
+        Err(err @ ConfigurationError::Toml(_)) => {
+            Ok((StatusCode::UNPROCESSABLE_ENTITY, err.to_string()).into_response())
+        }
+        Err(ConfigurationError::Http(err)) => Err(anyhow::anyhow!("{err:?}").into()),
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct BulkRelabelRequest {
+    /// GitHub search query, e.g. `repo:rust-lang/rust is:open label:A-diagnostics`.
+    query: String,
+    /// Label deltas, in the same syntax as `@rustbot label`, e.g. `+A-foo -B-bar`.
+    labels: String,
+    /// Only report the changes, without applying them.
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Applies a label delta to all the issues matching a search query.
+pub async fn relabel_bulk(
+    State(ctx): State<Arc<Context>>,
+    Json(req): Json<BulkRelabelRequest>,
+) -> axum::response::Result<Response, AppError> {
+    let deltas = match req
+        .labels
+        .split_whitespace()
+        .map(str::parse::<LabelDelta>)
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(deltas)
+            if deltas
+                .iter()
+                .any(|delta| matches!(delta, LabelDelta::Toggle(_))) =>
+        {
+            return Ok((
+                StatusCode::BAD_REQUEST,
+                "Toggled labels (`~label`) are not supported by the bulk relabel.",
+            )
+                .into_response());
+        }
+        Ok(deltas) if !deltas.is_empty() => deltas,
+        Ok(_) => return Ok((StatusCode::BAD_REQUEST, "No label deltas given.").into_response()),
+        Err(err) => {
+            return Ok((
+                StatusCode::BAD_REQUEST,
+                format!("Invalid label deltas: {err}."),
+            )
+                .into_response());
+        }
+    };
+
+    let report = bulk_relabel(&ctx, &req.query, &deltas, req.dry_run).await?;
+    Ok(Json(report).into_response())
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct BulkCloseRequest {
+    /// GitHub search query, e.g. `repo:rust-lang/rust is:open label:S-inactive`.
+    query: String,
+    /// The comment posted on each issue before closing it.
+    comment: String,
+    /// Only report the issues that would be closed, without closing them.
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Closes all the issues matching a search query, with a comment.
+pub async fn close_bulk(
+    State(ctx): State<Arc<Context>>,
+    Json(req): Json<BulkCloseRequest>,
+) -> axum::response::Result<Response, AppError> {
+    if req.comment.trim().is_empty() {
+        return Ok((StatusCode::BAD_REQUEST, "No closing comment given.").into_response());
+    }
+
+    let report = bulk_close(&ctx, &req.query, &req.comment, req.dry_run).await?;
+    Ok(Json(report).into_response())
+}
+
+#[derive(Debug, serde::Serialize)]
+struct DisabledHandlers {
+    /// The handlers disabled in the repository, sorted by name.
+    disabled: Vec<String>,
+}
+
+/// Lists the handlers disabled in a repository.
+pub async fn disabled_handlers(
+    Path((owner, repo)): Path<(String, String)>,
+    State(ctx): State<Arc<Context>>,
+) -> axum::response::Result<Response, AppError> {
+    let db = ctx.db.get().await;
+    let mut disabled = get_disabled_handlers(&db, &format!("{owner}/{repo}"))
+        .await?
+        .into_iter()
+        .collect::<Vec<_>>();
+    disabled.sort();
+    Ok(Json(DisabledHandlers { disabled }).into_response())
+}
+
+/// Disables a handler in a repository, e.g. `milestone_prs`, until it is
+/// re-enabled with [`enable_handler`].
+///
+/// Handlers are named after their module, which is their kebab-case
+/// `triagebot.toml` section in snake case (e.g. `major_change` for
+/// `[major-change]`). The commands implemented in a submodule are disabled
+/// with their parent, e.g. `@rustbot hold` with `major_change`. The events of
+/// the repository are no longer passed to a disabled handler.
+pub async fn disable_handler(
+    Path((owner, repo, handler)): Path<(String, String, String)>,
+    State(ctx): State<Arc<Context>>,
+) -> axum::response::Result<Response, AppError> {
+    toggle_handler(&ctx, &format!("{owner}/{repo}"), &handler, true).await
+}
+
+/// Re-enables a handler disabled with [`disable_handler`].
+pub async fn enable_handler(
+    Path((owner, repo, handler)): Path<(String, String, String)>,
+    State(ctx): State<Arc<Context>>,
+) -> axum::response::Result<Response, AppError> {
+    toggle_handler(&ctx, &format!("{owner}/{repo}"), &handler, false).await
+}
+
+async fn toggle_handler(
+    ctx: &Context,
+    repo: &str,
+    handler: &str,
+    disabled: bool,
+) -> axum::response::Result<Response, AppError> {
+    if !is_handler_name(handler) {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            format!("Unknown handler `{handler}`."),
+        )
+            .into_response());
+    }
+
+    let db = ctx.db.get().await;
+    let changed = set_handler_disabled(&db, repo, handler, disabled).await?;
+    forget_disabled_handlers(repo);
+    let state = if disabled { "disabled" } else { "enabled" };
+    log::info!("the {handler} handler of {repo} was {state} with the API");
+
+    let message = if changed {
+        format!("The {handler} handler is now {state} in {repo}.")
+    } else {
+        format!("The {handler} handler was already {state} in {repo}.")
+    };
+    Ok(message.into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    fn headers(authorization: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_str(authorization).unwrap());
+        headers
+    }
+
+    #[test]
+    fn authorization() {
+        assert!(is_authorized(&headers("Bearer secret"), "secret"));
+        assert!(!is_authorized(&headers("Bearer wrong"), "secret"));
+        assert!(!is_authorized(&headers("secret"), "secret"));
+        assert!(!is_authorized(&HeaderMap::new(), "secret"));
+        assert!(!is_authorized(&headers("Bearer "), ""));
+    }
+
+    #[tokio::test]
+    async fn config_of_unknown_repository() {
+        let mut ctx = crate::tests::offline_context();
+        ctx.github = crate::tests::mock_github_client(axum::Router::new()).await;
+
+        let response = config(
+            Path(("rust-lang".to_string(), "missing".to_string())),
+            State(Arc::new(ctx)),
+        )
+        .await
+        .unwrap_or_else(IntoResponse::into_response);
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -3,7 +3,7 @@ mod rustc;
 use comrak::{Arena, ComrakOptions, ComrakRenderOptions, nodes::AstNode};
 use std::collections::HashMap;
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub(crate) enum ChangelogFormat {
     Rustc,
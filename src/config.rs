@@ -1,7 +1,8 @@
 use crate::changelogs::ChangelogFormat;
-use crate::github::{GithubClient, Repository};
+use crate::github::{GithubClient, MilestoneState, Repository};
+use anyhow::Context as _;
 use parser::command::relabel::{Label, LabelDelta, RelabelCommand};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt;
 use std::num::NonZeroU8;
 use std::sync::{Arc, LazyLock, RwLock};
@@ -29,7 +30,7 @@ macro_rules! define_config {
     ($(
         $(#[$field_attr:meta])* $field:ident: $ty:ty
     ),* $(,)?) => {
-        #[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+        #[derive(PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
         #[serde(rename_all = "kebab-case")]
         #[serde(deny_unknown_fields)]
         pub(crate) struct Config {
@@ -39,7 +40,7 @@ macro_rules! define_config {
             )*
         }
 
-        #[derive(Debug, serde::Deserialize)]
+        #[derive(Debug, serde::Deserialize, serde::Serialize)]
         #[serde(rename_all = "kebab-case")]
         #[serde(deny_unknown_fields)]
         struct OrgConfig {
@@ -95,16 +96,27 @@ define_config! {
     range_diff: RangeDiffConfig,
     review_changes_since: ReviewChangesSinceConfig,
     view_all_comments_link: ViewAllCommentsLinkConfig,
+    blocked_on: BlockedOnConfig,
+    watch_label: WatchLabelConfig,
+    priority: PriorityConfig,
+    milestone_prs: MilestonePrsConfig,
+    template_labels: TemplateLabelsConfig,
+    dry_run: DryRunConfig,
+    milestone: MilestoneConfig,
+    escalate: EscalateConfig,
+    tracked_by: TrackedByConfig,
+    needs_repro: NeedsReproConfig,
+    defer: DeferConfig,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct NominateConfig {
     // team name -> label
     pub(crate) teams: HashMap<String, String>,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub(crate) struct PingConfig {
     // team name -> message
     // message will have the cc string appended
@@ -128,20 +140,28 @@ impl PingConfig {
     }
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct PingTeamConfig {
     pub(crate) message: String,
     #[serde(default)]
     pub(crate) alias: HashSet<String>,
     pub(crate) label: Option<String>,
+    /// Ping the members of the team individually instead of its GitHub teams,
+    /// as some people don't watch the team mentions.
+    #[serde(default)]
+    pub(crate) ping_members: bool,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct AssignReviewPrefsConfig {}
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct DeferConfig {}
+
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct AssignCustomMessages {
@@ -153,7 +173,7 @@ pub(crate) struct AssignCustomMessages {
     pub(crate) auto_assign_no_one: String,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct AssignConfig {
     /// If enabled, then posts a warning comment if the PR is opened against a
@@ -173,8 +193,13 @@ pub(crate) struct AssignConfig {
     /// usernames, team names, or ad-hoc groups.
     #[serde(default)]
     pub(crate) owners: HashMap<String, Vec<String>>,
-    #[serde(default)]
+    #[serde(default, serialize_with = "redacted")]
     pub(crate) users_on_vacation: HashSet<String>,
+    /// Reviewers with an expertise in the area of a label.
+    /// The key is a label name, and the value is a list of usernames that are
+    /// preferred amongst the candidates when a PR has that label.
+    #[serde(default)]
+    pub(crate) expertise: HashMap<String, Vec<String>>,
     /// Should review preferences be taken into account when deciding who to assign to a PR?
     #[serde(default)]
     pub(crate) review_prefs: Option<AssignReviewPrefsConfig>,
@@ -182,6 +207,9 @@ pub(crate) struct AssignConfig {
     #[serde(default)]
     #[serde(alias = "custom_welcome_messages")]
     pub(crate) custom_messages: Option<AssignCustomMessages>,
+    /// Allows rotating the review of stale PRs to a new reviewer with `@rustbot re-review`
+    #[serde(default)]
+    pub(crate) re_review: Option<AssignReReviewConfig>,
 }
 
 impl AssignConfig {
@@ -199,7 +227,7 @@ impl AssignConfig {
     }
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct AssignCommunityReviewsConfig {
     /// The minimum number of community reviews before automatic assignment is performed
@@ -208,7 +236,28 @@ pub(crate) struct AssignCommunityReviewsConfig {
     pub(crate) label: String,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct AssignReReviewConfig {
+    /// Number of days without activity of the reviewer (since they were assigned,
+    /// commented or reviewed) after which a new reviewer can be requested
+    #[serde(default = "AssignReReviewConfig::stale_after_days_default")]
+    pub(crate) stale_after_days: u32,
+    /// Minimum number of days between two rotations of the review of a PR
+    #[serde(default = "AssignReReviewConfig::cooldown_days_default")]
+    pub(crate) cooldown_days: u32,
+}
+
+impl AssignReReviewConfig {
+    fn stale_after_days_default() -> u32 {
+        14
+    }
+    fn cooldown_days_default() -> u32 {
+        7
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 #[serde(untagged)]
 pub(crate) enum WarnNonDefaultBranchConfig {
@@ -221,7 +270,7 @@ pub(crate) enum WarnNonDefaultBranchConfig {
     },
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct WarnNonDefaultBranchException {
     /// Substring in the title that match this exception
@@ -247,7 +296,7 @@ impl WarnNonDefaultBranchConfig {
     }
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct NoMergesConfig {
     /// No action will be taken on PRs with these substrings in the title.
@@ -264,28 +313,73 @@ pub(crate) struct NoMergesConfig {
     pub(crate) message: Option<String>,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct NoteConfig {
-    #[serde(default)]
+    #[serde(default, skip_serializing)]
     _empty: (),
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct ConcernConfig {
     /// Set the labels on the PR when concerns are active.
     #[serde(default)]
     pub(crate) labels: Vec<String>,
+    /// Maximum number of characters allowed in a concern title.
+    #[serde(default = "ConcernConfig::max_title_length_default")]
+    pub(crate) max_title_length: usize,
+}
+
+impl ConcernConfig {
+    fn max_title_length_default() -> usize {
+        200
+    }
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BlockedOnConfig {
+    /// Label set on an issue while it's blocked on other issues.
+    pub(crate) label: Option<String>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct WatchLabelConfig {
+    #[serde(default, skip_serializing)]
+    _empty: (),
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PriorityConfig {
+    /// Priority level (e.g. `high`) -> label (e.g. `P-high`).
+    ///
+    /// The labels are exclusive, setting a priority removes the other ones.
+    pub(crate) levels: HashMap<String, String>,
+}
+
+impl PriorityConfig {
+    /// Returns the label of the given priority level, matched case-insensitively.
+    pub(crate) fn label(&self, level: &str) -> Option<&str> {
+        self.levels
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(level))
+            .map(|(_, label)| label.as_str())
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub(crate) struct MentionsConfig {
     #[serde(flatten)]
     pub(crate) entries: HashMap<String, MentionsEntryConfig>,
 }
 
-#[derive(PartialEq, Eq, Debug, Default, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Default, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub(crate) enum MentionsEntryType {
     #[default]
@@ -293,7 +387,7 @@ pub(crate) enum MentionsEntryType {
     Content,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct MentionsEntryConfig {
     #[serde(alias = "type")]
@@ -304,35 +398,102 @@ pub(crate) struct MentionsEntryConfig {
     pub(crate) cc: Vec<String>,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub(crate) struct RelabelConfig {
     #[serde(default)]
     pub(crate) allow_unauthenticated: Vec<String>,
+    /// Labels (glob patterns) the author of an issue can remove, even if they
+    /// aren't a team member (e.g. `needs-triage` after adding details).
+    #[serde(default)]
+    pub(crate) author_can_remove: Vec<String>,
+    /// Named sets of label deltas (e.g. `["+C-bug", "-needs-triage"]`), applied
+    /// with `relabel preset NAME`.
+    #[serde(default)]
+    pub(crate) presets: HashMap<String, Vec<String>>,
+    /// Post the reasons given for the added labels (`+label: "reason"`) as a comment.
+    #[serde(default)]
+    pub(crate) post_reasons: bool,
+    /// Labels which shouldn't be used anymore, warned about (or refused) when added.
+    #[serde(default)]
+    pub(crate) deprecated: HashMap<String, DeprecatedLabelConfig>,
+    /// Minimum age (in days) of the GitHub account of non-team members before
+    /// their label commands are honored. Users who already interacted with the
+    /// repository are exempt.
+    #[serde(default)]
+    pub(crate) min_account_age_days: Option<u32>,
     // alias identifier -> labels
     #[serde(flatten)]
     pub(crate) aliases: HashMap<String, RelabelAliasConfig>,
 }
 
 impl RelabelConfig {
-    pub(crate) fn retrieve_command_from_alias(&self, input: RelabelCommand) -> RelabelCommand {
+    /// Replaces the preset of the command (if any) by its configured deltas.
+    pub(crate) fn expand_preset(&self, input: RelabelCommand) -> Result<RelabelCommand, String> {
+        let Some(name) = &input.preset else {
+            return Ok(input);
+        };
+        let Some(preset) = self.presets.get(name) else {
+            return Err(format!("Unknown relabel preset `{name}`."));
+        };
+
+        let mut deltas = preset
+            .iter()
+            .map(|delta| {
+                delta.parse().map_err(|err| {
+                    format!("Invalid delta `{delta}` in the relabel preset `{name}`: {err}")
+                })
+            })
+            .collect::<Result<Vec<LabelDelta>, String>>()?;
+        deltas.extend(input.deltas);
+
+        Ok(RelabelCommand {
+            deltas,
+            preset: None,
+            ..input
+        })
+    }
+
+    pub(crate) fn retrieve_command_from_alias(
+        &self,
+        input: RelabelCommand,
+    ) -> Result<RelabelCommand, String> {
         let mut deltas = vec![];
         // parse all tokens: if one matches an alias, extract the labels
         // else, it will assumed to be a label
-        for tk in input.0.into_iter() {
+        for tk in input.deltas.into_iter() {
             let name = tk.label() as &str;
             if let Some(alias) = self.aliases.get(name) {
-                let cmd = alias.to_command(matches!(tk, LabelDelta::Remove(_)));
-                deltas.extend(cmd.0);
+                let inverted = match tk {
+                    LabelDelta::Add(_) => false,
+                    LabelDelta::Remove(_) => true,
+                    LabelDelta::Toggle(_) => {
+                        return Err(format!(
+                            "The alias `{name}` can't be toggled, use `{name}` or `-{name}` instead."
+                        ));
+                    }
+                };
+                deltas.extend(alias.to_command(inverted).deltas);
             } else {
                 deltas.push(tk);
             }
         }
-        RelabelCommand(deltas)
+        Ok(RelabelCommand { deltas, ..input })
     }
 }
 
-#[derive(Default, PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(Default, PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub(crate) struct DeprecatedLabelConfig {
+    /// The label to use instead, suggested in the warning.
+    pub(crate) replacement: Option<String>,
+    /// Refuse to add the label, instead of adding it with a warning.
+    #[serde(default)]
+    pub(crate) block: bool,
+}
+
+#[derive(Default, PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct RelabelAliasConfig {
@@ -360,24 +521,24 @@ impl RelabelAliasConfig {
         for l in rem_labels.iter() {
             deltas.push(LabelDelta::Remove(Label(l.into())));
         }
-        RelabelCommand(deltas)
+        RelabelCommand::from_deltas(deltas)
     }
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct ShortcutConfig {
-    #[serde(default)]
+    #[serde(default, skip_serializing)]
     _empty: (),
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct PrioritizeConfig {
     pub(crate) label: String,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub(crate) struct AutolabelConfig {
     #[serde(flatten)]
     pub(crate) labels: HashMap<String, AutolabelLabelConfig>,
@@ -395,7 +556,7 @@ impl AutolabelConfig {
     }
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct AutolabelLabelConfig {
     #[serde(default)]
@@ -414,13 +575,14 @@ pub(crate) struct AutolabelLabelConfig {
     pub(crate) pr_merged: bool,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub(crate) struct NotifyZulipConfig {
     #[serde(flatten)]
     pub(crate) labels: HashMap<String, NotifyZulipTablesConfig>,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Serialize)]
+#[serde(transparent)]
 pub(crate) struct NotifyZulipTablesConfig {
     pub(crate) subtables: HashMap<String, NotifyZulipLabelConfig>,
 }
@@ -464,9 +626,10 @@ impl<'de> serde::Deserialize<'de> for NotifyZulipTablesConfig {
     }
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct NotifyZulipLabelConfig {
+    #[serde(serialize_with = "redacted")]
     pub(crate) zulip_stream: u64,
     pub(crate) topic: String,
     #[serde(default)]
@@ -495,17 +658,26 @@ pub(crate) struct NotifyZulipLabelConfig {
     pub(crate) required_labels: Vec<String>,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct MajorChangeConfig {
     /// A username (typically a group, e.g. T-lang) to ping on Zulip for newly
     /// opened proposals.
-    pub(crate) zulip_ping: String,
+    ///
+    /// Can also be a list of groups, the first one which exists on Zulip is pinged,
+    /// the next ones being fallbacks in case the first one was renamed.
+    #[serde(deserialize_with = "string_or_seq")]
+    pub(crate) zulip_ping: Vec<String>,
     /// This label allows an issue to participate in the major change process
     /// (i.e., creates a Zulip thread, tracks seconding, etc.)
+    ///
+    /// Can also be a list of labels, any of them enabling the process.
     // This has a default primarily for backwards compatibility.
-    #[serde(default = "MajorChangeConfig::enabling_label_default")]
-    pub(crate) enabling_label: String,
+    #[serde(
+        default = "MajorChangeConfig::enabling_label_default",
+        deserialize_with = "non_empty_string_or_seq"
+    )]
+    pub(crate) enabling_label: Vec<String>,
     /// This is the label applied when issuing a `@rustbot second` command, it
     /// indicates that the proposal has moved into the 10 day waiting period.
     pub(crate) second_label: String,
@@ -528,17 +700,86 @@ pub(crate) struct MajorChangeConfig {
     pub(crate) auto_closing: bool,
     /// The Zulip stream ID where the messages about the status of
     /// the major changed should be relayed.
+    #[serde(serialize_with = "redacted")]
     pub(crate) zulip_stream: u64,
+    /// Overrides the Zulip stream (and optionally the ping) of the proposals with
+    /// one of these labels, e.g. to discuss the `T-lang` proposals in another stream.
+    /// The first label of the issue with an override wins.
+    #[serde(default)]
+    pub(crate) label_streams: HashMap<String, MajorChangeLabelStreamConfig>,
+    /// If set, a message posted in the topic of a proposal less than this many
+    /// seconds after the previous one is appended to it, instead of being posted
+    /// as a new message.
+    pub(crate) zulip_message_window: Option<u64>,
+    /// Always include the organization and repository in the issue reference of
+    /// the Zulip topics (`org/repo#123`), to disambiguate proposals across repositories.
+    #[serde(default)]
+    pub(crate) zulip_topic_full_reference: bool,
+    /// The maximum length of the Zulip topics, to match the `max_topic_length`
+    /// of self-hosted Zulip servers (60 by default, as on zulipchat.com).
+    #[serde(default = "MajorChangeConfig::zulip_topic_max_length_default")]
+    pub(crate) zulip_topic_max_length: usize,
+    /// Post the boilerplate comment on newly opened proposals, when disabled
+    /// only the Zulip message is sent.
+    #[serde(default = "MajorChangeConfig::post_github_comment_default")]
+    pub(crate) post_github_comment: bool,
+    /// Minimize (hide as outdated) the previous boilerplate comment of the bot
+    /// when it's re-posted with `@rustbot mcp-comment`.
+    #[serde(default)]
+    pub(crate) minimize_superseded_comment: bool,
     /// Extra text in the opening major change.
     pub(crate) open_extra_text: Option<String>,
+    /// An optional Zulip stream ID where newly opened proposals are also
+    /// announced, linking back to the discussion topic.
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "redacted")]
+    pub(crate) announce_stream: Option<u64>,
+    /// An optional emoji (e.g. `eyes`) the bot reacts with on the Zulip message
+    /// announcing a new proposal, so team members can react to claim its review.
+    pub(crate) zulip_initial_reaction: Option<String>,
+    /// Prefix the Zulip topic of accepted proposals with `[accepted]`.
+    #[serde(default)]
+    pub(crate) archive_accepted_topic: bool,
+    /// Mark the Zulip topic of accepted proposals as resolved.
+    #[serde(default)]
+    pub(crate) resolve_accepted_topic: bool,
+    /// Enables handling of proposals without any activity for a while.
+    pub(crate) stale: Option<MajorChangeStaleConfig>,
+    /// Overrides the comment posted on newly opened proposals.
+    pub(crate) new_proposal_template: Option<MajorChangeTemplate>,
+    /// Overrides the Zulip message posted when concerns are raised.
+    pub(crate) concerns_added_template: Option<MajorChangeTemplate>,
+    /// Overrides the Zulip message posted when all concerns are resolved.
+    pub(crate) concerns_resolved_template: Option<MajorChangeTemplate>,
+    /// Comment posted on the issue of a proposal when it's accepted (i.e. when
+    /// the `accept_label` is added), no comment is posted if unset.
+    pub(crate) accepted_proposal_template: Option<MajorChangeTemplate>,
     /// Template for a tracking issue to be created when the major change is accepted
     #[serde(rename = "tracking-issue-template")]
     pub(crate) tracking_issue_template: Option<MajorChangeTrackingIssueTemplateConfig>,
 }
 
 impl MajorChangeConfig {
-    fn enabling_label_default() -> String {
-        String::from("major-change")
+    fn enabling_label_default() -> Vec<String> {
+        vec![String::from("major-change")]
+    }
+
+    /// Whether `label` is one of the labels enabling the major change process.
+    pub(crate) fn is_enabling_label(&self, label: &str) -> bool {
+        self.enabling_label.iter().any(|l| l == label)
+    }
+
+    /// Whether any of the `labels` enables the major change process.
+    pub(crate) fn has_enabling_label(&self, labels: &[crate::github::Label]) -> bool {
+        labels.iter().any(|l| self.is_enabling_label(&l.name))
+    }
+
+    /// The enabling labels, formatted for the error messages (e.g. "`major-change` or `initiative`").
+    pub(crate) fn enabling_labels_description(&self) -> String {
+        self.enabling_label
+            .iter()
+            .map(|l| format!("`{l}`"))
+            .collect::<Vec<_>>()
+            .join(" or ")
     }
     fn accept_label_default() -> String {
         String::from("major-change-accepted")
@@ -546,9 +787,96 @@ impl MajorChangeConfig {
     fn waiting_period_default() -> u16 {
         10
     }
+    fn post_github_comment_default() -> bool {
+        true
+    }
+    fn zulip_topic_max_length_default() -> usize {
+        crate::handlers::major_change::ZULIP_TOPIC_MAX_LENGTH
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct MajorChangeLabelStreamConfig {
+    /// The Zulip stream ID of the proposals with this label.
+    #[serde(serialize_with = "redacted")]
+    pub(crate) zulip_stream: u64,
+    /// The group to ping instead of the default `zulip_ping`, if any.
+    #[serde(default, deserialize_with = "string_or_seq")]
+    pub(crate) zulip_ping: Vec<String>,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+/// A message template of the major change process.
+///
+/// The `{issue}` (issue number), `{url}` (issue URL) and `{stream}` (URL of the
+/// Zulip topic) placeholders are substituted when rendering the template.
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(try_from = "String")]
+pub(crate) struct MajorChangeTemplate(String);
+
+impl MajorChangeTemplate {
+    const PLACEHOLDERS: &[&str] = &["issue", "url", "stream"];
+
+    pub(crate) fn render(&self, issue: u64, url: &str, stream: &str) -> String {
+        self.0
+            .replace("{issue}", &issue.to_string())
+            .replace("{url}", url)
+            .replace("{stream}", stream)
+    }
+}
+
+impl TryFrom<String> for MajorChangeTemplate {
+    type Error = String;
+
+    fn try_from(template: String) -> Result<Self, Self::Error> {
+        // Only `{word}` is considered a placeholder, so that other uses of
+        // braces (e.g. in code blocks) are left alone.
+        let mut rest = template.as_str();
+        while let Some(start) = rest.find('{') {
+            rest = &rest[start + 1..];
+            let Some(end) = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')) else {
+                break;
+            };
+            if end > 0 && rest[end..].starts_with('}') {
+                let placeholder = &rest[..end];
+                if !Self::PLACEHOLDERS.contains(&placeholder) {
+                    return Err(format!(
+                        "unknown placeholder `{{{placeholder}}}` in template, \
+                         expected one of {}",
+                        Self::PLACEHOLDERS
+                            .iter()
+                            .map(|p| format!("`{{{p}}}`"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ));
+                }
+            }
+        }
+        Ok(MajorChangeTemplate(template))
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct MajorChangeStaleConfig {
+    /// Number of days without any activity after which a proposal is considered stale.
+    pub(crate) after_days: u32,
+    /// What to do with stale proposals.
+    #[serde(default)]
+    pub(crate) action: MajorChangeStaleAction,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum MajorChangeStaleAction {
+    /// Only post a comment about the inactivity.
+    #[default]
+    Warn,
+    /// Post a comment about the inactivity and close the proposal.
+    Close,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct MajorChangeTrackingIssueTemplateConfig {
@@ -562,29 +890,61 @@ pub(crate) struct MajorChangeTrackingIssueTemplateConfig {
     pub(crate) body: String,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct LockConfig {}
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct CloseConfig {}
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct MilestoneConfig {}
+
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub(crate) struct EscalateConfig {
+    /// Team name (e.g. `T-compiler`) -> id of the Zulip stream issues are escalated to.
+    #[serde(serialize_with = "redacted_values")]
+    pub(crate) teams: HashMap<String, u64>,
+    /// Users allowed to escalate issues, in addition to the team members.
+    #[serde(default)]
+    pub(crate) allowed_users: Vec<String>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct TrackedByConfig {
+    /// Label set on an issue once it is linked to a tracking issue.
+    pub(crate) label: Option<String>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct NeedsReproConfig {
+    /// Markdown comment posted to ask for a reproduction.
+    pub(crate) message: String,
+    /// Label added to the issue, e.g. `S-needs-repro`.
+    pub(crate) label: Option<String>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct ReviewSubmittedConfig {
     pub(crate) review_labels: Vec<String>,
     pub(crate) reviewed_label: String,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct ReviewRequestedConfig {
     pub(crate) remove_labels: Vec<String>,
     pub(crate) add_labels: Vec<String>,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct GitHubReleasesConfig {
@@ -594,18 +954,73 @@ pub(crate) struct GitHubReleasesConfig {
     pub(crate) changelog_branch: String,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
-pub(crate) struct ReviewPrefsConfig {
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub(crate) struct MilestonePrsConfig {
+    /// The state of the milestones created in the submodule repositories
+    /// (e.g. rust-lang/cargo), "closed" by default.
+    #[serde(default = "MilestonePrsConfig::submodule_milestone_state_default")]
+    pub(crate) submodule_milestone_state: MilestoneState,
+    /// Sections of the changelog generated from a milestone, the merged pull
+    /// requests without any of their labels are listed under "Other".
     #[serde(default)]
+    pub(crate) changelog_sections: Vec<ChangelogSectionConfig>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ChangelogSectionConfig {
+    /// The label of the pull requests listed in the section (e.g. `relnotes-language`).
+    pub(crate) label: String,
+    /// The title of the section (e.g. `Language`).
+    pub(crate) title: String,
+}
+
+impl MilestonePrsConfig {
+    fn submodule_milestone_state_default() -> MilestoneState {
+        MilestoneState::Closed
+    }
+}
+
+/// When present, mutating actions of the handlers (label changes, comments,
+/// milestones, Zulip messages) are only logged instead of being executed.
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct DryRunConfig {
+    #[serde(default, skip_serializing)]
     _empty: (),
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub(crate) struct TemplateLabelsConfig {
+    // checkbox text -> labels added when it is checked in the issue body
+    #[serde(flatten)]
+    pub(crate) checkboxes: HashMap<String, Vec<String>>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ReviewPrefsConfig {
+    /// Enables the periodic rebalancing of the reviewer workqueue.
+    pub(crate) rebalance: Option<WorkqueueRebalanceConfig>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub(crate) struct WorkqueueRebalanceConfig {
+    /// Maximum number of pull requests reassigned on each run.
+    pub(crate) max_moves: usize,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct TransferConfig {}
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct MergeConfig {
@@ -613,14 +1028,14 @@ pub(crate) struct MergeConfig {
     pub(crate) type_: MergeType,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub(crate) enum MergeType {
     /// Github Merge queue
     MergeQueue,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct MergeConflictConfig {
@@ -635,12 +1050,12 @@ pub(crate) struct MergeConflictConfig {
     pub(crate) consider_prs_from_bots: bool,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct BotPullRequests {}
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct RenderedLinkConfig {
@@ -651,7 +1066,7 @@ pub(crate) struct RenderedLinkConfig {
     pub(crate) exclude_files: Vec<String>,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct IssueLinksConfig {
@@ -708,8 +1123,24 @@ impl<'de> serde::Deserialize<'de> for IssueLinksCheckCommitsConfig {
     }
 }
 
+impl serde::Serialize for IssueLinksCheckCommitsConfig {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        // Mirrors the accepted values of the `check-commits` option
+        match self {
+            IssueLinksCheckCommitsConfig::Off => serializer.serialize_bool(false),
+            IssueLinksCheckCommitsConfig::Uncanonicalized => {
+                serializer.serialize_str("uncanonicalized")
+            }
+            IssueLinksCheckCommitsConfig::All => serializer.serialize_bool(true),
+        }
+    }
+}
+
 /// Configuration for PR behind commits checks
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct BehindUpstreamConfig {
@@ -718,14 +1149,14 @@ pub(crate) struct BehindUpstreamConfig {
     pub(crate) days_threshold: Option<usize>,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub(crate) struct BackportConfig {
     // Config identifier -> labels
     #[serde(flatten)]
     pub(crate) configs: HashMap<String, BackportRuleConfig>,
 }
 
-#[derive(Default, PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(Default, PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct BackportRuleConfig {
@@ -738,7 +1169,7 @@ pub(crate) struct BackportRuleConfig {
 }
 
 /// Configuration for rebase range-diff comment
-#[derive(Default, PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(Default, PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct RangeDiffConfig {
@@ -748,13 +1179,13 @@ pub(crate) struct RangeDiffConfig {
 }
 
 /// Configuration for the changes since link on review body
-#[derive(Default, PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(Default, PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct ReviewChangesSinceConfig {}
 
 /// Configuration for "View all comments" link feature
-#[derive(Default, PartialEq, Eq, Debug, Clone, serde::Deserialize)]
+#[derive(Default, PartialEq, Eq, Debug, Clone, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct ViewAllCommentsLinkConfig {
@@ -779,6 +1210,46 @@ struct WithExcludedRepos<T> {
     excluded_repos: Vec<String>,
 }
 
+impl Config {
+    /// The config as JSON, along with the names of its enabled sections
+    /// (and therefore handlers), as returned by the `/config` API.
+    ///
+    /// The sensitive fields (the Zulip streams, which may be private, and the
+    /// users on vacation) are redacted.
+    pub(crate) fn to_json(&self) -> anyhow::Result<serde_json::Value> {
+        let config = serde_json::to_value(self).context("failed to serialize the config")?;
+        let enabled = config
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(_, section)| !section.is_null())
+            .map(|(name, _)| name.clone())
+            .collect::<BTreeSet<_>>();
+        Ok(serde_json::json!({
+            "enabled": enabled,
+            "config": config,
+        }))
+    }
+}
+
+/// The placeholder of the sensitive fields in the serialized config.
+const REDACTED: &str = "<redacted>";
+
+fn redacted<T, S>(_value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(REDACTED)
+}
+
+/// Redacts the values of a map, keeping its keys.
+fn redacted_values<V, S>(map: &HashMap<String, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.collect_map(map.keys().map(|key| (key, REDACTED)))
+}
+
 pub(crate) async fn get(gh: &GithubClient, repo: &Repository) -> MaybeConfig {
     if let Some(config) = get_cached_config(&repo.full_name) {
         log::trace!("returning config for {} from cache", repo.full_name);
@@ -898,6 +1369,18 @@ where
     deserializer.deserialize_any(Visitor)
 }
 
+/// Same as [`string_or_seq`], but rejects an empty list.
+fn non_empty_string_or_seq<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let values = string_or_seq(deserializer)?;
+    if values.is_empty() {
+        return Err(serde::de::Error::custom("expected at least one value"));
+    }
+    Ok(values)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -981,6 +1464,7 @@ mod tests {
                 message: "So many people!".to_owned(),
                 label: Some("T-compiler".to_owned()),
                 alias: HashSet::new(),
+                ping_members: false,
             },
         );
         ping_teams.insert(
@@ -989,6 +1473,7 @@ mod tests {
                 message: "Testing".to_owned(),
                 label: None,
                 alias: HashSet::new(),
+                ping_members: false,
             },
         );
         let mut nominate_teams = HashMap::new();
@@ -1015,6 +1500,11 @@ mod tests {
             Config {
                 relabel: Some(RelabelConfig {
                     allow_unauthenticated: vec!["C-*".into()],
+                    author_can_remove: vec![],
+                    presets: HashMap::new(),
+                    post_reasons: false,
+                    deprecated: HashMap::new(),
+                    min_account_age_days: None,
                     aliases: HashMap::new()
                 }),
                 assign: Some(AssignConfig {
@@ -1023,9 +1513,11 @@ mod tests {
                     adhoc_groups: HashMap::new(),
                     owners: HashMap::new(),
                     users_on_vacation: HashSet::from(["jyn514".into()]),
+                    expertise: HashMap::new(),
                     review_prefs: None,
                     custom_messages: None,
                     community_reviews: None,
+                    re_review: None,
                 }),
                 note: Some(NoteConfig { _empty: () }),
                 ping: Some(PingConfig { teams: ping_teams }),
@@ -1072,6 +1564,14 @@ mod tests {
                 }),
                 no_merges: None,
                 pr_tracking: None,
+                milestone_prs: None,
+                template_labels: None,
+                dry_run: None,
+                milestone: None,
+                escalate: None,
+                tracked_by: None,
+                needs_repro: None,
+                defer: None,
                 transfer: None,
                 merge_conflicts: None,
                 bot_pull_requests: None,
@@ -1087,6 +1587,7 @@ mod tests {
                 }),
                 concern: Some(ConcernConfig {
                     labels: vec!["has-concerns".to_string()],
+                    max_title_length: 200,
                 }),
                 backport: Some(backport_team_config),
                 range_diff: Some(RangeDiffConfig {
@@ -1094,6 +1595,9 @@ mod tests {
                 }),
                 review_changes_since: Some(ReviewChangesSinceConfig {}),
                 view_all_comments_link: None,
+                watch_label: None,
+                priority: None,
+                blocked_on: None,
                 merge: Some(MergeConfig {
                     type_: MergeType::MergeQueue
                 }),
@@ -1155,8 +1659,10 @@ mod tests {
                     adhoc_groups: HashMap::new(),
                     owners: HashMap::new(),
                     users_on_vacation: HashSet::new(),
+                    expertise: HashMap::new(),
                     review_prefs: None,
                     community_reviews: None,
+                    re_review: None,
                 }),
                 note: None,
                 ping: None,
@@ -1175,6 +1681,14 @@ mod tests {
                 mentions: None,
                 no_merges: None,
                 pr_tracking: None,
+                milestone_prs: None,
+                template_labels: None,
+                dry_run: None,
+                milestone: None,
+                escalate: None,
+                tracked_by: None,
+                needs_repro: None,
+                defer: None,
                 transfer: None,
                 merge_conflicts: None,
                 bot_pull_requests: None,
@@ -1189,6 +1703,9 @@ mod tests {
                 range_diff: None,
                 review_changes_since: None,
                 view_all_comments_link: None,
+                watch_label: None,
+                priority: None,
+                blocked_on: None,
                 merge: None,
             }
         );
@@ -1262,6 +1779,11 @@ mod tests {
 
         let expected_cfg = RelabelConfig {
             allow_unauthenticated: vec![],
+            author_can_remove: vec![],
+            presets: HashMap::new(),
+            post_reasons: false,
+            deprecated: HashMap::new(),
+            min_account_age_days: None,
             aliases: relabel_configs,
         };
 
@@ -1275,6 +1797,11 @@ mod tests {
         // rem-labels = ["Bravo", "Charlie"]
         let relabel_cfg = RelabelConfig {
             allow_unauthenticated: vec![],
+            author_can_remove: vec![],
+            presets: HashMap::new(),
+            post_reasons: false,
+            deprecated: HashMap::new(),
+            min_account_age_days: None,
             aliases: HashMap::from([(
                 "my-alias".to_string(),
                 RelabelAliasConfig {
@@ -1286,10 +1813,12 @@ mod tests {
 
         // @triagebot label my-alias
         let deltas = vec![LabelDelta::Add(Label("my-alias".into()))];
-        let new_input = relabel_cfg.retrieve_command_from_alias(RelabelCommand(deltas));
+        let new_input = relabel_cfg
+            .retrieve_command_from_alias(RelabelCommand::from_deltas(deltas))
+            .unwrap();
         assert_eq!(
             new_input,
-            RelabelCommand(vec![
+            RelabelCommand::from_deltas(vec![
                 LabelDelta::Add(Label("Alpha".into())),
                 LabelDelta::Remove(Label("Bravo".into())),
                 LabelDelta::Remove(Label("Charlie".into())),
@@ -1298,15 +1827,82 @@ mod tests {
 
         // @triagebot label -my-alias
         let deltas = vec![LabelDelta::Remove(Label("my-alias".into()))];
-        let new_input = relabel_cfg.retrieve_command_from_alias(RelabelCommand(deltas));
+        let new_input = relabel_cfg
+            .retrieve_command_from_alias(RelabelCommand::from_deltas(deltas))
+            .unwrap();
         assert_eq!(
             new_input,
-            RelabelCommand(vec![
+            RelabelCommand::from_deltas(vec![
                 LabelDelta::Add(Label("Bravo".into())),
                 LabelDelta::Add(Label("Charlie".into())),
                 LabelDelta::Remove(Label("Alpha".into())),
             ])
         );
+
+        // @triagebot label ~my-alias
+        let deltas = vec![LabelDelta::Toggle(Label("my-alias".into()))];
+        assert_eq!(
+            relabel_cfg.retrieve_command_from_alias(RelabelCommand::from_deltas(deltas)),
+            Err(
+                "The alias `my-alias` can't be toggled, use `my-alias` or `-my-alias` instead."
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn relabel_presets() {
+        let config = r#"
+            [relabel]
+            allow-unauthenticated = ["C-*"]
+            presets = { "new-bug" = ["+C-bug", "+needs-triage"] }
+        "#;
+        let config = toml::from_str::<Config>(&config).unwrap();
+
+        assert_eq!(
+            config.relabel,
+            Some(RelabelConfig {
+                allow_unauthenticated: vec!["C-*".to_string()],
+                author_can_remove: vec![],
+                presets: HashMap::from([(
+                    "new-bug".to_string(),
+                    vec!["+C-bug".to_string(), "+needs-triage".to_string()]
+                )]),
+                post_reasons: false,
+                deprecated: HashMap::new(),
+                min_account_age_days: None,
+                aliases: HashMap::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn relabel_deprecated() {
+        let config = r#"
+            [relabel]
+            deprecated = { "A-old" = { replacement = "A-new" }, "I-prioritize" = { block = true } }
+        "#;
+        let config = toml::from_str::<Config>(&config).unwrap();
+
+        assert_eq!(
+            config.relabel.unwrap().deprecated,
+            HashMap::from([
+                (
+                    "A-old".to_string(),
+                    DeprecatedLabelConfig {
+                        replacement: Some("A-new".to_string()),
+                        block: false,
+                    }
+                ),
+                (
+                    "I-prioritize".to_string(),
+                    DeprecatedLabelConfig {
+                        replacement: None,
+                        block: true,
+                    }
+                ),
+            ])
+        );
     }
 
     #[test]
@@ -1314,15 +1910,22 @@ mod tests {
         // empty alias config
         let relabel_cfg = RelabelConfig {
             allow_unauthenticated: vec![],
+            author_can_remove: vec![],
+            presets: HashMap::new(),
+            post_reasons: false,
+            deprecated: HashMap::new(),
+            min_account_age_days: None,
             aliases: HashMap::new(),
         };
 
         // @triagebot label T-compiler
         let deltas = vec![LabelDelta::Add(Label("T-compiler".into()))];
-        let new_input = relabel_cfg.retrieve_command_from_alias(RelabelCommand(deltas));
+        let new_input = relabel_cfg
+            .retrieve_command_from_alias(RelabelCommand::from_deltas(deltas))
+            .unwrap();
         assert_eq!(
             new_input,
-            RelabelCommand(vec![LabelDelta::Add(Label("T-compiler".into())),])
+            RelabelCommand::from_deltas(vec![LabelDelta::Add(Label("T-compiler".into())),])
         );
     }
 
@@ -1341,6 +1944,203 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn major_change_stale() {
+        let config = r#"
+            [major-change]
+            meeting_label = "to-announce"
+            second_label = "final-comment-period"
+            zulip_stream = 224082
+            zulip_ping = "Urgau"
+
+            [major-change.stale]
+            after_days = 90
+            action = "close"
+        "#;
+        let config = toml::from_str::<Config>(&config).unwrap();
+        assert_eq!(
+            config.major_change.unwrap().stale,
+            Some(MajorChangeStaleConfig {
+                after_days: 90,
+                action: MajorChangeStaleAction::Close,
+            })
+        );
+    }
+
+    #[test]
+    fn major_change_enabling_labels() {
+        let config = |enabling_label: &str| {
+            let config = format!(
+                r#"
+                [major-change]
+                meeting_label = "to-announce"
+                second_label = "final-comment-period"
+                zulip_stream = 224082
+                zulip_ping = "Urgau"
+                {enabling_label}
+                "#
+            );
+            toml::from_str::<Config>(&config)
+                .unwrap()
+                .major_change
+                .unwrap()
+        };
+        let labels = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| crate::github::Label {
+                    name: name.to_string(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let single = config(r#"enabling_label = "major-change""#);
+        assert_eq!(single.enabling_label, vec!["major-change"]);
+        assert!(single.has_enabling_label(&labels(&["T-compiler", "major-change"])));
+        assert!(!single.has_enabling_label(&labels(&["initiative"])));
+        assert_eq!(single.enabling_labels_description(), "`major-change`");
+
+        let multi = config(r#"enabling_label = ["major-change", "initiative"]"#);
+        assert!(multi.has_enabling_label(&labels(&["major-change"])));
+        assert!(multi.has_enabling_label(&labels(&["initiative"])));
+        assert!(!multi.has_enabling_label(&labels(&["T-compiler"])));
+        assert_eq!(
+            multi.enabling_labels_description(),
+            "`major-change` or `initiative`"
+        );
+
+        assert_eq!(config("").enabling_label, vec!["major-change"]);
+
+        let empty = toml::from_str::<Config>(
+            r#"
+            [major-change]
+            meeting_label = "to-announce"
+            second_label = "final-comment-period"
+            zulip_stream = 224082
+            zulip_ping = "Urgau"
+            enabling_label = []
+            "#,
+        );
+        assert!(empty.is_err());
+    }
+
+    #[test]
+    fn pr_tracking_rebalance() {
+        let config = r#"
+            [pr-tracking.rebalance]
+            max-moves = 3
+        "#;
+        let config = toml::from_str::<Config>(&config).unwrap();
+        assert_eq!(
+            config.pr_tracking,
+            Some(ReviewPrefsConfig {
+                rebalance: Some(WorkqueueRebalanceConfig { max_moves: 3 }),
+            })
+        );
+
+        let config = toml::from_str::<Config>("[pr-tracking]").unwrap();
+        assert_eq!(
+            config.pr_tracking,
+            Some(ReviewPrefsConfig { rebalance: None })
+        );
+    }
+
+    #[test]
+    fn milestone_prs() {
+        let config = toml::from_str::<Config>("[milestone-prs]").unwrap();
+        assert_eq!(
+            config.milestone_prs,
+            Some(MilestonePrsConfig {
+                submodule_milestone_state: MilestoneState::Closed,
+                changelog_sections: vec![],
+            })
+        );
+
+        let config = r#"
+            [milestone-prs]
+            submodule-milestone-state = "open"
+            changelog-sections = [
+                { label = "relnotes-language", title = "Language" },
+            ]
+        "#;
+        let config = toml::from_str::<Config>(config).unwrap();
+        assert_eq!(
+            config.milestone_prs,
+            Some(MilestonePrsConfig {
+                submodule_milestone_state: MilestoneState::Open,
+                changelog_sections: vec![ChangelogSectionConfig {
+                    label: "relnotes-language".to_string(),
+                    title: "Language".to_string(),
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn major_change_templates() {
+        let config = r#"
+            [major-change]
+            meeting_label = "to-announce"
+            second_label = "final-comment-period"
+            zulip_stream = 224082
+            zulip_ping = "Urgau"
+            concerns_added_template = "Concerns raised on [#{issue}]({url}), see {stream}."
+        "#;
+        let config = toml::from_str::<Config>(&config).unwrap();
+        let template = config
+            .major_change
+            .unwrap()
+            .concerns_added_template
+            .unwrap();
+        assert_eq!(
+            template.render(
+                123,
+                "https://github.com/rust-lang/compiler-team/issues/123",
+                "https://rust-lang.zulipchat.com/#narrow/channel/233931"
+            ),
+            "Concerns raised on [#123](https://github.com/rust-lang/compiler-team/issues/123), \
+             see https://rust-lang.zulipchat.com/#narrow/channel/233931."
+        );
+
+        let config = r#"
+            [major-change]
+            meeting_label = "to-announce"
+            second_label = "final-comment-period"
+            zulip_stream = 224082
+            zulip_ping = "Urgau"
+            new_proposal_template = "New proposal {issue} by {author}"
+        "#;
+        let err = toml::from_str::<Config>(&config).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("unknown placeholder `{author}` in template"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn major_change_zulip_ping_fallbacks() {
+        let config = r#"
+            [major-change]
+            meeting_label = "to-announce"
+            second_label = "final-comment-period"
+            zulip_stream = 224082
+            zulip_ping = ["T-compiler", "t-compiler"]
+        "#;
+        let config = toml::from_str::<Config>(&config).unwrap();
+        assert_eq!(
+            config.major_change.unwrap().zulip_ping,
+            vec!["T-compiler".to_string(), "t-compiler".to_string()]
+        );
+    }
+
+    #[test]
+    fn major_change_template_ignores_other_braces() {
+        let template =
+            MajorChangeTemplate::try_from("fn main() { {issue} }\n{ x: 1 }".to_string()).unwrap();
+        assert_eq!(template.render(1, "", ""), "fn main() { 1 }\n{ x: 1 }");
+    }
+
     #[test]
     fn major_change() {
         let config = r#"
@@ -1355,6 +2155,13 @@ mod tests {
             zulip_stream = 224082
             zulip_ping = "Urgau"
 
+            [major-change.label_streams.T-lang]
+            zulip_stream = 213817
+            zulip_ping = "T-lang"
+
+            [major-change.label_streams.T-types]
+            zulip_stream = 326866
+
             [major-change.tracking-issue-template]
             repository = "triagebot"
             title = "Tracking issue for MCP#${mcp_number}"
@@ -1367,8 +2174,8 @@ Multi text body with ${mcp_issue} and ${mcp_title}
         assert_eq!(
             config.major_change,
             Some(MajorChangeConfig {
-                zulip_ping: "Urgau".to_string(),
-                enabling_label: "major-change".to_string(),
+                zulip_ping: vec!["Urgau".to_string()],
+                enabling_label: vec!["major-change".to_string()],
                 second_label: "final-comment-period".to_string(),
                 accept_label: "major-change-accepted".to_string(),
                 meeting_label: "to-announce".to_string(),
@@ -1376,7 +2183,37 @@ Multi text body with ${mcp_issue} and ${mcp_title}
                 waiting_period: 1,
                 auto_closing: true,
                 zulip_stream: 224082,
+                label_streams: HashMap::from([
+                    (
+                        "T-lang".to_string(),
+                        MajorChangeLabelStreamConfig {
+                            zulip_stream: 213817,
+                            zulip_ping: vec!["T-lang".to_string()],
+                        }
+                    ),
+                    (
+                        "T-types".to_string(),
+                        MajorChangeLabelStreamConfig {
+                            zulip_stream: 326866,
+                            zulip_ping: vec![],
+                        }
+                    ),
+                ]),
+                zulip_message_window: None,
+                zulip_topic_full_reference: false,
+                zulip_topic_max_length: 60,
+                post_github_comment: true,
+                minimize_superseded_comment: false,
                 open_extra_text: None,
+                announce_stream: None,
+                zulip_initial_reaction: None,
+                archive_accepted_topic: false,
+                resolve_accepted_topic: false,
+                stale: None,
+                new_proposal_template: None,
+                concerns_added_template: None,
+                concerns_resolved_template: None,
+                accepted_proposal_template: None,
                 tracking_issue_template: Some(MajorChangeTrackingIssueTemplateConfig {
                     title: "Tracking issue for MCP#${mcp_number}".to_string(),
                     repository: Some("triagebot".to_string()),
@@ -1413,6 +2250,11 @@ Multi text body with ${mcp_issue} and ${mcp_title}
 
         let expected_cfg = RelabelConfig {
             allow_unauthenticated: vec!["ABCD-*".to_string()],
+            author_can_remove: vec![],
+            presets: HashMap::new(),
+            post_reasons: false,
+            deprecated: HashMap::new(),
+            min_account_age_days: None,
             aliases: relabel_configs,
         };
 
@@ -1738,4 +2580,67 @@ Multi text body with ${mcp_issue} and ${mcp_title}
             }
         );
     }
+
+    #[test]
+    fn config_to_json() {
+        let config = r#"
+            [relabel]
+            allow-unauthenticated = ["A-*", "!A-secret"]
+
+            [note]
+
+            [issue-links]
+            check-commits = "uncanonicalized"
+
+            [major-change]
+            zulip_ping = "T-compiler"
+            second_label = "final-comment-period"
+            meeting_label = "to-announce"
+            zulip_stream = 1
+        "#;
+        let config = toml::from_str::<Config>(config).unwrap();
+        let json = config.to_json().unwrap();
+
+        assert_eq!(
+            json["enabled"],
+            serde_json::json!(["issue-links", "major-change", "note", "relabel"])
+        );
+        assert_eq!(
+            json["config"]["relabel"]["allow-unauthenticated"],
+            serde_json::json!(["A-*", "!A-secret"])
+        );
+        assert_eq!(json["config"]["note"], serde_json::json!({}));
+        assert_eq!(
+            json["config"]["issue-links"],
+            serde_json::json!({ "check-commits": "uncanonicalized" })
+        );
+        assert_eq!(json["config"]["assign"], serde_json::Value::Null);
+
+        // The serialized sections match the parsed structs, defaults included.
+        let major_change = config.major_change.as_ref().unwrap();
+        assert_eq!(
+            json["config"]["major-change"]["zulip_ping"],
+            serde_json::json!(major_change.zulip_ping)
+        );
+        assert_eq!(
+            json["config"]["major-change"]["accept_label"],
+            serde_json::json!(major_change.accept_label)
+        );
+        assert_eq!(
+            json["config"]["major-change"]["waiting_period"],
+            serde_json::json!(major_change.waiting_period)
+        );
+
+        // The Zulip streams are redacted, and unset ones are omitted.
+        assert_eq!(
+            json["config"]["major-change"]["zulip_stream"],
+            serde_json::json!("<redacted>")
+        );
+        assert!(
+            !json["config"]["major-change"]
+                .as_object()
+                .unwrap()
+                .contains_key("announce_stream")
+        );
+    }
 }
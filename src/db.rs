@@ -7,11 +7,23 @@ use std::sync::{Arc, LazyLock, Mutex};
 use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio_postgres::Client as DbClient;
 
+pub mod agenda_archive;
+pub mod disabled_handlers;
+pub mod issue_blockers;
 pub mod issue_data;
+pub mod issue_transfers;
 pub mod jobs;
+pub mod label_subscriptions;
+pub mod major_change_holds;
+pub mod major_change_topics;
+pub mod milestone_progress;
+pub mod review_deferrals;
 pub mod review_prefs;
 pub mod rustc_commits;
+pub mod tracking_issues;
 pub mod users;
+pub mod zulip_rename_breadcrumbs;
+pub mod zulip_topic_messages;
 
 const CERT_URL: &str = "https://truststore.pki.rds.amazonaws.com/global/global-bundle.pem";
 
@@ -21,6 +33,7 @@ static CERTIFICATE_PEMS: LazyLock<Vec<u8>> = LazyLock::new(|| {
     resp.bytes().expect("failed to get RDS cert body").to_vec()
 });
 
+#[derive(Clone)]
 pub struct ClientPool {
     connections: Arc<Mutex<Vec<tokio_postgres::Client>>>,
     permits: Arc<Semaphore>,
@@ -235,12 +248,22 @@ pub async fn schedule_job(
     Ok(())
 }
 
-pub async fn run_scheduled_jobs(ctx: &Context) -> anyhow::Result<()> {
+/// Runs the jobs due for execution, stopping early (between two jobs) once
+/// `shutdown` is set.
+pub async fn run_scheduled_jobs(
+    ctx: &Context,
+    shutdown: &tokio::sync::watch::Receiver<bool>,
+) -> anyhow::Result<()> {
     let db = &ctx.db.get().await;
     let jobs = get_jobs_to_execute(db).await?;
     tracing::trace!("jobs to execute: {jobs:#?}");
 
     for job in &jobs {
+        if *shutdown.borrow() {
+            tracing::info!("shutting down, not running the remaining jobs");
+            break;
+        }
+
         update_job_executed_at(db, &job.id).await?;
 
         match handle_job(ctx, &job.name, &job.metadata).await {
@@ -369,4 +392,103 @@ SELECT user_id, 'rust-lang/rust', max_assigned_prs
 FROM review_prefs
 WHERE max_assigned_prs IS NOT NULL
     "#,
+    r#"
+CREATE TABLE IF NOT EXISTS issue_blockers (
+    repo TEXT NOT NULL,
+    issue_number INTEGER NOT NULL,
+    blocked_on INTEGER NOT NULL,
+    PRIMARY KEY (repo, issue_number, blocked_on)
+);
+"#,
+    r#"
+CREATE TABLE IF NOT EXISTS label_subscriptions (
+    user_id BIGINT REFERENCES users(user_id),
+    repo TEXT NOT NULL,
+    issue_number INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    PRIMARY KEY (user_id, repo, issue_number, label)
+);
+"#,
+    r#"
+CREATE TABLE IF NOT EXISTS zulip_topic_messages (
+    stream_id BIGINT NOT NULL,
+    topic TEXT NOT NULL,
+    message_id BIGINT NOT NULL,
+    content TEXT NOT NULL,
+    posted_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (stream_id, topic)
+);
+"#,
+    r#"
+CREATE TABLE IF NOT EXISTS major_change_holds (
+    repo TEXT NOT NULL,
+    issue_number BIGINT NOT NULL,
+    held_by TEXT NOT NULL,
+    held_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (repo, issue_number)
+);
+"#,
+    r#"
+CREATE TABLE IF NOT EXISTS zulip_rename_breadcrumbs (
+    stream_id BIGINT NOT NULL,
+    repo TEXT NOT NULL,
+    issue_number BIGINT NOT NULL,
+    topic TEXT NOT NULL,
+    message_id BIGINT NOT NULL,
+    PRIMARY KEY (stream_id, repo, issue_number)
+);
+"#,
+    r#"
+CREATE TABLE IF NOT EXISTS agenda_archive (
+    team TEXT NOT NULL,
+    iso_year INTEGER NOT NULL,
+    iso_week INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    archived_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (team, iso_year, iso_week)
+);
+"#,
+    r#"
+CREATE TABLE IF NOT EXISTS disabled_handlers (
+    repo TEXT NOT NULL,
+    handler TEXT NOT NULL,
+    disabled_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (repo, handler)
+);
+"#,
+    r#"
+CREATE TABLE IF NOT EXISTS tracking_issues (
+    repo TEXT NOT NULL,
+    issue_number BIGINT NOT NULL,
+    tracking_issue BIGINT NOT NULL,
+    PRIMARY KEY (repo, issue_number, tracking_issue)
+);
+"#,
+    r#"
+CREATE TABLE IF NOT EXISTS review_deferrals (
+    repo TEXT NOT NULL,
+    pr_number BIGINT NOT NULL,
+    user_login TEXT NOT NULL,
+    deferred_until TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (repo, pr_number, user_login)
+);
+"#,
+    r#"
+CREATE TABLE IF NOT EXISTS milestone_progress (
+    repo TEXT NOT NULL,
+    milestone TEXT NOT NULL,
+    pr_number BIGINT NOT NULL,
+    PRIMARY KEY (repo, milestone, pr_number)
+);
+"#,
+    r#"
+CREATE TABLE IF NOT EXISTS major_change_topics (
+    repo TEXT NOT NULL,
+    issue_number BIGINT NOT NULL,
+    stream_id BIGINT NOT NULL,
+    topic TEXT NOT NULL,
+    PRIMARY KEY (repo, issue_number)
+);
+"#,
+    "ALTER TABLE major_change_holds ADD COLUMN released_at TIMESTAMP WITH TIME ZONE;",
 ];
@@ -0,0 +1,105 @@
+//! The `agenda_archive` table keeps the meeting agendas generated with
+//! `?archive=1`, one per team and ISO week.
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use tokio_postgres::Client as DbClient;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchivedAgenda {
+    pub content: String,
+    /// When the agenda was last archived.
+    pub archived_at: DateTime<Utc>,
+}
+
+/// Archives the agenda of the team for the ISO week, replacing any agenda
+/// already archived for that week.
+pub async fn archive_agenda(
+    db: &DbClient,
+    team: &str,
+    iso_year: i32,
+    iso_week: u32,
+    agenda: &ArchivedAgenda,
+) -> anyhow::Result<()> {
+    db.execute(
+        r"
+INSERT INTO agenda_archive (team, iso_year, iso_week, content, archived_at)
+VALUES ($1, $2, $3, $4, $5)
+ON CONFLICT (team, iso_year, iso_week) DO UPDATE
+SET content = EXCLUDED.content, archived_at = EXCLUDED.archived_at",
+        &[
+            &team,
+            &iso_year,
+            &(iso_week as i32),
+            &agenda.content,
+            &agenda.archived_at,
+        ],
+    )
+    .await
+    .context("upserting archived agenda")?;
+    Ok(())
+}
+
+/// Returns the agenda of the team archived for the ISO week, if any.
+pub async fn get_archived_agenda(
+    db: &DbClient,
+    team: &str,
+    iso_year: i32,
+    iso_week: u32,
+) -> anyhow::Result<Option<ArchivedAgenda>> {
+    let row = db
+        .query_opt(
+            r"
+SELECT content, archived_at
+FROM agenda_archive
+WHERE team = $1 AND iso_year = $2 AND iso_week = $3",
+            &[&team, &iso_year, &(iso_week as i32)],
+        )
+        .await
+        .context("selecting archived agenda")?;
+    Ok(row.map(|row| ArchivedAgenda {
+        content: row.get(0),
+        archived_at: row.get(1),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::agenda_archive::{ArchivedAgenda, archive_agenda, get_archived_agenda};
+    use crate::tests::run_db_test;
+
+    #[tokio::test]
+    async fn archive_round_trip() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let first = ArchivedAgenda {
+                content: "# Types planning\n\n- first draft".to_string(),
+                archived_at: "2025-02-10T10:00:00Z".parse().unwrap(),
+            };
+
+            assert_eq!(get_archived_agenda(&db, "types", 2025, 7).await?, None);
+            archive_agenda(&db, "types", 2025, 7, &first).await?;
+            assert_eq!(
+                get_archived_agenda(&db, "types", 2025, 7).await?,
+                Some(first)
+            );
+
+            // Archiving again the same week replaces the agenda
+            let second = ArchivedAgenda {
+                content: "# Types planning\n\n- final".to_string(),
+                archived_at: "2025-02-11T10:00:00Z".parse().unwrap(),
+            };
+            archive_agenda(&db, "types", 2025, 7, &second).await?;
+            assert_eq!(
+                get_archived_agenda(&db, "types", 2025, 7).await?,
+                Some(second)
+            );
+
+            assert_eq!(get_archived_agenda(&db, "types", 2025, 8).await?, None);
+            assert_eq!(get_archived_agenda(&db, "lang", 2025, 7).await?, None);
+
+            Ok(ctx)
+        })
+        .await;
+    }
+}
@@ -0,0 +1,80 @@
+//! The `disabled_handlers` table records the handlers temporarily disabled in
+//! a repository, e.g. while one of them misbehaves, without having to edit its
+//! `triagebot.toml`.
+
+use std::collections::HashSet;
+
+use anyhow::Context;
+use tokio_postgres::Client as DbClient;
+
+/// Returns the names of the handlers disabled in the repository.
+pub async fn get_disabled_handlers(db: &DbClient, repo: &str) -> anyhow::Result<HashSet<String>> {
+    let rows = db
+        .query(
+            "SELECT handler FROM disabled_handlers WHERE repo = $1",
+            &[&repo],
+        )
+        .await
+        .context("selecting disabled handlers")?;
+    Ok(rows.into_iter().map(|row| row.get(0)).collect())
+}
+
+/// Disables or re-enables the handler in the repository.
+///
+/// Returns `false` if the handler was already in the requested state.
+pub async fn set_handler_disabled(
+    db: &DbClient,
+    repo: &str,
+    handler: &str,
+    disabled: bool,
+) -> anyhow::Result<bool> {
+    let changed = if disabled {
+        db.execute(
+            r"
+INSERT INTO disabled_handlers (repo, handler, disabled_at)
+VALUES ($1, $2, NOW())
+ON CONFLICT DO NOTHING",
+            &[&repo, &handler],
+        )
+        .await
+        .context("inserting disabled handler")?
+    } else {
+        db.execute(
+            "DELETE FROM disabled_handlers WHERE repo = $1 AND handler = $2",
+            &[&repo, &handler],
+        )
+        .await
+        .context("deleting disabled handler")?
+    };
+    Ok(changed == 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::disabled_handlers::{get_disabled_handlers, set_handler_disabled};
+    use crate::tests::run_db_test;
+
+    #[tokio::test]
+    async fn disable_and_enable() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let repo = "rust-lang/rust";
+
+            assert!(get_disabled_handlers(&db, repo).await?.is_empty());
+            assert!(set_handler_disabled(&db, repo, "milestone_prs", true).await?);
+            assert!(!set_handler_disabled(&db, repo, "milestone_prs", true).await?);
+            assert!(set_handler_disabled(&db, "rust-lang/cargo", "relabel", true).await?);
+            assert_eq!(
+                get_disabled_handlers(&db, repo).await?,
+                ["milestone_prs".to_string()].into()
+            );
+
+            assert!(set_handler_disabled(&db, repo, "milestone_prs", false).await?);
+            assert!(!set_handler_disabled(&db, repo, "milestone_prs", false).await?);
+            assert!(get_disabled_handlers(&db, repo).await?.is_empty());
+
+            Ok(ctx)
+        })
+        .await;
+    }
+}
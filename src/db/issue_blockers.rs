@@ -0,0 +1,134 @@
+//! The `issue_blockers` table tracks which issues are blocked on other issues
+//! of the same repository, as registered with `@rustbot blocked-on #123`.
+
+use anyhow::Context;
+use tokio_postgres::Client as DbClient;
+
+/// Records that `issue_number` is blocked on `blocked_on`.
+pub async fn add_blocker(
+    db: &DbClient,
+    repo: &str,
+    issue_number: u64,
+    blocked_on: u64,
+) -> anyhow::Result<()> {
+    db.execute(
+        r"
+INSERT INTO issue_blockers (repo, issue_number, blocked_on) VALUES ($1, $2, $3)
+ON CONFLICT DO NOTHING",
+        &[&repo, &(issue_number as i32), &(blocked_on as i32)],
+    )
+    .await
+    .context("inserting issue blocker")?;
+    Ok(())
+}
+
+/// Returns the issues which are blocked on `blocked_on`.
+pub async fn get_blocked_issues(
+    db: &DbClient,
+    repo: &str,
+    blocked_on: u64,
+) -> anyhow::Result<Vec<u64>> {
+    let rows = db
+        .query(
+            r"
+SELECT issue_number
+FROM issue_blockers
+WHERE repo = $1 AND blocked_on = $2
+ORDER BY issue_number",
+            &[&repo, &(blocked_on as i32)],
+        )
+        .await
+        .context("selecting blocked issues")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| row.get::<_, i32>(0) as u64)
+        .collect())
+}
+
+/// Returns the issues `issue_number` is still blocked on.
+pub async fn get_blockers(
+    db: &DbClient,
+    repo: &str,
+    issue_number: u64,
+) -> anyhow::Result<Vec<u64>> {
+    let rows = db
+        .query(
+            r"
+SELECT blocked_on
+FROM issue_blockers
+WHERE repo = $1 AND issue_number = $2
+ORDER BY blocked_on",
+            &[&repo, &(issue_number as i32)],
+        )
+        .await
+        .context("selecting issue blockers")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| row.get::<_, i32>(0) as u64)
+        .collect())
+}
+
+/// Removes the edge of `issue_number` being blocked on `blocked_on`.
+///
+/// Returns `false` if there was no such edge.
+pub async fn remove_blocker(
+    db: &DbClient,
+    repo: &str,
+    issue_number: u64,
+    blocked_on: u64,
+) -> anyhow::Result<bool> {
+    let deleted = db
+        .execute(
+            r"
+DELETE FROM issue_blockers
+WHERE repo = $1 AND issue_number = $2 AND blocked_on = $3",
+            &[&repo, &(issue_number as i32), &(blocked_on as i32)],
+        )
+        .await
+        .context("deleting issue blocker")?;
+    Ok(deleted == 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::issue_blockers::{
+        add_blocker, get_blocked_issues, get_blockers, remove_blocker,
+    };
+    use crate::tests::run_db_test;
+
+    #[tokio::test]
+    async fn add_and_remove_blockers() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+
+            add_blocker(&db, "rust-lang/rust", 10, 1).await?;
+            add_blocker(&db, "rust-lang/rust", 11, 1).await?;
+            add_blocker(&db, "rust-lang/rust", 11, 2).await?;
+            // Adding the same edge twice is a no-op
+            add_blocker(&db, "rust-lang/rust", 10, 1).await?;
+            // Same numbers in another repository are unrelated
+            add_blocker(&db, "rust-lang/cargo", 12, 1).await?;
+
+            assert_eq!(
+                get_blocked_issues(&db, "rust-lang/rust", 1).await?,
+                vec![10, 11]
+            );
+            assert_eq!(get_blockers(&db, "rust-lang/rust", 11).await?, vec![1, 2]);
+
+            assert!(remove_blocker(&db, "rust-lang/rust", 11, 1).await?);
+            assert!(!remove_blocker(&db, "rust-lang/rust", 11, 1).await?);
+            assert_eq!(
+                get_blocked_issues(&db, "rust-lang/rust", 1).await?,
+                vec![10]
+            );
+            assert_eq!(get_blockers(&db, "rust-lang/rust", 11).await?, vec![2]);
+            assert_eq!(
+                get_blocked_issues(&db, "rust-lang/cargo", 1).await?,
+                vec![12]
+            );
+
+            Ok(ctx)
+        })
+        .await;
+    }
+}
@@ -0,0 +1,116 @@
+//! Moves the rows tracked for an issue (notes, concerns, holds, ...) to its new
+//! repository and number when the issue is transferred, dropping its blockers
+//! and tracking issues which can't cross repositories.
+
+use anyhow::Context;
+use tokio_postgres::Client as DbClient;
+
+/// The tables with rows keyed by `(repo, issue_number)`, and whether their
+/// `issue_number` column is a `BIGINT` (otherwise an `INTEGER`).
+///
+/// The edges of [`EDGE_TABLES`] are handled separately, as both of their ends
+/// are issues of the repository.
+const ISSUE_TABLES: &[(&str, bool)] = &[
+    ("issue_data", false),
+    ("label_subscriptions", false),
+    ("major_change_holds", true),
+    ("major_change_topics", true),
+    ("zulip_rename_breadcrumbs", true),
+];
+
+/// The tables with edges between two issues of the same repository, with the
+/// columns of both ends and whether they are `BIGINT`s (otherwise `INTEGER`s).
+const EDGE_TABLES: &[(&str, &str, &str, bool)] = &[
+    ("issue_blockers", "issue_number", "blocked_on", false),
+    ("tracking_issues", "issue_number", "tracking_issue", true),
+];
+
+/// The rows of a transferred issue.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TransferredRows {
+    /// The number of rows moved to the new issue.
+    pub moved: u64,
+    /// The edges of [`EDGE_TABLES`] dropped, as `(table, from, to)` in the old
+    /// repository: both ends are always issues of the same repository, which
+    /// the transferred issue isn't part of anymore.
+    pub dropped_edges: Vec<(&'static str, u64, u64)>,
+}
+
+/// Moves the rows of `from_repo#from_number` to `to_repo#to_number`, in a single
+/// transaction.
+///
+/// Rows already recorded for `to_repo#to_number` (e.g. left over by a deleted
+/// issue) are replaced by the ones of the transferred issue.
+pub async fn transfer_issue(
+    db: &mut DbClient,
+    (from_repo, from_number): (&str, u64),
+    (to_repo, to_number): (&str, u64),
+) -> anyhow::Result<TransferredRows> {
+    let transaction = db.transaction().await?;
+    let mut rows = TransferredRows::default();
+    for (table, bigint) in ISSUE_TABLES {
+        let delete = format!("DELETE FROM {table} WHERE repo = $1 AND issue_number = $2");
+        let update = format!(
+            "UPDATE {table} SET repo = $1, issue_number = $2 WHERE repo = $3 AND issue_number = $4"
+        );
+        let res = if *bigint {
+            let (to_number, from_number) = (to_number as i64, from_number as i64);
+            transaction
+                .execute(&delete, &[&to_repo, &to_number])
+                .await
+                .with_context(|| format!("replacing the rows of {table}"))?;
+            transaction
+                .execute(&update, &[&to_repo, &to_number, &from_repo, &from_number])
+                .await
+        } else {
+            let (to_number, from_number) = (to_number as i32, from_number as i32);
+            transaction
+                .execute(&delete, &[&to_repo, &to_number])
+                .await
+                .with_context(|| format!("replacing the rows of {table}"))?;
+            transaction
+                .execute(&update, &[&to_repo, &to_number, &from_repo, &from_number])
+                .await
+        };
+        rows.moved +=
+            res.with_context(|| format!("moving the transferred issue rows of {table}"))?;
+    }
+
+    for (table, from, to, bigint) in EDGE_TABLES {
+        let query = format!(
+            "DELETE FROM {table} WHERE repo = $1 AND ({from} = $2 OR {to} = $2) RETURNING {from}, {to}"
+        );
+        let dropped = if *bigint {
+            transaction
+                .query(&query, &[&from_repo, &(from_number as i64)])
+                .await
+                .map(|dropped| {
+                    dropped
+                        .into_iter()
+                        .map(|row| (row.get::<_, i64>(0) as u64, row.get::<_, i64>(1) as u64))
+                        .collect::<Vec<_>>()
+                })
+        } else {
+            transaction
+                .query(&query, &[&from_repo, &(from_number as i32)])
+                .await
+                .map(|dropped| {
+                    dropped
+                        .into_iter()
+                        .map(|row| (row.get::<_, i32>(0) as u64, row.get::<_, i32>(1) as u64))
+                        .collect::<Vec<_>>()
+                })
+        };
+        let mut dropped =
+            dropped.with_context(|| format!("dropping the transferred issue edges of {table}"))?;
+        dropped.sort();
+        rows.dropped_edges
+            .extend(dropped.into_iter().map(|(from, to)| (*table, from, to)));
+    }
+
+    transaction
+        .commit()
+        .await
+        .context("committing the issue transfer")?;
+    Ok(rows)
+}
@@ -0,0 +1,109 @@
+//! The `label_subscriptions` table stores the users who want to be notified
+//! on Zulip when a given label is added to or removed from an issue, as
+//! registered with `@rustbot watch-label LABEL`.
+
+use crate::db::users::{DbUser, record_username};
+use crate::github::GitHubUser;
+use anyhow::Context;
+use tokio_postgres::Client as DbClient;
+
+/// Subscribes `user` to the changes of `label` on the given issue.
+pub async fn subscribe(
+    db: &DbClient,
+    user: &GitHubUser,
+    repo: &str,
+    issue_number: u64,
+    label: &str,
+) -> anyhow::Result<()> {
+    record_username(db, user.id, &user.login)
+        .await
+        .context("failed to record username")?;
+    db.execute(
+        r"
+INSERT INTO label_subscriptions (user_id, repo, issue_number, label) VALUES ($1, $2, $3, $4)
+ON CONFLICT DO NOTHING",
+        &[&(user.id as i64), &repo, &(issue_number as i32), &label],
+    )
+    .await
+    .context("inserting label subscription")?;
+    Ok(())
+}
+
+/// Removes the subscription of `user`, returning whether there was one.
+pub async fn unsubscribe(
+    db: &DbClient,
+    user_id: u64,
+    repo: &str,
+    issue_number: u64,
+    label: &str,
+) -> anyhow::Result<bool> {
+    let deleted = db
+        .execute(
+            r"
+DELETE FROM label_subscriptions
+WHERE user_id = $1 AND repo = $2 AND issue_number = $3 AND label = $4",
+            &[&(user_id as i64), &repo, &(issue_number as i32), &label],
+        )
+        .await
+        .context("deleting label subscription")?;
+    Ok(deleted > 0)
+}
+
+/// Returns the users subscribed to the changes of `label` on the given issue.
+pub async fn get_subscribers(
+    db: &DbClient,
+    repo: &str,
+    issue_number: u64,
+    label: &str,
+) -> anyhow::Result<Vec<DbUser>> {
+    let rows = db
+        .query(
+            r"
+SELECT subs.user_id, users.username
+FROM label_subscriptions AS subs
+JOIN users ON users.user_id = subs.user_id
+WHERE subs.repo = $1 AND subs.issue_number = $2 AND subs.label = $3
+ORDER BY subs.user_id",
+            &[&repo, &(issue_number as i32), &label],
+        )
+        .await
+        .context("selecting label subscribers")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| DbUser {
+            id: row.get::<_, i64>(0) as u64,
+            login: row.get(1),
+        })
+        .collect())
+}
+
+/// Moves the subscriptions to `old` in the repository to the label `new`, after
+/// the label was renamed, returning the number of moved subscriptions.
+///
+/// Subscriptions to `old` of users already subscribed to `new` on the same
+/// issue are dropped.
+pub async fn rename_label(db: &DbClient, repo: &str, old: &str, new: &str) -> anyhow::Result<u64> {
+    let renamed = db
+        .execute(
+            r"
+UPDATE label_subscriptions AS subs
+SET label = $3
+WHERE subs.repo = $1 AND subs.label = $2 AND NOT EXISTS (
+    SELECT 1 FROM label_subscriptions AS other
+    WHERE other.user_id = subs.user_id
+        AND other.repo = subs.repo
+        AND other.issue_number = subs.issue_number
+        AND other.label = $3
+)",
+            &[&repo, &old, &new],
+        )
+        .await
+        .context("renaming label subscriptions")?;
+    db.execute(
+        "DELETE FROM label_subscriptions WHERE repo = $1 AND label = $2",
+        &[&repo, &old],
+    )
+    .await
+    .context("deleting duplicate label subscriptions")?;
+    Ok(renamed)
+}
@@ -0,0 +1,160 @@
+//! The `major_change_holds` table records the seconded major changes whose
+//! approval countdown has been paused with `@rustbot hold`.
+//!
+//! Released holds are kept with their `released_at` time, so that acceptance
+//! jobs scheduled before the release can be told apart from the new one.
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use tokio_postgres::Client as DbClient;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MajorChangeHold {
+    /// GitHub login of the user who put the proposal on hold.
+    pub held_by: String,
+    pub held_at: DateTime<Utc>,
+}
+
+/// Returns the hold of the proposal, if it is on hold.
+pub async fn get_hold(
+    db: &DbClient,
+    repo: &str,
+    issue_number: u64,
+) -> anyhow::Result<Option<MajorChangeHold>> {
+    let row = db
+        .query_opt(
+            r"
+SELECT held_by, held_at FROM major_change_holds
+WHERE repo = $1 AND issue_number = $2 AND released_at IS NULL",
+            &[&repo, &(issue_number as i64)],
+        )
+        .await
+        .context("selecting major change hold")?;
+    Ok(row.map(|row| MajorChangeHold {
+        held_by: row.get(0),
+        held_at: row.get(1),
+    }))
+}
+
+/// Returns when the last hold of the proposal was released, if it ever was
+/// and it is not on hold again.
+pub async fn get_last_release(
+    db: &DbClient,
+    repo: &str,
+    issue_number: u64,
+) -> anyhow::Result<Option<DateTime<Utc>>> {
+    let row = db
+        .query_opt(
+            "SELECT released_at FROM major_change_holds WHERE repo = $1 AND issue_number = $2",
+            &[&repo, &(issue_number as i64)],
+        )
+        .await
+        .context("selecting major change hold release")?;
+    Ok(row.and_then(|row| row.get(0)))
+}
+
+/// Puts the proposal on hold.
+///
+/// Returns `false` if it was already on hold, in which case the existing hold
+/// is kept.
+pub async fn hold(
+    db: &DbClient,
+    repo: &str,
+    issue_number: u64,
+    hold: &MajorChangeHold,
+) -> anyhow::Result<bool> {
+    let inserted = db
+        .execute(
+            r"
+INSERT INTO major_change_holds (repo, issue_number, held_by, held_at)
+VALUES ($1, $2, $3, $4)
+ON CONFLICT (repo, issue_number) DO UPDATE
+SET held_by = EXCLUDED.held_by, held_at = EXCLUDED.held_at, released_at = NULL
+WHERE major_change_holds.released_at IS NOT NULL",
+            &[&repo, &(issue_number as i64), &hold.held_by, &hold.held_at],
+        )
+        .await
+        .context("inserting major change hold")?;
+    Ok(inserted == 1)
+}
+
+/// Releases the hold of the proposal at `released_at`, returning it if there
+/// was one.
+pub async fn unhold(
+    db: &DbClient,
+    repo: &str,
+    issue_number: u64,
+    released_at: DateTime<Utc>,
+) -> anyhow::Result<Option<MajorChangeHold>> {
+    let row = db
+        .query_opt(
+            r"
+UPDATE major_change_holds
+SET released_at = $3
+WHERE repo = $1 AND issue_number = $2 AND released_at IS NULL
+RETURNING held_by, held_at",
+            &[&repo, &(issue_number as i64), &released_at],
+        )
+        .await
+        .context("releasing major change hold")?;
+    Ok(row.map(|row| MajorChangeHold {
+        held_by: row.get(0),
+        held_at: row.get(1),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::major_change_holds::{
+        MajorChangeHold, get_hold, get_last_release, hold, unhold,
+    };
+    use crate::tests::run_db_test;
+
+    #[tokio::test]
+    async fn hold_and_unhold() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let repo = "rust-lang/compiler-team";
+            let first = MajorChangeHold {
+                held_by: "Urgau".to_string(),
+                held_at: "2025-01-01T10:00:00Z".parse().unwrap(),
+            };
+
+            assert_eq!(get_hold(&db, repo, 1).await?, None);
+            assert!(hold(&db, repo, 1, &first).await?);
+            assert!(
+                !hold(
+                    &db,
+                    repo,
+                    1,
+                    &MajorChangeHold {
+                        held_by: "ghost".to_string(),
+                        held_at: first.held_at,
+                    }
+                )
+                .await?
+            );
+            assert_eq!(get_hold(&db, repo, 1).await?, Some(first.clone()));
+            assert_eq!(get_hold(&db, repo, 2).await?, None);
+
+            assert_eq!(get_last_release(&db, repo, 1).await?, None);
+
+            let released_at = "2025-01-02T10:00:00Z".parse().unwrap();
+            assert_eq!(unhold(&db, repo, 1, released_at).await?, Some(first));
+            assert_eq!(unhold(&db, repo, 1, released_at).await?, None);
+            assert_eq!(get_hold(&db, repo, 1).await?, None);
+            assert_eq!(get_last_release(&db, repo, 1).await?, Some(released_at));
+
+            let second = MajorChangeHold {
+                held_by: "ghost".to_string(),
+                held_at: "2025-01-03T10:00:00Z".parse().unwrap(),
+            };
+            assert!(hold(&db, repo, 1, &second).await?);
+            assert_eq!(get_hold(&db, repo, 1).await?, Some(second));
+            assert_eq!(get_last_release(&db, repo, 1).await?, None);
+
+            Ok(ctx)
+        })
+        .await;
+    }
+}
@@ -0,0 +1,117 @@
+//! The `major_change_topics` table remembers the Zulip stream and topic a
+//! proposal was opened in, and the ones it was moved to (e.g. when its topic
+//! was renamed on acceptance), as they can't always be derived from its
+//! current labels and title.
+
+use anyhow::Context;
+use tokio_postgres::Client as DbClient;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProposalTopic {
+    pub stream_id: u64,
+    pub topic: String,
+}
+
+/// Returns the recorded stream and topic of the proposal, if any.
+pub async fn get_proposal_topic(
+    db: &DbClient,
+    repo: &str,
+    issue_number: u64,
+) -> anyhow::Result<Option<ProposalTopic>> {
+    let row = db
+        .query_opt(
+            r"
+SELECT stream_id, topic
+FROM major_change_topics
+WHERE repo = $1 AND issue_number = $2",
+            &[&repo, &(issue_number as i64)],
+        )
+        .await
+        .context("selecting major change topic")?;
+    Ok(row.map(|row| ProposalTopic {
+        stream_id: row.get::<_, i64>(0) as u64,
+        topic: row.get(1),
+    }))
+}
+
+/// Returns the repositories with a recorded proposal, i.e. the ones using the
+/// major change process.
+pub async fn get_proposal_repositories(db: &DbClient) -> anyhow::Result<Vec<String>> {
+    let rows = db
+        .query(
+            "SELECT DISTINCT repo FROM major_change_topics ORDER BY repo",
+            &[],
+        )
+        .await
+        .context("selecting major change repositories")?;
+    Ok(rows.into_iter().map(|row| row.get(0)).collect())
+}
+
+/// Records the stream and topic of the proposal, replacing the previous ones.
+pub async fn set_proposal_topic(
+    db: &DbClient,
+    repo: &str,
+    issue_number: u64,
+    topic: &ProposalTopic,
+) -> anyhow::Result<()> {
+    db.execute(
+        r"
+INSERT INTO major_change_topics (repo, issue_number, stream_id, topic)
+VALUES ($1, $2, $3, $4)
+ON CONFLICT (repo, issue_number) DO UPDATE
+SET stream_id = EXCLUDED.stream_id, topic = EXCLUDED.topic",
+        &[
+            &repo,
+            &(issue_number as i64),
+            &(topic.stream_id as i64),
+            &topic.topic,
+        ],
+    )
+    .await
+    .context("upserting major change topic")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::major_change_topics::{
+        ProposalTopic, get_proposal_repositories, get_proposal_topic, set_proposal_topic,
+    };
+    use crate::tests::run_db_test;
+
+    #[tokio::test]
+    async fn proposal_topic() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let repo = "rust-lang/compiler-team";
+
+            assert_eq!(get_proposal_topic(&db, repo, 42).await?, None);
+
+            let opened = ProposalTopic {
+                stream_id: 1,
+                topic: "Proposal compiler-team#42".to_string(),
+            };
+            set_proposal_topic(&db, repo, 42, &opened).await?;
+            assert_eq!(get_proposal_topic(&db, repo, 42).await?, Some(opened));
+
+            let accepted = ProposalTopic {
+                stream_id: 1,
+                topic: "[accepted] Proposal compiler-team#42".to_string(),
+            };
+            set_proposal_topic(&db, repo, 42, &accepted).await?;
+            assert_eq!(get_proposal_topic(&db, repo, 42).await?, Some(accepted));
+
+            assert_eq!(get_proposal_topic(&db, repo, 43).await?, None);
+
+            set_proposal_topic(&db, "rust-lang/lang-team", 7, &accepted).await?;
+            set_proposal_topic(&db, repo, 43, &opened).await?;
+            assert_eq!(
+                get_proposal_repositories(&db).await?,
+                vec!["rust-lang/compiler-team", "rust-lang/lang-team"]
+            );
+
+            Ok(ctx)
+        })
+        .await;
+    }
+}
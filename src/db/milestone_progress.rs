@@ -0,0 +1,94 @@
+//! The `milestone_progress` table records the submodule PRs already
+//! milestoned, so an interrupted run can be resumed without milestoning them
+//! again.
+
+use std::collections::HashSet;
+
+use anyhow::Context;
+use tokio_postgres::Client as DbClient;
+
+/// Records that `pr_number` of `repo` was milestoned with `milestone`.
+pub async fn record_milestoned(
+    db: &DbClient,
+    repo: &str,
+    milestone: &str,
+    pr_number: u64,
+) -> anyhow::Result<()> {
+    db.execute(
+        r"
+INSERT INTO milestone_progress (repo, milestone, pr_number) VALUES ($1, $2, $3)
+ON CONFLICT DO NOTHING",
+        &[&repo, &milestone, &(pr_number as i64)],
+    )
+    .await
+    .context("inserting milestone progress")?;
+    Ok(())
+}
+
+/// Returns the PRs of `repo` already milestoned with `milestone`.
+pub async fn milestoned_prs(
+    db: &DbClient,
+    repo: &str,
+    milestone: &str,
+) -> anyhow::Result<HashSet<u64>> {
+    let rows = db
+        .query(
+            "SELECT pr_number FROM milestone_progress WHERE repo = $1 AND milestone = $2",
+            &[&repo, &milestone],
+        )
+        .await
+        .context("selecting milestone progress")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| row.get::<_, i64>(0) as u64)
+        .collect())
+}
+
+/// Forgets the milestoned PRs of `repo` once the run for `milestone` is complete.
+pub async fn clear_milestoned(db: &DbClient, repo: &str, milestone: &str) -> anyhow::Result<()> {
+    db.execute(
+        "DELETE FROM milestone_progress WHERE repo = $1 AND milestone = $2",
+        &[&repo, &milestone],
+    )
+    .await
+    .context("deleting milestone progress")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::milestone_progress::{clear_milestoned, milestoned_prs, record_milestoned};
+    use crate::tests::run_db_test;
+
+    #[tokio::test]
+    async fn progress_per_milestone() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let repo = "rust-lang/cargo";
+
+            record_milestoned(&db, repo, "1.90.0", 15000).await?;
+            record_milestoned(&db, repo, "1.90.0", 15002).await?;
+            // Recording a PR twice is a no-op
+            record_milestoned(&db, repo, "1.90.0", 15000).await?;
+            record_milestoned(&db, repo, "1.91.0", 15010).await?;
+
+            assert_eq!(
+                milestoned_prs(&db, repo, "1.90.0").await?,
+                [15000, 15002].into()
+            );
+            assert_eq!(milestoned_prs(&db, repo, "1.91.0").await?, [15010].into());
+            assert!(
+                milestoned_prs(&db, "rust-lang/reference", "1.90.0")
+                    .await?
+                    .is_empty()
+            );
+
+            clear_milestoned(&db, repo, "1.90.0").await?;
+            assert!(milestoned_prs(&db, repo, "1.90.0").await?.is_empty());
+            assert_eq!(milestoned_prs(&db, repo, "1.91.0").await?, [15010].into());
+
+            Ok(ctx)
+        })
+        .await;
+    }
+}
@@ -0,0 +1,94 @@
+//! The `review_deferrals` table records the reviewers who deferred the pings
+//! of a pull request until a given time, with `@rustbot defer 3d`.
+
+use std::collections::HashSet;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use tokio_postgres::Client as DbClient;
+
+/// Defers the pings of `login` on `pr_number` until `until`, replacing any
+/// previous deferral.
+pub async fn defer_pings(
+    db: &DbClient,
+    repo: &str,
+    pr_number: u64,
+    login: &str,
+    until: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    db.execute(
+        r"
+INSERT INTO review_deferrals (repo, pr_number, user_login, deferred_until) VALUES ($1, $2, $3, $4)
+ON CONFLICT (repo, pr_number, user_login) DO UPDATE SET deferred_until = EXCLUDED.deferred_until",
+        &[&repo, &(pr_number as i64), &login.to_lowercase(), &until],
+    )
+    .await
+    .context("inserting review deferral")?;
+    Ok(())
+}
+
+/// Returns the lowercased logins of the users whose pings on `pr_number` are
+/// still deferred at `now`.
+pub async fn deferred_users(
+    db: &DbClient,
+    repo: &str,
+    pr_number: u64,
+    now: DateTime<Utc>,
+) -> anyhow::Result<HashSet<String>> {
+    let rows = db
+        .query(
+            r"
+SELECT user_login
+FROM review_deferrals
+WHERE repo = $1 AND pr_number = $2 AND deferred_until > $3",
+            &[&repo, &(pr_number as i64), &now],
+        )
+        .await
+        .context("selecting review deferrals")?;
+    Ok(rows.into_iter().map(|row| row.get(0)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::review_deferrals::{defer_pings, deferred_users};
+    use crate::tests::run_db_test;
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn deferred_until_expiry() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let repo = "rust-lang/rust";
+            let now = Utc::now();
+
+            defer_pings(&db, repo, 1, "Kobzol", now + Duration::days(3)).await?;
+            defer_pings(&db, repo, 2, "jieyouxu", now + Duration::hours(1)).await?;
+
+            assert_eq!(
+                deferred_users(&db, repo, 1, now).await?,
+                ["kobzol".to_string()].into()
+            );
+            assert!(
+                deferred_users(&db, "rust-lang/cargo", 1, now)
+                    .await?
+                    .is_empty()
+            );
+            // The deferral expires after the given duration
+            assert!(
+                deferred_users(&db, repo, 1, now + Duration::days(4))
+                    .await?
+                    .is_empty()
+            );
+
+            // Deferring again replaces the previous deferral
+            defer_pings(&db, repo, 1, "kobzol", now + Duration::days(5)).await?;
+            assert_eq!(
+                deferred_users(&db, repo, 1, now + Duration::days(4)).await?,
+                ["kobzol".to_string()].into()
+            );
+
+            Ok(ctx)
+        })
+        .await;
+    }
+}
@@ -0,0 +1,99 @@
+//! The `tracking_issues` table records which issues are tracked by a tracking
+//! issue of the same repository, as registered with `@rustbot tracked-by #123`.
+
+use anyhow::Context;
+use tokio_postgres::Client as DbClient;
+
+/// Records that `issue_number` is tracked by `tracking_issue`.
+///
+/// Returns `false` if the relationship was already recorded.
+pub async fn add_tracking_issue(
+    db: &DbClient,
+    repo: &str,
+    issue_number: u64,
+    tracking_issue: u64,
+) -> anyhow::Result<bool> {
+    let inserted = db
+        .execute(
+            r"
+INSERT INTO tracking_issues (repo, issue_number, tracking_issue) VALUES ($1, $2, $3)
+ON CONFLICT DO NOTHING",
+            &[&repo, &(issue_number as i64), &(tracking_issue as i64)],
+        )
+        .await
+        .context("inserting tracking issue")?;
+    Ok(inserted == 1)
+}
+
+/// Returns the tracking issues of `issue_number`.
+pub async fn get_tracking_issues(
+    db: &DbClient,
+    repo: &str,
+    issue_number: u64,
+) -> anyhow::Result<Vec<u64>> {
+    let rows = db
+        .query(
+            r"
+SELECT tracking_issue
+FROM tracking_issues
+WHERE repo = $1 AND issue_number = $2
+ORDER BY tracking_issue",
+            &[&repo, &(issue_number as i64)],
+        )
+        .await
+        .context("selecting tracking issues")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| row.get::<_, i64>(0) as u64)
+        .collect())
+}
+
+/// Returns the issues tracked by `tracking_issue`.
+pub async fn get_tracked_issues(
+    db: &DbClient,
+    repo: &str,
+    tracking_issue: u64,
+) -> anyhow::Result<Vec<u64>> {
+    let rows = db
+        .query(
+            r"
+SELECT issue_number
+FROM tracking_issues
+WHERE repo = $1 AND tracking_issue = $2
+ORDER BY issue_number",
+            &[&repo, &(tracking_issue as i64)],
+        )
+        .await
+        .context("selecting tracked issues")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| row.get::<_, i64>(0) as u64)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::tracking_issues::{add_tracking_issue, get_tracked_issues, get_tracking_issues};
+    use crate::tests::run_db_test;
+
+    #[tokio::test]
+    async fn tracking_relationships() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let repo = "rust-lang/rust";
+
+            assert!(add_tracking_issue(&db, repo, 10, 1).await?);
+            assert!(!add_tracking_issue(&db, repo, 10, 1).await?);
+            assert!(add_tracking_issue(&db, repo, 11, 1).await?);
+            assert!(add_tracking_issue(&db, repo, 10, 2).await?);
+            assert!(add_tracking_issue(&db, "rust-lang/cargo", 12, 1).await?);
+
+            assert_eq!(get_tracking_issues(&db, repo, 10).await?, vec![1, 2]);
+            assert_eq!(get_tracked_issues(&db, repo, 1).await?, vec![10, 11]);
+            assert_eq!(get_tracked_issues(&db, repo, 3).await?, vec![]);
+
+            Ok(ctx)
+        })
+        .await;
+    }
+}
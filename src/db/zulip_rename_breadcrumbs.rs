@@ -0,0 +1,111 @@
+//! The `zulip_rename_breadcrumbs` table remembers the breadcrumb message posted
+//! in the original Zulip topic of a renamed issue, so that later renames update
+//! it instead of posting new ones.
+
+use anyhow::Context;
+use tokio_postgres::Client as DbClient;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameBreadcrumb {
+    /// The topic the breadcrumb was posted in.
+    pub topic: String,
+    pub message_id: u64,
+}
+
+/// Returns the breadcrumb posted for the renames of the issue, if any.
+pub async fn get_rename_breadcrumb(
+    db: &DbClient,
+    stream_id: u64,
+    repo: &str,
+    issue_number: u64,
+) -> anyhow::Result<Option<RenameBreadcrumb>> {
+    let row = db
+        .query_opt(
+            r"
+SELECT topic, message_id
+FROM zulip_rename_breadcrumbs
+WHERE stream_id = $1 AND repo = $2 AND issue_number = $3",
+            &[&(stream_id as i64), &repo, &(issue_number as i64)],
+        )
+        .await
+        .context("selecting zulip rename breadcrumb")?;
+    Ok(row.map(|row| RenameBreadcrumb {
+        topic: row.get(0),
+        message_id: row.get::<_, i64>(1) as u64,
+    }))
+}
+
+/// Records the breadcrumb posted for the renames of the issue.
+///
+/// Returns `false` if a breadcrumb was already recorded, in which case the
+/// existing one is kept.
+pub async fn record_rename_breadcrumb(
+    db: &DbClient,
+    stream_id: u64,
+    repo: &str,
+    issue_number: u64,
+    breadcrumb: &RenameBreadcrumb,
+) -> anyhow::Result<bool> {
+    let inserted = db
+        .execute(
+            r"
+INSERT INTO zulip_rename_breadcrumbs (stream_id, repo, issue_number, topic, message_id)
+VALUES ($1, $2, $3, $4, $5)
+ON CONFLICT DO NOTHING",
+            &[
+                &(stream_id as i64),
+                &repo,
+                &(issue_number as i64),
+                &breadcrumb.topic,
+                &(breadcrumb.message_id as i64),
+            ],
+        )
+        .await
+        .context("inserting zulip rename breadcrumb")?;
+    Ok(inserted == 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::zulip_rename_breadcrumbs::{
+        RenameBreadcrumb, get_rename_breadcrumb, record_rename_breadcrumb,
+    };
+    use crate::tests::run_db_test;
+
+    #[tokio::test]
+    async fn breadcrumb_posted_once_per_rename_chain() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let repo = "rust-lang/compiler-team";
+
+            // First rename: no breadcrumb yet, one is posted in the original topic
+            assert_eq!(get_rename_breadcrumb(&db, 1, repo, 42).await?, None);
+            let first = RenameBreadcrumb {
+                topic: "Original title compiler-team#42".to_string(),
+                message_id: 100,
+            };
+            assert!(record_rename_breadcrumb(&db, 1, repo, 42, &first).await?);
+
+            // Following renames find (and update) the same breadcrumb
+            for message_id in [101, 102] {
+                assert_eq!(
+                    get_rename_breadcrumb(&db, 1, repo, 42).await?,
+                    Some(first.clone())
+                );
+                let next = RenameBreadcrumb {
+                    topic: "Renamed title compiler-team#42".to_string(),
+                    message_id,
+                };
+                assert!(!record_rename_breadcrumb(&db, 1, repo, 42, &next).await?);
+            }
+            assert_eq!(get_rename_breadcrumb(&db, 1, repo, 42).await?, Some(first));
+
+            // Other issues and streams have their own breadcrumbs
+            assert_eq!(get_rename_breadcrumb(&db, 1, repo, 43).await?, None);
+            assert_eq!(get_rename_breadcrumb(&db, 2, repo, 42).await?, None);
+
+            Ok(ctx)
+        })
+        .await;
+    }
+}
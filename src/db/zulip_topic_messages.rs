@@ -0,0 +1,161 @@
+//! The `zulip_topic_messages` table remembers the last message posted by the bot
+//! in a Zulip topic, so that closely following messages can be merged into it.
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use tokio_postgres::Client as DbClient;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicMessage {
+    pub message_id: u64,
+    pub content: String,
+    /// When the message was first posted, later edits don't change it.
+    pub posted_at: DateTime<Utc>,
+}
+
+/// Returns the last message posted by the bot in the topic, if any.
+pub async fn get_last_topic_message(
+    db: &DbClient,
+    stream_id: u64,
+    topic: &str,
+) -> anyhow::Result<Option<TopicMessage>> {
+    let row = db
+        .query_opt(
+            r"
+SELECT message_id, content, posted_at
+FROM zulip_topic_messages
+WHERE stream_id = $1 AND topic = $2",
+            &[&(stream_id as i64), &topic],
+        )
+        .await
+        .context("selecting last zulip topic message")?;
+    Ok(row.map(|row| TopicMessage {
+        message_id: row.get::<_, i64>(0) as u64,
+        content: row.get(1),
+        posted_at: row.get(2),
+    }))
+}
+
+/// Records `message` as the last message posted by the bot in the topic.
+pub async fn set_last_topic_message(
+    db: &DbClient,
+    stream_id: u64,
+    topic: &str,
+    message: &TopicMessage,
+) -> anyhow::Result<()> {
+    db.execute(
+        r"
+INSERT INTO zulip_topic_messages (stream_id, topic, message_id, content, posted_at)
+VALUES ($1, $2, $3, $4, $5)
+ON CONFLICT (stream_id, topic) DO UPDATE
+SET message_id = EXCLUDED.message_id, content = EXCLUDED.content, posted_at = EXCLUDED.posted_at",
+        &[
+            &(stream_id as i64),
+            &topic,
+            &(message.message_id as i64),
+            &message.content,
+            &message.posted_at,
+        ],
+    )
+    .await
+    .context("upserting last zulip topic message")?;
+    Ok(())
+}
+
+/// Moves the last message of the topic `from` to the topic `to`, after the topic was
+/// renamed on Zulip.
+pub async fn rename_topic_messages(
+    db: &mut DbClient,
+    stream_id: u64,
+    from: &str,
+    to: &str,
+) -> anyhow::Result<()> {
+    let transaction = db.transaction().await?;
+    transaction
+        .execute(
+            "DELETE FROM zulip_topic_messages WHERE stream_id = $1 AND topic = $2",
+            &[&(stream_id as i64), &to],
+        )
+        .await
+        .context("deleting the zulip topic message of the renamed topic")?;
+    transaction
+        .execute(
+            "UPDATE zulip_topic_messages SET topic = $3 WHERE stream_id = $1 AND topic = $2",
+            &[&(stream_id as i64), &from, &to],
+        )
+        .await
+        .context("renaming the zulip topic message")?;
+    transaction
+        .commit()
+        .await
+        .context("committing the zulip topic rename")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::zulip_topic_messages::{
+        TopicMessage, get_last_topic_message, rename_topic_messages, set_last_topic_message,
+    };
+    use crate::tests::run_db_test;
+    use chrono::{DateTime, Utc};
+
+    #[tokio::test]
+    async fn last_topic_message() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let posted_at: DateTime<Utc> = "2025-01-01T10:00:00Z".parse().unwrap();
+
+            assert_eq!(get_last_topic_message(&db, 1, "topic #1").await?, None);
+
+            let first = TopicMessage {
+                message_id: 100,
+                content: "first".to_string(),
+                posted_at,
+            };
+            set_last_topic_message(&db, 1, "topic #1", &first).await?;
+            assert_eq!(
+                get_last_topic_message(&db, 1, "topic #1").await?,
+                Some(first)
+            );
+
+            let second = TopicMessage {
+                message_id: 101,
+                content: "second".to_string(),
+                posted_at,
+            };
+            set_last_topic_message(&db, 1, "topic #1", &second).await?;
+            assert_eq!(
+                get_last_topic_message(&db, 1, "topic #1").await?,
+                Some(second)
+            );
+            assert_eq!(get_last_topic_message(&db, 2, "topic #1").await?, None);
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn renamed_topic_message() {
+        run_db_test(|mut ctx| async {
+            let db = ctx.db_client_mut();
+            let message = TopicMessage {
+                message_id: 100,
+                content: "first".to_string(),
+                posted_at: "2025-01-01T10:00:00Z".parse().unwrap(),
+            };
+            set_last_topic_message(db, 1, "topic #1", &message).await?;
+
+            rename_topic_messages(db, 1, "topic #1", "[accepted] topic #1").await?;
+            assert_eq!(get_last_topic_message(db, 1, "topic #1").await?, None);
+            assert_eq!(
+                get_last_topic_message(db, 1, "[accepted] topic #1").await?,
+                Some(message)
+            );
+
+            Ok(ctx)
+        })
+        .await;
+    }
+}
@@ -0,0 +1,131 @@
+//! Endpoints helping to debug the behavior of triagebot.
+//!
+//! They are only enabled when the `TRIAGEBOT_DEBUG_ENDPOINTS` environment
+//! variable is set, which is not the case in production.
+
+use std::fmt::Write;
+
+use axum::extract::Query;
+use parser::command::Input;
+
+use crate::github::{IssueRepository, ZulipGitHubReference};
+use crate::handlers::major_change::{ZULIP_TOPIC_MAX_LENGTH, zulip_topic_from_issue};
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ZulipTopicParams {
+    title: String,
+    number: u64,
+    /// The `owner/repo` of the issue, `rust-lang/rust` by default.
+    repo: Option<String>,
+    /// Whether to always include the repository in the reference, like the
+    /// `zulip_topic_full_reference` option of `[major-change]`.
+    #[serde(default)]
+    full_reference: bool,
+    /// The maximum length of the topic, like the `zulip_topic_max_length` option
+    /// of `[major-change]`.
+    max_length: Option<usize>,
+}
+
+/// Shows the Zulip topic the major change handler would use for an issue.
+pub async fn zulip_topic(Query(params): Query<ZulipTopicParams>) -> String {
+    let (organization, repository) = params
+        .repo
+        .as_deref()
+        .and_then(|repo| repo.split_once('/'))
+        .unwrap_or(("rust-lang", "rust"));
+    let issue = ZulipGitHubReference {
+        number: params.number,
+        title: params.title,
+        repository: IssueRepository {
+            organization: organization.to_string(),
+            repository: repository.to_string(),
+        },
+    };
+    zulip_topic_from_issue(
+        &issue,
+        params.full_reference,
+        params.max_length.unwrap_or(ZULIP_TOPIC_MAX_LENGTH),
+    )
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ParseParams {
+    /// The name of the bot the commands are addressed to, `rustbot` by default.
+    bot: Option<String>,
+}
+
+/// Shows the commands the parser extracts from a comment body, without running them.
+pub async fn parse(Query(params): Query<ParseParams>, body: String) -> String {
+    describe_commands(&body, params.bot.as_deref().unwrap_or("rustbot"))
+}
+
+fn describe_commands(body: &str, bot: &str) -> String {
+    let mut out = String::new();
+    for (i, command) in Input::new(body, vec![bot]).enumerate() {
+        let n = i + 1;
+        match command.error() {
+            Some(err) => writeln!(out, "{n}. error: {err}"),
+            None => writeln!(out, "{n}. {command:#?}"),
+        }
+        .unwrap();
+    }
+    if out.is_empty() {
+        out.push_str("No commands found.\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::body::Body;
+    use axum::routing::get;
+    use hyper::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn zulip_topic_of_long_title() {
+        let app = Router::new().route("/debug/zulip-topic", get(zulip_topic));
+        let response = app
+            .oneshot(
+                Request::get(
+                    "/debug/zulip-topic?number=42&repo=rust-lang/compiler-team\
+                     &title=Make%20the%20compiler%20emit%20way%20more%20detailed%20diagnostics%20for%20everything",
+                )
+                .body(Body::empty())
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let topic = std::str::from_utf8(&body).unwrap();
+        assert_eq!(
+            topic,
+            "Make the compiler emit way more detailed d… compiler-team#42"
+        );
+        assert_eq!(topic.chars().count(), 60);
+    }
+
+    #[test]
+    fn parse_multiple_commands() {
+        let body = "Looks good!\n\n@rustbot claim\n@rustbot ready\n@rustbot assign foo\n";
+        let description = describe_commands(body, "rustbot");
+        let (commands, error) = description.split_once("3. error: ").unwrap();
+        assert_eq!(
+            commands,
+            "1. Assign(\n    Ok(\n        Claim,\n    ),\n)\n\
+             2. Shortcut(\n    Ok(\n        Ready,\n    ),\n)\n"
+        );
+        assert!(error.starts_with("user should start with @ when parsing"));
+
+        assert_eq!(
+            describe_commands("@bors r+", "rustbot"),
+            "No commands found.\n"
+        );
+    }
+}
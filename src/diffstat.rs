@@ -0,0 +1,87 @@
+//! Diffstat (files changed, insertions and deletions) of the GitHub comparisons,
+//! rendered in the header of the diff pages.
+
+use std::fmt;
+
+use crate::github::FileDiff;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DiffStat {
+    pub(crate) files: usize,
+    pub(crate) insertions: usize,
+    pub(crate) deletions: usize,
+}
+
+impl DiffStat {
+    /// Sums the diffstat of the files of a comparison, from the line counts
+    /// reported by GitHub (which remain accurate when the patches are truncated).
+    pub(crate) fn from_files(files: &[FileDiff]) -> DiffStat {
+        files
+            .iter()
+            .fold(DiffStat::default(), |total, file| DiffStat {
+                files: total.files + 1,
+                insertions: total.insertions + file.additions,
+                deletions: total.deletions + file.deletions,
+            })
+    }
+}
+
+impl fmt::Display for DiffStat {
+    /// Formats the diffstat like `git diff --shortstat`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        write!(
+            f,
+            "{} file{} changed, {} insertion{}(+), {} deletion{}(-)",
+            self.files,
+            plural(self.files),
+            self.insertions,
+            plural(self.insertions),
+            self.deletions,
+            plural(self.deletions)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(filename: &str, additions: usize, deletions: usize) -> FileDiff {
+        FileDiff {
+            filename: filename.to_string(),
+            previous_filename: None,
+            // The patches are ignored, they may be truncated or omitted.
+            patch: String::new(),
+            additions,
+            deletions,
+        }
+    }
+
+    #[test]
+    fn diffstat_of_files() {
+        let files = [
+            file("src/lib.rs", 3, 2),
+            file("src/renamed.rs", 0, 0),
+            file("README.md", 1, 0),
+        ];
+
+        let stat = DiffStat::from_files(&files);
+        assert_eq!(
+            stat,
+            DiffStat {
+                files: 3,
+                insertions: 4,
+                deletions: 2,
+            }
+        );
+        assert_eq!(
+            stat.to_string(),
+            "3 files changed, 4 insertions(+), 2 deletions(-)"
+        );
+        assert_eq!(
+            DiffStat::from_files(&files[2..]).to_string(),
+            "1 file changed, 1 insertion(+), 0 deletions(-)"
+        );
+    }
+}
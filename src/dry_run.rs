@@ -0,0 +1,106 @@
+//! Dry-run mode for repositories with a `[dry-run]` section in their `triagebot.toml`.
+//!
+//! The mode is scoped to the handling of a single webhook event (see `handlers::handle`).
+//! The mutating actions (label changes, comments, milestones, Zulip messages) check it
+//! before talking to the external service, and only log what they would have done.
+//!
+//! The mode is a task-local, so tasks spawned while handling an event must be
+//! wrapped with [`propagate`] to inherit it. The jobs acting on a repository
+//! run in its mode with [`scope`], see [`is_enabled_for`].
+
+use std::fmt;
+use std::future::Future;
+
+use anyhow::Context as _;
+use tracing as log;
+
+use crate::github::GithubClient;
+
+tokio::task_local! {
+    static DRY_RUN: bool;
+}
+
+#[cfg(test)]
+tokio::task_local! {
+    static RECORDED: std::cell::RefCell<Vec<String>>;
+}
+
+/// Runs `fut` with the dry-run mode set to `enabled`.
+pub(crate) async fn scope<F: Future>(enabled: bool, fut: F) -> F::Output {
+    DRY_RUN.scope(enabled, fut).await
+}
+
+/// Wraps `fut`, to be spawned as a new task, so that it runs with the dry-run
+/// mode of the current task.
+pub(crate) fn propagate<F: Future>(fut: F) -> impl Future<Output = F::Output> {
+    scope(is_enabled(), fut)
+}
+
+/// Runs `fut` in dry-run mode, returning the actions it would have done.
+#[cfg(test)]
+pub(crate) async fn record<F: Future>(fut: F) -> (F::Output, Vec<String>) {
+    RECORDED
+        .scope(Default::default(), async {
+            let output = scope(true, fut).await;
+            (output, RECORDED.with(|recorded| recorded.take()))
+        })
+        .await
+}
+
+/// Whether the repository `repo` (`owner/name`) has a `[dry-run]` section in
+/// its `triagebot.toml`, for the actions taken outside of a webhook event.
+pub(crate) async fn is_enabled_for(github: &GithubClient, repo: &str) -> anyhow::Result<bool> {
+    let repository = github
+        .repository(repo)
+        .await
+        .context("failed retrieving the repository informations")?;
+    Ok(crate::config::get(github, &repository)
+        .await
+        .is_ok_and(|config| config.dry_run.is_some()))
+}
+
+/// Whether the current task runs in dry-run mode.
+pub(crate) fn is_enabled() -> bool {
+    DRY_RUN.try_with(|enabled| *enabled).unwrap_or(false)
+}
+
+/// Returns `true` if the given action must be skipped, after logging it.
+pub(crate) fn skip(action: fmt::Arguments<'_>) -> bool {
+    if is_enabled() {
+        log::info!(target: "dry_run", "dry run: would have {action}");
+        #[cfg(test)]
+        let _ = RECORDED.try_with(|recorded| recorded.borrow_mut().push(action.to_string()));
+        true
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn scoped() {
+        assert!(!is_enabled());
+        assert!(scope(true, async { is_enabled() }).await);
+        assert!(!scope(false, async { is_enabled() }).await);
+        assert!(!skip(format_args!("done nothing")));
+    }
+
+    #[tokio::test]
+    async fn recorded() {
+        let ((), actions) = record(async {
+            assert!(skip(format_args!("done {}", 1)));
+            // Spawned tasks don't inherit the mode, unless propagated
+            assert!(!tokio::spawn(async { is_enabled() }).await.unwrap());
+            assert!(
+                tokio::spawn(propagate(async { is_enabled() }))
+                    .await
+                    .unwrap()
+            );
+        })
+        .await;
+        assert_eq!(actions, vec!["done 1".to_string()]);
+    }
+}
@@ -88,7 +88,7 @@ r##"<!DOCTYPE html>
 
     // Yes, use our Github range-diff instead
     Ok(Redirect::to(&format!(
-        "/gh-range-diff/{owner}/{repo}/{oldbase}..{oldhead}/{newbase}..{newhead}"
+        "/gh-range-diff/{owner}/{repo}/{oldbase}...{oldhead}/{newbase}...{newhead}"
     ))
     .into_response())
 }
@@ -19,7 +19,8 @@ use pulldown_cmark_escape::FmtWriter;
 use regex::Regex;
 use unicode_segmentation::UnicodeSegmentation;
 
-use crate::github::{GithubCommit, GithubCompare};
+use crate::diffstat::DiffStat;
+use crate::github::{CompareMode, GithubCommit, GithubCompare};
 use crate::utils::is_known_and_public_repo;
 use crate::{errors::AppError, github, handlers::Context};
 
@@ -30,12 +31,14 @@ static MARKER_RE: LazyLock<Regex> =
 
 /// Compute and renders an emulated `git range-diff` between two pushes (old and new).
 ///
-/// `basehead` is `OLDHEAD..NEWHEAD`, both `OLDHEAD` and `NEWHEAD` must be SHAs or branch names.
+/// `basehead` is `OLDHEAD..NEWHEAD` (or `OLDHEAD...NEWHEAD`), both `OLDHEAD` and `NEWHEAD`
+/// must be SHAs or branch names. The bases are always the merge-bases of the heads with the
+/// default branch, so both separators give the same result.
 pub async fn gh_range_diff(
     Path((owner, repo, basehead)): Path<(String, String, String)>,
     State(ctx): State<Arc<Context>>,
 ) -> axum::response::Result<impl IntoResponse, AppError> {
-    let Some((oldhead, newhead)) = basehead.split_once("..") else {
+    let Some((oldhead, newhead, _)) = parse_range(&basehead) else {
         return Ok((
             StatusCode::BAD_REQUEST,
             HeaderMap::new(),
@@ -129,13 +132,16 @@ pub async fn gh_range_diff(
 
 /// Compute and renders an emulated `git range-diff` between two pushes (old and new).
 ///
-/// - `oldbasehead` is `OLDBASE..OLDHEAD`
-/// - `newbasehead` is `NEWBASE..NEWHEAD`
+/// - `oldbasehead` is `OLDBASE..OLDHEAD` or `OLDBASE...OLDHEAD`
+/// - `newbasehead` is `NEWBASE..NEWHEAD` or `NEWBASE...NEWHEAD`
+///
+/// Like `git diff`, `..` compares the head against the base itself, while `...` compares
+/// it against its merge-base with the base. The links we generate always use `...`.
 pub async fn gh_ranges_diff(
     Path((owner, repo, oldbasehead, newbasehead)): Path<(String, String, String, String)>,
     State(ctx): State<Arc<Context>>,
 ) -> axum::response::Result<impl IntoResponse, AppError> {
-    let Some((oldbase, oldhead)) = oldbasehead.split_once("..") else {
+    let Some((oldbase, oldhead, oldmode)) = parse_range(&oldbasehead) else {
         return Ok((
             StatusCode::BAD_REQUEST,
             HeaderMap::new(),
@@ -143,7 +149,7 @@ pub async fn gh_ranges_diff(
         ));
     };
 
-    let Some((newbase, newhead)) = newbasehead.split_once("..") else {
+    let Some((newbase, newhead, newmode)) = parse_range(&newbasehead) else {
         return Ok((
             StatusCode::BAD_REQUEST,
             HeaderMap::new(),
@@ -167,7 +173,7 @@ pub async fn gh_ranges_diff(
     // Get the comparison between the oldbase..oldhead
     let old = async {
         ctx.github
-            .compare(&issue_repo, oldbase, oldhead)
+            .compare_with(&issue_repo, oldbase, oldhead, oldmode)
             .await
             .with_context(|| {
                 format!("failed to retrive the comparison between {oldbase} and {oldhead}")
@@ -177,7 +183,7 @@ pub async fn gh_ranges_diff(
     // Get the comparison between the newbase..newhead
     let new = async {
         ctx.github
-            .compare(&issue_repo, newbase, newhead)
+            .compare_with(&issue_repo, newbase, newhead, newmode)
             .await
             .with_context(|| {
                 format!("failed to retrive the comparison between {newbase} and {newhead}")
@@ -194,6 +200,17 @@ pub async fn gh_ranges_diff(
     )
 }
 
+/// Splits a `base..head` or `base...head` range, returning the [`CompareMode`] of its separator.
+fn parse_range(range: &str) -> Option<(&str, &str, CompareMode)> {
+    if let Some((base, head)) = range.split_once("...") {
+        Some((base, head, CompareMode::MergeBase))
+    } else if let Some((base, head)) = range.split_once("..") {
+        Some((base, head, CompareMode::Literal))
+    } else {
+        None
+    }
+}
+
 fn process_old_new(
     (owner, repo): (&str, &str),
     (oldbase, oldhead, mut old): (&str, &str, GithubCompare),
@@ -211,6 +228,9 @@ fn process_old_new(
     // Create the HTML buffer with a very rough approximation for the capacity
     let mut html: String = String::with_capacity(800 + old.files.len() * 100);
 
+    let old_diffstat = DiffStat::from_files(&old.files);
+    let new_diffstat = DiffStat::from_files(&new.files);
+
     let a_compare_before = a_github_compare("compare-before", owner, repo, oldbase, oldhead);
     let a_compare_after = a_github_compare("compare-after", owner, repo, newbase, newhead);
 
@@ -366,6 +386,9 @@ fn process_old_new(
 </head>
 <body>
 <h3>range-diff of {a_compare_before} {a_compare_after} in {owner}/{repo}</h3>
+<span>Before: {old_diffstat}</span><br>
+<span>After: {new_diffstat}</span>
+<div class="spacer"></div>
 <span>Legend: {REMOVED_BLOCK_SIGN}&nbsp;Removed from previous diff | {ADDED_BLOCK_SIGN}&nbsp;Added in new diff</span>
 <div class="spacer"></div>
 <h3>Changes</h3>
@@ -961,3 +984,55 @@ fn a_github_commit(class: &str, owner: &str, repo: &str, sha: &str) -> String {
         sha_6 = &sha[..sha.len().min(7)],
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_separators() {
+        assert_eq!(
+            parse_range("oldbase...oldhead"),
+            Some(("oldbase", "oldhead", CompareMode::MergeBase))
+        );
+        assert_eq!(
+            parse_range("oldbase..oldhead"),
+            Some(("oldbase", "oldhead", CompareMode::Literal))
+        );
+        assert_eq!(parse_range("oldbase.oldhead"), None);
+        assert_eq!(parse_range("oldhead"), None);
+    }
+
+    #[tokio::test]
+    async fn range_separators_select_the_comparison() {
+        use axum::{Router, routing::get};
+        use std::sync::Mutex;
+
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let router = Router::new().route(
+            "/repos/{owner}/{repo}/compare/{range}",
+            get({
+                let requested = requested.clone();
+                move |Path((_, _, range)): Path<(String, String, String)>| async move {
+                    requested.lock().unwrap().push(range);
+                    StatusCode::NOT_FOUND
+                }
+            }),
+        );
+        let github = crate::tests::mock_github_client(router).await;
+        let repo = github::IssueRepository {
+            organization: "rust-lang".to_string(),
+            repository: "rust".to_string(),
+        };
+
+        for range in ["oldbase...oldhead", "oldbase..oldhead"] {
+            let (base, head, mode) = parse_range(range).unwrap();
+            assert!(github.compare_with(&repo, base, head, mode).await.is_err());
+        }
+        // `...` asks GitHub for the merge-base comparison, `..` for the literal one.
+        assert_eq!(
+            *requested.lock().unwrap(),
+            vec!["oldbase...oldhead", "oldbase..oldhead"]
+        );
+    }
+}
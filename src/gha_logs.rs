@@ -5,13 +5,21 @@ use crate::handlers::Context;
 use crate::interactions::REPORT_TO;
 use crate::utils::{immutable_headers, is_known_and_public_repo};
 use anyhow::Context as _;
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::http::HeaderValue;
-use axum::response::IntoResponse;
-use hyper::header::{CONTENT_SECURITY_POLICY, CONTENT_TYPE};
+use axum::response::{IntoResponse, Response};
+use base64::Engine as _;
+use bytes::Bytes;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use hyper::header::{
+    ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_SECURITY_POLICY, CONTENT_TYPE, VARY,
+};
 use hyper::{HeaderMap, StatusCode};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::ops::Range;
 use std::sync::Arc;
-use uuid::Uuid;
 
 pub const GHA_LOGS_JS: &str = include_str!("gha_logs/gha_logs.js");
 pub const ANSI_UP_URL: &str = "/gha_logs/ansi_up@0.0.1-custom.js";
@@ -22,40 +30,148 @@ pub const GHA_LOGS_CACHE_CAPACITY_BYTES: usize = 50 * 1024 * 1024; // 50 Mb
 
 pub type GitHubActionLogsCache = cache::LeastRecentlyUsedCache<String, CachedLog>;
 
+/// A rendered logs page.
+///
+/// The page is cached along with its gzip-compressed form, so that repeated
+/// views don't need to render nor compress it again. As the page is served
+/// as is, its inline script is allowed by the CSP through its hash rather
+/// than through a nonce, which must not be reused across responses.
 pub struct CachedLog {
-    job: WorkflowRunJob,
-    tree_roots: String,
-    logs: String,
+    script_hash: String,
+    html: Bytes,
+    gzip_html: Bytes,
+}
+
+impl CachedLog {
+    fn new(html: String, script: &str) -> anyhow::Result<CachedLog> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(html.as_bytes())?;
+        let gzip_html = encoder.finish()?;
+
+        Ok(CachedLog {
+            script_hash: script_hash(script),
+            html: html.into(),
+            gzip_html: gzip_html.into(),
+        })
+    }
+
+    /// Builds the response to a request with the given headers, serving the
+    /// compressed page when the client accepts it.
+    fn response(&self, req_headers: &HeaderMap) -> Response {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("text/html; charset=utf-8"),
+        );
+        headers.insert(
+            CONTENT_SECURITY_POLICY,
+            HeaderValue::from_str(&format!(
+                "default-src 'none'; script-src '{}' 'self'; style-src 'unsafe-inline'; img-src 'self' rust-lang.org",
+                self.script_hash
+            )).unwrap(),
+        );
+        headers.insert(VARY, HeaderValue::from_static("accept-encoding"));
+
+        let body = if accepts_gzip(req_headers) {
+            headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+            self.gzip_html.clone()
+        } else {
+            self.html.clone()
+        };
+
+        (StatusCode::OK, headers, body).into_response()
+    }
 }
 
 impl cache::EstimatedSize for CachedLog {
     fn estimated_size(&self) -> usize {
-        std::mem::size_of::<WorkflowRunJob>() + self.tree_roots.len() + self.logs.len()
+        self.script_hash.len() + self.html.len() + self.gzip_html.len()
+    }
+}
+
+/// The CSP source (`'sha256-...'`) allowing an inline script.
+fn script_hash(script: &str) -> String {
+    let digest = Sha256::digest(script.as_bytes());
+    format!(
+        "sha256-{}",
+        base64::engine::general_purpose::STANDARD.encode(digest)
+    )
+}
+
+/// Whether the `Accept-Encoding` header allows a gzip-compressed response.
+fn accepts_gzip(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|coding| {
+            let mut params = coding.split(';').map(str::trim);
+            let name = params.next().unwrap_or_default();
+            let rejected = params.any(|param| {
+                param
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .is_some_and(|q| q == 0.0)
+            });
+            (name.eq_ignore_ascii_case("gzip") || name == "*") && !rejected
+        })
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct GhaLogsParams {
+    /// Start of the range of the logs to show, in bytes.
+    start: Option<u64>,
+    /// End (excluded) of the range of the logs to show, in bytes.
+    end: Option<u64>,
+}
+
+impl GhaLogsParams {
+    fn range(&self) -> Option<Range<u64>> {
+        if self.start.is_none() && self.end.is_none() {
+            return None;
+        }
+        Some(self.start.unwrap_or(0)..self.end.unwrap_or(u64::MAX))
     }
 }
 
 pub async fn gha_logs(
     Path((owner, repo, log_id)): Path<(String, String, u128)>,
+    Query(params): Query<GhaLogsParams>,
     State(ctx): State<Arc<Context>>,
-) -> axum::response::Result<impl IntoResponse, AppError> {
+    req_headers: HeaderMap,
+) -> axum::response::Result<Response, AppError> {
     if !is_known_and_public_repo(&ctx, &owner, &repo).await? {
         return Ok((
             StatusCode::UNAUTHORIZED,
-            HeaderMap::new(),
             format!("repository `{owner}/{repo}` is not part of the Rust Project team repos"),
-        ));
+        )
+            .into_response());
     }
 
-    let log_uuid = format!("{owner}/{repo}${log_id}");
+    let range = params.range();
+    if let Some(range) = &range
+        && range.is_empty()
+    {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "invalid range: `start` ({}) must be before `end` ({})",
+                range.start, range.end
+            ),
+        )
+            .into_response());
+    }
 
-    let CachedLog {
-        job,
-        tree_roots,
-        logs,
-    } = &*'logs: {
-        if let Some(logs) = ctx.gha_logs.write().await.get(&log_uuid) {
+    let log_uuid = match &range {
+        Some(range) => format!("{owner}/{repo}${log_id}@{}-{}", range.start, range.end),
+        None => format!("{owner}/{repo}${log_id}"),
+    };
+
+    let page = 'page: {
+        if let Some(page) = ctx.gha_logs.write().await.get(&log_uuid) {
             tracing::info!("gha_logs: cache hit for log {log_uuid}");
-            break 'logs logs;
+            break 'page page;
         }
 
         tracing::info!("gha_logs: cache miss for log {log_uuid}");
@@ -126,11 +242,15 @@ pub async fn gha_logs(
         };
 
         let logs = async {
-            let logs = ctx
-                .github
-                .raw_job_logs(&repo, log_id)
-                .await
-                .with_context(|| format!("unable to get the raw logs for log {log_id}"))?;
+            let logs = match &range {
+                Some(range) => {
+                    ctx.github
+                        .raw_job_logs_range(&repo, log_id, range.clone())
+                        .await
+                }
+                None => ctx.github.raw_job_logs(&repo, log_id).await,
+            }
+            .with_context(|| format!("unable to get the raw logs for log {log_id}"))?;
 
             let json_logs =
                 serde_json::to_string(&*logs).context("unable to JSON-ify the raw logs")?;
@@ -152,9 +272,8 @@ pub async fn gha_logs(
                 tracing::info!("gha_logs: raw logs too big (over 50 mib) for {log_uuid}");
                 return Ok((
                     StatusCode::BAD_REQUEST,
-                    HeaderMap::new(),
-                    "The requested logs are too large (over 50 Mib).\n\nTry download the raw logs from GitHub instead.".to_string(),
-                ));
+                    "The requested logs are too large (over 50 Mib).\n\nTry download the raw logs from GitHub instead.",
+                ).into_response());
             }
             Err(err) if matches!(err.downcast_ref::<reqwest::Error>(), Some(err) if err.status() == Some(StatusCode::GONE)) =>
             {
@@ -162,27 +281,61 @@ pub async fn gha_logs(
                 tracing::info!("gha_logs: raw logs gone for log {log_uuid}");
                 return Ok((
                     StatusCode::GONE,
-                    HeaderMap::new(),
-                    "The requested logs are no longer available.\n\nGitHub only retains logs for up to 90 days, after which they become permanently inaccessible.".to_string(),
-                ));
+                    "The requested logs are no longer available.\n\nGitHub only retains logs for up to 90 days, after which they become permanently inaccessible.",
+                ).into_response());
             }
             Err(err) => return Err(err.into()),
         };
 
-        ctx.gha_logs.write().await.put(
-            log_uuid.clone(),
-            CachedLog {
-                job,
-                tree_roots,
-                logs,
-            }
-            .into(),
-        )
+        let script = logs_script(&owner, &repo.repository, &job, &tree_roots, &logs);
+        let html = render_logs(&owner, &repo.repository, &job, &script);
+        let page = CachedLog::new(html, &script).context("unable to compress the logs page")?;
+
+        ctx.gha_logs
+            .write()
+            .await
+            .put(log_uuid.clone(), page.into())
     };
 
-    let nonce = Uuid::new_v4().to_hyphenated().to_string();
-    let job_name = &*job.name;
+    tracing::info!("gha_logs: serving logs for {log_uuid}");
+
+    Ok(page.response(&req_headers))
+}
+
+/// The inline script of the logs page, rendering the logs.
+fn logs_script(
+    owner: &str,
+    repo: &str,
+    job: &WorkflowRunJob,
+    tree_roots: &str,
+    logs: &str,
+) -> String {
     let sha = &*job.head_sha;
+
+    format!(
+        r###"
+        import {{ AnsiUp }} from '{ANSI_UP_URL}'
+        
+        try {{
+
+        const logs = {logs};
+        const tree_roots = {tree_roots};
+        const owner = "{owner}";
+        const repo = "{repo}";
+        const sha = "{sha}";
+
+        {GHA_LOGS_JS}
+
+        }} catch (e) {{
+           console.error(e);
+           document.body.innerText = `Something went wrong: ${{e}}\n\n{REPORT_TO}`;
+        }}
+    "###,
+    )
+}
+
+fn render_logs(owner: &str, repo: &str, job: &WorkflowRunJob, script: &str) -> String {
+    let job_name = &*job.name;
     let short_sha = &job.head_sha[..7];
 
     let icon_status = match job.conclusion {
@@ -197,7 +350,7 @@ pub async fn gha_logs(
         }
     };
 
-    let html = format!(
+    format!(
         r###"<!DOCTYPE html>
 <html lang="en" translate="no">
 <head>
@@ -263,24 +416,7 @@ tr.selected {{
 .italic {{ font-style: italic; }}
 .underline {{ text-decoration: underline; }}
     </style>
-    <script type="module" nonce="{nonce}">
-        import {{ AnsiUp }} from '{ANSI_UP_URL}'
-        
-        try {{
-
-        const logs = {logs};
-        const tree_roots = {tree_roots};
-        const owner = "{owner}";
-        const repo = "{repo}";
-        const sha = "{sha}";
-
-        {GHA_LOGS_JS}
-
-        }} catch (e) {{
-           console.error(e);
-           document.body.innerText = `Something went wrong: ${{e}}\n\n{REPORT_TO}`;
-        }}
-    </script>
+    <script type="module">{script}</script>
 </head>
 <body>
 <table>
@@ -289,23 +425,7 @@ tr.selected {{
 </table>
 </body>
 </html>"###,
-    );
-
-    tracing::info!("gha_logs: serving logs for {log_uuid}");
-
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        CONTENT_TYPE,
-        HeaderValue::from_static("text/html; charset=utf-8"),
-    );
-    headers.insert(
-        CONTENT_SECURITY_POLICY,
-        HeaderValue::from_str(&format!(
-            "default-src 'none'; script-src 'nonce-{nonce}' 'self'; style-src 'unsafe-inline'; img-src 'self' rust-lang.org"
-        )).unwrap(),
-    );
-
-    Ok((StatusCode::OK, headers, html))
+    )
 }
 
 pub async fn ansi_up_min_js() -> impl IntoResponse {
@@ -334,3 +454,70 @@ pub async fn failure_svg() -> impl IntoResponse {
         FAILURE_SVG,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn headers(accept_encoding: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            ACCEPT_ENCODING,
+            HeaderValue::from_str(accept_encoding).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn accept_encoding() {
+        assert!(accepts_gzip(&headers("gzip")));
+        assert!(accepts_gzip(&headers("br, gzip;q=0.8")));
+        assert!(accepts_gzip(&headers("*")));
+        assert!(!accepts_gzip(&headers("br")));
+        assert!(!accepts_gzip(&headers("gzip;q=0")));
+        assert!(!accepts_gzip(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn cached_page_is_compressed_once() {
+        let html = "<!DOCTYPE html><p>logs</p>".repeat(100);
+        let mut cache = GitHubActionLogsCache::default();
+        cache.put(
+            "rust-lang/rust$1".to_string(),
+            CachedLog::new(html.clone(), "").unwrap().into(),
+        );
+
+        let first = cache.get(&"rust-lang/rust$1".to_string()).unwrap();
+        let second = cache.get(&"rust-lang/rust$1".to_string()).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(first.gzip_html.as_ptr(), second.gzip_html.as_ptr());
+
+        let mut decompressed = String::new();
+        flate2::read::GzDecoder::new(&*second.gzip_html)
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, html);
+
+        let response = second.response(&headers("gzip, deflate"));
+        assert_eq!(response.headers()[CONTENT_ENCODING], "gzip");
+        assert_eq!(response.headers()[VARY], "accept-encoding");
+
+        let response = second.response(&HeaderMap::new());
+        assert!(response.headers().get(CONTENT_ENCODING).is_none());
+    }
+
+    #[test]
+    fn inline_script_is_allowed_by_its_hash() {
+        let page = CachedLog::new("<!DOCTYPE html>".to_string(), "").unwrap();
+        let response = page.response(&HeaderMap::new());
+        let csp = response.headers()[CONTENT_SECURITY_POLICY]
+            .to_str()
+            .unwrap();
+        assert!(
+            csp.contains("script-src 'sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=' 'self'"),
+            "{csp}"
+        );
+        assert!(!csp.contains("nonce"));
+    }
+}
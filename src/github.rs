@@ -1,3 +1,4 @@
+pub(crate) mod app_auth;
 pub(crate) mod client;
 pub(crate) mod issue;
 pub(crate) mod issue_query;
@@ -6,7 +7,8 @@ pub(crate) mod repos;
 pub(crate) mod utils;
 mod webhook;
 
-pub use client::{GithubClient, default_token_from_env};
+pub use app_auth::GithubAppCredentials;
+pub use client::{GithubClient, default_octocrab_from_env, default_token_from_env};
 pub use issue::*;
 pub use repos::*;
 pub use webhook::event::*;
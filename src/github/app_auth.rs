@@ -0,0 +1,226 @@
+//! Authentication as a GitHub App installation.
+//!
+//! When `GITHUB_APP_ID`, `GITHUB_APP_INSTALLATION_ID` and `GITHUB_APP_PRIVATE_KEY`
+//! are set, triagebot authenticates as an installation of that GitHub App instead
+//! of using a personal access token. Installation tokens are only valid for an
+//! hour, so they are minted lazily and refreshed shortly before they expire.
+
+use anyhow::Context;
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use octocrab::Octocrab;
+use octocrab::models::{AppId, InstallationId};
+use reqwest::Client;
+use reqwest::header::{ACCEPT, USER_AGENT};
+use secrecy::{ExposeSecret, SecretString};
+use std::sync::RwLock;
+use tracing as log;
+
+/// Installation tokens are refreshed when they expire in less than this many minutes.
+const REFRESH_MARGIN_MINUTES: i64 = 5;
+
+#[derive(Clone)]
+pub struct GithubAppCredentials {
+    pub app_id: u64,
+    pub installation_id: u64,
+    pub private_key: SecretString,
+}
+
+impl GithubAppCredentials {
+    /// Reads the GitHub App credentials from the environment.
+    ///
+    /// Returns `None` if `GITHUB_APP_ID` is not set, in which case a personal
+    /// access token should be used instead.
+    pub fn from_env() -> Option<Self> {
+        let app_id = std::env::var("GITHUB_APP_ID").ok()?;
+        Some(GithubAppCredentials {
+            app_id: app_id.parse().expect("GITHUB_APP_ID must be a number"),
+            installation_id: std::env::var("GITHUB_APP_INSTALLATION_ID")
+                .expect("GITHUB_APP_INSTALLATION_ID is required when GITHUB_APP_ID is set")
+                .parse()
+                .expect("GITHUB_APP_INSTALLATION_ID must be a number"),
+            private_key: std::env::var("GITHUB_APP_PRIVATE_KEY")
+                .expect("GITHUB_APP_PRIVATE_KEY is required when GITHUB_APP_ID is set")
+                .into(),
+        })
+    }
+
+    fn encoding_key(&self) -> anyhow::Result<EncodingKey> {
+        EncodingKey::from_rsa_pem(self.private_key.expose_secret().as_bytes())
+            .context("invalid GitHub App private key")
+    }
+
+    /// Builds an octocrab instance authenticated as the app installation.
+    ///
+    /// octocrab takes care of refreshing the installation token by itself.
+    pub fn octocrab(&self) -> anyhow::Result<Octocrab> {
+        let app = octocrab::OctocrabBuilder::new()
+            .app(AppId(self.app_id), self.encoding_key()?)
+            .build()?;
+        Ok(app.installation(InstallationId(self.installation_id))?)
+    }
+
+    /// Creates the short-lived JWT used to authenticate as the app itself.
+    fn jwt(&self, now: DateTime<Utc>) -> anyhow::Result<String> {
+        #[derive(serde::Serialize)]
+        struct Claims {
+            iat: i64,
+            exp: i64,
+            iss: String,
+        }
+
+        // Backdate the token a bit to allow for clock drift, as recommended by GitHub.
+        let claims = Claims {
+            iat: (now - Duration::seconds(60)).timestamp(),
+            exp: (now + Duration::minutes(9)).timestamp(),
+            iss: self.app_id.to_string(),
+        };
+        Ok(jsonwebtoken::encode(
+            &Header::new(Algorithm::RS256),
+            &claims,
+            &self.encoding_key()?,
+        )?)
+    }
+}
+
+struct InstallationToken {
+    token: SecretString,
+    expires_at: DateTime<Utc>,
+}
+
+impl InstallationToken {
+    fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at - now > Duration::minutes(REFRESH_MARGIN_MINUTES)
+    }
+}
+
+/// Caches the installation token of a GitHub App, minting a new one when the
+/// current one is about to expire.
+pub(crate) struct InstallationTokenCache {
+    credentials: GithubAppCredentials,
+    token: RwLock<Option<InstallationToken>>,
+    refresh: tokio::sync::Mutex<()>,
+}
+
+impl InstallationTokenCache {
+    pub(crate) fn new(credentials: GithubAppCredentials) -> Self {
+        InstallationTokenCache {
+            credentials,
+            token: RwLock::new(None),
+            refresh: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Returns the cached token, unless it is missing or about to expire at `now`.
+    pub(crate) fn cached(&self, now: DateTime<Utc>) -> Option<SecretString> {
+        self.token
+            .read()
+            .unwrap()
+            .as_ref()
+            .filter(|token| token.is_fresh(now))
+            .map(|token| token.token.clone())
+    }
+
+    /// Returns a valid installation token, minting a new one if needed.
+    pub(crate) async fn token(
+        &self,
+        client: &Client,
+        api_url: &str,
+    ) -> anyhow::Result<SecretString> {
+        if let Some(token) = self.cached(Utc::now()) {
+            return Ok(token);
+        }
+
+        // Only let a single task mint a new token, the others wait for it and reuse it.
+        let _guard = self.refresh.lock().await;
+        if let Some(token) = self.cached(Utc::now()) {
+            return Ok(token);
+        }
+
+        let token = self.mint(client, api_url).await?;
+        let secret = token.token.clone();
+        *self.token.write().unwrap() = Some(token);
+        Ok(secret)
+    }
+
+    async fn mint(&self, client: &Client, api_url: &str) -> anyhow::Result<InstallationToken> {
+        #[derive(serde::Deserialize)]
+        struct AccessTokenResponse {
+            token: String,
+            expires_at: DateTime<Utc>,
+        }
+
+        let url = format!(
+            "{api_url}/app/installations/{}/access_tokens",
+            self.credentials.installation_id
+        );
+        let resp: AccessTokenResponse = client
+            .post(&url)
+            .header(USER_AGENT, "rust-lang-triagebot")
+            .header(ACCEPT, "application/vnd.github+json")
+            .bearer_auth(self.credentials.jwt(Utc::now())?)
+            .send()
+            .await
+            .with_context(|| format!("failed to mint an installation token at {url}"))?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        log::info!(
+            "minted a new GitHub App installation token, expiring at {}",
+            resp.expires_at
+        );
+
+        Ok(InstallationToken {
+            token: resp.token.into(),
+            expires_at: resp.expires_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_with_token(expires_at: DateTime<Utc>) -> InstallationTokenCache {
+        let cache = InstallationTokenCache::new(GithubAppCredentials {
+            app_id: 1,
+            installation_id: 2,
+            private_key: String::new().into(),
+        });
+        *cache.token.write().unwrap() = Some(InstallationToken {
+            token: "ghs_token".to_string().into(),
+            expires_at,
+        });
+        cache
+    }
+
+    #[test]
+    fn empty_cache_needs_refresh() {
+        let cache = InstallationTokenCache::new(GithubAppCredentials {
+            app_id: 1,
+            installation_id: 2,
+            private_key: String::new().into(),
+        });
+        assert!(cache.cached(Utc::now()).is_none());
+    }
+
+    #[test]
+    fn fresh_token_is_reused() {
+        let now = Utc::now();
+        let cache = cache_with_token(now + Duration::hours(1));
+        assert_eq!(
+            cache.cached(now).map(|t| t.expose_secret().to_string()),
+            Some("ghs_token".to_string())
+        );
+    }
+
+    #[test]
+    fn token_is_refreshed_before_expiry() {
+        let now = Utc::now();
+        let cache = cache_with_token(now + Duration::hours(1));
+        assert!(cache.cached(now + Duration::minutes(54)).is_some());
+        assert!(cache.cached(now + Duration::minutes(56)).is_none());
+        assert!(cache.cached(now + Duration::hours(2)).is_none());
+    }
+}
@@ -4,14 +4,17 @@ use futures::{FutureExt, future::BoxFuture};
 use http_body_util::BodyExt;
 use http_body_util::Limited;
 use reqwest::Body;
-use reqwest::header::{AUTHORIZATION, USER_AGENT};
+use reqwest::header::{AUTHORIZATION, HeaderMap, USER_AGENT};
 use reqwest::{Client, Request, RequestBuilder, Response, StatusCode};
 use secrecy::{ExposeSecret, SecretString};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
 use tracing as log;
 
 use bytes::Bytes;
 
+use crate::github::app_auth::{GithubAppCredentials, InstallationTokenCache};
 use crate::jobs::Job;
 
 // TODO: Update to "2026-03-10" and see what breaks
@@ -44,9 +47,28 @@ fn get_token_from_git_config() -> anyhow::Result<String> {
     Ok(git_token)
 }
 
+/// Builds an octocrab instance, authenticated as a GitHub App installation
+/// if its credentials are available, or with a personal token otherwise.
+pub fn default_octocrab_from_env() -> anyhow::Result<octocrab::Octocrab> {
+    match GithubAppCredentials::from_env() {
+        Some(credentials) => credentials.octocrab(),
+        None => Ok(octocrab::OctocrabBuilder::new()
+            .personal_token(default_token_from_env())
+            .build()?),
+    }
+}
+
+#[derive(Clone)]
+enum GithubAuth {
+    /// A personal access token.
+    Token(SecretString),
+    /// A GitHub App installation, whose tokens are minted on demand.
+    App(Arc<InstallationTokenCache>),
+}
+
 #[derive(Clone)]
 pub struct GithubClient {
-    token: SecretString,
+    auth: GithubAuth,
     client: Client,
     pub(in crate::github) api_url: String,
     pub(in crate::github) graphql_url: String,
@@ -62,18 +84,77 @@ pub struct RateLimitResources {
     pub graphql: RateLimit,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
 pub struct RateLimit {
     pub limit: u64,
     pub remaining: u64,
     pub reset: u64,
 }
 
+/// The latest GitHub rate limits, as reported in the headers of the responses.
+pub(crate) static GITHUB_RATE_LIMITS: RateLimitGauges = RateLimitGauges::new();
+
+/// Fraction of the rate limit below which a warning is logged for each request.
+const RATE_LIMIT_WARNING_RATIO: f64 = 0.1;
+
+/// Latest rate limit of each GitHub API resource (`core`, `search`, `graphql`, ...).
+pub(crate) struct RateLimitGauges {
+    limits: Mutex<BTreeMap<String, RateLimit>>,
+}
+
+impl RateLimitGauges {
+    const fn new() -> Self {
+        Self {
+            limits: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Records the rate limit reported in the headers of a response, returning
+    /// its resource and value if the headers were present.
+    fn update(&self, headers: &HeaderMap) -> Option<(String, RateLimit)> {
+        let header = |name: &str| headers.get(name)?.to_str().ok();
+        let number = |name: &str| header(name)?.parse::<u64>().ok();
+
+        let resource = header("X-RateLimit-Resource").unwrap_or("core").to_string();
+        let rate_limit = RateLimit {
+            limit: number("X-RateLimit-Limit")?,
+            remaining: number("X-RateLimit-Remaining")?,
+            reset: number("X-RateLimit-Reset")?,
+        };
+        self.limits
+            .lock()
+            .unwrap()
+            .insert(resource.clone(), rate_limit.clone());
+        Some((resource, rate_limit))
+    }
+
+    /// Returns the latest rate limit of each resource.
+    pub(crate) fn snapshot(&self) -> BTreeMap<String, RateLimit> {
+        self.limits.lock().unwrap().clone()
+    }
+}
+
+/// Records the rate limit reported in the headers of a response, warning when
+/// few requests remain.
+fn record_rate_limit(headers: &HeaderMap) {
+    let Some((resource, rate_limit)) = GITHUB_RATE_LIMITS.update(headers) else {
+        return;
+    };
+    if (rate_limit.remaining as f64) < rate_limit.limit as f64 * RATE_LIMIT_WARNING_RATIO {
+        log::warn!(
+            "GitHub rate limit of `{resource}` almost exhausted: {} of {} requests remaining, reset in {}s",
+            rate_limit.remaining,
+            rate_limit.limit,
+            GithubClient::calc_sleep(rate_limit.reset)
+        );
+    }
+}
+
 impl GithubClient {
     pub fn new(token: SecretString, api_url: String, graphql_url: String, raw_url: String) -> Self {
         GithubClient {
             client: Client::new(),
-            token,
+            auth: GithubAuth::Token(token),
             api_url,
             graphql_url,
             raw_url,
@@ -81,16 +162,49 @@ impl GithubClient {
         }
     }
 
+    /// Creates a client authenticated as a GitHub App installation, minting
+    /// and refreshing installation tokens as needed.
+    pub fn new_app(
+        credentials: GithubAppCredentials,
+        api_url: String,
+        graphql_url: String,
+        raw_url: String,
+    ) -> Self {
+        GithubClient {
+            client: Client::new(),
+            auth: GithubAuth::App(Arc::new(InstallationTokenCache::new(credentials))),
+            api_url,
+            graphql_url,
+            raw_url,
+            retry_rate_limit: false,
+        }
+    }
+
+    /// Creates a client from the environment, authenticating as a GitHub App
+    /// if `GITHUB_APP_ID` is set, and with a personal token otherwise.
     pub fn new_from_env() -> Self {
-        Self::new(
-            default_token_from_env(),
-            std::env::var("GITHUB_API_URL")
-                .unwrap_or_else(|_| "https://api.github.com".to_string()),
-            std::env::var("GITHUB_GRAPHQL_API_URL")
-                .unwrap_or_else(|_| "https://api.github.com/graphql".to_string()),
-            std::env::var("GITHUB_RAW_URL")
-                .unwrap_or_else(|_| "https://raw.githubusercontent.com".to_string()),
-        )
+        let api_url = std::env::var("GITHUB_API_URL")
+            .unwrap_or_else(|_| "https://api.github.com".to_string());
+        let graphql_url = std::env::var("GITHUB_GRAPHQL_API_URL")
+            .unwrap_or_else(|_| "https://api.github.com/graphql".to_string());
+        let raw_url = std::env::var("GITHUB_RAW_URL")
+            .unwrap_or_else(|_| "https://raw.githubusercontent.com".to_string());
+
+        match GithubAppCredentials::from_env() {
+            Some(credentials) => Self::new_app(credentials, api_url, graphql_url, raw_url),
+            None => Self::new(default_token_from_env(), api_url, graphql_url, raw_url),
+        }
+    }
+
+    /// Sets the authorization header of `req`, refreshing the installation
+    /// token first when authenticating as a GitHub App.
+    pub(in crate::github) async fn authorize(&self, req: &mut Request) -> anyhow::Result<()> {
+        if let GithubAuth::App(cache) = &self.auth {
+            let token = cache.token(&self.client, &self.api_url).await?;
+            req.headers_mut()
+                .insert(AUTHORIZATION, authorization_header(&token));
+        }
+        Ok(())
     }
 
     /// Sets whether or not this client will retry when it hits GitHub's rate limit.
@@ -117,15 +231,29 @@ impl GithubClient {
         req: RequestBuilder,
         max_response_size: usize,
     ) -> anyhow::Result<(Bytes, String)> {
+        let (_status, body, req_dbg) = self
+            .send_req_with_limit_and_status(req, max_response_size)
+            .await?;
+        Ok((body, req_dbg))
+    }
+
+    /// Same as [`GithubClient::send_req_with_limit`], but also returns the
+    /// status of the (successful) response.
+    pub async fn send_req_with_limit_and_status(
+        &self,
+        req: RequestBuilder,
+        max_response_size: usize,
+    ) -> anyhow::Result<(StatusCode, Bytes, String)> {
         const MAX_ATTEMPTS: u32 = 2;
 
         log::debug!("send_req with {:?}", req);
 
         let req_dbg = format!("{req:?}");
 
-        let req = req
+        let mut req = req
             .build()
             .with_context(|| format!("building reqwest {req_dbg}"))?;
+        self.authorize(&mut req).await?;
 
         let req_url = req.url().to_string();
 
@@ -135,7 +263,9 @@ impl GithubClient {
         {
             resp = self.retry(req, sleep, MAX_ATTEMPTS).await?;
         }
+        record_rate_limit(resp.headers());
 
+        let status = resp.status();
         let maybe_err = resp.error_for_status_ref().err();
         let github_request_id = resp.headers().get("x-github-request-id").cloned();
 
@@ -183,7 +313,7 @@ impl GithubClient {
             });
         }
 
-        Ok((body, req_dbg))
+        Ok((status, body, req_dbg))
     }
 
     async fn needs_retry(resp: &Response) -> Option<Duration> {
@@ -216,7 +346,7 @@ impl GithubClient {
         req: Request,
         sleep: Duration,
         remaining_attempts: u32,
-    ) -> BoxFuture<'_, Result<Response, reqwest::Error>> {
+    ) -> BoxFuture<'_, anyhow::Result<Response>> {
         #[derive(Debug, serde::Deserialize)]
         struct RateLimitResponse {
             resources: RateLimitResources,
@@ -232,16 +362,14 @@ impl GithubClient {
             tokio::time::sleep(sleep).await;
 
             // check rate limit
-            let rate_resp = self
+            let mut rate_req = self
                 .client
-                .execute(
-                    self.client
-                        .get(format!("{}/rate_limit", self.api_url))
-                        .configure(self)
-                        .build()
-                        .unwrap(),
-                )
-                .await?;
+                .get(format!("{}/rate_limit", self.api_url))
+                .configure(self)
+                .build()
+                .unwrap();
+            self.authorize(&mut rate_req).await?;
+            let rate_resp = self.client.execute(rate_req).await?;
             rate_resp.error_for_status_ref()?;
             let rate_limit_response = rate_resp.json::<RateLimitResponse>().await?;
 
@@ -265,6 +393,9 @@ impl GithubClient {
                 }
             }
 
+            // The installation token may have expired while sleeping.
+            let mut req = req;
+            self.authorize(&mut req).await?;
             let resp = self.client.execute(req.try_clone().unwrap()).await?;
             if let Some(sleep) = Self::needs_retry(&resp).await
                 && remaining_attempts > 0
@@ -408,17 +539,28 @@ trait RequestSend: Sized {
     fn configure(self, g: &GithubClient) -> Self;
 }
 
+fn authorization_header(token: &SecretString) -> reqwest::header::HeaderValue {
+    let mut auth =
+        reqwest::header::HeaderValue::from_maybe_shared(format!("token {}", token.expose_secret()))
+            .unwrap();
+    auth.set_sensitive(true);
+    auth
+}
+
 impl RequestSend for RequestBuilder {
     fn configure(self, g: &GithubClient) -> RequestBuilder {
-        let mut auth = reqwest::header::HeaderValue::from_maybe_shared(format!(
-            "token {}",
-            g.token.expose_secret()
-        ))
-        .unwrap();
-        auth.set_sensitive(true);
-        self.header(USER_AGENT, "rust-lang-triagebot")
-            .header(AUTHORIZATION, &auth)
-            .header("X-GitHub-Api-Version", GITHUB_API_VERSION)
+        let req = self
+            .header(USER_AGENT, "rust-lang-triagebot")
+            .header("X-GitHub-Api-Version", GITHUB_API_VERSION);
+        // Installation tokens are only known once refreshed, see `GithubClient::authorize`.
+        let token = match &g.auth {
+            GithubAuth::Token(token) => Some(token.clone()),
+            GithubAuth::App(cache) => cache.cached(chrono::Utc::now()),
+        };
+        match token {
+            Some(token) => req.header(AUTHORIZATION, authorization_header(&token)),
+            None => req,
+        }
     }
 }
 
@@ -463,7 +605,7 @@ impl Job for GithubRateLimitLoggingJob {
 fn gh_api_version() {
     let c = GithubClient {
         client: Client::new(),
-        token: String::new().into(),
+        auth: GithubAuth::Token(String::new().into()),
         api_url: String::new(),
         graphql_url: "".to_string(),
         raw_url: "".to_string(),
@@ -483,3 +625,50 @@ fn gh_api_version() {
         .collect::<Vec<_>>();
     assert_eq!(headers[0].to_str().unwrap(), "2022-11-28");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{RateLimit, RateLimitGauges};
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    #[test]
+    fn rate_limit_headers() {
+        let gauges = RateLimitGauges::new();
+        let headers = |resource: Option<&'static str>, remaining: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("x-ratelimit-limit", HeaderValue::from_static("5000"));
+            headers.insert("x-ratelimit-remaining", HeaderValue::from_static(remaining));
+            headers.insert("x-ratelimit-reset", HeaderValue::from_static("1750000000"));
+            if let Some(resource) = resource {
+                headers.insert("x-ratelimit-resource", HeaderValue::from_static(resource));
+            }
+            headers
+        };
+        let rate_limit = |remaining| RateLimit {
+            limit: 5000,
+            remaining,
+            reset: 1750000000,
+        };
+
+        assert_eq!(gauges.update(&HeaderMap::new()), None);
+        assert!(gauges.snapshot().is_empty());
+
+        assert_eq!(
+            gauges.update(&headers(None, "4999")),
+            Some(("core".to_string(), rate_limit(4999)))
+        );
+        gauges.update(&headers(Some("graphql"), "120"));
+        gauges.update(&headers(Some("core"), "4998"));
+        assert_eq!(
+            gauges.snapshot().into_iter().collect::<Vec<_>>(),
+            vec![
+                ("core".to_string(), rate_limit(4998)),
+                ("graphql".to_string(), rate_limit(120)),
+            ]
+        );
+
+        // Malformed headers leave the gauges untouched
+        assert_eq!(gauges.update(&headers(Some("core"), "many")), None);
+        assert_eq!(gauges.snapshot()["core"], rate_limit(4998));
+    }
+}
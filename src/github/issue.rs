@@ -1,12 +1,13 @@
 use anyhow::Context;
 use chrono::Utc;
+use futures::StreamExt as _;
 use reqwest::StatusCode;
 use std::fmt;
 use std::sync::OnceLock;
 use tracing as log;
 
 use super::client::GithubClient;
-use super::repos::{GitHubUser, Milestone, Repository};
+use super::repos::{GitHubUser, GitHubUserType, Milestone, MilestoneState, Repository};
 use super::utils::{Selection, opt_string};
 use crate::errors::{AssignmentError, UserError};
 use crate::github::GithubCommit;
@@ -115,11 +116,15 @@ pub struct CommitBase {
 /// An indicator used to differentiate between an issue and a pull request.
 ///
 /// Some webhook events include a `pull_request` field in the Issue object,
-/// and some don't. GitHub does include a few fields here, but they aren't
-/// needed at this time (merged_at, diff_url, html_url, patch_url, url).
+/// and some don't. GitHub does include a few fields here, but most of them
+/// aren't needed at this time (diff_url, html_url, patch_url, url).
 #[derive(Debug, serde::Deserialize)]
 #[cfg_attr(test, derive(Default))]
 pub struct PullRequestDetails {
+    /// When the PR was merged, only available when the PR is listed as an
+    /// issue (e.g. with the issues API), see [`Issue::merged`] otherwise.
+    #[serde(default)]
+    pub merged_at: Option<chrono::DateTime<Utc>>,
     /// This is a slot to hold the diff for a PR.
     ///
     /// This will be filled in only once as an optimization since multiple
@@ -132,6 +137,7 @@ pub struct PullRequestDetails {
 impl PullRequestDetails {
     pub fn new() -> PullRequestDetails {
         PullRequestDetails {
+            merged_at: None,
             compare: tokio::sync::OnceCell::new(),
         }
     }
@@ -166,6 +172,12 @@ pub struct FileDiff {
     /// (like when a file is renamed without it's content being modified).
     #[serde(default)]
     pub patch: String,
+    /// The number of lines added to the file.
+    #[serde(default)]
+    pub additions: usize,
+    /// The number of lines removed from the file.
+    #[serde(default)]
+    pub deletions: usize,
 }
 
 impl Issue {
@@ -200,6 +212,12 @@ impl Issue {
     }
 
     pub async fn edit_body(&self, client: &GithubClient, body: &str) -> anyhow::Result<()> {
+        if crate::dry_run::skip(format_args!(
+            "edited the body of {}: {body:?}",
+            self.global_id()
+        )) {
+            return Ok(());
+        }
         let edit_url = format!("{}/issues/{}", self.repository().url(client), self.number);
         #[derive(serde::Serialize)]
         struct ChangedIssue<'a> {
@@ -218,6 +236,12 @@ impl Issue {
         id: u64,
         new_body: &str,
     ) -> anyhow::Result<()> {
+        if crate::dry_run::skip(format_args!(
+            "edited review {id} on {}: {new_body:?}",
+            self.global_id()
+        )) {
+            return Ok(());
+        }
         let comment_url = format!(
             "{}/pulls/{}/reviews/{}",
             self.repository().url(client),
@@ -245,6 +269,12 @@ impl Issue {
         id: u64,
         new_body: &str,
     ) -> anyhow::Result<Comment> {
+        if crate::dry_run::skip(format_args!(
+            "edited review comment {id} on {}: {new_body:?}",
+            self.global_id()
+        )) {
+            return Ok(Comment::dry_run(new_body));
+        }
         let comment_url = format!("{}/pulls/comments/{}", self.repository().url(client), id);
         #[derive(serde::Serialize)]
         struct EditComment<'a> {
@@ -284,8 +314,16 @@ impl Issue {
             return Ok(());
         }
 
+        if crate::dry_run::skip(format_args!(
+            "removed labels {:?} from {}",
+            labels.iter().map(|l| &l.name).collect::<Vec<_>>(),
+            self.global_id()
+        )) {
+            return Ok(());
+        }
+
         // There is no API to remove all labels at once, so we issue as many
-        // API requests are required in parallel.
+        // API requests are required, a few of them in parallel.
         let requests = labels.into_iter().map(|label| async move {
             // DELETE /repos/:owner/:repo/issues/:number/labels/{name}
             let url = format!(
@@ -301,9 +339,9 @@ impl Issue {
                 .with_context(|| format!("failed to remove {label:?}"))
         });
 
-        futures::future::try_join_all(requests).await?;
-
-        Ok(())
+        run_all_bounded(requests, LABEL_REMOVAL_CONCURRENCY)
+            .await
+            .with_context(|| format!("failed to remove labels from {}", self.global_id()))
     }
 
     pub async fn add_labels(
@@ -333,6 +371,13 @@ impl Issue {
             return Ok(());
         }
 
+        if crate::dry_run::skip(format_args!(
+            "added labels {labels:?} to {}",
+            self.global_id()
+        )) {
+            return Ok(());
+        }
+
         let mut unknown_labels = vec![];
         let mut known_labels = vec![];
         for label in labels {
@@ -408,6 +453,13 @@ impl Issue {
                 .collect::<Vec<_>>(),
         };
 
+        if crate::dry_run::skip(format_args!(
+            "removed assignees {assignees:?} from {}",
+            self.global_id()
+        )) {
+            return Ok(());
+        }
+
         #[derive(serde::Serialize)]
         struct AssigneeReq<'a> {
             assignees: &'a [&'a str],
@@ -429,6 +481,9 @@ impl Issue {
         user: &str,
     ) -> Result<(), AssignmentError> {
         log::info!("add_assignee {} for {}", user, self.global_id());
+        if crate::dry_run::skip(format_args!("assigned {user} to {}", self.global_id())) {
+            return Ok(());
+        }
         let url = format!(
             "{repo_url}/issues/{number}/assignees",
             repo_url = self.repository().url(client),
@@ -482,9 +537,16 @@ impl Issue {
             title
         );
 
+        if crate::dry_run::skip(format_args!(
+            "set milestone {title:?} on {}",
+            self.global_id()
+        )) {
+            return Ok(());
+        }
+
         let full_repo_name = self.repository().full_repo_name();
         let milestone = client
-            .get_or_create_milestone(&full_repo_name, title, "open")
+            .get_or_create_milestone(&full_repo_name, title, MilestoneState::Open)
             .await?;
 
         client
@@ -494,6 +556,9 @@ impl Issue {
     }
 
     pub async fn close(&self, client: &GithubClient) -> anyhow::Result<()> {
+        if crate::dry_run::skip(format_args!("closed {}", self.global_id())) {
+            return Ok(());
+        }
         let edit_url = format!("{}/issues/{}", self.repository().url(client), self.number);
         #[derive(serde::Serialize)]
         struct CloseIssue<'a> {
@@ -610,6 +675,12 @@ impl Issue {
         owner: &str,
         repo: &str,
     ) -> anyhow::Result<()> {
+        if crate::dry_run::skip(format_args!(
+            "transferred {} to {owner}/{repo}",
+            self.global_id()
+        )) {
+            return Ok(());
+        }
         let issue_id = self.graphql_issue_id(client).await?;
         let repo_id = client.graphql_repo_id(owner, repo).await?;
         client
@@ -634,6 +705,12 @@ impl Issue {
 
     /// Enqueues this pull request into the repository's merge queue.
     pub async fn enqueue_to_merge_queue(&self, client: &GithubClient) -> anyhow::Result<()> {
+        if crate::dry_run::skip(format_args!(
+            "enqueued {} to the merge queue",
+            self.global_id()
+        )) {
+            return Ok(());
+        }
         let pr_id = self.graphql_issue_id(client).await?;
 
         client
@@ -722,6 +799,29 @@ pub struct Comment {
     pub author_association: AuthorAssociation,
 }
 
+impl Comment {
+    /// Placeholder for a comment that was not posted because of the dry-run mode.
+    fn dry_run(body: &str) -> Comment {
+        Comment {
+            id: 0,
+            node_id: String::new(),
+            in_reply_to_id: None,
+            pull_request_review_id: None,
+            body: body.to_string(),
+            html_url: String::new(),
+            user: GitHubUser {
+                login: String::new(),
+                id: 0,
+                r#type: GitHubUserType::Bot,
+            },
+            created_at: None,
+            updated_at: None,
+            pr_review_state: None,
+            author_association: AuthorAssociation(octocrab::models::AuthorAssociation::None),
+        }
+    }
+}
+
 #[derive(Debug, serde::Deserialize, Eq, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum PullRequestReviewState {
@@ -762,6 +862,12 @@ impl Issue {
         id: u64,
         new_body: &str,
     ) -> anyhow::Result<Comment> {
+        if crate::dry_run::skip(format_args!(
+            "edited comment {id} on {}: {new_body:?}",
+            self.global_id()
+        )) {
+            return Ok(Comment::dry_run(new_body));
+        }
         let comment_url = format!("{}/issues/comments/{}", self.repository().url(client), id);
         #[derive(serde::Serialize)]
         struct NewComment<'a> {
@@ -783,6 +889,12 @@ impl Issue {
         struct PostComment<'a> {
             body: &'a str,
         }
+        if crate::dry_run::skip(format_args!(
+            "posted comment on {}: {body:?}",
+            self.global_id()
+        )) {
+            return Ok(Comment::dry_run(body));
+        }
         let comments_path = self
             .comments_url
             .strip_prefix("https://api.github.com")
@@ -841,6 +953,25 @@ pub enum ReportedContentClassifiers {
     Spam,
 }
 
+/// Minimizes (hides) a comment, given its GraphQL node id.
+const MINIMIZE_COMMENT_MUTATION: &str =
+    "mutation($node_id: ID!, $reason: ReportedContentClassifiers!) {
+        minimizeComment(input: {subjectId: $node_id, classifier: $reason}) {
+            __typename
+        }
+    }";
+
+/// The variables of [`MINIMIZE_COMMENT_MUTATION`].
+fn minimize_comment_variables(
+    node_id: &str,
+    reason: &ReportedContentClassifiers,
+) -> serde_json::Value {
+    serde_json::json!({
+        "node_id": node_id,
+        "reason": reason,
+    })
+}
+
 impl Issue {
     pub async fn hide_comment(
         &self,
@@ -848,17 +979,16 @@ impl Issue {
         node_id: &str,
         reason: ReportedContentClassifiers,
     ) -> anyhow::Result<()> {
+        if crate::dry_run::skip(format_args!(
+            "hidden comment {node_id} on {} as {reason:?}",
+            self.global_id()
+        )) {
+            return Ok(());
+        }
         client
             .graphql_query(
-                "mutation($node_id: ID!, $reason: ReportedContentClassifiers!) {
-                    minimizeComment(input: {subjectId: $node_id, classifier: $reason}) {
-                        __typename
-                    }
-                }",
-                serde_json::json!({
-                    "node_id": node_id,
-                    "reason": reason,
-                }),
+                MINIMIZE_COMMENT_MUTATION,
+                minimize_comment_variables(node_id, &reason),
             )
             .await?;
         Ok(())
@@ -886,6 +1016,9 @@ impl Issue {
         client: &GithubClient,
         reason: Option<LockReason>,
     ) -> anyhow::Result<()> {
+        if crate::dry_run::skip(format_args!("locked {} ({reason:?})", self.global_id())) {
+            return Ok(());
+        }
         let lock_url = format!(
             "{}/issues/{}/lock",
             self.repository().url(client),
@@ -912,6 +1045,9 @@ impl Issue {
 
     /// Unlock an issue.
     pub async fn unlock(&self, client: &GithubClient) -> anyhow::Result<()> {
+        if crate::dry_run::skip(format_args!("unlocked {}", self.global_id())) {
+            return Ok(());
+        }
         let lock_url = format!(
             "{}/issues/{}/lock",
             self.repository().url(client),
@@ -986,9 +1122,53 @@ impl ZulipGitHubReference {
                 format!("{}#{}", repo.repository, self.number)
             }
         } else {
-            format!("{}/{}#{}", repo.organization, repo.repository, self.number)
+            self.zulip_topic_full_reference()
         }
     }
+
+    /// Like [`ZulipGitHubReference::zulip_topic_reference`], but always including
+    /// the organization and repository, e.g. `rust-lang/rust#123`.
+    pub fn zulip_topic_full_reference(&self) -> String {
+        let repo = &self.repository;
+        format!("{}/{}#{}", repo.organization, repo.repository, self.number)
+    }
+
+    /// Extracts the repository and issue number from a Zulip topic ending with
+    /// the [`ZulipGitHubReference::zulip_topic_reference`] of an issue.
+    ///
+    /// The title part of the topic may have been truncated, only the reference
+    /// suffix is used.
+    pub fn issue_from_zulip_topic(topic: &str) -> Option<(IssueRepository, u64)> {
+        let (_, reference) = topic.rsplit_once(' ')?;
+        Self::parse_zulip_topic_reference(reference)
+    }
+
+    /// Parses a reference produced by [`ZulipGitHubReference::zulip_topic_reference`]
+    /// back into its repository and issue number.
+    pub fn parse_zulip_topic_reference(reference: &str) -> Option<(IssueRepository, u64)> {
+        let (repo, number) = reference.rsplit_once('#')?;
+        if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let number = number.parse().ok()?;
+
+        let (organization, repository) = match repo.split_once('/') {
+            None if repo.is_empty() => ("rust-lang", "rust"),
+            None => ("rust-lang", repo),
+            Some((org, repo)) if !org.is_empty() && !repo.is_empty() && !repo.contains('/') => {
+                (org, repo)
+            }
+            Some(_) => return None,
+        };
+
+        Some((
+            IssueRepository {
+                organization: organization.to_string(),
+                repository: repository.to_string(),
+            },
+            number,
+        ))
+    }
 }
 
 impl Issue {
@@ -1086,3 +1266,91 @@ impl GitHubPermission {
         }
     }
 }
+
+/// Maximum number of label removals sent in parallel.
+const LABEL_REMOVAL_CONCURRENCY: usize = 5;
+
+/// Runs all the `requests`, at most `limit` of them at once, returning an
+/// error listing all the failures instead of stopping at the first one.
+async fn run_all_bounded<F>(
+    requests: impl IntoIterator<Item = F>,
+    limit: usize,
+) -> anyhow::Result<()>
+where
+    F: Future<Output = anyhow::Result<()>>,
+{
+    let errors: Vec<anyhow::Error> = futures::stream::iter(requests)
+        .buffer_unordered(limit)
+        .filter_map(|res| async move { res.err() })
+        .collect()
+        .await;
+
+    match errors.as_slice() {
+        [] => Ok(()),
+        [err] => Err(anyhow::anyhow!("{err:#}")),
+        errors => Err(anyhow::anyhow!(
+            "{} requests failed:\n{}",
+            errors.len(),
+            errors
+                .iter()
+                .map(|err| format!("- {err:#}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn bounded_requests_aggregate_errors() {
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+        let requests = (0..8).map(|i| {
+            let (in_flight, max_in_flight) = (&in_flight, &max_in_flight);
+            async move {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                tokio::task::yield_now().await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                if i % 3 == 0 {
+                    anyhow::bail!("failed to remove label {i}");
+                }
+                Ok(())
+            }
+        });
+
+        let err = run_all_bounded(requests, 3).await.unwrap_err().to_string();
+        // The requests were issued concurrently, within the limit.
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 3);
+        // All the failures are reported, not only the first one.
+        assert!(err.starts_with("3 requests failed:"));
+        for i in [0, 3, 6] {
+            assert!(err.contains(&format!("- failed to remove label {i}")));
+        }
+
+        assert!(
+            run_all_bounded((0..4).map(|_| async { Ok(()) }), 2)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn minimize_comment_mutation() {
+        assert!(
+            MINIMIZE_COMMENT_MUTATION
+                .contains("minimizeComment(input: {subjectId: $node_id, classifier: $reason})")
+        );
+        assert_eq!(
+            minimize_comment_variables("IC_kwDOArhJjM6", &ReportedContentClassifiers::Outdated),
+            serde_json::json!({
+                "node_id": "IC_kwDOArhJjM6",
+                "reason": "OUTDATED",
+            })
+        );
+    }
+}
@@ -8,8 +8,11 @@ use bytes::Bytes;
 use chrono::{DateTime, FixedOffset, Utc};
 use itertools::Itertools;
 use octocrab::models::Author;
+use reqwest::RequestBuilder;
 use reqwest::StatusCode;
+use reqwest::header::RANGE;
 use std::collections::HashSet;
+use std::ops::Range;
 use tracing as log;
 
 // User
@@ -74,6 +77,11 @@ impl GithubClient {
             body: &'a str,
             labels: Vec<String>,
         }
+        if crate::dry_run::skip(format_args!(
+            "created the issue {title:?} in {repo} with labels {labels:?}"
+        )) {
+            return Ok(NewIssueResponse { number: 0 });
+        }
         let url = format!("{}/issues", repo.url(self));
         self.json(self.post(&url).json(&NewIssue {
             title,
@@ -106,6 +114,11 @@ impl GithubClient {
         struct Update {
             state: PrState,
         }
+        if crate::dry_run::skip(format_args!(
+            "set the state of {repo}#{number} to {state:?}"
+        )) {
+            return Ok(());
+        }
         let url = format!("{}/pulls/{number}", repo.url(self));
         self.send_req(self.patch(&url).json(&Update { state }))
             .await
@@ -180,6 +193,25 @@ impl GithubClient {
 
 // Others
 
+/// How [`GithubClient::compare_with`] computes the changes between two commits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareMode {
+    /// `base..head`: the changes between `base` itself and `head`.
+    Literal,
+    /// `base...head`: the changes of `head` since its merge-base with `base`.
+    MergeBase,
+}
+
+impl CompareMode {
+    /// The separator of the range, as used by git and the GitHub compare API.
+    pub fn separator(self) -> &'static str {
+        match self {
+            CompareMode::Literal => "..",
+            CompareMode::MergeBase => "...",
+        }
+    }
+}
+
 impl GithubClient {
     pub async fn raw_job_logs(
         &self,
@@ -196,18 +228,76 @@ impl GithubClient {
         Ok(String::from_utf8_lossy(&body).to_string())
     }
 
+    /// Retrieves the bytes in `range` of the raw logs of a job, trimmed to
+    /// whole lines.
+    ///
+    /// Only the requested bytes are downloaded when the storage backing the logs
+    /// supports HTTP range requests, otherwise the whole logs are downloaded and
+    /// the range is extracted from them.
+    pub async fn raw_job_logs_range(
+        &self,
+        repo: &IssueRepository,
+        job_id: u128,
+        range: Range<u64>,
+    ) -> anyhow::Result<String> {
+        const MAX_LOG_SIZE_IN_MB: usize = 50 * 1024 * 1024; // 50 Mib
+
+        // Also fetch the byte before the range, to know if the range starts at
+        // the beginning of a line.
+        let fetched = range.start.saturating_sub(1)..range.end;
+
+        let url = format!("{}/actions/jobs/{job_id}/logs", repo.url(self));
+        let (status, body, _req_dbg) = self
+            .send_req_with_limit_and_status(
+                with_range_header(self.get(&url), &fetched),
+                MAX_LOG_SIZE_IN_MB,
+            )
+            .await
+            .context("failed to retrieve job logs")?;
+
+        let logs = logs_range(status, &body, &fetched);
+        Ok(String::from_utf8_lossy(logs).to_string())
+    }
+
+    /// Compares `before` and `after` from their merge-base, like `git diff before...after`.
     pub async fn compare(
         &self,
         repo: &IssueRepository,
         before: &str,
         after: &str,
     ) -> anyhow::Result<GithubCompare> {
-        let url = format!("{}/compare/{before}...{after}", repo.url(self));
+        self.compare_with(repo, before, after, CompareMode::MergeBase)
+            .await
+    }
+
+    /// Compares `before` and `after` with the given [`CompareMode`].
+    pub async fn compare_with(
+        &self,
+        repo: &IssueRepository,
+        before: &str,
+        after: &str,
+        mode: CompareMode,
+    ) -> anyhow::Result<GithubCompare> {
+        let url = self.compare_url(repo, before, after, mode);
         self.json(self.get(&url))
             .await
             .context("failed to retrive the compare")
     }
 
+    fn compare_url(
+        &self,
+        repo: &IssueRepository,
+        before: &str,
+        after: &str,
+        mode: CompareMode,
+    ) -> String {
+        format!(
+            "{}/compare/{before}{}{after}",
+            repo.url(self),
+            mode.separator()
+        )
+    }
+
     pub async fn issue(&self, repo: &IssueRepository, issue_num: u64) -> anyhow::Result<Issue> {
         let url = format!("{}/issues/{issue_num}", repo.url(self));
         self.json(self.get(&url))
@@ -225,6 +315,37 @@ impl GithubClient {
         Ok(pr)
     }
 
+    /// Returns the issues and pull requests matching a search query, e.g.
+    /// `repo:rust-lang/rust is:open label:A-diagnostics`.
+    ///
+    /// The search API never returns more than the first 1000 results.
+    pub async fn search_issues(&self, query: &str) -> anyhow::Result<Vec<Issue>> {
+        #[derive(Debug, serde::Deserialize)]
+        struct IssueSearchResult {
+            total_count: u64,
+            items: Vec<Issue>,
+        }
+
+        let url = format!("{}/search/issues", self.api_url);
+        let mut issues = Vec::new();
+        for page in 1..=10 {
+            let result: IssueSearchResult = self
+                .json(self.get(&url).query(&[
+                    ("q", query),
+                    ("per_page", "100"),
+                    ("page", &page.to_string()),
+                ]))
+                .await
+                .with_context(|| format!("failed to search issues with `{query}`"))?;
+            let last_page = result.items.len() < 100;
+            issues.extend(result.items);
+            if last_page || issues.len() as u64 >= result.total_count {
+                break;
+            }
+        }
+        Ok(issues)
+    }
+
     pub async fn get_contents(
         &self,
         repo: &IssueRepository,
@@ -244,9 +365,10 @@ impl GithubClient {
         let url = format!("{}/{repo}/{branch}/{path}", self.raw_url);
         let req = self.get(&url);
         let req_dbg = format!("{req:?}");
-        let req = req
+        let mut req = req
             .build()
             .with_context(|| format!("failed to build request {req_dbg:?}"))?;
+        self.authorize(&mut req).await?;
         let resp = self.raw().execute(req).await.context(req_dbg.clone())?;
         let status = resp.status();
         let body = resp
@@ -468,12 +590,28 @@ impl GithubClient {
     }
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, PartialEq, Eq, serde::Deserialize)]
 pub struct Milestone {
     number: u64,
     title: String,
 }
 
+/// The state of a [`Milestone`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MilestoneState {
+    #[default]
+    Open,
+    Closed,
+}
+
+fn create_milestone_body(title: &str, state: MilestoneState) -> serde_json::Value {
+    serde_json::json!({
+        "title": title,
+        "state": state,
+    })
+}
+
 impl GithubClient {
     /// Get or create a [`Milestone`].
     ///
@@ -482,14 +620,24 @@ impl GithubClient {
         &self,
         full_repo_name: &str,
         title: &str,
-        state: &str,
+        state: MilestoneState,
     ) -> anyhow::Result<Milestone> {
+        if crate::dry_run::is_enabled() {
+            // Only the creation is skipped, an existing milestone is still looked up.
+            if let Some(milestone) = self.find_milestone(full_repo_name, title).await? {
+                return Ok(milestone);
+            }
+            crate::dry_run::skip(format_args!(
+                "created the milestone {title:?} ({state:?}) in {full_repo_name}"
+            ));
+            return Ok(Milestone {
+                number: 0,
+                title: title.to_string(),
+            });
+        }
         let url = format!("{}/repos/{full_repo_name}/milestones", self.api_url);
         let resp = self
-            .send_req(self.post(&url).json(&serde_json::json!({
-                "title": title,
-                "state": state,
-            })))
+            .send_req(self.post(&url).json(&create_milestone_body(title, state)))
             .await;
         match resp {
             Ok((body, _dbg)) => {
@@ -510,6 +658,17 @@ impl GithubClient {
             }
         }
         // In the case where it already exists, we need to search for its number.
+        self.find_milestone(full_repo_name, title)
+            .await?
+            .with_context(|| format!("expected to find milestone with title {title}"))
+    }
+
+    /// Finds a [`Milestone`] by its title, whatever its state.
+    pub(crate) async fn find_milestone(
+        &self,
+        full_repo_name: &str,
+        title: &str,
+    ) -> anyhow::Result<Option<Milestone>> {
         let mut page = 1;
         loop {
             let url = format!(
@@ -521,15 +680,39 @@ impl GithubClient {
                 .await
                 .with_context(|| format!("failed to get milestones {url} searching for {title}"))?;
             if milestones.is_empty() {
-                anyhow::bail!("expected to find milestone with title {title}");
+                return Ok(None);
             }
             if let Some(milestone) = milestones.into_iter().find(|m| m.title == title) {
-                return Ok(milestone);
+                return Ok(Some(milestone));
             }
             page += 1;
         }
     }
 
+    /// Lists the issues and pull requests of a milestone, whatever their state.
+    pub(crate) async fn milestone_issues(
+        &self,
+        full_repo_name: &str,
+        milestone: &Milestone,
+    ) -> anyhow::Result<Vec<Issue>> {
+        let mut issues = Vec::new();
+        for page in 1.. {
+            let url = format!(
+                "{}/repos/{full_repo_name}/issues?milestone={}&state=all&per_page=100&page={page}",
+                self.api_url, milestone.number
+            );
+            let page: Vec<Issue> = self.json(self.get(&url)).await.with_context(|| {
+                format!("failed to list the issues of milestone {}", milestone.title)
+            })?;
+            let last_page = page.len() < 100;
+            issues.extend(page);
+            if last_page {
+                break;
+            }
+        }
+        Ok(issues)
+    }
+
     /// Set the milestone of an issue or PR.
     pub(crate) async fn set_milestone(
         &self,
@@ -537,6 +720,12 @@ impl GithubClient {
         milestone: &Milestone,
         issue_num: u64,
     ) -> anyhow::Result<()> {
+        if crate::dry_run::skip(format_args!(
+            "set milestone {:?} on {full_repo_name}#{issue_num}",
+            milestone.title
+        )) {
+            return Ok(());
+        }
         let url = format!("{}/repos/{full_repo_name}/issues/{issue_num}", self.api_url);
         self.send_req(self.patch(&url).json(&serde_json::json!({
             "milestone": milestone.number
@@ -856,7 +1045,7 @@ impl Repository {
         &self,
         client: &GithubClient,
         title: &str,
-        state: &str,
+        state: MilestoneState,
     ) -> anyhow::Result<Milestone> {
         client
             .get_or_create_milestone(&self.full_name, title, state)
@@ -1105,6 +1294,58 @@ pub struct GitTreeObject {
     pub sha: String,
 }
 
+/// Maximum number of pages of commits fetched concurrently by
+/// [`Repository::github_commits_in_range`].
+const COMMIT_PAGES_CONCURRENCY: u32 = 4;
+
+/// Collects the commits of the pages returned by `fetch_page` (starting at
+/// page 1), until the commit `start` (excluded).
+///
+/// The pages are fetched [`COMMIT_PAGES_CONCURRENCY`] at a time, but are always
+/// assembled in order.
+async fn commits_until<F, Fut>(start: &str, fetch_page: F) -> anyhow::Result<Vec<GithubCommit>>
+where
+    F: Fn(u32) -> Fut,
+    Fut: Future<Output = anyhow::Result<Vec<GithubCommit>>>,
+{
+    let mut commits = Vec::new();
+    let mut first_page = 1;
+    loop {
+        let pages = futures::future::try_join_all(
+            (first_page..first_page + COMMIT_PAGES_CONCURRENCY).map(&fetch_page),
+        )
+        .await?;
+        for (page, mut this_page) in (first_page..).zip(pages) {
+            // This is a temporary debugging measure to investigate why the
+            // `/commits` endpoint is not returning the expected values in
+            // production.
+            let v: String = this_page
+                .iter()
+                .map(|commit| {
+                    format!(
+                        "({}, {}, {:?}) ",
+                        commit.sha, commit.commit.author.date, commit.parents
+                    )
+                })
+                .collect();
+            log::info!("page {page}: {v}");
+            if let Some(idx) = this_page.iter().position(|commit| commit.sha == start) {
+                this_page.truncate(idx);
+                commits.extend(this_page);
+                return Ok(commits);
+            }
+            if this_page.is_empty() {
+                anyhow::bail!(
+                    "commit {start} not found in the {} commits of the range",
+                    commits.len()
+                );
+            }
+            commits.extend(this_page);
+        }
+        first_page += COMMIT_PAGES_CONCURRENCY;
+    }
+}
+
 #[derive(Debug, serde::Deserialize)]
 pub struct GitUser {
     pub date: DateTime<FixedOffset>,
@@ -1126,39 +1367,17 @@ impl Repository {
         start: &str,
         end: &str,
     ) -> anyhow::Result<Vec<GithubCommit>> {
-        let mut commits = Vec::new();
-        let mut page = 1;
-        loop {
+        commits_until(start, |page| async move {
             let url = format!(
                 "{}/commits?sha={end}&per_page=100&page={page}",
                 self.url(client)
             );
-            let mut this_page: Vec<GithubCommit> = client
+            client
                 .json(client.get(&url))
                 .await
-                .with_context(|| format!("failed to fetch commits for {url}"))?;
-            // This is a temporary debugging measure to investigate why the
-            // `/commits` endpoint is not returning the expected values in
-            // production.
-            let v: String = this_page
-                .iter()
-                .map(|commit| {
-                    format!(
-                        "({}, {}, {:?}) ",
-                        commit.sha, commit.commit.author.date, commit.parents
-                    )
-                })
-                .collect();
-            log::info!("page {page}: {v}");
-            if let Some(idx) = this_page.iter().position(|commit| commit.sha == start) {
-                this_page.truncate(idx);
-                commits.extend(this_page);
-                return Ok(commits);
-            } else {
-                commits.extend(this_page);
-            }
-            page += 1;
-        }
+                .with_context(|| format!("failed to fetch commits for {url}"))
+        })
+        .await
     }
 
     pub async fn github_commit(
@@ -1443,3 +1662,172 @@ struct Ordering<'a> {
     per_page: &'a str,
     page: u64,
 }
+
+// Logs range
+
+/// Requests only the bytes in `range` (end excluded) of the response.
+fn with_range_header(req: RequestBuilder, range: &Range<u64>) -> RequestBuilder {
+    req.header(
+        RANGE,
+        format!("bytes={}-{}", range.start, range.end.saturating_sub(1)),
+    )
+}
+
+/// Extracts the lines in `range` from the logs fetched with a range request.
+///
+/// The whole logs were sent if the range request wasn't honored. The first
+/// byte of `range` is only used to know if the first line is complete, the
+/// incomplete lines at both ends of the range are dropped.
+fn logs_range<'a>(status: StatusCode, body: &'a [u8], range: &Range<u64>) -> &'a [u8] {
+    let mut logs = if status == StatusCode::PARTIAL_CONTENT {
+        body
+    } else {
+        let start = (range.start as usize).min(body.len());
+        let end = (range.end as usize).clamp(start, body.len());
+        &body[start..end]
+    };
+    let is_truncated = logs.len() as u64 == range.end - range.start;
+
+    if range.start > 0 {
+        logs = match logs.iter().position(|&b| b == b'\n') {
+            Some(pos) => &logs[pos + 1..],
+            None => &[],
+        };
+    }
+    if is_truncated && !logs.ends_with(b"\n") {
+        logs = match logs.iter().rposition(|&b| b == b'\n') {
+            Some(pos) => &logs[..=pos],
+            None => &[],
+        };
+    }
+    logs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOGS: &[u8] = b"line 1\nline 2\nline 3\nline 4\n";
+
+    #[test]
+    fn compare_separators() {
+        let client = crate::tests::unreachable_github_client();
+        let repo = IssueRepository {
+            organization: "rust-lang".to_string(),
+            repository: "rust".to_string(),
+        };
+        assert_eq!(
+            client.compare_url(&repo, "base", "head", CompareMode::MergeBase),
+            "http://127.0.0.1:1/repos/rust-lang/rust/compare/base...head"
+        );
+        assert_eq!(
+            client.compare_url(&repo, "base", "head", CompareMode::Literal),
+            "http://127.0.0.1:1/repos/rust-lang/rust/compare/base..head"
+        );
+    }
+
+    #[test]
+    fn range_header() {
+        let req = with_range_header(reqwest::Client::new().get("https://example.com"), &(6..20))
+            .build()
+            .unwrap();
+        assert_eq!(req.headers()[RANGE], "bytes=6-19");
+    }
+
+    #[test]
+    fn partial_logs_range() {
+        // Bytes 6..20 are "\nline 2\nline 3", the server only sends those.
+        assert_eq!(
+            logs_range(StatusCode::PARTIAL_CONTENT, &LOGS[6..20], &(6..20)),
+            b"line 2\n"
+        );
+        // Bytes 9..28 are "e 2\nline 3\nline 4\n", the first line is incomplete.
+        assert_eq!(
+            logs_range(StatusCode::PARTIAL_CONTENT, &LOGS[9..28], &(9..28)),
+            b"line 3\nline 4\n"
+        );
+        // The server sends less than requested at the end of the logs.
+        assert_eq!(
+            logs_range(StatusCode::PARTIAL_CONTENT, &LOGS[20..], &(20..100)),
+            b"line 4\n"
+        );
+    }
+
+    #[test]
+    fn full_logs_range() {
+        // Range requests are unsupported, the whole logs are sent.
+        assert_eq!(logs_range(StatusCode::OK, LOGS, &(6..20)), b"line 2\n");
+        assert_eq!(
+            logs_range(StatusCode::OK, LOGS, &(0..14)),
+            b"line 1\nline 2\n"
+        );
+        assert_eq!(logs_range(StatusCode::OK, LOGS, &(20..100)), b"line 4\n");
+        assert_eq!(logs_range(StatusCode::OK, LOGS, &(100..200)), b"");
+    }
+
+    fn commit(sha: &str) -> GithubCommit {
+        serde_json::from_value(serde_json::json!({
+            "sha": sha,
+            "commit": {
+                "author": { "date": "2025-01-01T00:00:00Z", "name": null, "email": null },
+                "message": format!("Commit {sha}"),
+                "tree": { "sha": "0000" },
+            },
+            "parents": [],
+            "html_url": "",
+        }))
+        .unwrap()
+    }
+
+    /// Serves pages of 2 commits (`c1`, `c2`, `c3`, ...), the first pages being
+    /// the slowest to arrive.
+    async fn fetch_page(
+        page: u32,
+        fetched: &std::sync::Mutex<Vec<u32>>,
+    ) -> anyhow::Result<Vec<GithubCommit>> {
+        fetched.lock().unwrap().push(page);
+        tokio::time::sleep(std::time::Duration::from_millis(u64::from(20 - page))).await;
+        if page > 6 {
+            return Ok(vec![]);
+        }
+        Ok((1..=2)
+            .map(|idx| commit(&format!("c{}", (page - 1) * 2 + idx)))
+            .collect())
+    }
+
+    #[tokio::test]
+    async fn commits_in_range_order() {
+        let fetched = std::sync::Mutex::new(Vec::new());
+        let commits = commits_until("c10", |page| fetch_page(page, &fetched))
+            .await
+            .unwrap();
+        assert_eq!(
+            commits.iter().map(|c| c.sha.as_str()).collect::<Vec<_>>(),
+            ["c1", "c2", "c3", "c4", "c5", "c6", "c7", "c8", "c9"]
+        );
+        let mut fetched = fetched.into_inner().unwrap();
+        fetched.sort();
+        assert_eq!(fetched, [1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let fetched = std::sync::Mutex::new(Vec::new());
+        let err = commits_until("unknown", |page| fetch_page(page, &fetched))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "commit unknown not found in the 12 commits of the range"
+        );
+    }
+
+    #[test]
+    fn milestone_state_in_create_body() {
+        assert_eq!(
+            create_milestone_body("1.90.0", MilestoneState::Closed),
+            serde_json::json!({ "title": "1.90.0", "state": "closed" })
+        );
+        assert_eq!(
+            create_milestone_body("1.90.0", MilestoneState::Open),
+            serde_json::json!({ "title": "1.90.0", "state": "open" })
+        );
+    }
+}
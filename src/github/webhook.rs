@@ -69,6 +69,13 @@ enum EventName {
     ///
     /// <https://docs.github.com/en/webhooks/webhook-events-and-payloads#org_block>
     OrgBlock,
+    /// A label of a repository was created, edited or deleted.
+    ///
+    /// This is handled directly by the label renames handler, and is not sent to
+    /// the other handlers.
+    ///
+    /// <https://docs.github.com/en/webhooks/webhook-events-and-payloads#label>
+    Label,
     /// All other unhandled webhooks.
     Other,
 }
@@ -85,6 +92,7 @@ impl std::str::FromStr for EventName {
             "push" => EventName::Push,
             "create" => EventName::Create,
             "org_block" => EventName::OrgBlock,
+            "label" => EventName::Label,
             _ => EventName::Other,
         })
     }
@@ -104,6 +112,7 @@ impl fmt::Display for EventName {
                 EventName::Push => "push",
                 EventName::Create => "create",
                 EventName::OrgBlock => "org_block",
+                EventName::Label => "label",
                 EventName::Other => "other",
             }
         )
@@ -280,42 +289,70 @@ async fn process_payload(
             }
             return Ok(true);
         }
+        EventName::Label => {
+            let payload = deserialize_payload::<LabelEvent>(payload)
+                .context("failed to deserialize to LabelEvent")?;
+
+            log::info!("handling label event {payload:?}");
+
+            Event::Label(payload)
+        }
         // Other events need not be handled
         EventName::Other => {
             return Ok(false);
         }
     };
     let errors = crate::handlers::handle(ctx, host, &event).await;
-    let mut other_error = false;
-    let mut message = String::new();
-    for err in errors {
-        match err {
-            HandlerError::Message(msg) => {
-                if !message.is_empty() {
-                    message.push_str("\n\n");
-                }
-                message.push_str(&msg);
-            }
-            HandlerError::Other(err) => {
-                log::error!("handling event failed: {err:?}");
-                other_error = true;
-            }
-        }
-    }
-    if !message.is_empty() {
+    let report = HandlersReport::new(errors);
+    if let Some(message) = report.comment {
         log::info!("user error: {}", message);
         if let Some(issue) = event.issue() {
             let cmnt = ErrorComment::new(issue, message);
             cmnt.post(&ctx.github).await?;
         }
     }
-    if other_error {
+    if report.internal_error {
         Err(anyhow::anyhow!("handling failed, error logged"))
     } else {
         Ok(true)
     }
 }
 
+/// How the errors of the handlers of an event are reported.
+#[derive(Debug, PartialEq, Eq)]
+struct HandlersReport {
+    /// The user-facing errors, posted as a comment on the issue.
+    comment: Option<String>,
+    /// Whether an internal error happened, which is logged and fails the
+    /// webhook delivery but is never posted on the issue.
+    internal_error: bool,
+}
+
+impl HandlersReport {
+    fn new(errors: Vec<HandlerError>) -> HandlersReport {
+        let mut internal_error = false;
+        let mut message = String::new();
+        for err in errors {
+            match err {
+                HandlerError::Message(msg) => {
+                    if !message.is_empty() {
+                        message.push_str("\n\n");
+                    }
+                    message.push_str(&msg);
+                }
+                HandlerError::Other(err) => {
+                    log::error!("handling event failed: {err:?}");
+                    internal_error = true;
+                }
+            }
+        }
+        HandlersReport {
+            comment: (!message.is_empty()).then_some(message),
+            internal_error,
+        }
+    }
+}
+
 #[derive(Debug)]
 struct SignedPayloadError;
 
@@ -356,3 +393,52 @@ fn check_payload_signed(signature: &str, payload: &[u8]) -> Result<(), SignedPay
 
     Err(SignedPayloadError)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handlers_report() {
+        assert_eq!(
+            HandlersReport::new(vec![]),
+            HandlersReport {
+                comment: None,
+                internal_error: false
+            }
+        );
+
+        // User errors are posted, and the delivery succeeds
+        assert_eq!(
+            HandlersReport::new(vec![
+                HandlerError::Message("Unknown labels: A-foo".to_string()),
+                HandlerError::Message("Invalid assignee".to_string()),
+            ]),
+            HandlersReport {
+                comment: Some("Unknown labels: A-foo\n\nInvalid assignee".to_string()),
+                internal_error: false
+            }
+        );
+
+        // Internal errors are not posted, but fail the delivery
+        assert_eq!(
+            HandlersReport::new(vec![HandlerError::Other(anyhow::anyhow!(
+                "secret database error"
+            ))]),
+            HandlersReport {
+                comment: None,
+                internal_error: true
+            }
+        );
+        assert_eq!(
+            HandlersReport::new(vec![
+                HandlerError::Other(anyhow::anyhow!("secret database error")),
+                HandlerError::Message("Unknown labels: A-foo".to_string()),
+            ]),
+            HandlersReport {
+                comment: Some("Unknown labels: A-foo".to_string()),
+                internal_error: true
+            }
+        );
+    }
+}
@@ -19,6 +19,8 @@ pub enum Event {
     IssueComment(IssueCommentEvent),
     /// Activity on an issue or PR.
     Issue(IssuesEvent),
+    /// A label of a repository is created, edited or deleted.
+    Label(LabelEvent),
     /// One or more commits are pushed to a repository branch or tag.
     Push(PushEvent),
 }
@@ -29,6 +31,7 @@ impl Event {
             Event::Create(event) => &event.repository,
             Event::IssueComment(event) => &event.repository,
             Event::Issue(event) => &event.repository,
+            Event::Label(event) => &event.repository,
             Event::Push(event) => &event.repository,
         }
     }
@@ -38,6 +41,7 @@ impl Event {
             Event::Create(_) => None,
             Event::IssueComment(event) => Some(&event.issue),
             Event::Issue(event) => Some(&event.issue),
+            Event::Label(_) => None,
             Event::Push(_) => None,
         }
     }
@@ -48,6 +52,7 @@ impl Event {
             Event::Create(_) => None,
             Event::Issue(e) => Some(&e.issue.body),
             Event::IssueComment(e) => Some(&e.comment.body),
+            Event::Label(_) => None,
             Event::Push(_) => None,
         }
     }
@@ -58,6 +63,7 @@ impl Event {
             Event::Create(_) => None,
             Event::Issue(e) => Some(&e.changes.as_ref()?.body.as_ref()?.from),
             Event::IssueComment(e) => Some(&e.changes.as_ref()?.body.as_ref()?.from),
+            Event::Label(_) => None,
             Event::Push(_) => None,
         }
     }
@@ -74,6 +80,7 @@ impl Event {
                 .changes
                 .as_ref()
                 .map_or(false, |changes| changes.has_comment_changed(&e.issue)),
+            Event::Label(_) => false,
             Event::Push(_) => false,
         }
     }
@@ -83,6 +90,7 @@ impl Event {
             Event::Create(_) => None,
             Event::Issue(e) => Some(&e.issue.html_url),
             Event::IssueComment(e) => Some(&e.comment.html_url),
+            Event::Label(_) => None,
             Event::Push(_) => None,
         }
     }
@@ -92,6 +100,7 @@ impl Event {
             Event::Create(e) => &e.sender,
             Event::Issue(e) => &e.issue.user,
             Event::IssueComment(e) => &e.comment.user,
+            Event::Label(e) => &e.sender,
             Event::Push(e) => &e.sender,
         }
     }
@@ -105,6 +114,7 @@ impl Event {
                 .updated_at
                 .or(e.comment.created_at)
                 .map(Into::into),
+            Event::Label(_) => None,
             Event::Push(_) => None,
         }
     }
@@ -163,6 +173,45 @@ pub struct OrgBlockEvent {
     pub sender: GitHubUser,
 }
 
+/// The action that occurred in a label event.
+#[derive(Debug, serde::Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelAction {
+    Created,
+    Edited,
+    Deleted,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct LabelChanges {
+    pub name: Option<ChangeInner>,
+}
+
+/// Event triggered when a label of a repository is created, edited or deleted.
+#[derive(Debug, serde::Deserialize)]
+pub struct LabelEvent {
+    pub action: LabelAction,
+    pub label: Label,
+    pub changes: Option<LabelChanges>,
+    pub repository: Repository,
+    sender: GitHubUser,
+}
+
+impl LabelEvent {
+    /// The previous name of the label, if the event renamed it.
+    pub fn renamed_from(&self) -> Option<&str> {
+        if self.action != LabelAction::Edited {
+            return None;
+        }
+        self.changes
+            .as_ref()?
+            .name
+            .as_ref()
+            .map(|name| name.from.as_str())
+            .filter(|from| *from != self.label.name)
+    }
+}
+
 #[derive(PartialEq, Eq, Debug, serde::Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum IssueCommentAction {
@@ -259,6 +308,8 @@ pub struct Changes {
     pub title: Option<ChangeInner>,
     pub body: Option<ChangeInner>,
     pub base: Option<BaseChange>,
+    /// The issue in its new repository, for `transferred` events.
+    pub new_issue: Option<Issue>,
 }
 
 #[derive(Debug, serde::Deserialize)]
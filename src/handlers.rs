@@ -7,35 +7,44 @@ use crate::team_data::TeamClient;
 use crate::zulip::client::ZulipClient;
 use octocrab::Octocrab;
 use parser::command::{Command, Input, assign::AssignCommand};
+use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::{Duration, Instant};
 use tracing as log;
 
 mod assign;
 mod autolabel;
 mod backport;
+mod blocked_on;
 mod bot_pull_requests;
 mod check_commits;
-mod close;
+pub(crate) mod close;
 mod concern;
+mod defer;
 pub mod docs_update;
+mod escalate;
 mod github_releases;
 mod issue_links;
+pub mod label_renames;
 mod lock;
 pub(crate) mod major_change;
 mod mentions;
 mod merge;
 mod merge_conflicts;
-mod milestone_prs;
+mod milestone;
+pub(crate) mod milestone_prs;
+mod needs_repro;
 mod nominate;
 mod note;
 mod notify_zulip;
 mod ping;
 pub mod pr_tracking;
 mod prioritize;
+mod priority;
 pub mod project_goals;
 pub mod pull_requests_assignment_update;
-mod relabel;
+pub(crate) mod relabel;
 mod relnotes;
 mod rendered_link;
 pub mod report_user_bans;
@@ -45,9 +54,15 @@ mod review_requested;
 mod review_submitted;
 pub mod rustc_commits;
 mod shortcut;
+mod template_labels;
+mod tracked_by;
 mod transfer;
+mod transferred_issues;
 pub mod types_planning_updates;
 mod view_all_comments_link;
+mod watch_label;
+pub mod workqueue_rebalance;
+pub mod workqueue_reconcile;
 
 pub struct Context {
     pub github: GithubClient,
@@ -71,17 +86,127 @@ pub async fn handle(ctx: &Context, host: &str, event: &Event) -> Vec<HandlerErro
     if let Err(e) = &config {
         log::warn!("configuration error {}: {e}", event.repo().full_name);
     }
+
+    let disabled = disabled_handlers(ctx, &event.repo().full_name).await;
+
+    let dry_run = config.as_ref().is_ok_and(|c| c.dry_run.is_some());
+    crate::dry_run::scope(
+        dry_run,
+        handle_with_config(ctx, host, event, config, &disabled),
+    )
+    .await
+}
+
+/// How long the disabled handlers of a repository are cached, so they aren't
+/// queried for every event. The API forgets them when a handler is toggled.
+const DISABLED_HANDLERS_REFRESH_EVERY: Duration = Duration::from_secs(2 * 60);
+
+static DISABLED_HANDLERS_CACHE: LazyLock<RwLock<HashMap<String, (Arc<HashSet<String>>, Instant)>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Returns the handlers disabled in the repository with the `/handlers` API.
+///
+/// If they can't be loaded, all the handlers are run.
+async fn disabled_handlers(ctx: &Context, repo: &str) -> Arc<HashSet<String>> {
+    if let Some((disabled, fetch_time)) = DISABLED_HANDLERS_CACHE.read().unwrap().get(repo)
+        && fetch_time.elapsed() < DISABLED_HANDLERS_REFRESH_EVERY
+    {
+        return disabled.clone();
+    }
+
+    let db = ctx.db.get().await;
+    match crate::db::disabled_handlers::get_disabled_handlers(&db, repo).await {
+        Ok(disabled) => {
+            let disabled = Arc::new(disabled);
+            DISABLED_HANDLERS_CACHE
+                .write()
+                .unwrap()
+                .insert(repo.to_string(), (disabled.clone(), Instant::now()));
+            disabled
+        }
+        Err(err) => {
+            log::error!("failed to load the disabled handlers of {repo}: {err:?}");
+            Arc::default()
+        }
+    }
+}
+
+/// Forgets the cached disabled handlers of the repository, after one of them
+/// was toggled.
+pub(crate) fn forget_disabled_handlers(repo: &str) {
+    DISABLED_HANDLERS_CACHE.write().unwrap().remove(repo);
+}
+
+/// The handlers run on events without going through [`issue_handlers!`] or
+/// [`command_handlers!`], by the name they can be disabled with.
+const EVENT_HANDLERS: &[&str] = &[
+    "prune_gh_comments",
+    "assign",
+    "pr_tracking",
+    "check_commits",
+    "project_goals",
+    "rustc_commits",
+    "transferred_issues",
+    "milestone_prs",
+    "rendered_link",
+    "view_all_comments_link",
+    "relnotes",
+    "bot_pull_requests",
+    "review_submitted",
+    "review_changes_since",
+    "github_releases",
+    "merge_conflicts",
+    "label_renames",
+];
+
+/// Whether `name` is the name of a handler, i.e. one that can be disabled.
+pub(crate) fn is_handler_name(name: &str) -> bool {
+    EVENT_HANDLERS
+        .iter()
+        .chain(ISSUE_HANDLERS)
+        .chain(COMMAND_HANDLERS)
+        .any(|handler| *handler == name)
+}
+
+/// Whether the handler is disabled, logging that it is skipped.
+fn is_disabled(disabled: &HashSet<String>, name: &str) -> bool {
+    let is_disabled = disabled.contains(name);
+    if is_disabled {
+        log::info!("skipping the disabled {name} handler");
+    }
+    is_disabled
+}
+
+/// Runs the `handler` unless it is disabled.
+async fn unless_disabled(
+    disabled: &HashSet<String>,
+    name: &str,
+    handler: impl Future<Output = Result<(), HandlerError>>,
+) -> Result<(), HandlerError> {
+    if is_disabled(disabled, name) {
+        return Ok(());
+    }
+    handler.await
+}
+
+async fn handle_with_config(
+    ctx: &Context,
+    host: &str,
+    event: &Event,
+    config: Result<Arc<Config>, ConfigurationError>,
+    disabled: &HashSet<String>,
+) -> Vec<HandlerError> {
     let mut errors = Vec::new();
 
     if let (Ok(config), Event::Issue(event)) = (config.as_ref(), event) {
-        handle_issue(ctx, event, config, &mut errors).await;
+        handle_issue(ctx, event, config, disabled, &mut errors).await;
     }
 
     if let Some(body) = event.comment_body() {
-        handle_command(ctx, event, &config, body, &mut errors).await;
+        handle_command(ctx, event, &config, disabled, body, &mut errors).await;
     }
 
-    let prune_gh_comments = async {
+    let prune_gh_comments = unless_disabled(disabled, "prune_gh_comments", async {
         if let Some(issue) = event.issue() {
             let repo = issue.repository();
 
@@ -96,9 +221,9 @@ pub async fn handle(ctx: &Context, host: &str, event: &Event) -> Vec<HandlerErro
             }
         }
         Ok(())
-    };
+    });
 
-    let assign_comments = async {
+    let assign_comments = unless_disabled(disabled, "assign", async {
         if let Some(assign_config) = config.as_ref().ok().and_then(|c| c.assign.as_ref())
             && let Event::IssueComment(event) = event
         {
@@ -108,9 +233,18 @@ pub async fn handle(ctx: &Context, host: &str, event: &Event) -> Vec<HandlerErro
         } else {
             Ok(())
         }
-    };
+    });
 
-    let check_commits = async {
+    let pr_tracking_reviews = unless_disabled(disabled, "pr_tracking", async {
+        if config.as_ref().is_ok_and(|c| c.pr_tracking.is_some())
+            && let Event::IssueComment(event) = event
+        {
+            pr_tracking::handle_review(ctx, event).await;
+        }
+        Ok(())
+    });
+
+    let check_commits = unless_disabled(disabled, "check_commits", async {
         if let Ok(check_commits_config) = &config {
             check_commits::handle(ctx, host, event, check_commits_config)
                 .await
@@ -118,27 +252,34 @@ pub async fn handle(ctx: &Context, host: &str, event: &Event) -> Vec<HandlerErro
         } else {
             Ok(())
         }
-    };
+    });
 
-    let project_goals = async {
+    let project_goals = unless_disabled(disabled, "project_goals", async {
         project_goals::handle(ctx, event)
             .await
             .map_err(|e| HandlerError::Other(e.context("project_goals handler failed")))
-    };
+    });
 
-    let rustc_commits = async {
+    let rustc_commits = unless_disabled(disabled, "rustc_commits", async {
         rustc_commits::handle(ctx, event)
             .await
             .map_err(|e| HandlerError::Other(e.context("rustc_commits handler failed")))
-    };
+    });
+
+    let transferred_issues = unless_disabled(disabled, "transferred_issues", async {
+        transferred_issues::handle(ctx, event)
+            .await
+            .map_err(|e| HandlerError::Other(e.context("transferred_issues handler failed")))
+    });
 
-    let milestone_prs = async {
-        milestone_prs::handle(ctx, event)
+    let milestone_prs = unless_disabled(disabled, "milestone_prs", async {
+        let milestone_prs_config = config.as_ref().ok().and_then(|c| c.milestone_prs.as_ref());
+        milestone_prs::handle(ctx, event, milestone_prs_config)
             .await
             .map_err(|e| HandlerError::Other(e.context("milestone_prs handler failed")))
-    };
+    });
 
-    let rendered_link = async {
+    let rendered_link = unless_disabled(disabled, "rendered_link", async {
         if let Some(rendered_link_config) =
             config.as_ref().ok().and_then(|c| c.rendered_link.as_ref())
         {
@@ -148,9 +289,9 @@ pub async fn handle(ctx: &Context, host: &str, event: &Event) -> Vec<HandlerErro
         } else {
             Ok(())
         }
-    };
+    });
 
-    let view_all_comments_link = async {
+    let view_all_comments_link = unless_disabled(disabled, "view_all_comments_link", async {
         if let Some(view_all_comments_config) = config
             .as_ref()
             .ok()
@@ -162,15 +303,15 @@ pub async fn handle(ctx: &Context, host: &str, event: &Event) -> Vec<HandlerErro
         } else {
             Ok(())
         }
-    };
+    });
 
-    let relnotes = async {
+    let relnotes = unless_disabled(disabled, "relnotes", async {
         relnotes::handle(ctx, event)
             .await
             .map_err(|e| HandlerError::Other(e.context("relnotes handler failed")))
-    };
+    });
 
-    let bot_pull_requests = async {
+    let bot_pull_requests = unless_disabled(disabled, "bot_pull_requests", async {
         if config.as_ref().is_ok_and(|c| c.bot_pull_requests.is_some()) {
             bot_pull_requests::handle(ctx, event)
                 .await
@@ -178,9 +319,9 @@ pub async fn handle(ctx: &Context, host: &str, event: &Event) -> Vec<HandlerErro
         } else {
             Ok(())
         }
-    };
+    });
 
-    let review_submitted = async {
+    let review_submitted = unless_disabled(disabled, "review_submitted", async {
         if let Some(review_submitted_config) = config
             .as_ref()
             .ok()
@@ -197,9 +338,9 @@ pub async fn handle(ctx: &Context, host: &str, event: &Event) -> Vec<HandlerErro
         } else {
             Ok(())
         }
-    };
+    });
 
-    let review_changes_since = async {
+    let review_changes_since = unless_disabled(disabled, "review_changes_since", async {
         if let Some(review_changes_since_config) = config
             .as_ref()
             .ok()
@@ -211,9 +352,9 @@ pub async fn handle(ctx: &Context, host: &str, event: &Event) -> Vec<HandlerErro
         } else {
             Ok(())
         }
-    };
+    });
 
-    let github_releases = async {
+    let github_releases = unless_disabled(disabled, "github_releases", async {
         if let Some(github_releases_config) = config
             .as_ref()
             .ok()
@@ -225,9 +366,9 @@ pub async fn handle(ctx: &Context, host: &str, event: &Event) -> Vec<HandlerErro
         } else {
             Ok(())
         }
-    };
+    });
 
-    let merge_conflicts = async {
+    let merge_conflicts = unless_disabled(disabled, "merge_conflicts", async {
         if let Some(merge_conflicts_config) = config
             .as_ref()
             .ok()
@@ -239,14 +380,26 @@ pub async fn handle(ctx: &Context, host: &str, event: &Event) -> Vec<HandlerErro
         } else {
             Ok(())
         }
-    };
+    });
+
+    let label_renames = unless_disabled(disabled, "label_renames", async {
+        if let Event::Label(event) = event {
+            label_renames::handle(ctx, event, config.as_deref().ok())
+                .await
+                .map_err(|e| HandlerError::Other(e.context("label_renames handler failed")))
+        } else {
+            Ok(())
+        }
+    });
 
     let (
         prune_gh_comments,
         assign_comments,
+        pr_tracking_reviews,
         check_commits,
         project_goals,
         rustc_commits,
+        transferred_issues,
         milestone_prs,
         rendered_link,
         view_all_comments,
@@ -256,12 +409,15 @@ pub async fn handle(ctx: &Context, host: &str, event: &Event) -> Vec<HandlerErro
         review_changes_since,
         github_releases,
         merge_conflicts,
+        label_renames,
     ) = futures::join!(
         prune_gh_comments,
         assign_comments,
+        pr_tracking_reviews,
         check_commits,
         project_goals,
         rustc_commits,
+        transferred_issues,
         milestone_prs,
         rendered_link,
         view_all_comments_link,
@@ -271,14 +427,17 @@ pub async fn handle(ctx: &Context, host: &str, event: &Event) -> Vec<HandlerErro
         review_changes_since,
         github_releases,
         merge_conflicts,
+        label_renames,
     );
 
     for result in [
         prune_gh_comments,
         assign_comments,
+        pr_tracking_reviews,
         check_commits,
         project_goals,
         rustc_commits,
+        transferred_issues,
         milestone_prs,
         rendered_link,
         view_all_comments,
@@ -288,6 +447,7 @@ pub async fn handle(ctx: &Context, host: &str, event: &Event) -> Vec<HandlerErro
         review_changes_since,
         github_releases,
         merge_conflicts,
+        label_renames,
     ] {
         if let Err(e) = result {
             errors.push(e);
@@ -299,31 +459,33 @@ pub async fn handle(ctx: &Context, host: &str, event: &Event) -> Vec<HandlerErro
 
 macro_rules! issue_handlers {
     ($($name:ident,)*) => {
+        const ISSUE_HANDLERS: &[&str] = &[$(stringify!($name)),*];
+
         async fn handle_issue(
             ctx: &Context,
             event: &IssuesEvent,
             config: &Arc<Config>,
+            disabled: &HashSet<String>,
             errors: &mut Vec<HandlerError>,
         ) {
             // Process the issue handlers concurrently
             let results = futures::join!(
                 $(
                     async {
+                        if is_disabled(disabled, stringify!($name)) {
+                            return Ok(());
+                        }
                         match $name::parse_input(ctx, event, config.$name.as_ref()).await {
                             Err(err) => Err(HandlerError::Message(err)),
                             Ok(Some(input)) => {
                                 if let Some(config) = &config.$name {
                                     $name::handle_input(ctx, config, event, input)
                                         .await
-                                        .map_err(|mut e| {
-                                            if let Some(err) = e.downcast_mut::<crate::errors::UserError>() {
-                                                HandlerError::Message(err.to_string())
-                                            } else {
-                                                HandlerError::Other(e.context(format!(
-                                                    "error when processing {} handler",
-                                                    stringify!($name)
-                                                )))
-                                            }
+                                        .map_err(|e| {
+                                            HandlerError::from_handler(e, || format!(
+                                                "error when processing {} handler",
+                                                stringify!($name)
+                                            ))
                                         })
                                 } else {
                                     Err(HandlerError::Message(format!(
@@ -361,20 +523,43 @@ issue_handlers! {
     assign,
     autolabel,
     backport,
+    blocked_on,
     issue_links,
     major_change,
     mentions,
     notify_zulip,
     review_requested,
     pr_tracking,
+    template_labels,
+    watch_label,
+}
+
+/// Parses the commands of a comment, skipping those already present in its
+/// previous body when the comment was edited, so they are not run twice.
+fn parse_new_commands<'a>(
+    body: &'a str,
+    previous: Option<&'a str>,
+    bots: Vec<&'a str>,
+) -> Vec<Command<'a>> {
+    let input = Input::new(body, bots.clone());
+    if let Some(previous) = previous {
+        let prev_commands = Input::new(previous, bots).collect::<Vec<_>>();
+        input.filter(|cmd| !prev_commands.contains(cmd)).collect()
+    } else {
+        input.collect()
+    }
 }
 
 macro_rules! command_handlers {
-    ($($name:ident: $enum:ident,)*) => {
+    ($($name:ident $(:: $submodule:ident)?: $enum:ident,)*) => {
+        // Commands implemented in a submodule are named after their parent module
+        const COMMAND_HANDLERS: &[&str] = &[$(stringify!($name)),*];
+
         async fn handle_command(
             ctx: &Context,
             event: &Event,
             config: &Result<Arc<Config>, ConfigurationError>,
+            disabled: &HashSet<String>,
             body: &str,
             errors: &mut Vec<HandlerError>,
         ) {
@@ -415,19 +600,13 @@ macro_rules! command_handlers {
                         }
                     }
                 }
-                Event::Push(_) | Event::Create(_) => {
+                Event::Push(_) | Event::Create(_) | Event::Label(_) => {
                     log::debug!("skipping unsupported event");
                     return;
                 }
             }
 
-            let input = Input::new(&body, vec![&ctx.username, "triagebot"]);
-            let commands = if let Some(previous) = event.comment_from() {
-                let prev_commands = Input::new(&previous, vec![&ctx.username, "triagebot"]).collect::<Vec<_>>();
-                input.filter(|cmd| !prev_commands.contains(cmd)).collect::<Vec<_>>()
-            } else {
-                input.collect()
-            };
+            let commands = parse_new_commands(&body, event.comment_from(), vec![&ctx.username, "triagebot"]);
 
             log::info!("Comment parsed to {commands:?}");
 
@@ -460,24 +639,25 @@ macro_rules! command_handlers {
             for command in commands {
                 match command {
                     $(
+                    Command::$enum(_) if is_disabled(disabled, stringify!($name)) => {}
                     Command::$enum(Ok(command)) => {
                         if let Some(config) = &config.$name {
-                            $name::handle_command(ctx, config, event, command)
+                            $name $(:: $submodule)?::handle_command(ctx, config, event, command)
                                 .await
-                                .unwrap_or_else(|mut err| {
-                                    if let Some(err) = err.downcast_mut::<crate::errors::UserError>() {
-                                        errors.push(HandlerError::Message(err.to_string()));
-                                    } else {
+                                .unwrap_or_else(|err| {
+                                    let err = HandlerError::from_handler(err, || format!(
+                                        "error when processing {} command handler",
+                                        stringify!($name)
+                                    ));
+                                    if err.is_internal() {
+                                        // Let the user know, without leaking the details of the error
                                         errors.push(HandlerError::Message(format!(
-                                            "`{}` handler unexpectedly failed in [this comment]({}): {err}",
+                                            "`{}` handler unexpectedly failed in [this comment]({}).",
                                             stringify!($name),
                                             event.html_url().expect("has html url"),
                                         )));
-                                        errors.push(HandlerError::Other(err.context(format!(
-                                            "error when processing {} command handler",
-                                            stringify!($name)
-                                        ))));
                                     }
+                                    errors.push(err);
                                 });
                         } else {
                             errors.push(HandlerError::Message(format!(
@@ -520,14 +700,42 @@ command_handlers! {
     concern: Concern,
     transfer: Transfer,
     merge: Merge,
+    blocked_on: BlockedOn,
+    watch_label: WatchLabel,
+    priority: Priority,
+    major_change::hold: Hold,
+    milestone: Milestone,
+    escalate: Escalate,
+    major_change::mcp_comment: McpComment,
+    tracked_by: TrackedBy,
+    needs_repro: NeedsRepro,
+    defer: Defer,
 }
 
+/// An error of a handler.
 #[derive(Debug)]
 pub enum HandlerError {
+    /// A user-facing error, posted as a comment on the issue.
     Message(String),
+    /// An internal error, only logged and failing the webhook delivery.
     Other(anyhow::Error),
 }
 
+impl HandlerError {
+    /// Classifies an error returned by a handler: [`UserError`](crate::errors::UserError)s
+    /// are shown to the user, while any other error is internal.
+    fn from_handler(err: anyhow::Error, context: impl FnOnce() -> String) -> HandlerError {
+        match err.downcast_ref::<crate::errors::UserError>() {
+            Some(err) => HandlerError::Message(err.to_string()),
+            None => HandlerError::Other(err.context(context())),
+        }
+    }
+
+    pub fn is_internal(&self) -> bool {
+        matches!(self, HandlerError::Other(_))
+    }
+}
+
 impl std::error::Error for HandlerError {}
 
 impl fmt::Display for HandlerError {
@@ -538,3 +746,85 @@ impl fmt::Display for HandlerError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::UserError;
+    use parser::command::relabel::{Label, LabelDelta, RelabelCommand};
+
+    #[test]
+    fn edited_comment_only_runs_new_commands() {
+        let previous = "@rustbot lable +A-docs\n\n@rustbot claim";
+        let body = "@rustbot label +A-docs\n\n@rustbot claim";
+
+        let commands = parse_new_commands(body, Some(previous), vec!["rustbot"]);
+        assert_eq!(
+            commands,
+            vec![Command::Relabel(Ok(RelabelCommand::from_deltas(vec![
+                LabelDelta::Add(Label("A-docs".to_string()))
+            ])))]
+        );
+
+        // Unchanged commands don't run again
+        assert_eq!(
+            parse_new_commands(body, Some(body), vec!["rustbot"]),
+            vec![]
+        );
+
+        // Without a previous body, all the commands run
+        assert_eq!(parse_new_commands(body, None, vec!["rustbot"]).len(), 2);
+    }
+
+    #[test]
+    fn handler_error_classification() {
+        let err = HandlerError::from_handler(
+            UserError::Message("Label `A-foo` doesn't exist".to_string()).into(),
+            || "relabel handler".to_string(),
+        );
+        assert!(matches!(&err, HandlerError::Message(msg) if msg == "Label `A-foo` doesn't exist"));
+        assert!(!err.is_internal());
+
+        let err =
+            HandlerError::from_handler(anyhow::anyhow!("database connection refused"), || {
+                "relabel handler".to_string()
+            });
+        assert!(err.is_internal());
+        // The details of internal errors are never shown to the user
+        assert_eq!(err.to_string(), "An internal error occurred.");
+    }
+
+    #[test]
+    fn handler_names() {
+        assert!(is_handler_name("milestone_prs"));
+        assert!(is_handler_name("major_change"));
+        assert!(is_handler_name("relabel"));
+        assert!(!is_handler_name("major-change"));
+        assert!(!is_handler_name("hold"));
+        assert!(!is_handler_name("unknown"));
+    }
+
+    #[tokio::test]
+    async fn disabled_handler_is_not_invoked() {
+        let invoked = &std::sync::atomic::AtomicBool::new(false);
+        let handler = || async move {
+            invoked.store(true, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        };
+
+        let disabled = HashSet::from(["milestone_prs".to_string()]);
+        assert!(
+            unless_disabled(&disabled, "milestone_prs", handler())
+                .await
+                .is_ok()
+        );
+        assert!(!invoked.load(std::sync::atomic::Ordering::SeqCst));
+
+        assert!(
+            unless_disabled(&disabled, "relnotes", handler())
+                .await
+                .is_ok()
+        );
+        assert!(invoked.load(std::sync::atomic::Ordering::SeqCst));
+    }
+}
@@ -5,9 +5,12 @@
 //! * `@rustbot assign @gh-user`: Assigns to the given user.
 //! * `@rustbot claim`: Assigns to the comment author.
 //! * `@rustbot release-assignment`: Removes the commenter's assignment.
+//!   `@rustbot release` can be used on PRs to give up a claimed review.
 //! * `r? @user`: Assigns to the given user (PRs only).
 //! * `@rustbot reroll`: Re-run the automatic assignment logic based on PR diff and owner map that
 //!   is normally triggered when a PR is opened.
+//! * `@rustbot re-review`: Rotates the review of a stale PR to a new reviewer, selected like with
+//!   `@rustbot reroll` (PRs only, configured with `[assign.re_review]`).
 //!
 //! Note: this module does not handle review assignments issued from the
 //! GitHub "Assignees" dropdown menu
@@ -22,11 +25,11 @@
 //! `assign.owners` config, it will auto-select an assignee based on the files
 //! the PR modifies.
 
-use crate::config::AssignCommunityReviewsConfig;
+use crate::config::{AssignCommunityReviewsConfig, AssignReReviewConfig};
 use crate::db::issue_data::IssueData;
 use crate::db::review_prefs::{RotationMode, get_review_prefs_batch};
 use crate::errors::{self, AssignmentError, user_error};
-use crate::handlers::pr_tracking::ReviewerWorkqueue;
+use crate::handlers::pr_tracking::{self, ReviewerWorkqueue};
 use crate::{
     config::AssignConfig,
     github::{
@@ -37,6 +40,8 @@ use crate::{
     interactions::EditIssueBody,
 };
 use anyhow::{Context as _, bail};
+use chrono::{DateTime, Duration, Utc};
+use futures::TryStreamExt;
 use parser::command::assign::AssignCommand;
 use parser::command::{Command, Input};
 use rand::seq::IteratorRandom;
@@ -54,6 +59,7 @@ mod messages;
 mod tests {
     mod tests_candidates;
     mod tests_from_diff;
+    mod tests_re_review;
 }
 
 // Special account that we use to prevent assignment.
@@ -69,6 +75,16 @@ struct Reviewers {
     names: HashSet<String>,
 }
 
+/// Key for the re-review state in the database
+const RE_REVIEW_KEY: &str = "re-review";
+
+/// Re-review state stored in the database
+#[derive(Debug, Clone, PartialEq, Default, serde::Deserialize, serde::Serialize)]
+struct ReReviewState {
+    /// When the review of the PR was last rotated to a new reviewer.
+    last_rotation: Option<DateTime<Utc>>,
+}
+
 /// Assignment data stored in the issue/PR body.
 #[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
 struct AssignData {
@@ -365,6 +381,13 @@ pub(super) async fn handle_input(
                                 format_candidates(&to)
                             )
                         }
+                        SelectionStep::Expertise { labels, to } => {
+                            format!(
+                                "Expertise in {}: {}",
+                                format_candidates(&labels),
+                                format_candidates(&to)
+                            )
+                        }
                     };
                     explanation.push_str(&format!("- {msg}\n"));
                 }
@@ -757,6 +780,45 @@ fn find_reviewers_from_diff(
     Ok(potential)
 }
 
+/// Returns the usernames of the reviewers a PR could be handed over to when
+/// rebalancing the workqueues.
+///
+/// They are the available reviewers among the `owners` of the modified files,
+/// or of the fallback group if none match. The current assignees are never
+/// candidates.
+pub(super) async fn rebalance_candidates(
+    ctx: &Context,
+    config: &AssignConfig,
+    pr: &Issue,
+) -> anyhow::Result<Vec<String>> {
+    let diff = pr_diff(ctx, pr).await?;
+    let mut names = find_reviewers_from_diff(config, diff)?;
+    if names.is_empty()
+        && let Some(fallback) = config.fallback_review_group()
+    {
+        names = fallback.to_vec();
+    }
+    if names.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut db_client = ctx.db.get().await;
+    let workqueue = get_repo_workqueue(ctx, &pr.repository().full_repo_name());
+    let teams = ctx.team.teams().await?;
+    match candidate_reviewers_from_names(&mut db_client, workqueue, &teams, config, pr, &names)
+        .await
+    {
+        Ok(candidates) => Ok(candidates
+            .into_iter()
+            // A single owner is selected even when unavailable
+            .filter(|c| c.suppressed_error.is_none())
+            .map(|c| c.name)
+            .collect()),
+        Err(FindReviewerError::NoReviewer { .. }) => Ok(vec![]),
+        Err(err) => Err(err.into()),
+    }
+}
+
 /// Handles a command posted in a comment.
 pub(super) async fn handle_command(
     ctx: &Context,
@@ -793,14 +855,27 @@ pub(super) async fn handle_command(
 
         let teams = ctx.team.teams().await?;
 
+        let is_claim = cmd == AssignCommand::Claim;
         let assignee = match cmd {
-            AssignCommand::Claim => event.user().login.clone(),
+            AssignCommand::Claim => {
+                if !is_team_member {
+                    return user_error!("Only Rust team members can claim the review of a PR.");
+                }
+                event.user().login.clone()
+            }
             AssignCommand::AssignUser { username } => username,
             AssignCommand::ReleaseAssignment => {
-                log::trace!(
-                    "ignoring release on PR {:?}, must always have assignee",
-                    issue.global_id()
-                );
+                let current = event.user();
+                if !issue.contain_assignee(&current.login) {
+                    return user_error!(
+                        "Cannot release the review of a PR you are not assigned to."
+                    );
+                }
+                issue
+                    .remove_assignees(&ctx.github, Selection::One(&current.login))
+                    .await?;
+                pr_tracking::release_pr(ctx, &event.repo().full_name, issue.number, current.id)
+                    .await;
                 return Ok(());
             }
             AssignCommand::RequestReview { name } => {
@@ -821,21 +896,8 @@ pub(super) async fn handle_command(
                 name
             }
             AssignCommand::Reroll => {
-                // We need to compute the PR diff here, but the IssuesEvent created from a
-                // comment webhook doesn't contain the required `base` and `head` fields.
-                // So we have to load the information about the pull request from the GitHub API
-                // explicitly.
-                let pr = ctx
-                    .github
-                    .pull_request(issue.repository(), issue.number)
-                    .await
-                    .context("Cannot load pull request from GitHub")?;
-                let Some(diff) = pr.diff(&ctx.github).await.context("Cannot load PR diff")? else {
-                    bail!(
-                        "expected issue {} to be a PR, but the diff could not be determined",
-                        issue.number
-                    );
-                };
+                let pr = load_pull_request(ctx, issue).await?;
+                let diff = pr_diff(ctx, &pr).await?;
 
                 let (assignee, _) =
                     determine_assignee(ctx, None, issue, &event.user().login, config, diff)
@@ -852,6 +914,9 @@ pub(super) async fn handle_command(
                 }
                 return Ok(());
             }
+            AssignCommand::ReReview => {
+                return re_review(ctx, config, event, issue, is_team_member).await;
+            }
         };
 
         // In the PR body, `r? ghost` means "do not assign anybody".
@@ -890,6 +955,12 @@ pub(super) async fn handle_command(
         };
 
         set_assignee(ctx, config, issue, &assignee).await?;
+
+        // Don't wait for the `assigned` webhook to update the workqueue of the
+        // reviewer claiming the PR, so that it's immediately in sync with GitHub.
+        if is_claim {
+            pr_tracking::claim_pr(ctx, &event.repo().full_name, issue, event.user()).await;
+        }
     } else {
         let mut client = ctx.db.get().await;
         let mut e: EditIssueBody<'_, AssignData> =
@@ -926,6 +997,9 @@ pub(super) async fn handle_command(
             AssignCommand::Reroll { .. } => {
                 return user_error!("reroll is only allowed on PRs.");
             }
+            AssignCommand::ReReview => {
+                return user_error!("re-review is only allowed on PRs.");
+            }
         };
         // Don't re-assign if already assigned, e.g. on comment edit
         if issue.contain_assignee(&to_assign) {
@@ -959,6 +1033,170 @@ pub(super) async fn handle_command(
     Ok(())
 }
 
+/// Loads the pull request from the GitHub API.
+///
+/// We need it to compute the PR diff, but the IssuesEvent created from a
+/// comment webhook doesn't contain the required `base` and `head` fields.
+async fn load_pull_request(ctx: &Context, issue: &Issue) -> anyhow::Result<Issue> {
+    ctx.github
+        .pull_request(issue.repository(), issue.number)
+        .await
+        .context("Cannot load pull request from GitHub")
+}
+
+async fn pr_diff<'a>(ctx: &Context, pr: &'a Issue) -> anyhow::Result<&'a [FileDiff]> {
+    let Some(diff) = pr.diff(&ctx.github).await.context("Cannot load PR diff")? else {
+        bail!(
+            "expected issue {} to be a PR, but the diff could not be determined",
+            pr.number
+        );
+    };
+    Ok(diff)
+}
+
+/// Rotates the review of a stale PR to a new reviewer, removing the PR from
+/// the workqueue of the current reviewers.
+async fn re_review(
+    ctx: &Context,
+    config: &AssignConfig,
+    event: &Event,
+    issue: &Issue,
+    is_team_member: bool,
+) -> anyhow::Result<()> {
+    let Some(re_review_config) = &config.re_review else {
+        return user_error!("Requesting a new reviewer is not enabled in this repository.");
+    };
+    if !is_team_member && issue.user.login != event.user().login {
+        return user_error!(
+            "Only the author of the PR or team members can request a new reviewer."
+        );
+    }
+    if issue.assignees.is_empty() {
+        return user_error!(
+            "This PR has no reviewer yet. Use `r? <username or team>` to request a reviewer."
+        );
+    }
+
+    let mut db = ctx.db.get().await;
+    let mut state: IssueData<'_, ReReviewState> =
+        IssueData::load(&mut db, issue, RE_REVIEW_KEY).await?;
+    let active_at = reviewers_active_at(ctx, issue).await?;
+    if let Err(err) = check_re_review(
+        re_review_config,
+        active_at,
+        state.data.last_rotation,
+        Utc::now(),
+    ) {
+        return user_error!(err);
+    }
+
+    let pr = load_pull_request(ctx, issue).await?;
+    let diff = pr_diff(ctx, &pr).await?;
+
+    // The current assignees and previous reviewers are filtered out of the
+    // candidates, so the review always goes to a different reviewer.
+    let (assignee, _) = determine_assignee(ctx, None, issue, &event.user().login, config, diff)
+        .await
+        .context("Cannot determine assignee when requesting a new reviewer")?;
+    let Some(assignee) = assignee else {
+        return user_error!(
+            "Cannot determine a new reviewer. Use `r? <username or team>` to request a specific reviewer or a team."
+        );
+    };
+    set_assignee(ctx, config, issue, &assignee)
+        .await
+        .context("Cannot set assignee when requesting a new reviewer")?;
+
+    for previous in &issue.assignees {
+        pr_tracking::release_pr(ctx, &event.repo().full_name, issue.number, previous.id).await;
+    }
+
+    let previous = issue
+        .assignees
+        .iter()
+        .map(|a| format!("`{}`", a.login))
+        .collect::<Vec<_>>()
+        .join(", ");
+    issue
+        .post_comment(
+            &ctx.github,
+            &format!(
+                "The review was rotated from {previous} to @{}.",
+                assignee.name
+            ),
+        )
+        .await
+        .context("failed to post the re-review comment")?;
+
+    state.data.last_rotation = Some(Utc::now());
+    state.save().await?;
+    Ok(())
+}
+
+/// Returns when the current reviewers of the PR were last active on it, i.e.
+/// when one of them was assigned, commented or reviewed it, or when it was
+/// opened if they weren't active since.
+async fn reviewers_active_at(ctx: &Context, issue: &Issue) -> anyhow::Result<DateTime<Utc>> {
+    use octocrab::models::Event;
+
+    let repo = issue.repository();
+    let timeline = ctx
+        .octocrab
+        .issues(&repo.organization, &repo.repository)
+        .list_timeline_events(issue.number)
+        .per_page(100)
+        .send()
+        .await
+        .context("unable to get the timeline of the PR")?
+        .into_stream(&ctx.octocrab);
+    let mut timeline = std::pin::pin!(timeline);
+
+    let is_reviewer = |author: &Option<octocrab::models::Author>| {
+        author
+            .as_ref()
+            .is_some_and(|author| issue.contain_assignee(&author.login))
+    };
+    let mut active_at = issue.created_at;
+    while let Some(event) = timeline.try_next().await? {
+        let is_reviewer_activity = match event.event {
+            Event::Assigned => is_reviewer(&event.assignee),
+            Event::Commented => is_reviewer(&event.actor),
+            Event::Reviewed => is_reviewer(&event.user),
+            _ => false,
+        };
+        if is_reviewer_activity && let Some(at) = event.created_at.or(event.submitted_at) {
+            active_at = active_at.max(at);
+        }
+    }
+    Ok(active_at)
+}
+
+/// Checks that the review of a PR whose reviewers were last active at
+/// `active_at` can be rotated at `now`.
+fn check_re_review(
+    config: &AssignReReviewConfig,
+    active_at: DateTime<Utc>,
+    last_rotation: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    let stale_at = active_at + Duration::days(config.stale_after_days.into());
+    if now < stale_at {
+        return Err(format!(
+            "A new reviewer can only be requested once the reviewer has been inactive for {} days.",
+            config.stale_after_days
+        ));
+    }
+    if let Some(last_rotation) = last_rotation
+        && now < last_rotation + Duration::days(config.cooldown_days.into())
+    {
+        return Err(format!(
+            "The review of this PR was already rotated less than {} days ago.",
+            config.cooldown_days
+        ));
+    }
+    Ok(())
+}
+
 /// Process a comment/review/review comment
 pub(super) async fn handle_comment(
     ctx: &Context,
@@ -1122,6 +1360,11 @@ enum SelectionStep {
     FileDiff(Vec<String>),
     /// A set of groups or teams were expanded into a list of reviewer usernames.
     Expansion { from: Vec<String>, to: Vec<String> },
+    /// The candidates were narrowed to the reviewers with an expertise matching the PR labels.
+    Expertise {
+        labels: Vec<String>,
+        to: Vec<String>,
+    },
 }
 
 /// Reviewer that was found to be eligible as a result of `r? <...>`.
@@ -1510,11 +1753,20 @@ async fn candidate_reviewers_from_names<'a>(
     }
     assert_eq!(candidates.len(), expanded_count);
 
-    let valid_candidates: HashSet<&str> = candidates
+    let mut valid_candidates: HashSet<&str> = candidates
         .iter()
         .filter_map(|res| res.as_ref().ok().map(|c| c.name.as_str()))
         .collect();
 
+    // Step 5: prefer the available reviewers with an expertise matching the PR labels,
+    // falling back to all of them if none matches.
+    if let Some((labels, experts)) = find_experts(config, issue, &valid_candidates) {
+        let mut to: Vec<String> = experts.iter().map(|e| e.to_string()).collect();
+        to.sort();
+        selection_steps.push(SelectionStep::Expertise { labels, to });
+        valid_candidates = experts;
+    }
+
     log::debug!(
         "Candidate reviewer results for review request `{}` on `{}`: {:?}",
         names.join(", "),
@@ -1563,6 +1815,37 @@ async fn candidate_reviewers_from_names<'a>(
     }
 }
 
+/// Returns the labels of the PR with an `expertise` entry, and the candidates listed in them.
+///
+/// Returns `None` if none of the candidates has an expertise matching the PR labels.
+fn find_experts<'a>(
+    config: &AssignConfig,
+    issue: &Issue,
+    candidates: &HashSet<&'a str>,
+) -> Option<(Vec<String>, HashSet<&'a str>)> {
+    let mut labels = Vec::new();
+    let mut experts = HashSet::new();
+    for label in &issue.labels {
+        let Some(reviewers) = config.expertise.get(&label.name) else {
+            continue;
+        };
+        let matching: Vec<&str> = candidates
+            .iter()
+            .copied()
+            .filter(|c| {
+                reviewers
+                    .iter()
+                    .any(|r| r.trim_start_matches('@').eq_ignore_ascii_case(c))
+            })
+            .collect();
+        if !matching.is_empty() {
+            labels.push(label.name.clone());
+            experts.extend(matching);
+        }
+    }
+    (!experts.is_empty()).then_some((labels, experts))
+}
+
 async fn get_previous_reviewer_names(db: &mut DbClient, issue: &Issue) -> HashSet<String> {
     let state: IssueData<'_, Reviewers> =
         match IssueData::load(db, issue, PREVIOUS_REVIEWERS_KEY).await {
@@ -6,6 +6,7 @@ use crate::github::{GitHubUser, PullRequestNumber, UserId};
 use crate::handlers::pr_tracking::{AssignedPullRequest, ReviewerWorkqueue};
 use crate::tests::github::{issue, user};
 use crate::tests::{TestContext, run_db_test};
+use crate::zulip::commands::WorkqueueLimit;
 
 #[must_use]
 struct AssignCtx {
@@ -56,6 +57,8 @@ impl AssignCtx {
                     pr_number,
                     AssignedPullRequest {
                         title: format!("PR {pr_number}"),
+                        created_at: chrono::Utc::now(),
+                        active: false,
                     },
                 )
             })
@@ -161,6 +164,15 @@ impl ReviewerSelection {
         });
         self
     }
+
+    /// Adds an Expertise selection step (test helper).
+    fn with_expertise(mut self, labels: &[&str], to: &[&str]) -> Self {
+        self.selection_steps.push(SelectionStep::Expertise {
+            labels: labels.iter().map(|s| s.to_string()).collect(),
+            to: to.iter().map(|s| s.to_string()).collect(),
+        });
+        self
+    }
 }
 
 /// Basic test function for testing `candidate_reviewers_from_names`.
@@ -242,6 +254,41 @@ async fn below_max_capacity() {
     .await;
 }
 
+#[tokio::test]
+async fn capacity_set_from_zulip() {
+    run_db_test(|ctx| async move {
+        let user = user("martin", 1);
+        let ctx = review_prefs_test(ctx)
+            .set_review_prefs(&user, Some(2), RotationMode::OnRotation)
+            .await
+            .assign_prs(user.id, 2)
+            .check(
+                &["martin"],
+                Ok(&[ReviewerSelection {
+                    name: "martin".to_string(),
+                    suppressed_error: Some(FindReviewerError::ReviewerAtMaxCapacity {
+                        username: "martin".to_string(),
+                    }),
+                    selection_steps: vec![],
+                }]),
+            )
+            .await?;
+
+        // `work capacity 3`
+        let response = crate::zulip::set_review_capacity(
+            ctx.test_ctx.db_client(),
+            user.clone().into(),
+            "rust-lang/rust",
+            &WorkqueueLimit::Limit(3),
+        )
+        .await?;
+        assert_eq!(response, "Review capacity in `rust-lang/rust` set to 3");
+
+        ctx.check(&["martin"], Ok(&[reviewer("martin")])).await
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn above_max_capacity() {
     let teams = toml::toml!(compiler = ["martin", "diana"]);
@@ -726,3 +773,53 @@ async fn previous_reviewers_direct_assignee() {
     })
     .await
 }
+
+#[tokio::test]
+async fn expertise_preferred() {
+    let teams = toml::toml!(compiler = ["martin", "diana", "jyn514"]);
+    let config = toml::toml!(
+        [review_prefs]
+        [expertise]
+        A-diagnostics = ["@diana"]
+    );
+    run_db_test(|ctx| async move {
+        let diana = user("diana", 2);
+        basic_test(ctx, config, issue().labels(vec!["A-diagnostics"]).call())
+            .teams(&teams)
+            .set_review_prefs(&diana, Some(3), RotationMode::OnRotation)
+            .await
+            .assign_prs(diana.id, 2)
+            .check(
+                &["compiler"],
+                Ok(&[reviewer("diana")
+                    .expanded(&["compiler"], &["diana", "jyn514", "martin"])
+                    .with_expertise(&["A-diagnostics"], &["diana"])]),
+            )
+            .await
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn expertise_at_max_capacity() {
+    let teams = toml::toml!(compiler = ["martin", "diana"]);
+    let config = toml::toml!(
+        [review_prefs]
+        [expertise]
+        A-diagnostics = ["diana"]
+    );
+    run_db_test(|ctx| async move {
+        let diana = user("diana", 2);
+        basic_test(ctx, config, issue().labels(vec!["A-diagnostics"]).call())
+            .teams(&teams)
+            .set_review_prefs(&diana, Some(3), RotationMode::OnRotation)
+            .await
+            .assign_prs(diana.id, 3)
+            .check(
+                &["compiler"],
+                Ok(&[reviewer("martin").expanded(&["compiler"], &["diana", "martin"])]),
+            )
+            .await
+    })
+    .await;
+}
@@ -34,6 +34,8 @@ fn make_fake_diff(paths: &[(&str, u32, u32)]) -> Vec<FileDiff> {
                 filename: path.to_string(),
                 patch: diff,
                 previous_filename: None,
+                additions: 0,
+                deletions: 0,
             }
         })
         .collect()
@@ -65,6 +67,8 @@ fn from_diff_submodule() {
         "
         .to_string(),
         previous_filename: None,
+        additions: 0,
+        deletions: 0,
     }];
     test_from_diff(&diff, config, &["user1", "user2"]);
 }
@@ -133,6 +137,8 @@ fn empty_file_still_counts() {
                 index 0000000..e69de29\n"
             .to_string(),
         previous_filename: None,
+        additions: 0,
+        deletions: 0,
     }];
     test_from_diff(&diff, config, &["parser"]);
 }
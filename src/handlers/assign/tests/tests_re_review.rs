@@ -0,0 +1,186 @@
+//! Tests for `@rustbot re-review`
+
+use super::super::*;
+use crate::tests::github::{comment_event, issue, user, user_json};
+use crate::tests::{mock_octocrab, run_db_test};
+use axum::{Json, Router, routing::get};
+
+fn re_review_config() -> AssignReReviewConfig {
+    AssignReReviewConfig {
+        stale_after_days: 14,
+        cooldown_days: 7,
+    }
+}
+
+#[test]
+fn re_review_after_staleness_threshold() {
+    let active_at = Utc::now() - Duration::days(20);
+    let config = re_review_config();
+
+    assert!(check_re_review(&config, active_at, None, active_at + Duration::days(13)).is_err());
+    assert!(check_re_review(&config, active_at, None, active_at + Duration::days(14)).is_ok());
+}
+
+#[test]
+fn re_review_cooldown() {
+    let active_at = Utc::now() - Duration::days(30);
+    let rotated_at = active_at + Duration::days(15);
+    let config = re_review_config();
+
+    assert!(
+        check_re_review(
+            &config,
+            active_at,
+            Some(rotated_at),
+            rotated_at + Duration::days(6)
+        )
+        .is_err()
+    );
+    assert!(
+        check_re_review(
+            &config,
+            active_at,
+            Some(rotated_at),
+            rotated_at + Duration::days(7)
+        )
+        .is_ok()
+    );
+}
+
+#[tokio::test]
+async fn re_review_selects_a_different_reviewer() {
+    run_db_test(|mut ctx| async move {
+        let config: AssignConfig = toml::toml!(
+            [adhoc_groups]
+            compiler = ["martin", "diana"]
+        )
+        .try_into()
+        .unwrap();
+        let issue = issue()
+            .author(user("jyn514", 3))
+            .assignees(vec![user("martin", 1)])
+            .call();
+
+        let selected = find_reviewer_from_names(
+            ctx.db_client_mut(),
+            Arc::new(RwLock::new(ReviewerWorkqueue::default())),
+            &Teams {
+                teams: Default::default(),
+            },
+            &config,
+            &issue,
+            "jyn514",
+            &["compiler".to_string()],
+        )
+        .await
+        .unwrap();
+        assert_eq!(selected.name, "diana");
+
+        Ok(ctx)
+    })
+    .await;
+}
+
+/// Requests a new reviewer for a PR opened 30 days ago and assigned to martin,
+/// with the given timeline.
+async fn request_re_review(
+    ctx: &mut crate::tests::TestContext,
+    timeline: serde_json::Value,
+) -> anyhow::Result<()> {
+    let router = Router::new().route(
+        "/repos/rust-lang/rust/issues/1/timeline",
+        get(move || async move { Json(timeline) }),
+    );
+    ctx.handler_ctx_mut().octocrab = mock_octocrab(router).await;
+
+    let config: AssignConfig = toml::toml!(
+        [re_review]
+        stale_after_days = 14
+    )
+    .try_into()
+    .unwrap();
+    let mut pr = issue()
+        .pr(true)
+        .author(user("jyn514", 3))
+        .assignees(vec![user("martin", 1)])
+        .call();
+    pr.created_at = Utc::now() - Duration::days(30);
+    let event = comment_event()
+        .issue(pr.clone())
+        .author(user("jyn514", 3))
+        .body("@rustbot reroll")
+        .call();
+
+    re_review(ctx.handler_ctx(), &config, &event, &pr, false).await
+}
+
+fn days_ago(days: i64) -> String {
+    (Utc::now() - Duration::days(days)).to_rfc3339()
+}
+
+#[tokio::test]
+async fn re_review_is_rejected_after_a_recent_assignment() {
+    run_db_test(|mut ctx| async move {
+        let timeline = serde_json::json!([{
+            "event": "assigned",
+            "actor": user_json("jyn514", 3),
+            "assignee": user_json("martin", 1),
+            "created_at": days_ago(2),
+        }]);
+        let err = request_re_review(&mut ctx, timeline).await.unwrap_err();
+        assert!(err.to_string().contains("inactive for 14 days"), "{err}");
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn re_review_is_rejected_after_a_recent_review() {
+    run_db_test(|mut ctx| async move {
+        let timeline = serde_json::json!([
+            {
+                "event": "assigned",
+                "actor": user_json("jyn514", 3),
+                "assignee": user_json("martin", 1),
+                "created_at": days_ago(25),
+            },
+            {
+                "event": "reviewed",
+                "user": user_json("martin", 1),
+                "submitted_at": days_ago(3),
+            },
+        ]);
+        let err = request_re_review(&mut ctx, timeline).await.unwrap_err();
+        assert!(err.to_string().contains("inactive for 14 days"), "{err}");
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn re_review_ignores_the_activity_of_others() {
+    run_db_test(|mut ctx| async move {
+        ctx.use_unreachable_services();
+        let timeline = serde_json::json!([
+            {
+                "event": "assigned",
+                "actor": user_json("jyn514", 3),
+                "assignee": user_json("martin", 1),
+                "created_at": days_ago(25),
+            },
+            {
+                "event": "commented",
+                "actor": user_json("jyn514", 3),
+                "created_at": days_ago(1),
+            },
+        ]);
+        // The review is stale, so the PR is then loaded (from an unreachable GitHub)
+        let err = request_re_review(&mut ctx, timeline).await.unwrap_err();
+        assert!(
+            err.to_string().contains("Cannot load pull request"),
+            "{err}"
+        );
+        Ok(ctx)
+    })
+    .await;
+}
@@ -0,0 +1,329 @@
+//! Tracks issues blocked on other issues.
+//!
+//! Team members can mark an issue as blocked with:
+//!
+//! ```md
+//! @rustbot blocked-on #123
+//! ```
+//!
+//! The optional configured label is added to the blocked issue, and when the
+//! blocking issue gets closed a comment is posted on every issue that was
+//! blocked on it (the label being removed once no blockers remain).
+
+use crate::{
+    config::BlockedOnConfig,
+    db::issue_blockers,
+    errors::user_error,
+    github::{Event, Issue, IssuesAction, IssuesEvent, Label},
+    handlers::{Context, tracked_by::is_not_found},
+};
+use anyhow::Context as _;
+use parser::command::blocked_on::BlockedOnCommand;
+use tracing as log;
+
+pub(super) async fn handle_command(
+    ctx: &Context,
+    config: &BlockedOnConfig,
+    event: &Event,
+    cmd: BlockedOnCommand,
+) -> anyhow::Result<()> {
+    let BlockedOnCommand(blocked_on) = cmd;
+    let Some(issue) = event.issue() else {
+        return user_error!(
+            "The `blocked-on` command can only be used on issues and pull requests."
+        );
+    };
+
+    if !ctx
+        .team
+        .is_team_member(&event.user().login)
+        .await
+        .context("failed to verify that the user is a team member")?
+    {
+        return user_error!("Only team members can mark an issue as blocked.");
+    }
+
+    if blocked_on == issue.number {
+        return user_error!("An issue cannot be blocked on itself.");
+    }
+
+    let blocking_issue = match ctx.github.issue(issue.repository(), blocked_on).await {
+        Ok(blocking_issue) => blocking_issue,
+        Err(err) if is_not_found(&err) => {
+            return user_error!(format!("Issue #{blocked_on} does not exist."));
+        }
+        Err(err) => return Err(err.context("failed to fetch the blocking issue")),
+    };
+    if !blocking_issue.is_open() {
+        return user_error!(format!("Issue #{blocked_on} is already closed."));
+    }
+
+    let repo = issue.repository().to_string();
+    if !crate::dry_run::skip(format_args!(
+        "recorded {repo}#{} as blocked on #{blocked_on}",
+        issue.number
+    )) {
+        issue_blockers::add_blocker(&*ctx.db.get().await, &repo, issue.number, blocked_on)
+            .await
+            .context("unable to record the blocking issue")?;
+    }
+
+    if let Some(label) = &config.label {
+        issue
+            .add_labels(
+                &ctx.github,
+                vec![Label {
+                    name: label.clone(),
+                }],
+            )
+            .await
+            .context("failed to add the blocked label")?;
+    }
+
+    issue
+        .post_comment(
+            &ctx.github,
+            &format!("This issue is now marked as blocked on #{blocked_on}."),
+        )
+        .await
+        .context("failed to post the blocked confirmation")?;
+
+    Ok(())
+}
+
+pub(super) struct BlockerClosed;
+
+pub(super) async fn parse_input(
+    _ctx: &Context,
+    event: &IssuesEvent,
+    config: Option<&BlockedOnConfig>,
+) -> Result<Option<BlockerClosed>, String> {
+    if config.is_none() || event.action != IssuesAction::Closed {
+        return Ok(None);
+    }
+    Ok(Some(BlockerClosed))
+}
+
+pub(super) async fn handle_input(
+    ctx: &Context,
+    config: &BlockedOnConfig,
+    event: &IssuesEvent,
+    _input: BlockerClosed,
+) -> anyhow::Result<()> {
+    let closed = &event.issue;
+    let repo = closed.repository().to_string();
+
+    let blocked_issues =
+        issue_blockers::get_blocked_issues(&*ctx.db.get().await, &repo, closed.number)
+            .await
+            .context("unable to get the issues blocked on the closed issue")?;
+
+    // Notify every blocked issue even if some of them fail, those keep their
+    // blocker so that they are notified again when it's closed the next time.
+    let mut failed = Vec::new();
+    for number in blocked_issues {
+        if let Err(err) = unblock(ctx, config, closed, number).await {
+            log::error!("unable to unblock {repo}#{number}: {err:?}");
+            failed.push(number);
+        }
+    }
+    if !failed.is_empty() {
+        anyhow::bail!(
+            "unable to unblock the issues {failed:?} blocked on {repo}#{}",
+            closed.number
+        );
+    }
+
+    Ok(())
+}
+
+/// Notifies the issue `number` that its blocker `closed` was closed, and only
+/// then removes the blocker.
+async fn unblock(
+    ctx: &Context,
+    config: &BlockedOnConfig,
+    closed: &Issue,
+    number: u64,
+) -> anyhow::Result<()> {
+    let repo = closed.repository().to_string();
+    let blocked = ctx
+        .github
+        .issue(closed.repository(), number)
+        .await
+        .context("unable to fetch the blocked issue")?;
+
+    let remaining: Vec<u64> = issue_blockers::get_blockers(&*ctx.db.get().await, &repo, number)
+        .await
+        .context("unable to get the remaining blockers")?
+        .into_iter()
+        .filter(|&blocker| blocker != closed.number)
+        .collect();
+
+    blocked
+        .post_comment(&ctx.github, &unblock_comment(closed.number, &remaining))
+        .await
+        .context("unable to post the unblock comment")?;
+
+    issue_blockers::remove_blocker(&*ctx.db.get().await, &repo, number, closed.number)
+        .await
+        .context("unable to remove the closed blocking issue")?;
+
+    if remaining.is_empty()
+        && let Some(label) = &config.label
+    {
+        blocked
+            .remove_labels(
+                &ctx.github,
+                vec![Label {
+                    name: label.clone(),
+                }],
+            )
+            .await
+            .context("failed to remove the blocked label")?;
+    }
+
+    Ok(())
+}
+
+fn unblock_comment(closed: u64, remaining: &[u64]) -> String {
+    if remaining.is_empty() {
+        format!("The blocking issue #{closed} has been closed, this issue is no longer blocked.")
+    } else {
+        let remaining = remaining
+            .iter()
+            .map(|n| format!("#{n}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "The blocking issue #{closed} has been closed, this issue is still blocked on {remaining}."
+        )
+    }
+}
+
+#[test]
+fn unblock_comment_no_remaining() {
+    assert_eq!(
+        unblock_comment(12, &[]),
+        "The blocking issue #12 has been closed, this issue is no longer blocked."
+    );
+}
+
+#[test]
+fn unblock_comment_remaining() {
+    assert_eq!(
+        unblock_comment(12, &[3, 45]),
+        "The blocking issue #12 has been closed, this issue is still blocked on #3, #45."
+    );
+}
+
+#[tokio::test]
+async fn closing_notifies_every_blocked_issue() {
+    use crate::db::issue_blockers::{add_blocker, get_blockers};
+    use crate::tests::github::{issue, issue_json, issues_event};
+    use axum::{Json, Router, extract::Path, http::StatusCode, routing::get};
+
+    crate::tests::run_db_test(|mut ctx| async {
+        ctx.use_unreachable_services();
+        // #11 can't be fetched, the other blocked issues are still notified.
+        let router = Router::new().route(
+            "/repos/rust-lang/rust/issues/{number}",
+            get(|Path(number): Path<u64>| async move {
+                if number == 11 {
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+                let mut issue = issue_json("rust-lang/rust", number);
+                issue["labels"] = serde_json::json!([{ "name": "S-blocked" }]);
+                Ok(Json(issue))
+            }),
+        );
+        ctx.handler_ctx_mut().github = crate::tests::mock_github_client(router).await;
+        let db = ctx.db_client();
+        add_blocker(&db, "rust-lang/rust", 10, 1).await?;
+        add_blocker(&db, "rust-lang/rust", 11, 1).await?;
+        add_blocker(&db, "rust-lang/rust", 12, 1).await?;
+        add_blocker(&db, "rust-lang/rust", 12, 2).await?;
+
+        let config = BlockedOnConfig {
+            label: Some("S-blocked".to_string()),
+        };
+        let event = issues_event(IssuesAction::Closed, issue().number(1).call());
+        let (result, actions) = crate::dry_run::record(handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &event,
+            BlockerClosed,
+        ))
+        .await;
+        assert!(result.is_err());
+        assert_eq!(
+            actions,
+            vec![
+                "posted comment on rust-lang/rust#10: \"The blocking issue #1 has been closed, this issue is no longer blocked.\"".to_string(),
+                r#"removed labels ["S-blocked"] from rust-lang/rust#10"#.to_string(),
+                "posted comment on rust-lang/rust#12: \"The blocking issue #1 has been closed, this issue is still blocked on #2.\"".to_string(),
+            ]
+        );
+
+        // Only the blocker of the issue which couldn't be notified is kept.
+        let db = ctx.db_client();
+        assert_eq!(get_blockers(&db, "rust-lang/rust", 10).await?, vec![]);
+        assert_eq!(get_blockers(&db, "rust-lang/rust", 11).await?, vec![1]);
+        assert_eq!(get_blockers(&db, "rust-lang/rust", 12).await?, vec![2]);
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn only_missing_blocking_issues_do_not_exist() {
+    use crate::tests::github::{comment_event, issue, user};
+    use axum::{Json, Router, http::StatusCode, routing::get};
+
+    let mut ctx = crate::tests::offline_context();
+    ctx.team = crate::tests::mock_team_client(Router::new().route(
+        "/teams.json",
+        get(|| async {
+            Json(serde_json::json!({
+                "all": {
+                    "name": "all",
+                    "kind": "team",
+                    "members": [{"name": "ferris", "github": "ferris", "github_id": 100, "is_lead": false}],
+                    "alumni": [],
+                    "discord": [],
+                    "roles": [],
+                }
+            }))
+        }),
+    ))
+    .await;
+    ctx.github = crate::tests::mock_github_client(
+        Router::new()
+            .route(
+                "/repos/rust-lang/rust/issues/404",
+                get(|| async { StatusCode::NOT_FOUND }),
+            )
+            .route(
+                "/repos/rust-lang/rust/issues/500",
+                get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+            ),
+    )
+    .await;
+    let config = BlockedOnConfig { label: None };
+    let event = comment_event()
+        .issue(issue().call())
+        .author(user("ferris", 2))
+        .call();
+
+    let err = handle_command(&ctx, &config, &event, BlockedOnCommand(404))
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Issue #404 does not exist.");
+
+    let err = handle_command(&ctx, &config, &event, BlockedOnCommand(500))
+        .await
+        .unwrap_err();
+    assert!(
+        err.downcast_ref::<crate::errors::UserError>().is_none(),
+        "{err:?}"
+    );
+}
@@ -504,6 +504,7 @@ r#":warning: **Warning** :warning:
             title: Some(crate::github::ChangeInner {
                 from: "Previous title".to_string(),
             }),
+            new_issue: None,
         });
         assert!(!should_handle_event(&event));
     }
@@ -523,6 +524,7 @@ r#":warning: **Warning** :warning:
                     from: "fake-sha".to_string(),
                 },
             }),
+            new_issue: None,
         });
         assert!(should_handle_event(&event));
     }
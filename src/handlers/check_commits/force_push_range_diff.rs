@@ -93,7 +93,7 @@ fn changed_base_commit(
     let newbase = &compare_after.merge_base_commit.sha;
 
     let message = format!(
-        r"This PR was rebased onto a different {branch} commit. Here's a [range-diff]({protocol}://{host}/gh-range-diff/{issue_repo}/{oldbase}..{oldhead}/{newbase}..{newhead}) highlighting what actually changed.
+        r"This PR was rebased onto a different {branch} commit. Here's a [range-diff]({protocol}://{host}/gh-range-diff/{issue_repo}/{oldbase}...{oldhead}/{newbase}...{newhead}) highlighting what actually changed.
 
 *Rebasing is a normal part of keeping PRs up to date, so no action is needed—this note is just to help reviewers.*"
     );
@@ -219,7 +219,7 @@ mod tests {
                 "newhead"
             ),
             Some(
-                r#"This PR was rebased onto a different master commit. Here's a [range-diff](https://mytriagebot.com/gh-range-diff/rust-lang/rust/before-merge-commit..oldhead/after-merge-commit..newhead) highlighting what actually changed.
+                r#"This PR was rebased onto a different master commit. Here's a [range-diff](https://mytriagebot.com/gh-range-diff/rust-lang/rust/before-merge-commit...oldhead/after-merge-commit...newhead) highlighting what actually changed.
 
 *Rebasing is a normal part of keeping PRs up to date, so no action is needed—this note is just to help reviewers.*"#
                 .to_string()
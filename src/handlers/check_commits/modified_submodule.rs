@@ -37,6 +37,8 @@ fn no_submodule_update() {
             +let mut my_var = \"tmp\";"
             .to_string(),
         previous_filename: None,
+        additions: 0,
+        deletions: 0,
     };
 
     assert_eq!(modifies_submodule(&[filediff]), None)
@@ -52,6 +54,8 @@ fn simple_submodule_update() {
             +Subproject commit 8158f78f738715c060d230351623a7f7cc01bf97"
             .to_string(),
         previous_filename: None,
+        additions: 0,
+        deletions: 0,
     };
 
     assert_eq!(
@@ -69,6 +73,8 @@ fn no_submodule_update_tricky_case() {
             +let mut subproject_commit = \"+Subproject commit \";"
             .to_string(),
         previous_filename: None,
+        additions: 0,
+        deletions: 0,
     };
 
     assert_eq!(modifies_submodule(&[filediff]), None)
@@ -32,28 +32,36 @@ pub(super) async fn validate_config(
 
     let triagebot_content = triagebot_content.unwrap_or_default();
     let triagebot_content = String::from_utf8_lossy(&triagebot_content);
+    let blob_url = format!(
+        "https://github.com/{}/blob/{}/{CONFIG_FILE_NAME}",
+        repo.full_name, pr_source.sha
+    );
 
-    match toml::from_str::<crate::handlers::Config>(&triagebot_content) {
+    Ok(check_config(&triagebot_content, &blob_url))
+}
+
+/// Checks the content of a `triagebot.toml`, returning the warning to post if it's
+/// invalid, including unknown keys (as the config is strict about them).
+///
+/// `blob_url` is the URL of the file on GitHub, used to link the invalid position.
+fn check_config(triagebot_content: &str, blob_url: &str) -> Option<String> {
+    match toml::from_str::<crate::handlers::Config>(triagebot_content) {
         Err(e) => {
             let position = match e.span() {
                 // toml sometimes gives bad spans, see https://github.com/toml-rs/toml/issues/589
                 Some(span) if span != (0..0) => {
-                    let (line, col) = translate_position(&triagebot_content, span.start);
-                    let url = format!(
-                        "https://github.com/{}/blob/{}/{CONFIG_FILE_NAME}#L{line}",
-                        repo.full_name, pr_source.sha
-                    );
-                    format!(" at position [{line}:{col}]({url})",)
+                    let (line, col) = translate_position(triagebot_content, span.start);
+                    format!(" at position [{line}:{col}]({blob_url}#L{line})",)
                 }
                 Some(_) | None => String::new(),
             };
 
-            Ok(Some(format!(
+            Some(format!(
                 "Invalid `triagebot.toml`{position}:\n\
                 `````\n\
                 {e}\n\
                 `````",
-            )))
+            ))
         }
         Ok(config) => {
             // Error if `[assign.owners]` is not empty (ie auto-assign) and the custom welcome message for assignee isn't set.
@@ -62,10 +70,10 @@ pub(super) async fn validate_config(
                 && let Some(custom_messages) = &assign.custom_messages
                 && custom_messages.auto_assign_someone.is_none()
             {
-                return Ok(Some(
+                return Some(
                     "Invalid `triagebot.toml`:\n\
                     `[assign.owners]` is populated but `[assign.custom_messages.auto-assign-someone]` is not set!".to_string()
-                ));
+                );
             }
 
             // Error if one the mentions entry is not a valid glob.
@@ -73,23 +81,23 @@ pub(super) async fn validate_config(
                 for (entry, MentionsEntryConfig { type_, .. }) in mentions.entries {
                     if type_ == MentionsEntryType::Filename {
                         if let Err(err) = globset::Glob::new(&entry) {
-                            return Ok(Some(format!(
+                            return Some(format!(
                                 "Invalid `triagebot.toml`:\n\
                                 `[mentions.\"{entry}\"]` has an invalid glob syntax: {err}"
-                            )));
+                            ));
                         }
 
                         if entry.starts_with('/') {
-                            return Ok(Some(format!(
+                            return Some(format!(
                                 "Invalid `triagebot.toml`:\n\
                                 `[mentions.\"{entry}\"]` has an invalid pattern: path must be relative (remove the `/` at the start)"
-                            )));
+                            ));
                         }
                     }
                 }
             }
 
-            Ok(None)
+            None
         }
     }
 }
@@ -128,3 +136,30 @@ fn translate_position(input: &str, index: usize) -> (usize, usize) {
 
     (line + 1, column + 1)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::check_config;
+
+    const BLOB_URL: &str = "https://github.com/rust-lang/rust/blob/abc/triagebot.toml";
+
+    #[test]
+    fn valid_config() {
+        assert_eq!(
+            check_config("[relabel]\nallow-unauthenticated = []\n", BLOB_URL),
+            None
+        );
+    }
+
+    #[test]
+    fn unknown_keys() {
+        let warning = check_config("[note]\n\n[pr-tracking]\nrebalence = {}\n", BLOB_URL)
+            .expect("the unknown key is reported");
+        assert!(warning.starts_with("Invalid `triagebot.toml`"), "{warning}");
+        assert!(warning.contains("unknown field `rebalence`"), "{warning}");
+
+        let warning = check_config("[relabl]\nallow-unauthenticated = []\n", BLOB_URL)
+            .expect("the unknown section is reported");
+        assert!(warning.contains("unknown field `relabl`"), "{warning}");
+    }
+}
@@ -1,7 +1,20 @@
-//! Allows to close an issue or a PR
+//! Allows to close an issue or a PR, and to bulk-close the issues matching
+//! a search query through the API.
 
-use crate::{config::CloseConfig, errors::user_error, github::Event, handlers::Context};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::{
+    config::CloseConfig,
+    errors::user_error,
+    github::{self, Event, GithubClient, IssueRepository},
+    handlers::Context,
+};
+use anyhow::Context as _;
+use async_trait::async_trait;
+use chrono::Utc;
 use parser::command::close::CloseCommand;
+use tracing as log;
 
 pub(super) async fn handle_command(
     ctx: &Context,
@@ -21,3 +34,293 @@ pub(super) async fn handle_command(
     issue.close(&ctx.github).await?;
     Ok(())
 }
+
+/// Delay between the closing of two issues by a bulk close, to stay well
+/// within the GitHub secondary rate limits.
+const BULK_CLOSE_DELAY: Duration = Duration::from_secs(1);
+
+/// An issue closed by a bulk close.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct ClosedIssue {
+    pub(crate) repository: String,
+    pub(crate) number: u64,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct BulkCloseReport {
+    pub(crate) dry_run: bool,
+    /// Number of issues matching the search query.
+    pub(crate) matched: usize,
+    /// Number of matching issues skipped as they were already closed (or are PRs).
+    pub(crate) skipped: usize,
+    /// Issues which are being closed in the background (or would be, in dry-run mode).
+    pub(crate) closed: Vec<ClosedIssue>,
+}
+
+/// Closes all the issues matching a GitHub search query, with a `comment` on
+/// each of them.
+///
+/// Already closed issues and PRs are skipped. The issues are closed by a job,
+/// as closing them one by one can take a while; the failures are logged. In
+/// dry-run mode, nothing is changed on GitHub and the report only lists the
+/// issues that would be closed.
+pub(crate) async fn bulk_close(
+    ctx: &Context,
+    query: &str,
+    comment: &str,
+    dry_run: bool,
+) -> anyhow::Result<BulkCloseReport> {
+    let issues = ctx
+        .github
+        .search_issues(&format!("{query} is:issue"))
+        .await?;
+    let closed = plan_bulk_close(&issues);
+
+    if !dry_run && !closed.is_empty() {
+        let args = BulkCloseJobArgs {
+            comment: comment.to_string(),
+            issues: closed.clone(),
+        };
+        crate::db::schedule_job(
+            &*ctx.db.get().await,
+            BULK_CLOSE_JOB_NAME,
+            serde_json::to_value(args)?,
+            Utc::now(),
+        )
+        .await
+        .context("failed to schedule the bulk close")?;
+    }
+
+    log::info!(
+        "bulk close of `{query}` (dry run: {dry_run}): closing {} of {} issues",
+        closed.len(),
+        issues.len()
+    );
+    Ok(BulkCloseReport {
+        dry_run,
+        matched: issues.len(),
+        skipped: issues.len() - closed.len(),
+        closed,
+    })
+}
+
+/// Selects the issues to close by a bulk close, skipping the already closed
+/// ones and the PRs.
+fn plan_bulk_close(issues: &[github::Issue]) -> Vec<ClosedIssue> {
+    issues
+        .iter()
+        .filter(|issue| issue.is_open() && !issue.is_pr())
+        .map(|issue| ClosedIssue {
+            repository: issue.repository().to_string(),
+            number: issue.number,
+        })
+        .collect()
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BulkCloseJobArgs {
+    comment: String,
+    issues: Vec<ClosedIssue>,
+}
+
+pub(crate) struct BulkCloseJob;
+
+const BULK_CLOSE_JOB_NAME: &str = "bulk_close";
+
+#[async_trait]
+impl crate::jobs::Job for BulkCloseJob {
+    fn name(&self) -> &str {
+        BULK_CLOSE_JOB_NAME
+    }
+
+    async fn run(&self, ctx: &Context, metadata: &serde_json::Value) -> anyhow::Result<()> {
+        let args: BulkCloseJobArgs =
+            serde_json::from_value(metadata.clone()).with_context(|| {
+                format!("failed to deserialize the metadata {metadata:?} into args")
+            })?;
+
+        // Each repository is closed in its own dry-run mode
+        let mut by_repository: BTreeMap<&str, Vec<ClosedIssue>> = BTreeMap::new();
+        for closed in &args.issues {
+            by_repository
+                .entry(&closed.repository)
+                .or_default()
+                .push(closed.clone());
+        }
+        let mut failures = Vec::new();
+        for (repository, issues) in by_repository {
+            match crate::dry_run::is_enabled_for(&ctx.github, repository).await {
+                Ok(dry_run) => failures.extend(
+                    crate::dry_run::scope(
+                        dry_run,
+                        close_issues(&ctx.github, &issues, &args.comment),
+                    )
+                    .await,
+                ),
+                Err(err) => failures.extend(issues.iter().map(|closed| {
+                    anyhow::anyhow!(
+                        "failed to close {}#{}: {err:#}",
+                        closed.repository,
+                        closed.number
+                    )
+                })),
+            }
+        }
+        log::info!(
+            "bulk close: {} of {} issues closed",
+            args.issues.len() - failures.len(),
+            args.issues.len()
+        );
+        // The failures aren't retried, as the job would comment again on the
+        // issues which were closed but couldn't be commented on.
+        for failure in failures {
+            log::error!("bulk close: {failure:?}");
+        }
+        Ok(())
+    }
+}
+
+/// Closes the issues, then comments on them, returning the failures.
+///
+/// The issues closed since they were planned (e.g. by a previous interrupted
+/// run) are skipped.
+async fn close_issues(
+    github: &GithubClient,
+    issues: &[ClosedIssue],
+    comment: &str,
+) -> Vec<anyhow::Error> {
+    let mut failures = Vec::new();
+    for (idx, closed) in issues.iter().enumerate() {
+        if idx > 0 {
+            tokio::time::sleep(BULK_CLOSE_DELAY).await;
+        }
+        if let Err(err) = close_issue(github, closed, comment).await {
+            failures.push(err.context(format!(
+                "failed to close {}#{}",
+                closed.repository, closed.number
+            )));
+        }
+    }
+    failures
+}
+
+async fn close_issue(
+    github: &GithubClient,
+    closed: &ClosedIssue,
+    comment: &str,
+) -> anyhow::Result<()> {
+    let (organization, repository) = closed
+        .repository
+        .split_once('/')
+        .with_context(|| format!("invalid repository `{}`", closed.repository))?;
+    let repo = IssueRepository {
+        organization: organization.to_string(),
+        repository: repository.to_string(),
+    };
+    let issue = github.issue(&repo, closed.number).await?;
+    if !issue.is_open() {
+        log::info!("bulk close: {} is already closed", issue.global_id());
+        return Ok(());
+    }
+    // Closing first, so that a failure doesn't leave the closing comment on
+    // an open issue.
+    issue.close(github).await?;
+    issue
+        .post_comment(github, comment)
+        .await
+        .context("failed to post the closing comment")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClosedIssue, bulk_close, close_issues, plan_bulk_close};
+    use crate::github::IssueState;
+    use crate::tests::github::{issue, issue_json, pull_request};
+    use crate::tests::{mock_github_client, offline_context};
+    use axum::extract::{Path, Query};
+    use axum::http::StatusCode;
+    use axum::{Json, Router, routing::get};
+    use std::collections::HashMap;
+
+    fn closed(number: u64) -> ClosedIssue {
+        ClosedIssue {
+            repository: "rust-lang/rust".to_string(),
+            number,
+        }
+    }
+
+    #[test]
+    fn bulk_close_plan() {
+        let issues = vec![
+            issue().number(1).call(),
+            issue().number(2).state(IssueState::Closed).call(),
+            issue().number(3).call(),
+            pull_request().number(4).call(),
+        ];
+
+        assert_eq!(plan_bulk_close(&issues), vec![closed(1), closed(3)]);
+    }
+
+    #[tokio::test]
+    async fn bulk_close_dry_run() {
+        // Only the search is served, and the database is unreachable: a dry run
+        // neither changes the issues nor schedules their closing.
+        let router = Router::new().route(
+            "/search/issues",
+            get(|Query(params): Query<HashMap<String, String>>| async move {
+                assert_eq!(params["q"], "label:S-inactive is:issue");
+                Json(serde_json::json!({
+                    "total_count": 2,
+                    "items": [issue_json("rust-lang/rust", 1), issue_json("rust-lang/rust", 2)],
+                }))
+            }),
+        );
+        let mut ctx = offline_context();
+        ctx.github = mock_github_client(router).await;
+
+        let report = bulk_close(&ctx, "label:S-inactive", "Closing inactive issues.", true)
+            .await
+            .unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.matched, 2);
+        assert_eq!(report.closed, vec![closed(1), closed(2)]);
+    }
+
+    #[tokio::test]
+    async fn bulk_close_reports_every_failure() {
+        let router = Router::new().route(
+            "/repos/rust-lang/rust/issues/{number}",
+            get(|Path(number): Path<u64>| async move {
+                match number {
+                    2 => Err(StatusCode::INTERNAL_SERVER_ERROR),
+                    3 => {
+                        let mut issue = issue_json("rust-lang/rust", number);
+                        issue["state"] = "closed".into();
+                        Ok(Json(issue))
+                    }
+                    _ => Ok(Json(issue_json("rust-lang/rust", number))),
+                }
+            }),
+        );
+        let github = mock_github_client(router).await;
+
+        let issues = [closed(1), closed(2), closed(3), closed(4)];
+        let (failures, actions) =
+            crate::dry_run::record(close_issues(&github, &issues, "Closing inactive issues."))
+                .await;
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].to_string().contains("rust-lang/rust#2"));
+        // The issues are closed before being commented on, and the already
+        // closed ones are skipped.
+        assert_eq!(
+            actions,
+            vec![
+                "closed rust-lang/rust#1".to_string(),
+                "posted comment on rust-lang/rust#1: \"Closing inactive issues.\"".to_string(),
+                "closed rust-lang/rust#4".to_string(),
+                "posted comment on rust-lang/rust#4: \"Closing inactive issues.\"".to_string(),
+            ]
+        );
+    }
+}
@@ -71,6 +71,18 @@ pub(super) async fn handle_command(
         );
     }
 
+    // Normalize the title so that the same concern written differently is still
+    // recognized as such, the length limit only applies to new concerns so that
+    // the existing ones can always be resolved
+    let cmd = match cmd {
+        ConcernCommand::Concern { title } => ConcernCommand::Concern {
+            title: normalize_title(&title, config.max_title_length)?,
+        },
+        ConcernCommand::Resolve { title } => ConcernCommand::Resolve {
+            title: collapse_whitespace(&title),
+        },
+    };
+
     let mut client = ctx.db.get().await;
     let mut edit: EditIssueBody<'_, ConcernData> =
         EditIssueBody::load(&mut client, issue, CONCERN_ISSUE_KEY)
@@ -82,7 +94,11 @@ pub(super) async fn handle_command(
     match cmd {
         ConcernCommand::Concern { title } => {
             // Only add a concern if it wasn't already added, we could be in an edit
-            if concern_data.concerns.iter().any(|c| c.title == title) {
+            if concern_data
+                .concerns
+                .iter()
+                .any(|c| same_title(&c.title, &title))
+            {
                 tracing::info!(
                     "concern with the same name ({title}) already exists ({:?})",
                     &concern_data.concerns
@@ -98,7 +114,7 @@ pub(super) async fn handle_command(
         ConcernCommand::Resolve { title } => concern_data
             .concerns
             .iter_mut()
-            .filter(|c| c.title == title)
+            .filter(|c| same_title(&c.title, &title))
             .for_each(|c| {
                 c.status = ConcernStatus::Resolved {
                     comment_url: comment_url.to_string(),
@@ -159,6 +175,34 @@ pub(super) async fn handle_command(
     Ok(())
 }
 
+/// Trims and collapses the whitespace of a concern title, rejecting titles
+/// longer than `max_length` characters.
+fn normalize_title(title: &str, max_length: usize) -> anyhow::Result<String> {
+    let title = collapse_whitespace(title);
+
+    if title.is_empty() {
+        return user_error!("The concern title cannot be empty.");
+    }
+
+    let length = title.chars().count();
+    if length > max_length {
+        return user_error!(format!(
+            "The concern title is too long ({length} characters), the maximum is {max_length} characters."
+        ));
+    }
+
+    Ok(title)
+}
+
+fn collapse_whitespace(title: &str) -> String {
+    title.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Titles are compared case-insensitively, so `resolve` doesn't need the exact casing.
+fn same_title(a: &str, b: &str) -> bool {
+    collapse_whitespace(a).to_lowercase() == collapse_whitespace(b).to_lowercase()
+}
+
 fn markdown_content(concerns: &[Concern], bot: &str) -> String {
     if concerns.is_empty() {
         return String::new();
@@ -265,3 +309,41 @@ fn resolved_concerns_markdown_content() {
 "#
     );
 }
+
+#[test]
+fn normalize_title_whitespace() {
+    assert_eq!(
+        normalize_title("  this   is\tmy  concern ", 200).unwrap(),
+        "this is my concern"
+    );
+}
+
+#[test]
+fn normalize_title_too_long() {
+    assert!(normalize_title("abcdef", 6).is_ok());
+
+    let err = normalize_title("abcdefg", 6).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The concern title is too long (7 characters), the maximum is 6 characters."
+    );
+}
+
+#[test]
+fn resolved_title_is_not_length_limited() {
+    // A concern added before the limit was lowered can still be resolved
+    let title = "a ".repeat(300);
+    assert!(normalize_title(&title, 200).is_err());
+    assert!(same_title(&collapse_whitespace(&title), title.trim()));
+}
+
+#[test]
+fn normalize_title_empty() {
+    assert!(normalize_title(" \t ", 200).is_err());
+}
+
+#[test]
+fn same_title_is_case_insensitive() {
+    assert!(same_title("This is my concern", "this IS my  concern"));
+    assert!(!same_title("This is my concern", "this is another concern"));
+}
@@ -0,0 +1,66 @@
+//! Allows reviewers to defer the pings of a pull request with
+//! `@rustbot defer 3d`.
+//!
+//! The deferral is stored in the database, and the user isn't pinged on the
+//! pull request by the `mentions` handler until it expires.
+//!
+//! Parsing is done in the `parser::command::defer` module.
+
+use anyhow::Context as _;
+use chrono::Utc;
+use parser::command::defer::DeferCommand;
+
+use crate::config::DeferConfig;
+use crate::db::review_deferrals;
+use crate::errors::user_error;
+use crate::github::Event;
+use crate::handlers::Context;
+
+/// The longest a reviewer can defer the pings of a pull request.
+const MAX_DEFERRAL: chrono::Duration = chrono::Duration::days(90);
+
+pub(super) async fn handle_command(
+    ctx: &Context,
+    _config: &DeferConfig,
+    event: &Event,
+    DeferCommand(duration): DeferCommand,
+) -> anyhow::Result<()> {
+    let Some(issue) = event.issue().filter(|issue| issue.is_pr()) else {
+        return user_error!("The `defer` command can only be used on pull requests.");
+    };
+
+    let duration = match chrono::Duration::from_std(duration) {
+        Ok(duration) if duration <= MAX_DEFERRAL => duration,
+        _ => {
+            return user_error!(format!(
+                "Pings can be deferred for at most {} days.",
+                MAX_DEFERRAL.num_days()
+            ));
+        }
+    };
+    let until = Utc::now() + duration;
+
+    let user = event.user();
+    review_deferrals::defer_pings(
+        &*ctx.db.get().await,
+        &issue.repository().to_string(),
+        issue.number,
+        &user.login,
+        until,
+    )
+    .await
+    .context("unable to record the deferral")?;
+
+    issue
+        .post_comment(
+            &ctx.github,
+            &format!(
+                "Pings to @{} on this pull request are deferred until {}.",
+                user.login,
+                until.format("%Y-%m-%d %H:%M UTC")
+            ),
+        )
+        .await
+        .context("failed to post the deferral comment")?;
+    Ok(())
+}
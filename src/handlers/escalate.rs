@@ -0,0 +1,147 @@
+//! Allows team members to escalate an issue to a team's Zulip stream, with
+//! `@rustbot escalate T-compiler "reason"`.
+//!
+//! The message contains a link to the issue, the reason and the current labels
+//! of the issue, and is posted in a topic named after the issue.
+//!
+//! Parsing is done in the `parser::command::escalate` module.
+
+use anyhow::Context as _;
+use parser::command::escalate::EscalateCommand;
+
+use crate::config::EscalateConfig;
+use crate::errors::user_error;
+use crate::github::{Event, Issue};
+use crate::handlers::Context;
+use crate::handlers::major_change::{ZULIP_TOPIC_MAX_LENGTH, zulip_topic_from_issue};
+use crate::zulip::MessageApiRequest;
+use crate::zulip::api::Recipient;
+
+pub(super) async fn handle_command(
+    ctx: &Context,
+    config: &EscalateConfig,
+    event: &Event,
+    cmd: EscalateCommand,
+) -> anyhow::Result<()> {
+    let Some(issue) = event.issue() else {
+        return user_error!("Can only escalate issues and pull requests");
+    };
+
+    let user = event.user();
+    let is_allowed = config
+        .allowed_users
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(&user.login))
+        || ctx.team.is_team_member(&user.login).await.unwrap_or(false);
+    if !is_allowed {
+        return user_error!("Only team members can escalate issues.");
+    }
+
+    let Some(stream) = team_stream(config, &cmd.team) else {
+        let mut teams: Vec<_> = config.teams.keys().map(|t| format!("`{t}`")).collect();
+        teams.sort();
+        return user_error!(format!(
+            "Unknown team `{}`, expected one of: {}.",
+            cmd.team,
+            teams.join(", ")
+        ));
+    };
+
+    let topic = zulip_topic_from_issue(
+        &issue.to_zulip_github_reference(),
+        false,
+        ZULIP_TOPIC_MAX_LENGTH,
+    );
+    let recipient = Recipient::Stream {
+        id: stream,
+        topic: &topic,
+    };
+    MessageApiRequest {
+        recipient,
+        content: &escalation_message(issue, &user.login, &cmd.reason),
+    }
+    .send(&ctx.zulip)
+    .await
+    .context("failed to post the escalation on Zulip")?;
+
+    issue
+        .post_comment(
+            &ctx.github,
+            &format!(
+                "Escalated to {} on [Zulip]({}).",
+                cmd.team,
+                recipient.url(&ctx.zulip)
+            ),
+        )
+        .await
+        .context("failed to post the escalation comment")?;
+    Ok(())
+}
+
+/// Returns the Zulip stream of the team, matched case-insensitively.
+fn team_stream(config: &EscalateConfig, team: &str) -> Option<u64> {
+    config
+        .teams
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(team))
+        .map(|(_, stream)| *stream)
+}
+
+/// The Zulip message escalating the issue.
+fn escalation_message(issue: &Issue, escalated_by: &str, reason: &str) -> String {
+    let labels = if issue.labels.is_empty() {
+        "none".to_string()
+    } else {
+        issue
+            .labels
+            .iter()
+            .map(|label| format!("`{}`", label.name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    format!(
+        "{escalated_by} escalated [{}]({}):\n\n> {reason}\n\nLabels: {labels}",
+        issue.global_id(),
+        issue.html_url,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::github::issue;
+    use std::collections::HashMap;
+
+    #[test]
+    fn message_from_issue() {
+        let labeled = issue()
+            .number(1234)
+            .labels(vec!["T-compiler", "regression-from-stable-to-beta"])
+            .call();
+        assert_eq!(
+            escalation_message(&labeled, "jieyouxu", "breaks a popular crate"),
+            format!(
+                "jieyouxu escalated [rust-lang/rust#1234]({}):\n\n\
+                 > breaks a popular crate\n\n\
+                 Labels: `T-compiler`, `regression-from-stable-to-beta`",
+                labeled.html_url
+            )
+        );
+
+        let unlabeled = issue().number(1).call();
+        assert!(
+            escalation_message(&unlabeled, "jieyouxu", "breaks a popular crate")
+                .ends_with("Labels: none")
+        );
+    }
+
+    #[test]
+    fn stream_of_team() {
+        let config = EscalateConfig {
+            teams: HashMap::from([("T-compiler".to_string(), 131828)]),
+            allowed_users: vec![],
+        };
+        assert_eq!(team_stream(&config, "t-compiler"), Some(131828));
+        assert_eq!(team_stream(&config, "T-lang"), None);
+    }
+}
@@ -0,0 +1,151 @@
+//! Handles the renames of labels, reported by the `label` webhook events.
+//!
+//! The issues keep their labels under the new name, but the references stored
+//! by triagebot use the old one: the label subscriptions are moved to the new
+//! name, and a warning is logged if the `triagebot.toml` of the repository still
+//! references the old name, as it needs to be updated manually.
+
+use crate::config::Config;
+use crate::db::label_subscriptions;
+use crate::github::LabelEvent;
+use crate::handlers::Context;
+use anyhow::Context as _;
+use tracing as log;
+
+pub(super) async fn handle(
+    ctx: &Context,
+    event: &LabelEvent,
+    config: Option<&Config>,
+) -> anyhow::Result<()> {
+    let Some(old) = event.renamed_from() else {
+        return Ok(());
+    };
+    let repo = &event.repository.full_name;
+    let new = &event.label.name;
+
+    if !crate::dry_run::skip(format_args!(
+        "moved the subscriptions to label `{old}` of {repo} to `{new}`"
+    )) {
+        let renamed = rename_references(&*ctx.db.get().await, event).await?;
+        log::info!("label `{old}` of {repo} renamed to `{new}`, moved {renamed} subscriptions");
+    }
+
+    if let Some(config) = config
+        && config_references_label(config, old)
+    {
+        log::warn!(
+            "label `{old}` of {repo} was renamed to `{new}`, but is still referenced in its triagebot.toml"
+        );
+    }
+    Ok(())
+}
+
+/// Updates the label references stored in the database after a rename,
+/// returning the number of updated references.
+async fn rename_references(db: &tokio_postgres::Client, event: &LabelEvent) -> anyhow::Result<u64> {
+    let Some(old) = event.renamed_from() else {
+        return Ok(0);
+    };
+    label_subscriptions::rename_label(db, &event.repository.full_name, old, &event.label.name)
+        .await
+        .context("failed to rename the label subscriptions")
+}
+
+/// Whether the label is used as a value or a key anywhere in the config.
+fn config_references_label(config: &Config, label: &str) -> bool {
+    fn references(value: &serde_json::Value, label: &str) -> bool {
+        match value {
+            serde_json::Value::String(s) => s == label,
+            serde_json::Value::Array(values) => values.iter().any(|v| references(v, label)),
+            serde_json::Value::Object(map) => map
+                .iter()
+                .any(|(key, v)| key == label || references(v, label)),
+            _ => false,
+        }
+    }
+    serde_json::to_value(config).is_ok_and(|value| references(&value, label))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::label_subscriptions::{get_subscribers, subscribe};
+    use crate::tests::github::user;
+    use crate::tests::run_db_test;
+
+    fn rename_event(from: &str, to: &str) -> LabelEvent {
+        serde_json::from_value(serde_json::json!({
+            "action": "edited",
+            "label": {"name": to, "color": "ededed"},
+            "changes": {"name": {"from": from}},
+            "repository": {"full_name": "rust-lang/rust", "default_branch": "main"},
+            "sender": {"login": "octocat", "id": 1, "type": "User"},
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn renamed_from() {
+        assert_eq!(
+            rename_event("S-waiting-on-bors", "S-waiting-on-merge").renamed_from(),
+            Some("S-waiting-on-bors")
+        );
+        // Only the color or the description changed
+        assert_eq!(rename_event("T-lang", "T-lang").renamed_from(), None);
+    }
+
+    #[tokio::test]
+    async fn rename_updates_subscriptions() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let repo = "rust-lang/rust";
+            let (old, new) = ("S-waiting-on-bors", "S-waiting-on-merge");
+
+            let watcher = user("watcher", 1);
+            let both = user("both", 2);
+            subscribe(&db, &watcher, repo, 10, old).await?;
+            subscribe(&db, &both, repo, 10, old).await?;
+            subscribe(&db, &both, repo, 10, new).await?;
+            subscribe(&db, &watcher, "rust-lang/cargo", 10, old).await?;
+
+            assert_eq!(rename_references(&db, &rename_event(old, new)).await?, 1);
+
+            assert!(get_subscribers(&db, repo, 10, old).await?.is_empty());
+            assert_eq!(
+                get_subscribers(&db, repo, 10, new)
+                    .await?
+                    .into_iter()
+                    .map(|u| u.login)
+                    .collect::<Vec<_>>(),
+                vec!["watcher".to_string(), "both".to_string()]
+            );
+            // Other repositories are unaffected
+            assert_eq!(
+                get_subscribers(&db, "rust-lang/cargo", 10, old)
+                    .await?
+                    .len(),
+                1
+            );
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[test]
+    fn label_referenced_in_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [autolabel."S-waiting-on-review"]
+            new_pr = true
+
+            [relabel]
+            allow-unauthenticated = ["T-*", "A-diagnostics"]
+            "#,
+        )
+        .unwrap();
+        assert!(config_references_label(&config, "S-waiting-on-review"));
+        assert!(config_references_label(&config, "A-diagnostics"));
+        assert!(!config_references_label(&config, "A-parser"));
+    }
+}
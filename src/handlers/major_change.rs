@@ -1,13 +1,29 @@
+use std::collections::HashSet;
 use std::fmt::Display;
 
+pub(super) mod hold;
+pub(super) mod mcp_comment;
+
+use crate::config::{MajorChangeStaleAction, MajorChangeStaleConfig, MajorChangeTemplate};
+use crate::db::major_change_holds::{get_hold, get_last_release};
+use crate::db::major_change_topics::{
+    ProposalTopic, get_proposal_repositories, get_proposal_topic, set_proposal_topic,
+};
+use crate::db::zulip_rename_breadcrumbs::{
+    RenameBreadcrumb, get_rename_breadcrumb, record_rename_breadcrumb,
+};
+use crate::db::zulip_topic_messages::{
+    TopicMessage, get_last_topic_message, rename_topic_messages, set_last_topic_message,
+};
 use crate::errors::user_error;
 use crate::github::LockReason;
+use crate::github::issue_query::Query;
 use crate::jobs::Job;
 use crate::utils::is_issue_under_rfcbot_fcp;
-use crate::zulip::api::Recipient;
+use crate::zulip::api::{AddReactionApiRequest, MessageApiResponse, Recipient};
 use crate::{
     config::MajorChangeConfig,
-    github::{Event, Issue, IssuesAction, IssuesEvent, Label, ZulipGitHubReference},
+    github::{Event, Issue, IssuesAction, IssuesEvent, Label, Repository, ZulipGitHubReference},
     handlers::Context,
 };
 use anyhow::Context as _;
@@ -35,8 +51,6 @@ pub(super) async fn parse_input(
     let Some(config) = config else {
         return Ok(None);
     };
-    let enabling_label = config.enabling_label.as_str();
-
     if event.action == IssuesAction::Edited {
         if let Some(changes) = &event.changes {
             if let Some(previous_title) = &changes.title {
@@ -45,12 +59,7 @@ pub(super) async fn parse_input(
                     title: previous_title.from.clone(),
                     repository: event.issue.repository().clone(),
                 };
-                if event
-                    .issue
-                    .labels()
-                    .iter()
-                    .any(|l| l.name == enabling_label)
-                {
+                if config.has_enabling_label(event.issue.labels()) {
                     return Ok(Some(Invocation::Rename { prev_issue }));
                 } else {
                     // Ignore renamed issues without primary label (e.g., major-change)
@@ -91,8 +100,11 @@ pub(super) async fn parse_input(
     // We want to treat reopened issues as new proposals but if the
     // issue is freshly opened, we only want to trigger once;
     // currently we do so on the label event.
-    if matches!(event.action, IssuesAction::Reopened if event.issue.labels().iter().any(|l| l.name == enabling_label))
-        || matches!(&event.action, IssuesAction::Labeled { label } if label.name == enabling_label)
+    //
+    // Adding another enabling label to an existing proposal doesn't make it new.
+    if matches!(event.action, IssuesAction::Reopened if config.has_enabling_label(event.issue.labels()))
+        || matches!(&event.action, IssuesAction::Labeled { label } if config.is_enabling_label(&label.name)
+            && !has_other_enabling_label(config, event.issue.labels(), &label.name))
     {
         return Ok(Some(Invocation::NewProposal));
     }
@@ -101,21 +113,23 @@ pub(super) async fn parse_input(
     Ok(None)
 }
 
+/// Whether `labels` contain an enabling label other than `label`.
+fn has_other_enabling_label(config: &MajorChangeConfig, labels: &[Label], label: &str) -> bool {
+    labels
+        .iter()
+        .any(|l| l.name != label && config.is_enabling_label(&l.name))
+}
+
 pub(super) async fn handle_input(
     ctx: &Context,
     config: &MajorChangeConfig,
     event: &IssuesEvent,
     cmd: Invocation,
 ) -> anyhow::Result<()> {
-    if !event
-        .issue
-        .labels()
-        .iter()
-        .any(|l| l.name == config.enabling_label)
-    {
+    if !config.has_enabling_label(event.issue.labels()) {
         return user_error!(format!(
-            "This issue is not ready for proposals; it lacks the `{}` label.",
-            config.enabling_label
+            "This issue is not ready for proposals; it lacks the {} label.",
+            config.enabling_labels_description()
         ));
     }
     let (zulip_msg, label_to_add) = match cmd {
@@ -140,13 +154,40 @@ pub(super) async fn handle_input(
         Invocation::Rename { prev_issue } => {
             let issue = &event.issue;
 
-            let prev_topic = zulip_topic_from_issue(&prev_issue);
+            // The topic may have been renamed since the proposal was opened (e.g. on
+            // acceptance), in which case the new title keeps its prefix.
+            let db = ctx.db.get().await;
+            let repo = issue.repository().to_string();
+            let recorded = get_proposal_topic(&db, &repo, issue.number).await?;
+            drop(db);
+            let prev_topic = match &recorded {
+                Some(recorded) => recorded.topic.clone(),
+                None => zulip_topic_from_issue(
+                    &prev_issue,
+                    config.zulip_topic_full_reference,
+                    config.zulip_topic_max_length,
+                ),
+            };
             let partial_issue = issue.to_zulip_github_reference();
-            let new_topic = zulip_topic_from_issue(&partial_issue);
+            let new_topic = if issue.labels().iter().any(|l| l.name == config.accept_label)
+                && let Some(topic) = accepted_topic(config, &partial_issue)
+            {
+                topic
+            } else {
+                zulip_topic_from_issue(
+                    &partial_issue,
+                    config.zulip_topic_full_reference,
+                    config.zulip_topic_max_length,
+                )
+            };
+            let zulip_stream = match &recorded {
+                Some(recorded) => recorded.stream_id,
+                None => zulip_route(config, issue.labels()).stream,
+            };
 
             let zulip_send_req = crate::zulip::MessageApiRequest {
                 recipient: Recipient::Stream {
-                    id: config.zulip_stream,
+                    id: zulip_stream,
                     topic: &prev_topic,
                 },
                 content: "The associated GitHub issue has been renamed. Renaming this Zulip topic.",
@@ -166,28 +207,69 @@ pub(super) async fn handle_input(
                 .send(&ctx.zulip)
                 .await
                 .context("zulip message update failed")?;
+            record_proposal_topic(
+                ctx,
+                issue,
+                &prev_topic,
+                &ProposalTopic {
+                    stream_id: zulip_stream,
+                    topic: new_topic.clone(),
+                },
+            )
+            .await?;
 
             // after renaming the zulip topic, post an additional comment under the old topic with a url to the new, renamed topic
             // this is necessary due to the lack of topic permalinks, see https://github.com/zulip/zulip/issues/15290
+            //
+            // only one breadcrumb is posted per issue, later renames update it to point to the latest topic
             let new_topic_url = Recipient::Stream {
-                id: config.zulip_stream,
+                id: zulip_stream,
                 topic: &new_topic,
             }
             .url(&ctx.zulip);
             let breadcrumb_comment = format!(
                 "The associated GitHub issue has been renamed. Please see the [renamed Zulip topic]({new_topic_url})."
             );
-            let zulip_send_breadcrumb_req = crate::zulip::MessageApiRequest {
-                recipient: Recipient::Stream {
-                    id: config.zulip_stream,
-                    topic: &prev_topic,
-                },
-                content: &breadcrumb_comment,
-            };
-            zulip_send_breadcrumb_req
+            let db = ctx.db.get().await;
+            if let Some(breadcrumb) =
+                get_rename_breadcrumb(&db, zulip_stream, &repo, issue.number).await?
+            {
+                crate::zulip::UpdateMessageApiRequest {
+                    message_id: breadcrumb.message_id,
+                    topic: None,
+                    propagate_mode: None,
+                    content: Some(&breadcrumb_comment),
+                }
                 .send(&ctx.zulip)
                 .await
-                .context("zulip post failed")?;
+                .context("zulip breadcrumb update failed")?;
+            } else {
+                let zulip_send_breadcrumb_req = crate::zulip::MessageApiRequest {
+                    recipient: Recipient::Stream {
+                        id: zulip_stream,
+                        topic: &prev_topic,
+                    },
+                    content: &breadcrumb_comment,
+                };
+                let breadcrumb_res = zulip_send_breadcrumb_req
+                    .send(&ctx.zulip)
+                    .await
+                    .context("zulip post failed")?;
+                // In dry-run mode, the message wasn't posted and has no id to record.
+                if !crate::dry_run::is_enabled() {
+                    record_rename_breadcrumb(
+                        &db,
+                        zulip_stream,
+                        &repo,
+                        issue.number,
+                        &RenameBreadcrumb {
+                            topic: prev_topic,
+                            message_id: breadcrumb_res.message_id,
+                        },
+                    )
+                    .await?;
+                }
+            }
 
             return Ok(());
         }
@@ -196,19 +278,28 @@ pub(super) async fn handle_input(
             //
             // However, since this handler is stateless, we can't track when to re-add it, it's also a bit unclear if it
             // should be re-added at all. Also historically the `enable_label` wasn't removed either, so we don't touch it.
-            format!(
-                "Concern(s) have been raised on the [associated GitHub issue]({}). This proposal is now blocked until those concerns are fully resolved.",
-                event.issue.html_url
-            ),
+            if let Some(template) = &config.concerns_added_template {
+                render_template(ctx, config, template, &event.issue).await?
+            } else {
+                format!(
+                    "Concern(s) have been raised on the [associated GitHub issue]({}). This proposal is now blocked until those concerns are fully resolved.",
+                    event.issue.html_url
+                )
+            },
             None,
         ),
-        Invocation::ConcernsResolved => (
-            if event.issue.labels().contains(&Label {
+        Invocation::ConcernsResolved => {
+            let seconded = event.issue.labels().contains(&Label {
                 name: config.second_label.to_string(),
-            }) {
+            });
+            if seconded {
                 // Re-schedule acceptance job to automaticaly close the MCP
                 schedule_acceptance_job(ctx, config, &event.issue).await?;
+            }
 
+            let zulip_msg = if let Some(template) = &config.concerns_resolved_template {
+                render_template(ctx, config, template, &event.issue).await?
+            } else if seconded {
                 format!(
                     "All concerns on the [associated GitHub issue]({}) have been resolved, this proposal is no longer blocked, and will be approved in {} days if no (new) objections are raised.",
                     event.issue.html_url, config.waiting_period
@@ -218,12 +309,12 @@ pub(super) async fn handle_input(
                     "All concerns on the [associated GitHub issue]({}) have been resolved, this proposal is no longer blocked.",
                     event.issue.html_url
                 )
-            },
-            None,
-        ),
+            };
+            (zulip_msg, None)
+        }
     };
 
-    handle(
+    let zulip_response = handle(
         ctx,
         config,
         &event.issue,
@@ -231,7 +322,46 @@ pub(super) async fn handle_input(
         label_to_add.cloned(),
         cmd == Invocation::NewProposal,
     )
-    .await
+    .await?;
+
+    if cmd == Invocation::AcceptedProposal {
+        let mut topic = proposal_topic(ctx, config, &event.issue).await?;
+        if let Some(accepted) = accepted_topic(config, &event.issue.to_zulip_github_reference())
+            && topic.topic != accepted
+        {
+            crate::zulip::UpdateMessageApiRequest::rename_topic(
+                zulip_response.message_id,
+                &accepted,
+            )
+            .send(&ctx.zulip)
+            .await
+            .context("failed to archive the Zulip topic of the accepted proposal")?;
+            let renamed = ProposalTopic {
+                topic: accepted,
+                ..topic.clone()
+            };
+            record_proposal_topic(ctx, &event.issue, &topic.topic, &renamed).await?;
+            topic = renamed;
+        }
+
+        // Link the topic as renamed above, the previous one doesn't exist anymore.
+        let topic_url = zulip_response.url(
+            &Recipient::Stream {
+                id: topic.stream_id,
+                topic: &topic.topic,
+            },
+            &ctx.zulip,
+        );
+        if let Some(comment) = accepted_proposal_comment(config, &event.issue, &topic_url) {
+            event
+                .issue
+                .post_comment(&ctx.github, &comment)
+                .await
+                .context("failed to post the acceptance comment")?;
+        }
+    }
+
+    Ok(())
 }
 
 pub(super) async fn handle_command(
@@ -242,14 +372,10 @@ pub(super) async fn handle_command(
 ) -> anyhow::Result<()> {
     let issue = event.issue().unwrap();
 
-    if !issue
-        .labels()
-        .iter()
-        .any(|l| l.name == config.enabling_label)
-    {
+    if !config.has_enabling_label(issue.labels()) {
         return user_error!(format!(
-            "This issue cannot be seconded; it lacks the `{}` label.",
-            config.enabling_label
+            "This issue cannot be seconded; it lacks the {} label.",
+            config.enabling_labels_description()
         ));
     }
 
@@ -282,7 +408,7 @@ pub(super) async fn handle_command(
         .iter()
         .any(|l| &l.name == &config.second_label);
 
-    let zulip_ping = &config.zulip_ping;
+    let zulip_ping = resolve_zulip_ping(ctx, zulip_route(config, issue.labels()).ping).await;
     let issue_number = issue.number;
     let issue_url = &issue.html_url;
     let bot_username = &ctx.username;
@@ -376,33 +502,145 @@ async fn handle(
     zulip_msg: String,
     label_to_add: Option<String>,
     new_proposal: bool,
-) -> anyhow::Result<()> {
-    if let Some(label_to_add) = label_to_add {
+) -> anyhow::Result<MessageApiResponse> {
+    let proposal = proposal_topic(ctx, config, issue).await?;
+    let zulip_topic = &proposal.topic;
+
+    let recipient = Recipient::Stream {
+        id: proposal.stream_id,
+        topic: zulip_topic,
+    };
+    let label = label_to_add.map(|name| async move {
         issue
-            .add_labels(&ctx.github, vec![Label { name: label_to_add }])
+            .add_labels(&ctx.github, vec![Label { name }])
+            .await
+            .context("failed to add the major change label")
+    });
+    let zulip_response = notify_then_label(
+        issue,
+        post_topic_message(ctx, config, recipient, &zulip_msg),
+        label,
+    )
+    .await?;
+
+    if new_proposal {
+        // Remember the stream and topic the proposal was opened in, so that the
+        // later messages are posted there even if its labels change.
+        if !crate::dry_run::is_enabled() {
+            set_proposal_topic(
+                &*ctx.db.get().await,
+                &issue.repository().to_string(),
+                issue.number,
+                &proposal,
+            )
+            .await?;
+        }
+
+        if let Some(emoji_name) = &config.zulip_initial_reaction {
+            // The reaction is merely a convenience, don't fail the whole flow for it.
+            if let Err(err) = (AddReactionApiRequest {
+                message_id: zulip_response.message_id,
+                emoji_name,
+            })
+            .send(&ctx.zulip)
             .await
-            .context("failed to add the major change label")?;
+            {
+                log::warn!(
+                    "failed to add the initial reaction to the proposal {}: {err:?}",
+                    issue.global_id()
+                );
+            }
+        }
+
+        let topic_url = zulip_response.url(&recipient, &ctx.zulip);
+        let comment = new_proposal_comment(config, issue, &topic_url).map(|comment| async move {
+            issue
+                .post_comment(&ctx.github, &comment)
+                .await
+                .context("post major change comment")
+                .map(|_| ())
+        });
+        let announcement = config.announce_stream.map(|stream| async move {
+            announce_new_proposal(ctx, stream, issue, zulip_topic, &topic_url)
+                .await
+                .with_context(|| format!("failed to announce it in stream {stream}"))
+        });
+        comment_then_announce(issue, comment, announcement).await?;
     }
 
-    let partial_issue = issue.to_zulip_github_reference();
-    let zulip_topic = zulip_topic_from_issue(&partial_issue);
+    Ok(zulip_response)
+}
 
-    let zulip_req = crate::zulip::MessageApiRequest {
-        recipient: Recipient::Stream {
-            id: config.zulip_stream,
-            topic: &zulip_topic,
-        },
-        content: &zulip_msg,
-    };
+/// Posts the Zulip message of a status change of a proposal, and only then
+/// applies its label, so that a Zulip failure doesn't leave the label applied
+/// without any notification and the whole change can be retried.
+async fn notify_then_label(
+    issue: &Issue,
+    notify: impl Future<Output = anyhow::Result<MessageApiResponse>>,
+    label: Option<impl Future<Output = anyhow::Result<()>>>,
+) -> anyhow::Result<MessageApiResponse> {
+    let zulip_response = notify.await?;
+    if let Some(label) = label
+        && let Err(err) = label.await
+    {
+        log::error!(
+            "the Zulip message of the major change {} was posted, but not its label: {err:?}",
+            issue.global_id()
+        );
+        return Err(err);
+    }
+    Ok(zulip_response)
+}
 
-    let zulip_response = zulip_req
-        .send(&ctx.zulip)
-        .await
-        .context("zulip post failed")?;
+/// Posts the GitHub comment of a new proposal, and then its announcement. The
+/// proposal is already fully set up at this point, so a failure to announce it
+/// is only logged, and not reported as a failure of the whole flow.
+async fn comment_then_announce(
+    issue: &Issue,
+    comment: Option<impl Future<Output = anyhow::Result<()>>>,
+    announcement: Option<impl Future<Output = anyhow::Result<()>>>,
+) -> anyhow::Result<()> {
+    if let Some(comment) = comment {
+        comment.await?;
+    }
+    if let Some(announcement) = announcement
+        && let Err(err) = announcement.await
+    {
+        log::warn!(
+            "failed to announce the major change {}: {err:?}",
+            issue.global_id()
+        );
+    }
+    Ok(())
+}
 
-    if new_proposal {
-        let topic_url = zulip_response.url(&zulip_req.recipient, &ctx.zulip);
-        let comment = format!(
+/// The comment posted on an accepted proposal, `None` if not configured.
+fn accepted_proposal_comment(
+    config: &MajorChangeConfig,
+    issue: &Issue,
+    topic_url: &str,
+) -> Option<String> {
+    let template = config.accepted_proposal_template.as_ref()?;
+    Some(template.render(issue.number, &issue.html_url, topic_url))
+}
+
+/// Hidden marker of the boilerplate comment of the proposals, identifying the
+/// previous ones regardless of their content (e.g. a topic url which changed).
+const PROPOSAL_COMMENT_MARKER: &str = "<!-- TRIAGEBOT_MCP_COMMENT -->";
+
+/// The comment posted on a newly opened proposal, `None` if disabled.
+fn new_proposal_comment(
+    config: &MajorChangeConfig,
+    issue: &Issue,
+    topic_url: &str,
+) -> Option<String> {
+    if !config.post_github_comment {
+        return None;
+    }
+    let comment = if let Some(template) = &config.new_proposal_template {
+        template.render(issue.number, &issue.html_url, topic_url)
+    } else {
+        format!(
             r"> [!IMPORTANT]
 > This issue is *not meant to be used for technical discussion*. There is a **Zulip [stream]** for that.
 > Use this issue to leave procedural comments, such as volunteering to review, indicating that you second the proposal (or third, etc), or raising a concern that you would like to be addressed.
@@ -427,26 +665,288 @@ See documentation at [https://forge.rust-lang.org](https://forge.rust-lang.org/c
 
 [stream]: {topic_url}",
             config.open_extra_text.as_deref().unwrap_or_default(),
-        );
-        issue
-            .post_comment(&ctx.github, &comment)
+        )
+    };
+    Some(format!("{PROPOSAL_COMMENT_MARKER}\n{comment}"))
+}
+
+/// Posts `content` in the topic of a proposal.
+///
+/// If `zulip_message_window` is configured and the last message of the bot in the
+/// topic was posted within that window, `content` is appended to it instead.
+async fn post_topic_message(
+    ctx: &Context,
+    config: &MajorChangeConfig,
+    recipient: Recipient<'_>,
+    content: &str,
+) -> anyhow::Result<MessageApiResponse> {
+    let Recipient::Stream { id: stream, topic } = recipient else {
+        unreachable!("proposals are discussed in streams");
+    };
+    // In dry-run mode, the messages aren't posted, so there is nothing to merge with.
+    let Some(window) = config
+        .zulip_message_window
+        .filter(|_| !crate::dry_run::is_enabled())
+    else {
+        return crate::zulip::MessageApiRequest { recipient, content }
+            .send(&ctx.zulip)
             .await
-            .context("post major change comment")?;
+            .context("zulip post failed");
+    };
+
+    let db = ctx.db.get().await;
+    let now = Utc::now();
+    if let Some(last) = get_last_topic_message(&db, stream, topic).await?
+        && let Some(merged) =
+            merged_topic_message(&last, content, now, Duration::seconds(window as i64))
+    {
+        crate::zulip::UpdateMessageApiRequest {
+            message_id: last.message_id,
+            topic: None,
+            propagate_mode: None,
+            content: Some(&merged),
+        }
+        .send(&ctx.zulip)
+        .await
+        .context("zulip message update failed")?;
+
+        let message_id = last.message_id;
+        set_last_topic_message(
+            &db,
+            stream,
+            topic,
+            &TopicMessage {
+                content: merged,
+                ..last
+            },
+        )
+        .await?;
+        return Ok(MessageApiResponse { message_id });
+    }
+
+    let response = crate::zulip::MessageApiRequest { recipient, content }
+        .send(&ctx.zulip)
+        .await
+        .context("zulip post failed")?;
+    set_last_topic_message(
+        &db,
+        stream,
+        topic,
+        &TopicMessage {
+            message_id: response.message_id,
+            content: content.to_string(),
+            posted_at: now,
+        },
+    )
+    .await?;
+    Ok(response)
+}
+
+/// Returns the content of `last` with `content` appended, if `last` was first
+/// posted within `window` and the result still fits in a single Zulip message.
+fn merged_topic_message(
+    last: &TopicMessage,
+    content: &str,
+    now: DateTime<Utc>,
+    window: Duration,
+) -> Option<String> {
+    const ZULIP_MAX_MESSAGE_LENGTH: usize = 10_000;
+
+    if now - last.posted_at > window {
+        return None;
     }
+    let merged = format!("{}\n\n{content}", last.content);
+    (merged.chars().count() <= ZULIP_MAX_MESSAGE_LENGTH).then_some(merged)
+}
 
+async fn announce_new_proposal(
+    ctx: &Context,
+    stream: u64,
+    issue: &Issue,
+    topic: &str,
+    topic_url: &str,
+) -> anyhow::Result<()> {
+    let content = announcement_message(issue, topic_url);
+    crate::zulip::MessageApiRequest {
+        recipient: Recipient::Stream { id: stream, topic },
+        content: &content,
+    }
+    .send(&ctx.zulip)
+    .await
+    .context("zulip announcement post failed")?;
     Ok(())
 }
 
-fn zulip_topic_from_issue(issue: &ZulipGitHubReference) -> String {
+/// The Zulip stream and ping of a proposal.
+#[derive(Debug, PartialEq, Eq)]
+struct ZulipRoute<'a> {
+    stream: u64,
+    ping: &'a [String],
+}
+
+/// Returns the Zulip stream and ping of a proposal with the given labels, the
+/// first label listed in `label_streams` wins, otherwise the default
+/// `zulip_stream` and `zulip_ping` are used.
+fn zulip_route<'a>(config: &'a MajorChangeConfig, labels: &[Label]) -> ZulipRoute<'a> {
+    let Some(route) = labels
+        .iter()
+        .find_map(|l| config.label_streams.get(&l.name))
+    else {
+        return ZulipRoute {
+            stream: config.zulip_stream,
+            ping: &config.zulip_ping,
+        };
+    };
+    ZulipRoute {
+        stream: route.zulip_stream,
+        ping: if route.zulip_ping.is_empty() {
+            &config.zulip_ping
+        } else {
+            &route.zulip_ping
+        },
+    }
+}
+
+/// Returns the first group of `zulip_ping` existing on Zulip, warning when
+/// falling back to the next ones.
+async fn resolve_zulip_ping<'a>(ctx: &Context, zulip_ping: &'a [String]) -> &'a str {
+    let first = zulip_ping.first().map(String::as_str).unwrap_or_default();
+    if zulip_ping.len() < 2 {
+        return first;
+    }
+
+    let groups = match ctx.zulip.get_zulip_user_groups().await {
+        Ok(groups) => groups,
+        Err(err) => {
+            log::warn!("unable to fetch the Zulip user groups, pinging `{first}`: {err:?}");
+            return first;
+        }
+    };
+    let groups: Vec<_> = groups
+        .iter()
+        .filter(|group| !group.deactivated)
+        .map(|group| group.name.as_str())
+        .collect();
+
+    match select_zulip_ping(zulip_ping, &groups) {
+        Some(ping) => {
+            if ping != first {
+                log::warn!("Zulip group `{first}` doesn't exist, falling back to `{ping}`");
+            }
+            ping
+        }
+        None => {
+            log::warn!("none of the Zulip groups {zulip_ping:?} exist, pinging `{first}`");
+            first
+        }
+    }
+}
+
+fn select_zulip_ping<'a>(zulip_ping: &'a [String], groups: &[&str]) -> Option<&'a str> {
+    zulip_ping
+        .iter()
+        .map(String::as_str)
+        .find(|ping| groups.contains(ping))
+}
+
+fn announcement_message(issue: &Issue, topic_url: &str) -> String {
+    format!(
+        "A new major change proposal has been opened: [{} #{}]({}). Discussion happens in [this topic]({topic_url}).",
+        issue.title, issue.number, issue.html_url,
+    )
+}
+
+/// Renders a configured template for the Zulip messages about `issue`.
+async fn render_template(
+    ctx: &Context,
+    config: &MajorChangeConfig,
+    template: &MajorChangeTemplate,
+    issue: &Issue,
+) -> anyhow::Result<String> {
+    Ok(template.render(
+        issue.number,
+        &issue.html_url,
+        &topic_url(ctx, config, issue).await?,
+    ))
+}
+
+/// The URL of the Zulip topic of the proposal.
+async fn topic_url(
+    ctx: &Context,
+    config: &MajorChangeConfig,
+    issue: &Issue,
+) -> anyhow::Result<String> {
+    let ProposalTopic { stream_id, topic } = proposal_topic(ctx, config, issue).await?;
+    Ok(Recipient::Stream {
+        id: stream_id,
+        topic: &topic,
+    }
+    .url(&ctx.zulip))
+}
+
+/// The Zulip stream and topic of the proposal: the recorded ones if any (the ones
+/// it was opened in, or renamed to e.g. on acceptance), otherwise the ones derived
+/// from its labels and title.
+async fn proposal_topic(
+    ctx: &Context,
+    config: &MajorChangeConfig,
+    issue: &Issue,
+) -> anyhow::Result<ProposalTopic> {
+    let repo = issue.repository().to_string();
+    if let Some(topic) = get_proposal_topic(&*ctx.db.get().await, &repo, issue.number).await? {
+        return Ok(topic);
+    }
+    Ok(ProposalTopic {
+        stream_id: zulip_route(config, issue.labels()).stream,
+        topic: zulip_topic_from_issue(
+            &issue.to_zulip_github_reference(),
+            config.zulip_topic_full_reference,
+            config.zulip_topic_max_length,
+        ),
+    })
+}
+
+/// Records the topic the proposal was moved to from `prev_topic`, along with the
+/// last message of the bot in it, so that later messages are posted (and merged)
+/// in the renamed topic.
+async fn record_proposal_topic(
+    ctx: &Context,
+    issue: &Issue,
+    prev_topic: &str,
+    topic: &ProposalTopic,
+) -> anyhow::Result<()> {
+    // In dry-run mode, the topic wasn't renamed.
+    if crate::dry_run::is_enabled() {
+        return Ok(());
+    }
+    let mut db = ctx.db.get().await;
+    set_proposal_topic(&db, &issue.repository().to_string(), issue.number, topic).await?;
+    rename_topic_messages(&mut db, topic.stream_id, prev_topic, &topic.topic).await
+}
+
+/// The default maximum length of a Zulip topic, as configured on zulipchat.com.
+pub(crate) const ZULIP_TOPIC_MAX_LENGTH: usize = 60;
+
+pub(crate) fn zulip_topic_from_issue(
+    issue: &ZulipGitHubReference,
+    full_reference: bool,
+    max_length: usize,
+) -> String {
     // Concatenate the issue title and the topic reference, truncating such that
-    // the overall length does not exceed 60 characters (a Zulip limitation).
-    let topic_ref = issue.zulip_topic_reference();
+    // the overall length does not exceed `max_length` characters (a Zulip limitation).
+    // The reference is never truncated, as it's needed to find the issue back from
+    // the topic, so the topic exceeds a limit too small to hold it.
+    let topic_ref = if full_reference {
+        issue.zulip_topic_full_reference()
+    } else {
+        issue.zulip_topic_reference()
+    };
     // Skip chars until the last characters that can be written:
-    // Maximum 60, minus the reference, minus the elipsis and the space
+    // Maximum length, minus the reference, minus the elipsis and the space
     let mut chars = issue
         .title
         .char_indices()
-        .skip(60 - topic_ref.chars().count() - 2);
+        .skip(max_length.saturating_sub(topic_ref.chars().count() + 2));
     match chars.next() {
         Some((len, _)) if chars.next().is_some() => {
             format!("{}… {}", &issue.title[..len], topic_ref)
@@ -455,6 +955,31 @@ fn zulip_topic_from_issue(issue: &ZulipGitHubReference) -> String {
     }
 }
 
+/// The topic an accepted proposal is moved to, prefixed with `[accepted]` and/or
+/// resolved (as done by the Zulip UI) per the config, `None` if neither is enabled.
+fn accepted_topic(config: &MajorChangeConfig, issue: &ZulipGitHubReference) -> Option<String> {
+    if !config.archive_accepted_topic && !config.resolve_accepted_topic {
+        return None;
+    }
+    let mut title = String::new();
+    if config.resolve_accepted_topic {
+        title.push_str(crate::zulip::RESOLVED_TOPIC_PREFIX);
+    }
+    if config.archive_accepted_topic {
+        title.push_str("[accepted] ");
+    }
+    title.push_str(&issue.title);
+    let issue = ZulipGitHubReference {
+        title,
+        ..issue.clone()
+    };
+    Some(zulip_topic_from_issue(
+        &issue,
+        config.zulip_topic_full_reference,
+        config.zulip_topic_max_length,
+    ))
+}
+
 #[derive(Debug)]
 enum SecondedLogicError {
     NotYetAcceptanceTime {
@@ -484,6 +1009,10 @@ enum SecondedLogicError {
     },
     NoMajorChangeConfig,
     UnderRfcBotFcp,
+    OnHold,
+    HoldReleased {
+        at: DateTime<Utc>,
+    },
 }
 
 impl std::error::Error for SecondedLogicError {}
@@ -517,6 +1046,10 @@ impl Display for SecondedLogicError {
             }
             SecondedLogicError::NoMajorChangeConfig => write!(f, "no `[major_change]` config"),
             SecondedLogicError::UnderRfcBotFcp => write!(f, "under rfcbot fcp"),
+            SecondedLogicError::OnHold => write!(f, "proposal is on hold"),
+            SecondedLogicError::HoldReleased { at } => {
+                write!(f, "hold released at {at}, after the job was scheduled")
+            }
         }
     }
 }
@@ -588,6 +1121,25 @@ async fn try_accept_mcp(
         });
     }
 
+    // Held proposals are only accepted once the hold is lifted, which
+    // schedules a new acceptance job. The jobs scheduled before that are
+    // stale and must not accept the proposal on the old schedule.
+    {
+        let db = ctx.db.get().await;
+        if get_hold(&db, &major_change.repo, major_change.issue)
+            .await?
+            .is_some()
+        {
+            anyhow::bail!(SecondedLogicError::OnHold);
+        }
+        if let Some(released_at) =
+            get_last_release(&db, &major_change.repo, major_change.issue).await?
+            && major_change.seconded_at < released_at
+        {
+            anyhow::bail!(SecondedLogicError::HoldReleased { at: released_at });
+        }
+    }
+
     let repo = ctx
         .github
         .repository(&major_change.repo)
@@ -598,11 +1150,25 @@ async fn try_accept_mcp(
         .await
         .context("failed to get triagebot configuration")?;
 
-    let config = config
+    let major_change_config = config
         .major_change
         .as_ref()
         .ok_or(SecondedLogicError::NoMajorChangeConfig)?;
 
+    crate::dry_run::scope(
+        config.dry_run.is_some(),
+        accept_mcp(ctx, major_change, &repo, major_change_config),
+    )
+    .await
+}
+
+/// Accepts the proposal, after checking that nothing changed since it was seconded.
+async fn accept_mcp(
+    ctx: &super::Context,
+    major_change: &MajorChangeSeconded,
+    repo: &Repository,
+    config: &MajorChangeConfig,
+) -> anyhow::Result<()> {
     let issue = repo
         .get_issue(&ctx.github, major_change.issue)
         .await
@@ -630,6 +1196,7 @@ async fn try_accept_mcp(
             .context("unable to get the timeline for the issue")?
             .into_stream(&ctx.octocrab);
         let mut timeline = std::pin::pin!(timeline);
+        let mut enabling_label_events = Vec::new();
 
         while let Some(event) = timeline.try_next().await? {
             use octocrab::models::Event;
@@ -647,8 +1214,8 @@ async fn try_accept_mcp(
             if event.event == Event::Unlabeled {
                 let label = event.label.context("unlabeled event without label")?;
 
-                if label.name == config.enabling_label {
-                    anyhow::bail!(SecondedLogicError::EnablingLabelRemoved { at });
+                if config.is_enabling_label(&label.name) {
+                    enabling_label_events.push((at, false, label.name));
                 } else if label.name == config.second_label {
                     anyhow::bail!(SecondedLogicError::SecondLabelRemoved { at });
                 }
@@ -657,6 +1224,8 @@ async fn try_accept_mcp(
 
                 if Some(&label.name) == config.concerns_label.as_ref() {
                     anyhow::bail!(SecondedLogicError::ConcernsLabelAdded { at })
+                } else if config.is_enabling_label(&label.name) {
+                    enabling_label_events.push((at, true, label.name));
                 }
             } else if event.event == Event::Closed || event.event == Event::ConvertToDraft {
                 anyhow::bail!(SecondedLogicError::IssueStateChanged {
@@ -666,12 +1235,17 @@ async fn try_accept_mcp(
                 });
             }
         }
+
+        if let Some(at) = enabling_labels_removed_at(config, &issue.labels, &enabling_label_events)
+        {
+            anyhow::bail!(SecondedLogicError::EnablingLabelRemoved { at });
+        }
     }
 
     {
         // Sanity checks to make sure the final state is still all right
 
-        if !issue.labels.iter().any(|l| l.name == config.enabling_label) {
+        if !config.has_enabling_label(&issue.labels) {
             anyhow::bail!(SecondedLogicError::EnablingLabelAbsent);
         }
 
@@ -790,6 +1364,949 @@ As the automated representative, I want to express gratitude to the author for t
     Ok(())
 }
 
+/// Returns when the proposal last lost all of its enabling labels, given the
+/// enabling labels added (`true`) or removed (`false`) since its second, oldest
+/// first, and its current labels.
+///
+/// Removing one of the enabling labels while another one remains doesn't
+/// disable the proposal.
+fn enabling_labels_removed_at(
+    config: &MajorChangeConfig,
+    labels: &[Label],
+    events: &[(DateTime<Utc>, bool, String)],
+) -> Option<DateTime<Utc>> {
+    // Replay the events backwards from the current labels.
+    let mut enabling: HashSet<&str> = labels
+        .iter()
+        .map(|l| l.name.as_str())
+        .filter(|name| config.is_enabling_label(name))
+        .collect();
+    for (at, added, label) in events.iter().rev() {
+        if *added {
+            enabling.remove(label.as_str());
+        } else {
+            if enabling.is_empty() {
+                return Some(*at);
+            }
+            enabling.insert(label);
+        }
+    }
+    None
+}
+
+const MAJOR_CHANGE_STALE_JOB_NAME: &str = "major_change_stale";
+
+/// Comments on (and optionally closes) proposals without any activity for a while,
+/// as configured by `[major-change.stale]`.
+///
+/// The repositories checked are the ones with a recorded proposal.
+pub(crate) struct MajorChangeStaleJob;
+
+#[async_trait]
+impl Job for MajorChangeStaleJob {
+    fn name(&self) -> &'static str {
+        MAJOR_CHANGE_STALE_JOB_NAME
+    }
+
+    async fn run(&self, ctx: &super::Context, _metadata: &serde_json::Value) -> anyhow::Result<()> {
+        let repositories = get_proposal_repositories(&*ctx.db.get().await).await?;
+
+        let now = Utc::now();
+
+        for repo in &repositories {
+            if let Err(err) = handle_stale_proposals(ctx, repo, now).await {
+                tracing::error!(
+                    "{}: failed to handle stale major changes of {repo}: {err:?}",
+                    self.name()
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+async fn handle_stale_proposals(
+    ctx: &super::Context,
+    repo: &str,
+    now: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let repo = ctx
+        .github
+        .repository(repo)
+        .await
+        .context("failed retrieving the repository informations")?;
+
+    let config = crate::config::get(&ctx.github, &repo)
+        .await
+        .context("failed to get triagebot configuration")?;
+
+    let dry_run = config.dry_run.is_some();
+    let Some(config) = config.major_change.as_ref() else {
+        return Ok(());
+    };
+    let Some(stale) = config.stale.as_ref() else {
+        return Ok(());
+    };
+
+    // The enabling labels are filtered here, as the included labels of the
+    // query must all be present and the proposals may have any of them.
+    let proposals = repo
+        .get_issues(
+            &ctx.github,
+            &Query {
+                filters: vec![("state", "open")],
+                include_labels: vec![&config.meeting_label],
+                exclude_labels: vec![],
+            },
+        )
+        .await
+        .context("unable to list the major change proposals")?;
+
+    let stale_after = Duration::days(stale.after_days.into());
+    for issue in proposals.iter().filter(|issue| {
+        config.has_enabling_label(&issue.labels)
+            && is_stale_proposal(issue, &config.second_label, stale_after, now)
+    }) {
+        let db = ctx.db.get().await;
+        if get_hold(&db, &repo.full_name, issue.number)
+            .await?
+            .is_some()
+        {
+            continue;
+        }
+        drop(db);
+
+        let handled =
+            crate::dry_run::scope(dry_run, handle_stale_proposal(ctx, config, stale, issue));
+        if let Err(err) = handled.await {
+            tracing::error!(
+                "failed to handle stale major change {}: {err:?}",
+                issue.global_id()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the proposal hasn't seen any activity for at least `stale_after`.
+///
+/// Seconded proposals are only waiting for their acceptance and are never stale.
+fn is_stale_proposal(
+    issue: &Issue,
+    second_label: &str,
+    stale_after: Duration,
+    now: DateTime<Utc>,
+) -> bool {
+    issue.is_open()
+        && !issue.labels.iter().any(|l| l.name == second_label)
+        && now - issue.updated_at >= stale_after
+}
+
+async fn handle_stale_proposal(
+    ctx: &super::Context,
+    config: &MajorChangeConfig,
+    stale: &MajorChangeStaleConfig,
+    issue: &Issue,
+) -> anyhow::Result<()> {
+    let message = match stale.action {
+        MajorChangeStaleAction::Warn => format!(
+            "This major change proposal hasn't seen any activity in the last {} days. \
+             It may be closed due to inactivity if nobody seconds it.",
+            stale.after_days
+        ),
+        MajorChangeStaleAction::Close => format!(
+            "This major change proposal is being closed due to inactivity, \
+             it hasn't seen any activity in the last {} days.",
+            stale.after_days
+        ),
+    };
+
+    issue
+        .post_comment(
+            &ctx.github,
+            &match stale.action {
+                MajorChangeStaleAction::Warn => message.clone(),
+                MajorChangeStaleAction::Close => format!(
+                    "{message}\n\n*If you want to continue working on this proposal, feel free to reopen it.*"
+                ),
+            },
+        )
+        .await
+        .context("unable to post the inactivity comment")?;
+
+    let zulip_topic = proposal_topic(ctx, config, issue).await?;
+    crate::zulip::MessageApiRequest {
+        recipient: Recipient::Stream {
+            id: zulip_topic.stream_id,
+            topic: &zulip_topic.topic,
+        },
+        content: &message,
+    }
+    .send(&ctx.zulip)
+    .await
+    .context("zulip post failed")?;
+
+    if stale.action == MajorChangeStaleAction::Close {
+        issue
+            .close(&ctx.github)
+            .await
+            .context("unable to close the issue")?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn enabling_labels_removal() {
+    let config = toml::from_str::<MajorChangeConfig>(
+        r#"
+        zulip_ping = "T-compiler"
+        second_label = "final-comment-period"
+        meeting_label = "to-announce"
+        zulip_stream = 1
+        enabling_label = ["major-change", "initiative"]
+        "#,
+    )
+    .unwrap();
+    let labels = |names: &[&str]| {
+        names
+            .iter()
+            .map(|name| Label {
+                name: name.to_string(),
+            })
+            .collect::<Vec<_>>()
+    };
+    let at = |hour: u32| {
+        "2025-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap() + Duration::hours(hour.into())
+    };
+
+    // Removing one of the two enabling labels keeps the proposal enabled.
+    assert_eq!(
+        enabling_labels_removed_at(
+            &config,
+            &labels(&["major-change"]),
+            &[(at(1), false, "initiative".to_string())]
+        ),
+        None
+    );
+    // Removing the last one disables it, even if it was added back since.
+    assert_eq!(
+        enabling_labels_removed_at(
+            &config,
+            &labels(&["major-change"]),
+            &[
+                (at(1), false, "major-change".to_string()),
+                (at(2), true, "major-change".to_string()),
+            ]
+        ),
+        Some(at(1))
+    );
+    // Swapping the enabling labels never leaves the proposal without one.
+    assert_eq!(
+        enabling_labels_removed_at(
+            &config,
+            &labels(&["initiative"]),
+            &[
+                (at(1), true, "initiative".to_string()),
+                (at(2), false, "major-change".to_string()),
+            ]
+        ),
+        None
+    );
+}
+
+#[tokio::test]
+async fn another_enabling_label_is_not_a_new_proposal() {
+    let ctx = crate::tests::offline_context();
+    let config = toml::from_str::<MajorChangeConfig>(
+        r#"
+        zulip_ping = "T-compiler"
+        second_label = "final-comment-period"
+        meeting_label = "to-announce"
+        zulip_stream = 1
+        enabling_label = ["major-change", "initiative"]
+        "#,
+    )
+    .unwrap();
+    let labeled = |labels: Vec<&str>| {
+        crate::tests::github::issues_event(
+            IssuesAction::Labeled {
+                label: Label {
+                    name: "initiative".to_string(),
+                },
+            },
+            crate::tests::github::issue().labels(labels).call(),
+        )
+    };
+
+    assert_eq!(
+        parse_input(&ctx, &labeled(vec!["initiative"]), Some(&config)).await,
+        Ok(Some(Invocation::NewProposal))
+    );
+    assert_eq!(
+        parse_input(
+            &ctx,
+            &labeled(vec!["major-change", "initiative"]),
+            Some(&config)
+        )
+        .await,
+        Ok(None)
+    );
+}
+
+#[test]
+fn announcement_message_links_the_topic() {
+    let issue = crate::tests::github::issue().number(123).call();
+    assert_eq!(
+        announcement_message(
+            &issue,
+            "https://rust-lang.zulipchat.com/#narrow/channel/1/topic/foo"
+        ),
+        "A new major change proposal has been opened: [Issue #123 #123](https://github.com/rust-lang/rust/pull/123). Discussion happens in [this topic](https://rust-lang.zulipchat.com/#narrow/channel/1/topic/foo)."
+    );
+}
+
+#[test]
+fn new_proposal_comment_can_be_disabled() {
+    let config = |extra: &str| {
+        toml::from_str::<MajorChangeConfig>(&format!(
+            r#"
+            zulip_ping = "T-compiler"
+            second_label = "final-comment-period"
+            meeting_label = "to-announce"
+            zulip_stream = 1
+            {extra}
+            "#
+        ))
+        .unwrap()
+    };
+    let issue = crate::tests::github::issue().number(123).call();
+    let topic_url = "https://rust-lang.zulipchat.com/#narrow/channel/1/topic/foo";
+
+    let comment = new_proposal_comment(&config(""), &issue, topic_url).unwrap();
+    assert!(comment.ends_with(&format!("[stream]: {topic_url}")));
+    assert_eq!(
+        new_proposal_comment(&config("post_github_comment = false"), &issue, topic_url),
+        None
+    );
+}
+
+#[test]
+fn accepted_proposal_comment_when_configured() {
+    let config = |extra: &str| {
+        toml::from_str::<MajorChangeConfig>(&format!(
+            r#"
+            zulip_ping = "T-compiler"
+            second_label = "final-comment-period"
+            meeting_label = "to-announce"
+            zulip_stream = 1
+            {extra}
+            "#
+        ))
+        .unwrap()
+    };
+    let issue = crate::tests::github::issue().number(123).call();
+    let topic_url = "https://rust-lang.zulipchat.com/#narrow/channel/1/topic/foo";
+
+    assert_eq!(
+        accepted_proposal_comment(
+            &config(r#"accepted_proposal_template = "MCP {issue} was accepted, see {stream}.""#),
+            &issue,
+            topic_url
+        ),
+        Some(format!("MCP 123 was accepted, see {topic_url}."))
+    );
+    assert_eq!(
+        accepted_proposal_comment(&config(""), &issue, topic_url),
+        None
+    );
+}
+
+#[tokio::test]
+async fn dry_run_makes_no_external_calls() {
+    use crate::tests::{unreachable_github_client, unreachable_zulip_client};
+
+    let github = unreachable_github_client();
+    let zulip = unreachable_zulip_client();
+    let issue = crate::tests::github::issue().number(123).call();
+
+    crate::dry_run::scope(true, async {
+        let res = crate::zulip::MessageApiRequest {
+            recipient: Recipient::Stream {
+                id: 1,
+                topic: "Proposal rust-lang/rust#123",
+            },
+            content: "A new proposal has been announced",
+        }
+        .send(&zulip)
+        .await
+        .unwrap();
+        crate::zulip::UpdateMessageApiRequest {
+            message_id: res.message_id,
+            topic: Some("Renamed proposal rust-lang/rust#123"),
+            propagate_mode: Some("change_all"),
+            content: None,
+        }
+        .send(&zulip)
+        .await
+        .unwrap();
+        AddReactionApiRequest {
+            message_id: res.message_id,
+            emoji_name: "tada",
+        }
+        .send(&zulip)
+        .await
+        .unwrap();
+        issue.post_comment(&github, "comment").await.unwrap();
+        issue
+            .add_labels(
+                &github,
+                vec![Label {
+                    name: "major-change".to_string(),
+                }],
+            )
+            .await
+            .unwrap();
+    })
+    .await;
+
+    // Outside of the dry-run, the same calls reach the (unreachable) GitHub API.
+    assert!(issue.post_comment(&github, "comment").await.is_err());
+}
+
+#[cfg(test)]
+fn handler_test_config() -> MajorChangeConfig {
+    toml::from_str::<MajorChangeConfig>(
+        r#"
+        zulip_ping = "T-compiler"
+        second_label = "final-comment-period"
+        meeting_label = "to-announce"
+        concerns_label = "concerns"
+        zulip_stream = 1
+        new_proposal_template = "MCP {issue}, see {stream}."
+        "#,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn dry_run_handler_makes_no_external_calls() {
+    crate::tests::run_db_test(|mut ctx| async {
+        ctx.use_unreachable_services();
+        let issue = crate::tests::github::issue()
+            .number(123)
+            .labels(vec!["major-change"])
+            .call();
+        let event = crate::tests::github::issues_event(
+            IssuesAction::Labeled {
+                label: Label {
+                    name: "major-change".to_string(),
+                },
+            },
+            issue,
+        );
+
+        let (result, actions) = crate::dry_run::record(handle_input(
+            ctx.handler_ctx(),
+            &handler_test_config(),
+            &event,
+            Invocation::NewProposal,
+        ))
+        .await;
+        result?;
+        assert_eq!(actions.len(), 3, "{actions:?}");
+        assert!(actions[0].starts_with(
+            r#"sent Zulip message to stream 1 (topic "Issue #123 #123"): "A new proposal has been announced"#
+        ));
+        assert_eq!(
+            actions[1],
+            r#"added labels ["to-announce"] to rust-lang/rust#123"#
+        );
+        assert!(actions[2].starts_with(r#"posted comment on rust-lang/rust#123: "MCP 123, see "#));
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn messages_follow_the_recorded_topic() {
+    crate::tests::run_db_test(|mut ctx| async {
+        ctx.use_unreachable_services();
+        set_proposal_topic(
+            ctx.db_client(),
+            "rust-lang/rust",
+            123,
+            &ProposalTopic {
+                stream_id: 2,
+                topic: "[accepted] Issue #123 #123".to_string(),
+            },
+        )
+        .await?;
+        // The proposal was opened in stream 2, but its labels now route it to stream 3.
+        let issue = crate::tests::github::issue()
+            .number(123)
+            .labels(vec!["major-change", "concerns", "T-lang"])
+            .call();
+        let event = crate::tests::github::issues_event(
+            IssuesAction::Labeled {
+                label: Label {
+                    name: "concerns".to_string(),
+                },
+            },
+            issue,
+        );
+
+        let (result, actions) = crate::dry_run::record(handle_input(
+            ctx.handler_ctx(),
+            &MajorChangeConfig {
+                label_streams: std::collections::HashMap::from([(
+                    "T-lang".to_string(),
+                    crate::config::MajorChangeLabelStreamConfig {
+                        zulip_stream: 3,
+                        zulip_ping: vec![],
+                    },
+                )]),
+                ..handler_test_config()
+            },
+            &event,
+            Invocation::ConcernsAdded,
+        ))
+        .await;
+        result?;
+        assert_eq!(actions.len(), 1, "{actions:?}");
+        assert!(
+            actions[0].starts_with(
+                r#"sent Zulip message to stream 2 (topic "[accepted] Issue #123 #123")"#
+            ),
+            "{actions:?}"
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn acceptance_comment_links_the_renamed_topic() {
+    crate::tests::run_db_test(|mut ctx| async {
+        ctx.use_unreachable_services();
+        let config = MajorChangeConfig {
+            archive_accepted_topic: true,
+            accepted_proposal_template: Some(
+                MajorChangeTemplate::try_from("Accepted, see {stream}.".to_string()).unwrap(),
+            ),
+            ..handler_test_config()
+        };
+        let issue = crate::tests::github::issue()
+            .number(123)
+            .labels(vec!["major-change", "major-change-accepted"])
+            .call();
+        let event = crate::tests::github::issues_event(
+            IssuesAction::Labeled {
+                label: Label {
+                    name: "major-change-accepted".to_string(),
+                },
+            },
+            issue,
+        );
+
+        let (result, actions) = crate::dry_run::record(handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &event,
+            Invocation::AcceptedProposal,
+        ))
+        .await;
+        result?;
+        let comment = actions
+            .iter()
+            .find(|action| action.starts_with("posted comment on rust-lang/rust#123"))
+            .unwrap();
+        assert!(
+            comment.contains("/topic/.5Baccepted.5D.20Issue.20.23123.20.23123/"),
+            "{comment}"
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn zulip_failure_leaves_label_unapplied() {
+    use std::cell::Cell;
+
+    let issue = crate::tests::github::issue().number(123).call();
+    let labeled = Cell::new(false);
+    let label = || {
+        Some(async {
+            labeled.set(true);
+            Ok(())
+        })
+    };
+
+    let res = notify_then_label(
+        &issue,
+        async { Err(anyhow::anyhow!("zulip post failed")) },
+        label(),
+    )
+    .await;
+    assert!(res.is_err());
+    assert!(!labeled.get());
+
+    let res = notify_then_label(
+        &issue,
+        async { Ok(MessageApiResponse { message_id: 1 }) },
+        label(),
+    )
+    .await;
+    assert_eq!(res.unwrap().message_id, 1);
+    assert!(labeled.get());
+}
+
+#[tokio::test]
+async fn announcement_failure_keeps_the_comment() {
+    use std::cell::Cell;
+
+    let issue = crate::tests::github::issue().number(123).call();
+    let commented = Cell::new(false);
+    let comment = Some(async {
+        commented.set(true);
+        Ok(())
+    });
+
+    let res = comment_then_announce(
+        &issue,
+        comment,
+        Some(async { Err(anyhow::anyhow!("zulip announcement post failed")) }),
+    )
+    .await;
+    assert!(res.is_ok());
+    assert!(commented.get());
+}
+
+#[test]
+fn accepted_topic_update_request() {
+    let config = |extra: &str| {
+        toml::from_str::<MajorChangeConfig>(&format!(
+            r#"
+            zulip_ping = "T-compiler"
+            second_label = "final-comment-period"
+            meeting_label = "to-announce"
+            zulip_stream = 1
+            {extra}
+            "#
+        ))
+        .unwrap()
+    };
+    let issue = zulip_reference("rust-lang", "rust", 123, "Proposal");
+
+    assert_eq!(accepted_topic(&config(""), &issue), None);
+
+    let topic = accepted_topic(
+        &config("archive_accepted_topic = true\nresolve_accepted_topic = true"),
+        &issue,
+    )
+    .unwrap();
+    assert_eq!(topic, "✔ [accepted] Proposal #123");
+    assert_eq!(
+        crate::zulip::UpdateMessageApiRequest::rename_topic(42, &topic),
+        crate::zulip::UpdateMessageApiRequest {
+            message_id: 42,
+            topic: Some("✔ [accepted] Proposal #123"),
+            propagate_mode: Some("change_all"),
+            content: None,
+        }
+    );
+    assert_eq!(
+        accepted_topic(&config("resolve_accepted_topic = true"), &issue).as_deref(),
+        Some("✔ Proposal #123")
+    );
+
+    // The prefixes count in the topic length limit, the reference is kept.
+    let long = zulip_reference("rust-lang", "rust", 123, &"a".repeat(60));
+    let topic = accepted_topic(&config("archive_accepted_topic = true"), &long).unwrap();
+    assert!(topic.starts_with("[accepted] aaa"));
+    assert!(topic.ends_with("… #123"));
+    assert_eq!(topic.chars().count(), 60);
+}
+
+#[test]
+fn zulip_route_by_label() {
+    let config = toml::from_str::<MajorChangeConfig>(
+        r#"
+        zulip_ping = "T-compiler"
+        second_label = "final-comment-period"
+        meeting_label = "to-announce"
+        zulip_stream = 1
+
+        [label_streams.T-lang]
+        zulip_stream = 2
+        zulip_ping = "T-lang"
+
+        [label_streams.T-types]
+        zulip_stream = 3
+        "#,
+    )
+    .unwrap();
+    let labels = |names: &[&str]| {
+        names
+            .iter()
+            .map(|name| Label {
+                name: name.to_string(),
+            })
+            .collect::<Vec<_>>()
+    };
+    let compiler = ["T-compiler".to_string()];
+    let lang = ["T-lang".to_string()];
+
+    assert_eq!(
+        zulip_route(&config, &labels(&["major-change", "T-lang"])),
+        ZulipRoute {
+            stream: 2,
+            ping: &lang
+        }
+    );
+    // Without a ping of its own, the route pings the default group
+    assert_eq!(
+        zulip_route(&config, &labels(&["T-types"])),
+        ZulipRoute {
+            stream: 3,
+            ping: &compiler
+        }
+    );
+    // The first label of the issue wins
+    assert_eq!(
+        zulip_route(&config, &labels(&["T-types", "T-lang"])).stream,
+        3
+    );
+    assert_eq!(
+        zulip_route(&config, &labels(&["major-change", "T-libs"])),
+        ZulipRoute {
+            stream: 1,
+            ping: &compiler
+        }
+    );
+}
+
+#[test]
+fn zulip_ping_fallbacks() {
+    let zulip_ping = vec!["T-compiler".to_string(), "t-compiler".to_string()];
+    assert_eq!(
+        select_zulip_ping(&zulip_ping, &["T-compiler", "t-compiler"]),
+        Some("T-compiler")
+    );
+    assert_eq!(
+        select_zulip_ping(&zulip_ping, &["T-lang", "t-compiler"]),
+        Some("t-compiler")
+    );
+    assert_eq!(select_zulip_ping(&zulip_ping, &["T-lang"]), None);
+}
+
+#[cfg(test)]
+fn zulip_reference(org: &str, repo: &str, number: u64, title: &str) -> ZulipGitHubReference {
+    ZulipGitHubReference {
+        number,
+        title: title.to_string(),
+        repository: crate::github::IssueRepository {
+            organization: org.to_string(),
+            repository: repo.to_string(),
+        },
+    }
+}
+
+#[test]
+fn zulip_topic_round_trip() {
+    let long_title =
+        "This is a very long title which will need to be truncated to fit in a Zulip topic";
+    for issue in [
+        zulip_reference("rust-lang", "rust", 123, "Short title"),
+        zulip_reference("rust-lang", "rust", 123456, long_title),
+        zulip_reference("rust-lang", "compiler-team", 42, "Title with #123 inside"),
+        zulip_reference("rust-lang", "compiler-team", 42, long_title),
+        zulip_reference(
+            "rust-lang-test",
+            "triagebot-test",
+            7,
+            "Ends with a number 1",
+        ),
+        zulip_reference("rust-lang-test", "triagebot-test", 7, long_title),
+        zulip_reference(
+            "rust-lang",
+            "rust",
+            9,
+            "日本語のタイトルはとても長くなることがありますのでここで切り詰める必要があります",
+        ),
+    ] {
+        for full_reference in [false, true] {
+            let topic = zulip_topic_from_issue(&issue, full_reference, ZULIP_TOPIC_MAX_LENGTH);
+            assert_eq!(
+                ZulipGitHubReference::issue_from_zulip_topic(&topic),
+                Some((issue.repository.clone(), issue.number)),
+                "topic: {topic}"
+            );
+        }
+    }
+}
+
+#[test]
+fn zulip_topic_reference_formats() {
+    let short = zulip_reference("rust-lang", "compiler-team", 42, "Short title");
+    assert_eq!(
+        zulip_topic_from_issue(&short, false, ZULIP_TOPIC_MAX_LENGTH),
+        "Short title compiler-team#42"
+    );
+    assert_eq!(
+        zulip_topic_from_issue(&short, true, ZULIP_TOPIC_MAX_LENGTH),
+        "Short title rust-lang/compiler-team#42"
+    );
+
+    // The title is truncated further to make room for the longer reference
+    let long_title = "Make the compiler emit way more detailed diagnostics for everything";
+    let long = zulip_reference("rust-lang", "compiler-team", 42, long_title);
+    let topic = zulip_topic_from_issue(&long, false, ZULIP_TOPIC_MAX_LENGTH);
+    assert_eq!(
+        topic,
+        "Make the compiler emit way more detailed d… compiler-team#42"
+    );
+    assert_eq!(topic.chars().count(), 60);
+    let topic = zulip_topic_from_issue(&long, true, ZULIP_TOPIC_MAX_LENGTH);
+    assert_eq!(
+        topic,
+        "Make the compiler emit way more … rust-lang/compiler-team#42"
+    );
+    assert_eq!(topic.chars().count(), 60);
+}
+
+#[test]
+fn zulip_topic_configured_max_length() {
+    let long_title = "Make the compiler emit way more detailed diagnostics for everything";
+    let long = zulip_reference("rust-lang", "compiler-team", 42, long_title);
+
+    let topic = zulip_topic_from_issue(&long, false, 40);
+    assert_eq!(topic, "Make the compiler emit… compiler-team#42");
+    assert_eq!(topic.chars().count(), 40);
+
+    // The whole title fits in a larger limit
+    assert_eq!(
+        zulip_topic_from_issue(&long, false, 100),
+        format!("{long_title} compiler-team#42")
+    );
+
+    // A very small limit only leaves room for a couple of characters
+    let topic = zulip_topic_from_issue(&long, false, 20);
+    assert_eq!(topic, "Ma… compiler-team#42");
+    assert_eq!(topic.chars().count(), 20);
+
+    // A limit smaller than the reference keeps the whole reference
+    for max_length in [0, 5, 17] {
+        let topic = zulip_topic_from_issue(&long, false, max_length);
+        assert_eq!(topic, "… compiler-team#42");
+        assert_eq!(
+            ZulipGitHubReference::issue_from_zulip_topic(&topic),
+            Some((long.repository.clone(), 42))
+        );
+    }
+}
+
+#[test]
+fn zulip_topic_without_reference() {
+    assert_eq!(
+        ZulipGitHubReference::issue_from_zulip_topic("random topic"),
+        None
+    );
+    assert_eq!(
+        ZulipGitHubReference::issue_from_zulip_topic("random topic #"),
+        None
+    );
+    assert_eq!(
+        ZulipGitHubReference::issue_from_zulip_topic("random topic #12a"),
+        None
+    );
+    assert_eq!(ZulipGitHubReference::issue_from_zulip_topic("#123"), None);
+}
+
+#[test]
+fn stale_proposals() {
+    let now = Utc::now();
+    let stale_after = Duration::days(30);
+
+    let mut active = crate::tests::github::issue()
+        .labels(vec!["major-change", "to-announce"])
+        .call();
+    active.updated_at = now - Duration::days(2);
+    assert!(!is_stale_proposal(
+        &active,
+        "final-comment-period",
+        stale_after,
+        now
+    ));
+
+    let mut inactive = crate::tests::github::issue()
+        .labels(vec!["major-change", "to-announce"])
+        .call();
+    inactive.updated_at = now - Duration::days(45);
+    assert!(is_stale_proposal(
+        &inactive,
+        "final-comment-period",
+        stale_after,
+        now
+    ));
+
+    let mut seconded = crate::tests::github::issue()
+        .labels(vec!["major-change", "to-announce", "final-comment-period"])
+        .call();
+    seconded.updated_at = now - Duration::days(45);
+    assert!(!is_stale_proposal(
+        &seconded,
+        "final-comment-period",
+        stale_after,
+        now
+    ));
+}
+
+#[test]
+fn topic_message_merging() {
+    let posted_at: DateTime<Utc> = "2025-01-01T10:00:00Z".parse().unwrap();
+    let last = TopicMessage {
+        message_id: 42,
+        content: "Proposal seconded.".to_string(),
+        posted_at,
+    };
+    let window = Duration::minutes(5);
+
+    // Within the window, the message is appended to the previous one
+    assert_eq!(
+        merged_topic_message(
+            &last,
+            "Concern added.",
+            posted_at + Duration::minutes(2),
+            window
+        ),
+        Some("Proposal seconded.\n\nConcern added.".to_string())
+    );
+    // Outside of it, a new message is posted
+    assert_eq!(
+        merged_topic_message(
+            &last,
+            "Concern added.",
+            posted_at + Duration::minutes(6),
+            window
+        ),
+        None
+    );
+    // As well as when the merged message would be too long
+    assert_eq!(
+        merged_topic_message(
+            &last,
+            &"a".repeat(10_000),
+            posted_at + Duration::minutes(2),
+            window
+        ),
+        None
+    );
+}
+
 #[test]
 fn major_change_queue_serialize() {
     let original = MajorChangeSeconded {
@@ -805,3 +2322,85 @@ fn major_change_queue_serialize() {
 
     assert_eq!(original, deserialized);
 }
+
+#[tokio::test]
+async fn held_proposal_is_not_accepted() {
+    crate::tests::run_db_test(|ctx| async {
+        let now = Utc::now();
+        crate::db::major_change_holds::hold(
+            ctx.db_client(),
+            "rust-lang/compiler-team",
+            1245,
+            &crate::db::major_change_holds::MajorChangeHold {
+                held_by: "Urgau".to_string(),
+                held_at: now - Duration::days(2),
+            },
+        )
+        .await?;
+
+        let err = try_accept_mcp(
+            ctx.handler_ctx(),
+            &MajorChangeSeconded {
+                repo: "rust-lang/compiler-team".to_string(),
+                issue: 1245,
+                seconded_at: now - Duration::days(11),
+                accept_at: now - Duration::days(1),
+            },
+            now,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<SecondedLogicError>(),
+            Some(SecondedLogicError::OnHold)
+        ));
+
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn job_scheduled_before_unhold_is_not_accepted() {
+    crate::tests::run_db_test(|ctx| async {
+        let now = Utc::now();
+        crate::db::major_change_holds::hold(
+            ctx.db_client(),
+            "rust-lang/compiler-team",
+            1245,
+            &crate::db::major_change_holds::MajorChangeHold {
+                held_by: "Urgau".to_string(),
+                held_at: now - Duration::days(5),
+            },
+        )
+        .await?;
+        crate::db::major_change_holds::unhold(
+            ctx.db_client(),
+            "rust-lang/compiler-team",
+            1245,
+            now - Duration::days(2),
+        )
+        .await?;
+
+        // The job queued when the proposal was first seconded
+        let err = try_accept_mcp(
+            ctx.handler_ctx(),
+            &MajorChangeSeconded {
+                repo: "rust-lang/compiler-team".to_string(),
+                issue: 1245,
+                seconded_at: now - Duration::days(11),
+                accept_at: now - Duration::days(1),
+            },
+            now,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<SecondedLogicError>(),
+            Some(SecondedLogicError::HoldReleased { .. })
+        ));
+
+        Ok(ctx)
+    })
+    .await;
+}
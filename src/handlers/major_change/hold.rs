@@ -0,0 +1,114 @@
+//! Handles the `@rustbot hold` and `@rustbot unhold` commands, pausing and
+//! resuming the approval countdown of seconded major changes.
+//!
+//! Held proposals are skipped by the automatic acceptance job. Lifting the hold
+//! restarts the waiting period, the acceptance jobs scheduled before it are
+//! then rejected.
+
+use anyhow::Context as _;
+use chrono::Utc;
+use parser::command::hold::HoldCommand;
+
+use super::{handle, schedule_acceptance_job};
+use crate::config::MajorChangeConfig;
+use crate::db::major_change_holds::{self, MajorChangeHold};
+use crate::errors::user_error;
+use crate::github::Event;
+use crate::handlers::Context;
+
+pub(in crate::handlers) async fn handle_command(
+    ctx: &Context,
+    config: &MajorChangeConfig,
+    event: &Event,
+    cmd: HoldCommand,
+) -> anyhow::Result<()> {
+    let issue = event.issue().unwrap();
+
+    let is_team_member = ctx
+        .team
+        .is_team_member(&event.user().login)
+        .await
+        .ok()
+        .unwrap_or(false);
+
+    if !is_team_member {
+        return user_error!("Only team members can hold or unhold proposals.");
+    }
+
+    let repo = issue.repository().full_repo_name();
+    let issue_number = issue.number;
+    let issue_url = &issue.html_url;
+    let bot_username = &ctx.username;
+
+    let zulip_msg = match cmd {
+        HoldCommand::Hold => {
+            if !issue.labels().iter().any(|l| l.name == config.second_label) {
+                return user_error!("Only seconded proposals can be put on hold.");
+            }
+
+            let held = if crate::dry_run::skip(format_args!("put {repo}#{issue_number} on hold")) {
+                major_change_holds::get_hold(&*ctx.db.get().await, &repo, issue_number)
+                    .await?
+                    .is_none()
+            } else {
+                major_change_holds::hold(
+                    &*ctx.db.get().await,
+                    &repo,
+                    issue_number,
+                    &MajorChangeHold {
+                        held_by: event.user().login.clone(),
+                        held_at: Utc::now(),
+                    },
+                )
+                .await?
+            };
+            if !held {
+                return user_error!("This proposal is already on hold.");
+            }
+
+            format!(
+                "Proposal [#{issue_number}]({issue_url}) has been put on hold by @**{}**, it won't be approved until the hold is lifted with `@{bot_username} unhold` in the GitHub thread.",
+                event.user().login,
+            )
+        }
+        HoldCommand::Unhold => {
+            let held =
+                if crate::dry_run::skip(format_args!("lifted the hold on {repo}#{issue_number}")) {
+                    major_change_holds::get_hold(&*ctx.db.get().await, &repo, issue_number).await?
+                } else {
+                    major_change_holds::unhold(
+                        &*ctx.db.get().await,
+                        &repo,
+                        issue_number,
+                        Utc::now(),
+                    )
+                    .await?
+                };
+            if held.is_none() {
+                return user_error!("This proposal is not on hold.");
+            }
+
+            let has_concerns = config
+                .concerns_label
+                .as_ref()
+                .is_some_and(|concerns_label| {
+                    issue.labels().iter().any(|l| &l.name == concerns_label)
+                });
+            let seconded = issue.labels().iter().any(|l| l.name == config.second_label);
+
+            if seconded && !has_concerns {
+                schedule_acceptance_job(ctx, config, issue).await?;
+                format!(
+                    "The hold on proposal [#{issue_number}]({issue_url}) has been lifted, it will be approved in {} days if no objections are raised.",
+                    config.waiting_period,
+                )
+            } else {
+                format!("The hold on proposal [#{issue_number}]({issue_url}) has been lifted.")
+            }
+        }
+    };
+
+    handle(ctx, config, issue, zulip_msg, None, false)
+        .await
+        .context("unable to process hold command")
+}
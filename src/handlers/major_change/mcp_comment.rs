@@ -0,0 +1,165 @@
+//! Handles the `@rustbot mcp-comment` command, re-posting the boilerplate
+//! comment of a proposal, e.g. when it was deleted or missed.
+//!
+//! The comment is the same as the one posted on newly opened proposals, no
+//! message is sent on Zulip. With `minimize_superseded_comment`, the previous
+//! proposal comments of the bot are minimized as outdated.
+
+use anyhow::Context as _;
+use parser::command::mcp_comment::McpCommentCommand;
+
+use super::{PROPOSAL_COMMENT_MARKER, new_proposal_comment, topic_url};
+use crate::config::MajorChangeConfig;
+use crate::errors::user_error;
+use crate::github::{Comment, Event, Issue, ReportedContentClassifiers};
+use crate::handlers::Context;
+
+pub(in crate::handlers) async fn handle_command(
+    ctx: &Context,
+    config: &MajorChangeConfig,
+    event: &Event,
+    _cmd: McpCommentCommand,
+) -> anyhow::Result<()> {
+    let issue = event.issue().unwrap();
+
+    let is_team_member = ctx
+        .team
+        .is_team_member(&event.user().login)
+        .await
+        .ok()
+        .unwrap_or(false);
+
+    if !is_team_member {
+        return user_error!("Only team members can re-post the proposal comment.");
+    }
+
+    let topic_url = topic_url(ctx, config, issue).await?;
+    let comment = match proposal_comment(config, issue, &topic_url) {
+        Ok(comment) => comment,
+        Err(err) => return user_error!(err),
+    };
+    let superseded = if config.minimize_superseded_comment {
+        issue
+            .get_first100_comments(&ctx.github)
+            .await
+            .context("failed to list the comments of the proposal")?
+    } else {
+        Vec::new()
+    };
+    issue
+        .post_comment(&ctx.github, &comment)
+        .await
+        .context("failed to re-post the major change comment")?;
+
+    for previous in superseded
+        .iter()
+        .filter(|c| is_superseded(c, &ctx.username))
+    {
+        issue
+            .hide_comment(
+                &ctx.github,
+                &previous.node_id,
+                ReportedContentClassifiers::Outdated,
+            )
+            .await
+            .context("failed to minimize the previous major change comment")?;
+    }
+    Ok(())
+}
+
+/// The start of the default proposal comments posted before they had
+/// a [`PROPOSAL_COMMENT_MARKER`].
+const LEGACY_PROPOSAL_COMMENT_PREFIX: &str =
+    "> [!IMPORTANT]\n> This issue is *not meant to be used for technical discussion*.";
+
+/// Whether `previous` is an earlier proposal comment posted by the bot.
+fn is_superseded(previous: &Comment, bot_login: &str) -> bool {
+    previous.user.login.eq_ignore_ascii_case(bot_login)
+        && (previous.body.contains(PROPOSAL_COMMENT_MARKER)
+            || previous.body.starts_with(LEGACY_PROPOSAL_COMMENT_PREFIX))
+}
+
+/// The boilerplate comment of the proposal, or the reason it can't be posted.
+fn proposal_comment(
+    config: &MajorChangeConfig,
+    issue: &Issue,
+    topic_url: &str,
+) -> Result<String, String> {
+    if !config.has_enabling_label(issue.labels()) {
+        return Err(format!(
+            "This issue is not a proposal; it lacks the {} label.",
+            config.enabling_labels_description()
+        ));
+    }
+    new_proposal_comment(config, issue, topic_url)
+        .ok_or_else(|| "The proposal comment is disabled in this repository.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::github::issue;
+
+    fn config(extra: &str) -> MajorChangeConfig {
+        toml::from_str(&format!(
+            r#"
+            zulip_ping = "T-compiler"
+            second_label = "final-comment-period"
+            meeting_label = "to-announce"
+            zulip_stream = 1
+            {extra}
+            "#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn standard_comment() {
+        let topic_url = "https://rust-lang.zulipchat.com/#narrow/channel/1/topic/foo";
+        let proposal = issue().number(123).labels(vec!["major-change"]).call();
+
+        let comment = proposal_comment(&config(""), &proposal, topic_url).unwrap();
+        assert_eq!(
+            Some(comment.as_str()),
+            new_proposal_comment(&config(""), &proposal, topic_url).as_deref()
+        );
+        assert!(comment.starts_with(&format!("{PROPOSAL_COMMENT_MARKER}\n> [!IMPORTANT]")));
+        assert!(comment.ends_with(&format!("[stream]: {topic_url}")));
+
+        assert!(
+            proposal_comment(&config("post_github_comment = false"), &proposal, topic_url).is_err()
+        );
+        assert!(proposal_comment(&config(""), &issue().number(123).call(), topic_url).is_err());
+    }
+
+    #[test]
+    fn superseded_comments() {
+        let comment = |login: &str, body: &str| -> Comment {
+            serde_json::from_value(serde_json::json!({
+                "id": 1,
+                "node_id": "IC_kwDOArhJjM6",
+                "body": body,
+                "html_url": "https://github.com/rust-lang/compiler-team/issues/123#issuecomment-1",
+                "user": { "login": login, "id": 1, "type": "Bot" },
+                "author_association": "NONE",
+            }))
+            .unwrap()
+        };
+        let body = proposal_comment(
+            &config(""),
+            &issue().number(123).labels(vec!["major-change"]).call(),
+            "https://rust-lang.zulipchat.com/#narrow/channel/1/topic/new",
+        )
+        .unwrap();
+        let legacy = "> [!IMPORTANT]\n> This issue is *not meant to be used for technical discussion*.\n\n[stream]: https://rust-lang.zulipchat.com/#narrow/channel/1/topic/old";
+
+        assert!(is_superseded(&comment("rustbot", &body), "rustbot"));
+        // The previous comments linked to an old topic are superseded too
+        assert!(is_superseded(&comment("rustbot", legacy), "rustbot"));
+        assert!(!is_superseded(&comment("someone", &body), "rustbot"));
+        assert!(!is_superseded(
+            &comment("rustbot", "@rustbot second"),
+            "rustbot"
+        ));
+    }
+}
@@ -1,11 +1,14 @@
 //! Purpose: When opening a PR, or pushing new changes, check for any paths
 //! that are in the `mentions` config, and add a comment that pings the listed
 //! interested people.
+//!
+//! People who deferred the pings of the PR (with `@rustbot defer`) are not
+//! pinged until the deferral expires.
 
 use crate::{
     config::{MentionsConfig, MentionsEntryConfig, MentionsEntryType},
-    db::issue_data::IssueData,
-    github::{IssuesAction, IssuesEvent},
+    db::{issue_data::IssueData, review_deferrals},
+    github::{FileDiff, Issue, IssuesAction, IssuesEvent},
     handlers::Context,
 };
 use anyhow::Context as _;
@@ -81,14 +84,32 @@ pub(super) async fn parse_input(
             .map(|filename| Path::new(filename)),
     );
 
-    let to_mention: Vec<_> = config
+    let to_mention = to_mention(ctx, config, &event.issue, &modified_paths, modified_files).await;
+
+    if to_mention.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(MentionsInput { to_mention }))
+    }
+}
+
+/// Returns the entries of the config matching the modified files of `issue`,
+/// with the people to cc, other than its author and those who deferred the pings.
+async fn to_mention(
+    ctx: &Context,
+    config: &MentionsConfig,
+    issue: &Issue,
+    modified_paths: &[&Path],
+    modified_files: &[FileDiff],
+) -> Vec<ToMention> {
+    let mut to_mention: Vec<_> = config
         .entries
         .iter()
         .filter_map(|(entry, MentionsEntryConfig { cc, type_, .. })| {
             let relevant_file_paths: Vec<PathBuf> = match type_ {
                 MentionsEntryType::Filename => {
                     // Only mention matching paths.
-                    modified_paths_matches(&modified_paths, entry)
+                    modified_paths_matches(modified_paths, entry)
                 }
                 MentionsEntryType::Content => {
                     // Only mentions byte-for-byte matching content inside the patch.
@@ -104,8 +125,7 @@ pub(super) async fn parse_input(
             let relevant_ccs = cc
                 .iter()
                 .filter(|cc| {
-                    cc.trim_start_matches('@').to_lowercase()
-                        != event.issue.user.login.to_lowercase()
+                    cc.trim_start_matches('@').to_lowercase() != issue.user.login.to_lowercase()
                 })
                 .cloned()
                 .collect::<Vec<_>>();
@@ -125,11 +145,34 @@ pub(super) async fn parse_input(
         })
         .collect();
 
-    if to_mention.is_empty() {
-        Ok(None)
-    } else {
-        Ok(Some(MentionsInput { to_mention }))
+    // Only look for deferrals when someone would be pinged
+    if to_mention.iter().all(|m| m.relevant_ccs.is_empty()) {
+        return to_mention;
     }
+
+    let deferred = review_deferrals::deferred_users(
+        &*ctx.db.get().await,
+        &issue.repository().to_string(),
+        issue.number,
+        chrono::Utc::now(),
+    )
+    .await
+    .unwrap_or_else(|err| {
+        tracing::error!("failed to get the deferred users for mentions handler: {err:?}");
+        Default::default()
+    });
+
+    // Filter users who deferred the pings from the cc list, skipping the
+    // entries left with nobody to cc.
+    to_mention.retain_mut(|m| {
+        if m.relevant_ccs.is_empty() {
+            return true;
+        }
+        m.relevant_ccs
+            .retain(|cc| !deferred.contains(&cc.trim_start_matches('@').to_lowercase()));
+        !m.relevant_ccs.is_empty()
+    });
+    to_mention
 }
 
 pub(super) async fn handle_input(
@@ -243,6 +286,89 @@ fn patch_adds(patch: &str, needle: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tests::github::{pull_request, user};
+    use crate::tests::{offline_context, run_db_test};
+
+    fn file(filename: &str) -> FileDiff {
+        FileDiff {
+            filename: filename.to_string(),
+            previous_filename: None,
+            additions: 0,
+            deletions: 0,
+            patch: String::new(),
+        }
+    }
+
+    fn mentions_config() -> MentionsConfig {
+        toml::from_str(
+            r#"
+            ["compiler/"]
+            cc = ["@alice", "@Bob"]
+
+            ["library/"]
+            cc = ["@bob"]
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn deferred_users_are_not_pinged() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let now = chrono::Utc::now();
+            review_deferrals::defer_pings(
+                &db,
+                "rust-lang/rust",
+                1,
+                "bob",
+                now + chrono::Duration::days(1),
+            )
+            .await?;
+            review_deferrals::defer_pings(
+                &db,
+                "rust-lang/rust",
+                1,
+                "alice",
+                now - chrono::Duration::days(1),
+            )
+            .await?;
+
+            let pr = pull_request().number(1).author(user("carol", 3)).call();
+            let files = [file("compiler/lib.rs"), file("library/lib.rs")];
+            let paths = files
+                .iter()
+                .map(|f| Path::new(&f.filename))
+                .collect::<Vec<_>>();
+            let to_mention =
+                to_mention(ctx.handler_ctx(), &mentions_config(), &pr, &paths, &files).await;
+
+            // The expired deferral of alice doesn't apply anymore, and the
+            // `library/` entry is left without anyone to cc.
+            assert_eq!(to_mention.len(), 1);
+            assert_eq!(to_mention[0].entry, "compiler/");
+            assert_eq!(to_mention[0].relevant_ccs, vec!["@alice".to_string()]);
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn deferrals_are_only_loaded_with_someone_to_ping() {
+        // The database is unreachable, so it can't be queried
+        let ctx = offline_context();
+        let pr = pull_request().number(1).author(user("carol", 3)).call();
+        let files = [file("src/doc/index.md")];
+        let paths = files
+            .iter()
+            .map(|f| Path::new(&f.filename))
+            .collect::<Vec<_>>();
+        assert!(
+            to_mention(&ctx, &mentions_config(), &pr, &paths, &files)
+                .await
+                .is_empty()
+        );
+    }
 
     #[test]
     fn finds_added_line() {
@@ -399,7 +399,8 @@ async fn spawn_scan_for(
     };
 
     if should_spawn {
-        tokio::spawn(scan_fut);
+        // The spawned task must inherit the dry-run mode of the event.
+        tokio::spawn(crate::dry_run::propagate(scan_fut));
     }
 }
 
@@ -0,0 +1,170 @@
+//! Allows team members to set the milestone of an issue or a PR, with
+//! `@rustbot milestone <title>`.
+//!
+//! Unlike the automatic milestoning of merged PRs (see `milestone_prs`), the
+//! milestone is never created: it must already exist in the repository.
+
+use crate::{
+    config::MilestoneConfig,
+    errors::user_error,
+    github::{Event, Milestone},
+    handlers::Context,
+};
+use parser::command::milestone::MilestoneCommand;
+
+pub(super) async fn handle_command(
+    ctx: &Context,
+    _config: &MilestoneConfig,
+    event: &Event,
+    cmd: MilestoneCommand,
+) -> anyhow::Result<()> {
+    let issue = event.issue().unwrap();
+    let is_team_member = ctx
+        .team
+        .is_team_member(&event.user().login)
+        .await
+        .unwrap_or(false);
+    if !is_team_member {
+        return user_error!("Only team members can set the milestone.");
+    }
+
+    let full_repo_name = issue.repository().full_repo_name();
+    let milestone = ctx
+        .github
+        .find_milestone(&full_repo_name, &cmd.title)
+        .await?;
+    let milestone = match existing_milestone(milestone, &cmd.title, &full_repo_name) {
+        Ok(milestone) => milestone,
+        Err(err) => return user_error!(err),
+    };
+
+    ctx.github
+        .set_milestone(&full_repo_name, &milestone, issue.number)
+        .await?;
+    Ok(())
+}
+
+/// Returns the milestone to set, or the error to show when it does not exist.
+fn existing_milestone(
+    milestone: Option<Milestone>,
+    title: &str,
+    full_repo_name: &str,
+) -> Result<Milestone, String> {
+    milestone.ok_or_else(|| {
+        format!(
+            "The milestone `{title}` does not exist in {full_repo_name}. \
+             Milestones have to be created before they can be set."
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::github::{comment_event, issue, user};
+    use axum::{Json, Router, extract::Query, routing::get};
+    use std::collections::HashMap;
+
+    /// A context where `ferris` is a team member, and `1.90.0` the only
+    /// milestone of rust-lang/rust.
+    async fn context() -> Context {
+        let mut ctx = crate::tests::offline_context();
+        ctx.team = crate::tests::mock_team_client(Router::new().route(
+            "/teams.json",
+            get(|| async {
+                Json(serde_json::json!({
+                    "all": {
+                        "name": "all",
+                        "kind": "team",
+                        "members": [{"name": "ferris", "github": "ferris", "github_id": 100, "is_lead": false}],
+                        "alumni": [],
+                        "discord": [],
+                        "roles": [],
+                    }
+                }))
+            }),
+        ))
+        .await;
+        ctx.github = crate::tests::mock_github_client(Router::new().route(
+            "/repos/rust-lang/rust/milestones",
+            get(|Query(query): Query<HashMap<String, String>>| async move {
+                if query.get("page").map(String::as_str) == Some("1") {
+                    Json(serde_json::json!([{ "number": 42, "title": "1.90.0" }]))
+                } else {
+                    Json(serde_json::json!([]))
+                }
+            }),
+        ))
+        .await;
+        ctx
+    }
+
+    async fn set_milestone(
+        ctx: &Context,
+        login: &str,
+        title: &str,
+    ) -> (anyhow::Result<()>, Vec<String>) {
+        let event = comment_event()
+            .issue(issue().call())
+            .author(user(login, 2))
+            .call();
+        let cmd = MilestoneCommand {
+            title: title.to_string(),
+        };
+        crate::dry_run::record(handle_command(ctx, &MilestoneConfig {}, &event, cmd)).await
+    }
+
+    #[tokio::test]
+    async fn sets_the_milestone() {
+        let ctx = context().await;
+        let (result, actions) = set_milestone(&ctx, "ferris", "1.90.0").await;
+        result.unwrap();
+        assert_eq!(
+            actions,
+            vec![r#"set milestone "1.90.0" on rust-lang/rust#1"#.to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn only_team_members_can_set_the_milestone() {
+        let ctx = context().await;
+        let (result, actions) = set_milestone(&ctx, "octocat", "1.90.0").await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Only team members can set the milestone."
+        );
+        assert!(actions.is_empty(), "{actions:?}");
+    }
+
+    #[tokio::test]
+    async fn unknown_milestone() {
+        let ctx = context().await;
+        let (result, actions) = set_milestone(&ctx, "ferris", "1.91.0").await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "The milestone `1.91.0` does not exist in rust-lang/rust. \
+             Milestones have to be created before they can be set."
+        );
+        assert!(actions.is_empty(), "{actions:?}");
+    }
+
+    fn milestone(title: &str) -> Milestone {
+        serde_json::from_value(serde_json::json!({ "number": 42, "title": title })).unwrap()
+    }
+
+    #[test]
+    fn existing() {
+        let found = existing_milestone(Some(milestone("1.90.0")), "1.90.0", "rust-lang/rust");
+        assert_eq!(found, Ok(milestone("1.90.0")));
+    }
+
+    #[test]
+    fn non_existing() {
+        assert_eq!(
+            existing_milestone(None, "1.90.0", "rust-lang/rust"),
+            Err("The milestone `1.90.0` does not exist in rust-lang/rust. \
+                 Milestones have to be created before they can be set."
+                .to_string())
+        );
+    }
+}
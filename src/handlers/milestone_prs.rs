@@ -1,13 +1,47 @@
 use crate::{
-    github::{Event, GithubClient, IssuesAction, IssuesEvent},
+    config::MilestonePrsConfig,
+    db::milestone_progress::{clear_milestoned, milestoned_prs, record_milestoned},
+    github::{Event, GithubClient, Issue, IssuesAction, IssuesEvent, MilestoneState},
     handlers::Context,
 };
 use anyhow::Context as _;
+use async_trait::async_trait;
+use chrono::Utc;
 use regex::Regex;
 use reqwest::StatusCode;
+use std::collections::HashSet;
+use std::sync::LazyLock;
 use tracing as log;
 
-pub(super) async fn handle(ctx: &Context, event: &Event) -> anyhow::Result<()> {
+/// Subject lines of merge commits created by bors, GitHub (including the merge
+/// queue) and bors-ng, e.g. `Auto merge of #123 - ...`, `Merge pull request #123 from ...`
+/// or `Merge #123 #124`.
+static MERGE_COMMIT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?:Auto merge of #([0-9]+) |Merge pull request #([0-9]+) from |Merge((?: #[0-9]+)+)$)",
+    )
+    .unwrap()
+});
+
+/// Subject lines of squash merges, e.g. `Fix the thing (#123)`.
+static SQUASH_COMMIT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r" \(#([0-9]+)\)$").unwrap());
+
+/// PRs listed in the body of a rollup, e.g. ` - #123 (Fix the thing)` or
+/// ` - rust-lang/rust#123 (Fix the thing)`.
+static ROLLUP_MEMBER_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*[-*] (?:rust-lang/rust)?#([0-9]+)\b").unwrap());
+
+/// The reverted PR in the body of a revert PR, as created by the GitHub "Revert"
+/// button, e.g. `Reverts rust-lang/rust#123`, or by hand, e.g. `Reverts #123`.
+static REVERTED_PR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s*Reverts (?:rust-lang/rust)?#([0-9]+)\b").unwrap());
+
+pub(super) async fn handle(
+    ctx: &Context,
+    event: &Event,
+    config: Option<&MilestonePrsConfig>,
+) -> anyhow::Result<()> {
     let Event::Issue(e) = event else {
         return Ok(());
     };
@@ -17,36 +51,102 @@ pub(super) async fn handle(ctx: &Context, event: &Event) -> anyhow::Result<()> {
         return Ok(());
     }
 
-    let repo = e.issue.repository();
-    if !(repo.organization == "rust-lang" && repo.repository == "rust") {
-        return Ok(());
+    let version = match milestone_pr(ctx, &e.issue).await? {
+        MilestoneOutcome::Assigned { version } => version,
+        MilestoneOutcome::Skipped(
+            reason @ (SkipReason::UnsupportedRepository | SkipReason::NotMerged),
+        ) => {
+            log::trace!("Ignoring closing of {}: {reason}", e.issue.global_id());
+            return Ok(());
+        }
+        MilestoneOutcome::Skipped(reason) => {
+            log::error!("{}: {reason}", e.issue.global_id());
+            return Ok(());
+        }
+    };
+
+    let submodule_milestone_state = config.map_or(MilestoneState::Closed, |config| {
+        config.submodule_milestone_state
+    });
+    milestone_submodules(ctx, e, &version, submodule_milestone_state).await?;
+
+    if let Some(members) = rollup_members(&e.issue) {
+        milestone_rollup_members(&ctx.github, &e.issue, &version, &members).await?;
     }
 
-    if !e.issue.merged {
-        log::trace!(
-            "Ignoring closing of rust-lang/rust#{}: not merged",
-            e.issue.number
-        );
-        return Ok(());
+    if let Some(reverted) = reverted_pr(&e.issue) {
+        note_revert(&ctx.github, &e.issue, &version, reverted).await?;
     }
 
-    let Some(merge_sha) = &e.issue.merge_commit_sha else {
-        log::error!(
-            "rust-lang/rust#{}: no merge_commit_sha in event",
-            e.issue.number
-        );
-        return Ok(());
+    Ok(())
+}
+
+/// The outcome of [`milestone_pr`].
+#[derive(Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub(crate) enum MilestoneOutcome {
+    /// The PR was milestoned with the version it merged into.
+    Assigned { version: String },
+    /// The PR couldn't be milestoned.
+    Skipped(SkipReason),
+}
+
+#[derive(Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub(crate) enum SkipReason {
+    UnsupportedRepository,
+    NotMerged,
+    NoMergeSha,
+    UnknownVersion { merge_sha: String },
+    WeirdVersion { version: String },
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::UnsupportedRepository => {
+                write!(f, "only rust-lang/rust PRs are milestoned")
+            }
+            SkipReason::NotMerged => write!(f, "not merged"),
+            SkipReason::NoMergeSha => write!(f, "no merge_commit_sha"),
+            SkipReason::UnknownVersion { merge_sha } => {
+                write!(f, "could not find the version of {merge_sha:?}")
+            }
+            SkipReason::WeirdVersion { version } => write!(f, "weird version {version:?}"),
+        }
+    }
+}
+
+/// Returns the merge commit of the PR, if it can be milestoned.
+fn merge_sha(pr: &Issue) -> Result<&str, SkipReason> {
+    let repo = pr.repository();
+    if !(repo.organization == "rust-lang" && repo.repository == "rust") {
+        return Err(SkipReason::UnsupportedRepository);
+    }
+    if !pr.merged {
+        return Err(SkipReason::NotMerged);
+    }
+    pr.merge_commit_sha.as_deref().ok_or(SkipReason::NoMergeSha)
+}
+
+/// Sets the milestone of a merged rust-lang/rust PR to the version it merged into.
+pub(crate) async fn milestone_pr(ctx: &Context, pr: &Issue) -> anyhow::Result<MilestoneOutcome> {
+    let merge_sha = match merge_sha(pr) {
+        Ok(merge_sha) => merge_sha,
+        Err(reason) => return Ok(MilestoneOutcome::Skipped(reason)),
     };
 
     // Fetch the version from the upstream repository.
     let Some(version) = get_version_standalone(&ctx.github, merge_sha).await? else {
-        log::error!("could not find the version of {merge_sha:?}");
-        return Ok(());
+        return Ok(MilestoneOutcome::Skipped(SkipReason::UnknownVersion {
+            merge_sha: merge_sha.to_string(),
+        }));
     };
 
     if !version.starts_with("1.") && version.len() < 8 {
-        log::error!("Weird version {version:?} for {merge_sha:?}");
-        return Ok(());
+        return Ok(MilestoneOutcome::Skipped(SkipReason::WeirdVersion {
+            version,
+        }));
     }
 
     // Associate this merged PR with the version it merged into.
@@ -55,11 +155,9 @@ pub(super) async fn handle(ctx: &Context, event: &Event) -> anyhow::Result<()> {
     // auto-update when merging a beta-backport, for example, but that seems
     // fine; we can manually update without too much trouble in that case, and
     // eventually automate it separately.
-    e.issue.set_milestone(&ctx.github, &version).await?;
-
-    milestone_submodules(&ctx.github, e, &version).await?;
+    pr.set_milestone(&ctx.github, &version).await?;
 
-    Ok(())
+    Ok(MilestoneOutcome::Assigned { version })
 }
 
 async fn get_version_standalone(
@@ -93,12 +191,100 @@ async fn get_version_standalone(
     ))
 }
 
-async fn milestone_submodules(
+/// Returns the PRs merged by a rollup PR, or `None` if the PR isn't a rollup.
+fn rollup_members(pr: &Issue) -> Option<Vec<u64>> {
+    if !pr.title.starts_with("Rollup of ") {
+        return None;
+    }
+    Some(parse_rollup_members(&pr.body))
+}
+
+/// Parses the PR numbers listed in a rollup body, up to its `Failed merges:` section.
+fn parse_rollup_members(body: &str) -> Vec<u64> {
+    body.lines()
+        .take_while(|line| !line.trim_start().starts_with("Failed merges"))
+        .filter_map(|line| ROLLUP_MEMBER_RE.captures(line))
+        .map(|cap| cap[1].parse::<u64>().expect("digits only"))
+        .collect()
+}
+
+/// Returns the PR reverted by a revert PR, or `None` if the PR isn't a revert.
+fn reverted_pr(pr: &Issue) -> Option<u64> {
+    if !pr.title.starts_with("Revert") {
+        return None;
+    }
+    parse_reverted_pr(&pr.body)
+}
+
+/// Parses the PR number of a `Reverts #123` line.
+fn parse_reverted_pr(body: &str) -> Option<u64> {
+    REVERTED_PR_RE
+        .captures(body)
+        .map(|cap| cap[1].parse::<u64>().expect("digits only"))
+}
+
+/// Notes on a reverted PR the version its revert merged into, as the
+/// milestone of the reverted PR doesn't reflect it.
+async fn note_revert(
     gh: &GithubClient,
+    revert: &Issue,
+    version: &str,
+    reverted: u64,
+) -> anyhow::Result<()> {
+    let reverted = gh
+        .issue(revert.repository(), reverted)
+        .await
+        .context("failed to get the reverted PR")?;
+    log::info!(
+        "noting the revert of {} in {} ({version})",
+        reverted.global_id(),
+        revert.global_id()
+    );
+    reverted
+        .post_comment(
+            gh,
+            &format!(
+                "This PR was reverted in #{}, which merged into {version}.",
+                revert.number
+            ),
+        )
+        .await
+        .context("failed to note the revert on the reverted PR")?;
+    Ok(())
+}
+
+/// Milestones the PRs merged by a rollup with the version the rollup merged into.
+async fn milestone_rollup_members(
+    gh: &GithubClient,
+    rollup: &Issue,
+    version: &str,
+    members: &[u64],
+) -> anyhow::Result<()> {
+    if members.is_empty() {
+        return Ok(());
+    }
+    let full_repo_name = rollup.repository().full_repo_name();
+    let milestone = gh
+        .get_or_create_milestone(&full_repo_name, version, MilestoneState::Open)
+        .await?;
+    for pr_num in members {
+        log::info!(
+            "setting milestone {version} for {full_repo_name}#{pr_num} (rolled up in #{})",
+            rollup.number
+        );
+        gh.set_milestone(&full_repo_name, &milestone, *pr_num)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn milestone_submodules(
+    ctx: &Context,
     event: &IssuesEvent,
     version: &str,
+    milestone_state: MilestoneState,
 ) -> anyhow::Result<()> {
-    let Some(files) = event.issue.diff(gh).await? else {
+    let Some(files) = event.issue.diff(&ctx.github).await? else {
         return Ok(());
     };
     for (repo, submodule) in [
@@ -106,48 +292,112 @@ async fn milestone_submodules(
         ("rust-lang/reference", "src/doc/reference"),
     ] {
         if let Some(fd) = files.iter().find(|fd| fd.filename == submodule) {
-            // The webhook timeout of 10 seconds can be too short, so process in
-            // the background.
-            let diff = fd.patch.clone();
-            let ver = version.to_string();
-            tokio::task::spawn(async move {
-                let gh = GithubClient::new_from_env();
-                if let Err(e) = milestone_submodule(&gh, repo, submodule, &ver, &diff).await {
+            let (start, end) = match submodule_range(&fd.patch) {
+                Ok(range) => range,
+                Err(e) => {
                     log::error!("failed to milestone {submodule}: {e:?}");
+                    continue;
                 }
-            });
+            };
+            if crate::dry_run::skip(format_args!(
+                "scheduled the milestoning of {repo} PRs ({start}...{end}) with {version}"
+            )) {
+                continue;
+            }
+            // The webhook timeout of 10 seconds can be too short, so process in
+            // a job, which is also retried if it is interrupted.
+            let args = MilestoneSubmoduleJobArgs {
+                repo: repo.to_string(),
+                submodule: submodule.to_string(),
+                version: version.to_string(),
+                milestone_state,
+                start,
+                end,
+            };
+            crate::db::schedule_job(
+                &*ctx.db.get().await,
+                MILESTONE_SUBMODULE_JOB_NAME,
+                serde_json::to_value(args)?,
+                Utc::now(),
+            )
+            .await
+            .with_context(|| format!("failed to schedule the milestoning of {submodule}"))?;
         }
     }
 
     Ok(())
 }
 
-/// Milestones all PRs in the submodule when the submodule is synced in
-/// rust-lang/rust.
-async fn milestone_submodule(
-    gh: &GithubClient,
-    repo_name: &str,
-    submodule: &str,
-    release_version: &str,
-    submodule_diff: &str,
-) -> anyhow::Result<()> {
-    // Determine the start/end range of commits in this submodule update by
-    // looking at the diff content which indicates the old and new hash.
+/// Returns the old and new commits of a submodule update, from its diff
+/// (e.g. `Subproject commit 0123abcd`).
+fn submodule_range(submodule_diff: &str) -> anyhow::Result<(String, String)> {
     let subproject_re = Regex::new("Subproject commit ([0-9a-f]+)").unwrap();
     let mut caps = subproject_re.captures_iter(submodule_diff);
-    let submodule_start_hash = &caps.next().unwrap()[1];
-    let submodule_end_hash = &caps.next().unwrap()[1];
+    let (Some(start), Some(end)) = (caps.next(), caps.next()) else {
+        anyhow::bail!("missing submodule commits in {submodule_diff:?}");
+    };
     if let Some(next) = caps.next() {
         anyhow::bail!("unexpected submodule capture {}", &next[1]);
     }
+    Ok((start[1].to_string(), end[1].to_string()))
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct MilestoneSubmoduleJobArgs {
+    repo: String,
+    submodule: String,
+    version: String,
+    milestone_state: MilestoneState,
+    /// The commits of the submodule before and after the update.
+    start: String,
+    end: String,
+}
+
+pub(crate) struct MilestoneSubmoduleJob;
+
+const MILESTONE_SUBMODULE_JOB_NAME: &str = "milestone_submodule";
+
+#[async_trait]
+impl crate::jobs::Job for MilestoneSubmoduleJob {
+    fn name(&self) -> &str {
+        MILESTONE_SUBMODULE_JOB_NAME
+    }
+
+    async fn run(&self, ctx: &Context, metadata: &serde_json::Value) -> anyhow::Result<()> {
+        let args: MilestoneSubmoduleJobArgs = serde_json::from_value(metadata.clone())
+            .with_context(|| {
+                format!("failed to deserialize the metadata {metadata:?} into args")
+            })?;
+        milestone_submodule(ctx, &args).await
+    }
+}
+
+/// Milestones all PRs in the submodule when the submodule is synced in
+/// rust-lang/rust.
+///
+/// The milestoned PRs are recorded as they go, so that a run interrupted
+/// midway (e.g. by a restart) resumes where it stopped when the job is
+/// retried, instead of milestoning all the PRs again. The PRs which already
+/// have the milestone are skipped.
+async fn milestone_submodule(
+    ctx: &Context,
+    args: &MilestoneSubmoduleJobArgs,
+) -> anyhow::Result<()> {
+    let MilestoneSubmoduleJobArgs {
+        repo: repo_name,
+        submodule,
+        version: release_version,
+        milestone_state,
+        start,
+        end,
+    } = args;
+    let gh = &ctx.github;
 
     // Get all of the git commits in the submodule repo.
     let submodule_repo = gh.repository(repo_name).await?;
-    log::info!(
-        "loading submodule {repo_name} changes {submodule_start_hash}...{submodule_end_hash}"
-    );
+    log::info!("loading submodule {repo_name} changes {start}...{end}");
     let commits = submodule_repo
-        .github_commits_in_range(gh, submodule_start_hash, submodule_end_hash)
+        .github_commits_in_range(gh, start, end)
         .await?;
 
     // For each commit, look for a message that indicates which PR was merged.
@@ -159,34 +409,227 @@ async fn milestone_submodule(
     // and this is a bit simpler/faster. However, it is sensitive to the
     // specific messages generated by bors or GitHub merge queue, and won't
     // catch things merged beyond them.
-    let merge_re =
-        Regex::new(r"(?:Auto merge of|Merge pull request) #([0-9]+)|\(#([0-9]+)\)$").unwrap();
-
-    let pr_nums = commits
-        .iter()
-        .filter(|commit|
-            // Assumptions:
-            // * A merge commit always has two parent commits.
-            // * Submodule PRs never got merged as fast-forward / rebase / squash merge.
-            commit.parents.len() == 2)
-        .filter_map(|commit| {
-            let first = commit.commit.message.lines().next().unwrap_or_default();
-            merge_re.captures(first).map(|cap| {
-                cap.get(1)
-                    .or_else(|| cap.get(2))
-                    .unwrap()
-                    .as_str()
-                    .parse::<u64>()
-                    .expect("digits only")
-            })
-        });
+    let pr_nums = commits.iter().flat_map(|commit| {
+        let subject = commit.commit.message.lines().next().unwrap_or_default();
+        merged_pr_numbers(subject, commit.parents.len())
+    });
     let milestone = submodule_repo
-        .get_or_create_milestone(gh, release_version, "closed")
+        .get_or_create_milestone(gh, release_version, *milestone_state)
         .await?;
-    for pr_num in pr_nums {
-        log::info!("setting submodule {submodule} milestone {milestone:?} for {pr_num}");
-        submodule_repo.set_milestone(gh, &milestone, pr_num).await?;
+    let done = milestoned_prs(&*ctx.db.get().await, repo_name, release_version).await?;
+    if !done.is_empty() {
+        log::info!(
+            "resuming submodule {submodule} milestone {release_version}, {} PRs already done",
+            done.len()
+        );
+    }
+    for pr_num in remaining_prs(pr_nums, &done) {
+        let pr = submodule_repo.get_pr(gh, pr_num).await?;
+        if pr.milestone.as_ref() == Some(&milestone) {
+            log::info!("submodule {submodule} PR {pr_num} already has milestone {milestone:?}");
+        } else {
+            log::info!("setting submodule {submodule} milestone {milestone:?} for {pr_num}");
+            submodule_repo.set_milestone(gh, &milestone, pr_num).await?;
+        }
+        record_milestoned(&*ctx.db.get().await, repo_name, release_version, pr_num).await?;
     }
 
+    // The run is complete, its progress isn't needed anymore
+    clear_milestoned(&*ctx.db.get().await, repo_name, release_version).await?;
     Ok(())
 }
+
+/// The PRs left to milestone, in order and without duplicates, skipping the
+/// `done` ones of a previous run.
+fn remaining_prs(pr_nums: impl IntoIterator<Item = u64>, done: &HashSet<u64>) -> Vec<u64> {
+    let mut seen = HashSet::new();
+    pr_nums
+        .into_iter()
+        .filter(|pr_num| !done.contains(pr_num) && seen.insert(*pr_num))
+        .collect()
+}
+
+/// Returns the PRs merged by a commit, based on its subject line and its number
+/// of parents.
+///
+/// Merge commits always have two parents, whereas squash merges (as done by the
+/// GitHub merge queue) only have one. To avoid picking up an arbitrary `(#123)`
+/// reference, the number of a squash merge must be at the very end of the subject.
+fn merged_pr_numbers(subject: &str, parents: usize) -> Vec<u64> {
+    let parse = |num: &str| num.parse::<u64>().expect("digits only");
+    if parents == 2
+        && let Some(cap) = MERGE_COMMIT_RE.captures(subject)
+    {
+        if let Some(batch) = cap.get(3) {
+            return batch
+                .as_str()
+                .split_whitespace()
+                .map(|num| parse(num.trim_start_matches('#')))
+                .collect();
+        }
+        let num = cap.get(1).or_else(|| cap.get(2)).unwrap();
+        return vec![parse(num.as_str())];
+    }
+    if parents > 2 {
+        return Vec::new();
+    }
+    SQUASH_COMMIT_RE
+        .captures(subject)
+        .map(|cap| vec![parse(&cap[1])])
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::github::pull_request;
+
+    #[test]
+    fn skip_reasons() {
+        let mut pr = pull_request().call();
+        assert_eq!(merge_sha(&pr), Err(SkipReason::NotMerged));
+
+        pr.merged = true;
+        assert_eq!(merge_sha(&pr), Err(SkipReason::NoMergeSha));
+
+        pr.merge_commit_sha = Some("0123abcd".to_string());
+        assert_eq!(merge_sha(&pr), Ok("0123abcd"));
+
+        let mut pr = crate::tests::github::issue()
+            .pr(true)
+            .org("rust-lang")
+            .repo("cargo")
+            .call();
+        pr.merged = true;
+        pr.merge_commit_sha = Some("0123abcd".to_string());
+        assert_eq!(merge_sha(&pr), Err(SkipReason::UnsupportedRepository));
+    }
+
+    #[test]
+    fn rollup_members_from_body() {
+        let body = "Successful merges:
+
+ - #146001 (Fix the thing)
+ - rust-lang/rust#146002 (Mention #1 in the docs)
+ - #146003 (Rollup of the docs)
+
+Failed merges:
+
+ - #146004 (Break everything)
+
+r? @ghost
+@rustbot modify labels: rollup
+";
+        let mut pr = pull_request().call();
+        pr.title = "Rollup of 4 pull requests".to_string();
+        pr.body = body.to_string();
+        assert_eq!(rollup_members(&pr), Some(vec![146001, 146002, 146003]));
+
+        pr.title = "Fix the thing".to_string();
+        assert_eq!(rollup_members(&pr), None);
+        assert_eq!(parse_rollup_members("See #123 and #124"), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn reverted_pr_from_body() {
+        let mut pr = pull_request().call();
+        pr.title = "Revert \"Fix the thing\"".to_string();
+        pr.body = "Reverts rust-lang/rust#146001\n\nIt broke everything.".to_string();
+        assert_eq!(reverted_pr(&pr), Some(146001));
+
+        pr.body = "It broke everything.\n\nReverts #146002".to_string();
+        assert_eq!(reverted_pr(&pr), Some(146002));
+
+        pr.body = "Reverts rust-lang/cargo#146003".to_string();
+        assert_eq!(reverted_pr(&pr), None);
+
+        pr.title = "Fix the thing".to_string();
+        pr.body = "Reverts #146001".to_string();
+        assert_eq!(reverted_pr(&pr), None);
+        assert_eq!(parse_reverted_pr("This reverts #146001"), None);
+    }
+
+    #[test]
+    fn outcome_serialization() {
+        assert_eq!(
+            serde_json::to_value(MilestoneOutcome::Skipped(SkipReason::NoMergeSha)).unwrap(),
+            serde_json::json!({ "status": "skipped", "reason": "no_merge_sha" })
+        );
+        assert_eq!(
+            serde_json::to_value(MilestoneOutcome::Assigned {
+                version: "1.90.0".to_string()
+            })
+            .unwrap(),
+            serde_json::json!({ "status": "assigned", "version": "1.90.0" })
+        );
+    }
+
+    #[test]
+    fn merged_pr_numbers_matrix() {
+        let cases: &[(&str, usize, &[u64])] = &[
+            // bors
+            (
+                "Auto merge of #15000 - ehuss:fix-thing, r=weihanglo",
+                2,
+                &[15000],
+            ),
+            // GitHub merge commits, with or without the merge queue
+            ("Merge pull request #15123 from epage/msrv", 2, &[15123]),
+            // GitHub merge queue, squash merges
+            ("fix(toml): Don't panic on empty keys (#15200)", 1, &[15200]),
+            ("Update crates.io index handling (#15201)", 2, &[15201]),
+            // bors-ng, single and batched
+            ("Merge #4242", 2, &[4242]),
+            ("Merge #4242 #4243", 2, &[4242, 4243]),
+            // Not merges of a PR
+            ("Fix the regression from #15000", 1, &[]),
+            ("Fix the regression from #15000", 2, &[]),
+            ("Revert (#15000) for now", 1, &[]),
+            ("Merge branch 'master' into feature", 2, &[]),
+            ("Merge #4242 into the release branch", 2, &[]),
+            ("See (#15000)", 3, &[]),
+            ("Mention Merge pull request #15123 from epage/msrv", 2, &[]),
+            // Merge commit messages on a commit with a single parent are rebases
+            (
+                "Auto merge of #15000 - ehuss:fix-thing, r=weihanglo",
+                1,
+                &[],
+            ),
+            ("Merge pull request #15123 from epage/msrv", 1, &[]),
+        ];
+        for (subject, parents, expected) in cases {
+            assert_eq!(
+                merged_pr_numbers(subject, *parents),
+                *expected,
+                "{subject:?} with {parents} parent(s)"
+            );
+        }
+    }
+
+    #[test]
+    fn submodule_commits() {
+        let diff = "@@ -1 +1 @@\n-Subproject commit 0123abcd\n+Subproject commit 4567ef01\n";
+        assert_eq!(
+            submodule_range(diff).unwrap(),
+            ("0123abcd".to_string(), "4567ef01".to_string())
+        );
+        assert!(submodule_range("-Subproject commit 0123abcd\n").is_err());
+    }
+
+    #[test]
+    fn resumed_run_skips_processed_prs() {
+        let pr_nums = [15000, 15002, 15000, 15004, 15006];
+
+        // A fresh run milestones every PR once
+        assert_eq!(
+            remaining_prs(pr_nums, &HashSet::new()),
+            vec![15000, 15002, 15004, 15006]
+        );
+        // A resumed run only milestones the PRs not done by the interrupted one
+        assert_eq!(
+            remaining_prs(pr_nums, &HashSet::from([15000, 15002])),
+            vec![15004, 15006]
+        );
+        assert!(remaining_prs(pr_nums, &HashSet::from(pr_nums)).is_empty());
+    }
+}
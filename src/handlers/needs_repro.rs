@@ -0,0 +1,116 @@
+//! Asks for a reproduction of an issue with `@rustbot needs-repro`.
+//!
+//! Team members can use it to post the canned comment configured in the
+//! `[needs-repro]` section, the optional configured label (e.g. `S-needs-repro`)
+//! being added to the issue at the same time:
+//!
+//! ```toml
+//! [needs-repro]
+//! message = "Could you provide a minimal reproduction of the problem?"
+//! label = "S-needs-repro"
+//! ```
+//!
+//! Parsing is done in the `parser::command::needs_repro` module.
+
+use anyhow::Context as _;
+use parser::command::needs_repro::NeedsReproCommand;
+use parser::command::relabel::{Label, LabelDelta};
+
+use crate::config::NeedsReproConfig;
+use crate::errors::user_error;
+use crate::github::{Event, Issue};
+use crate::handlers::{Context, relabel};
+
+pub(super) async fn handle_command(
+    ctx: &Context,
+    config: &NeedsReproConfig,
+    event: &Event,
+    _cmd: NeedsReproCommand,
+) -> anyhow::Result<()> {
+    let Some(issue) = event.issue() else {
+        return user_error!(
+            "The `needs-repro` command can only be used on issues and pull requests."
+        );
+    };
+
+    if !ctx
+        .team
+        .is_team_member(&event.user().login)
+        .await
+        .context("failed to verify that the user is a team member")?
+    {
+        return user_error!("Only team members can ask for a reproduction.");
+    }
+
+    ask_for_repro(ctx, config, issue).await
+}
+
+/// Posts the configured comment on the issue and adds the configured label.
+async fn ask_for_repro(
+    ctx: &Context,
+    config: &NeedsReproConfig,
+    issue: &Issue,
+) -> anyhow::Result<()> {
+    issue
+        .post_comment(&ctx.github, &config.message)
+        .await
+        .context("failed to post the needs-repro comment")?;
+    relabel::apply_label_deltas(ctx, issue, &label_deltas(config)).await
+}
+
+/// The label changes applied when asking for a reproduction.
+fn label_deltas(config: &NeedsReproConfig) -> Vec<LabelDelta> {
+    config
+        .label
+        .iter()
+        .map(|label| LabelDelta::Add(Label(label.clone())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::github::issue;
+    use crate::tests::offline_context;
+
+    fn config() -> NeedsReproConfig {
+        toml::from_str(
+            r#"
+            message = "Could you provide a minimal reproduction?"
+            label = "S-needs-repro"
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn configured_label() {
+        assert_eq!(
+            label_deltas(&config()),
+            vec![LabelDelta::Add(Label("S-needs-repro".to_string()))]
+        );
+        let config = NeedsReproConfig {
+            label: None,
+            ..config()
+        };
+        assert!(label_deltas(&config).is_empty());
+    }
+
+    #[tokio::test]
+    async fn comment_and_label_applied() {
+        let ctx = offline_context();
+        let issue = issue().number(123).call();
+
+        let (result, actions) =
+            crate::dry_run::record(ask_for_repro(&ctx, &config(), &issue)).await;
+        result.unwrap();
+        assert_eq!(
+            actions,
+            vec![
+                r#"posted comment on rust-lang/rust#123: "Could you provide a minimal reproduction?""#
+                    .to_string(),
+                r#"added labels ["S-needs-repro"] to rust-lang/rust#123"#.to_string(),
+            ]
+        );
+    }
+}
@@ -8,6 +8,9 @@
 //!
 //! If this is the first summary entry, rustbot will amend the original post (the top-level comment) to add a "Notes" section. The section should **not** be edited by hand.
 //!
+//! The command can also be used in the original post itself, to add notes when opening the issue.
+//! Such notes link to the issue, and are only added again if their command is edited.
+//!
 //! ```md
 //! <!-- TRIAGEBOT_SUMMARY_START -->
 //!
@@ -19,6 +22,12 @@
 //! <!-- TRIAGEBOT_SUMMARY_END -->
 //! ```
 //!
+//! A note can be renamed, keeping the link to its comment, with:
+//!
+//! ```md
+//! @rustbot note rename "Summary title" "New summary title"
+//! ```
+//!
 //! If this is *not* the first summary entry, rustbot will simply append the new entry to the existing notes section:
 //!
 //! ```md
@@ -34,6 +43,7 @@
 //! ```
 //!
 
+use crate::errors::user_error;
 use crate::{config::NoteConfig, github::Event, handlers::Context, interactions::EditIssueBody};
 use itertools::Itertools;
 use parser::command::note::NoteCommand;
@@ -86,6 +96,40 @@ impl NoteData {
         })
     }
 
+    /// Adds a note pointing to the comment which created it, or updates its
+    /// title if that comment already created a note (e.g. when it is edited).
+    pub(crate) fn add(&mut self, title: String, comment_url: String, author: String) {
+        if let Some(existing_entry) = self.entries_by_url.get_mut(&comment_url) {
+            existing_entry.title = title;
+            log::debug!("Updated existing entry: {:#?}", existing_entry);
+        } else {
+            let new_entry = NoteDataEntry {
+                title,
+                comment_url: comment_url.clone(),
+                author,
+            };
+            log::debug!("New Note Entry: {:#?}", new_entry);
+            self.entries_by_url.insert(comment_url, new_entry);
+            log::debug!("Entries by URL: {:#?}", self.entries_by_url);
+        }
+    }
+
+    /// Renames the note with exactly the `old` title, keeping its comment and author.
+    pub(crate) fn rename(&mut self, old: &str, new: &str) -> Result<(), String> {
+        if self.entries_by_url.values().any(|entry| entry.title == new) {
+            return Err(format!("A note titled \"{new}\" already exists."));
+        }
+        let Some(entry) = self
+            .entries_by_url
+            .values_mut()
+            .find(|entry| entry.title == old)
+        else {
+            return Err(format!("There is no note titled \"{old}\"."));
+        };
+        entry.title = new.to_string();
+        Ok(())
+    }
+
     pub(crate) fn remove_by_title(&mut self, title: &str) -> Option<NoteDataEntry> {
         if let Some(url_to_remove) = self.get_url_from_title_prefix(title) {
             if let Some(entry) = self.entries_by_url.remove(&url_to_remove) {
@@ -136,19 +180,11 @@ pub(super) async fn handle_command(
 
     match &cmd {
         NoteCommand::Summary { title } => {
-            let title = title.to_owned();
-            if let Some(existing_entry) = current.entries_by_url.get_mut(&comment_url) {
-                existing_entry.title = title;
-                log::debug!("Updated existing entry: {:#?}", existing_entry);
-            } else {
-                let new_entry = NoteDataEntry {
-                    title,
-                    comment_url: comment_url.clone(),
-                    author,
-                };
-                log::debug!("New Note Entry: {:#?}", new_entry);
-                current.entries_by_url.insert(comment_url, new_entry);
-                log::debug!("Entries by URL: {:#?}", current.entries_by_url);
+            current.add(title.to_owned(), comment_url, author);
+        }
+        NoteCommand::Rename { old, new } => {
+            if let Err(err) = current.rename(old, new) {
+                return user_error!(err);
             }
         }
         NoteCommand::Remove { title } => {
@@ -167,3 +203,110 @@ pub(super) async fn handle_command(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::parse_new_commands;
+    use parser::command::Command;
+
+    const COMMENT_URL: &str = "https://github.com/rust-lang/rust/issues/1#issuecomment-42";
+
+    #[test]
+    fn note_links_to_its_comment() {
+        let mut data = NoteData::default();
+        data.add(
+            "design decision".to_string(),
+            COMMENT_URL.to_string(),
+            "ghost".to_string(),
+        );
+        assert_eq!(
+            data.entries_by_url[COMMENT_URL],
+            NoteDataEntry {
+                title: "design decision".to_string(),
+                comment_url: COMMENT_URL.to_string(),
+                author: "ghost".to_string(),
+            }
+        );
+        assert!(data.to_markdown("rustbot").contains(
+            "- [design decision](https://github.com/rust-lang/rust/issues/1#issuecomment-42) \
+             by [ghost](https://github.com/ghost)"
+        ));
+
+        // Editing the comment updates the note instead of adding another one
+        data.add(
+            "final design decision".to_string(),
+            COMMENT_URL.to_string(),
+            "ghost".to_string(),
+        );
+        assert_eq!(data.entries_by_url.len(), 1);
+        assert_eq!(
+            data.entries_by_url[COMMENT_URL].title,
+            "final design decision"
+        );
+    }
+
+    #[test]
+    fn rename_keeps_the_comment() {
+        let mut data = NoteData::default();
+        data.add(
+            "design".to_string(),
+            COMMENT_URL.to_string(),
+            "ghost".to_string(),
+        );
+        data.add(
+            "other".to_string(),
+            format!("{COMMENT_URL}0"),
+            "octocat".to_string(),
+        );
+
+        assert_eq!(data.rename("design", "design decision"), Ok(()));
+        assert_eq!(
+            data.entries_by_url[COMMENT_URL],
+            NoteDataEntry {
+                title: "design decision".to_string(),
+                comment_url: COMMENT_URL.to_string(),
+                author: "ghost".to_string(),
+            }
+        );
+
+        assert_eq!(
+            data.rename("design", "whatever"),
+            Err("There is no note titled \"design\".".to_string())
+        );
+        assert_eq!(
+            data.rename("design decision", "other"),
+            Err("A note titled \"other\" already exists.".to_string())
+        );
+    }
+
+    #[test]
+    fn opening_body_note_runs_once() {
+        let body = "Tracking issue for the feature.\n\n@rustbot note design decision\n";
+
+        // The note is added when the issue is opened...
+        assert_eq!(
+            parse_new_commands(body, None, vec!["rustbot"]),
+            vec![Command::Note(Ok(NoteCommand::Summary {
+                title: "design decision".to_string()
+            }))]
+        );
+
+        // ... but not again when the notes section is added to the body
+        let mut data = NoteData::default();
+        data.add(
+            "design decision".to_string(),
+            "https://github.com/rust-lang/rust/issues/1".to_string(),
+            "ghost".to_string(),
+        );
+        let edited = format!(
+            "{body}\n\n<!-- TRIAGEBOT_START -->\n\n<!-- TRIAGEBOT_SUMMARY_START -->\n{}\n\
+             <!-- TRIAGEBOT_SUMMARY_END -->\n<!-- TRIAGEBOT_END -->",
+            data.to_markdown("rustbot")
+        );
+        assert_eq!(
+            parse_new_commands(&edited, Some(body), vec!["rustbot"]),
+            vec![]
+        );
+    }
+}
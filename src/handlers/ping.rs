@@ -11,8 +11,10 @@ use crate::{
     errors::user_error,
     github::{self, Event},
     handlers::Context,
+    team_data::member_logins,
 };
 use parser::command::ping::PingCommand;
+use rust_team_data::v1::Team;
 
 pub(super) async fn handle_command(
     ctx: &Context,
@@ -60,21 +62,8 @@ pub(super) async fn handle_command(
         }
     }
 
-    let mut users = Vec::new();
-
-    if let Some(gh) = team.github {
-        let repo = event.issue().expect("has issue").repository();
-        // Ping all github teams associated with this team repo team that are in this organization.
-        // We cannot ping across organizations, but this should not matter, as teams should be
-        // sync'd to the org for which triagebot is configured.
-        for gh_team in gh.teams.iter().filter(|t| t.org == repo.organization) {
-            users.push(format!("@{}/{}", gh_team.org, gh_team.name));
-        }
-    } else {
-        for member in &team.members {
-            users.push(format!("@{}", member.github));
-        }
-    }
+    let repo = event.issue().expect("has issue").repository();
+    let users = ping_targets(&team, &repo.organization, config.ping_members);
 
     let ping_msg: Cow<_> = if users.is_empty() {
         "no known users to ping?".into()
@@ -90,3 +79,75 @@ pub(super) async fn handle_command(
 
     Ok(())
 }
+
+/// The mentions pinging the team from a repository of `org`.
+///
+/// The GitHub teams of the team are pinged, unless `ping_members` is set or the
+/// team has no GitHub team, in which case its members are pinged individually.
+fn ping_targets(team: &Team, org: &str, ping_members: bool) -> Vec<String> {
+    match &team.github {
+        Some(gh) if !ping_members => {
+            // Ping all github teams associated with this team repo team that are in this organization.
+            // We cannot ping across organizations, but this should not matter, as teams should be
+            // sync'd to the org for which triagebot is configured.
+            gh.teams
+                .iter()
+                .filter(|t| t.org == org)
+                .map(|gh_team| format!("@{}/{}", gh_team.org, gh_team.name))
+                .collect()
+        }
+        _ => member_logins(team)
+            .into_iter()
+            .map(|login| format!("@{login}"))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn team() -> Team {
+        let member = |login: &str| serde_json::json!({"name": login, "github": login, "github_id": 100, "is_lead": false});
+        serde_json::from_value(serde_json::json!({
+            "name": "wg-triage",
+            "kind": "working-group",
+            "members": [member("tmiasko"), member("apiraino"), member("Noratrieb")],
+            "alumni": [],
+            "github": {
+                "teams": [
+                    {"org": "rust-lang", "name": "wg-triage", "members": []},
+                    {"org": "rust-lang-nursery", "name": "wg-triage", "members": []},
+                ]
+            },
+            "discord": [],
+            "roles": [],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn pings_github_teams() {
+        assert_eq!(
+            ping_targets(&team(), "rust-lang", false),
+            vec!["@rust-lang/wg-triage"]
+        );
+    }
+
+    #[test]
+    fn expands_team_members() {
+        assert_eq!(
+            ping_targets(&team(), "rust-lang", true),
+            vec!["@apiraino", "@Noratrieb", "@tmiasko"]
+        );
+
+        let team = Team {
+            github: None,
+            ..team()
+        };
+        assert_eq!(
+            ping_targets(&team, "rust-lang", false),
+            vec!["@apiraino", "@Noratrieb", "@tmiasko"]
+        );
+    }
+}
@@ -5,20 +5,25 @@
 //!
 //! - Adds the PR to the workqueue of one team member (after the PR has been assigned or reopened)
 //! - Removes the PR from the workqueue of one team member (after the PR has been unassigned or closed)
+//! - Marks the PR as active in the workqueue of one team member (after they submitted a review)
 
-use crate::github::{GitHubUser, GitHubUserType, UserId};
+use crate::github::{
+    Comment, GitHubUser, GitHubUserType, Issue, IssueCommentAction, IssueCommentEvent,
+    PullRequestReviewState, UserId,
+};
 use crate::github::{Label, PullRequestNumber};
 use crate::{
     config::ReviewPrefsConfig,
     github::{IssuesAction, IssuesEvent},
     handlers::Context,
 };
+use chrono::{DateTime, Utc};
 use futures::TryStreamExt;
 use octocrab::Octocrab;
 use octocrab::models::IssueState;
 use octocrab::params::pulls::Sort;
 use octocrab::params::{Direction, State};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
 use tokio::sync::{RwLock, RwLockWriteGuard};
 use tracing as log;
@@ -32,9 +37,14 @@ pub fn get_review_tracked_repositories() -> Vec<TrackedRepository> {
     ]
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct AssignedPullRequest {
     pub title: String,
+    /// When the pull request was opened.
+    pub created_at: DateTime<Utc>,
+    /// Whether the reviewer submitted a review, i.e. the review is in progress.
+    /// Active pull requests are not moved by the workqueue rebalancing.
+    pub active: bool,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash)]
@@ -85,12 +95,95 @@ impl ReviewerWorkqueue {
         Self { reviewers }
     }
 
+    /// Creates a workqueue from the pull request numbers assigned to each reviewer,
+    /// with placeholder titles and creation dates.
+    #[cfg(test)]
+    pub(crate) fn from_pr_numbers(reviewers: HashMap<UserId, Vec<PullRequestNumber>>) -> Self {
+        let reviewers = reviewers
+            .into_iter()
+            .map(|(user_id, prs)| {
+                let prs = prs
+                    .into_iter()
+                    .map(|pr| {
+                        let assigned_pr = AssignedPullRequest {
+                            title: format!("PR {pr}"),
+                            created_at: DateTime::UNIX_EPOCH,
+                            active: false,
+                        };
+                        (pr, assigned_pr)
+                    })
+                    .collect();
+                (user_id, prs)
+            })
+            .collect();
+        Self::new(reviewers)
+    }
+
     pub fn assigned_pr_count(&self, user_id: UserId) -> u64 {
         self.reviewers
             .get(&user_id)
             .map(|prs| prs.len() as u64)
             .unwrap_or(0)
     }
+
+    /// Returns the number of pull requests assigned to each reviewer, omitting
+    /// the reviewers without any.
+    pub(crate) fn assigned_pr_counts(&self) -> BTreeMap<UserId, u64> {
+        self.reviewers
+            .iter()
+            .filter(|(_, prs)| !prs.is_empty())
+            .map(|(user_id, prs)| (*user_id, prs.len() as u64))
+            .collect()
+    }
+
+    /// Returns the pull requests assigned to the reviewer.
+    pub(crate) fn assigned_prs(&self, user_id: UserId) -> BTreeSet<PullRequestNumber> {
+        self.reviewers
+            .get(&user_id)
+            .map(|prs| prs.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Marks the pull request as actively reviewed by the reviewer.
+    ///
+    /// Returns false if the pull request isn't in the workqueue of the reviewer.
+    pub(crate) fn mark_active(&mut self, user_id: UserId, pr: PullRequestNumber) -> bool {
+        let Some(assigned_pr) = self
+            .reviewers
+            .get_mut(&user_id)
+            .and_then(|prs| prs.get_mut(&pr))
+        else {
+            return false;
+        };
+        assigned_pr.active = true;
+        true
+    }
+
+    /// Adds the pull request to the workqueue of the reviewer, replacing its
+    /// previous entry.
+    pub(crate) fn assign(
+        &mut self,
+        user_id: UserId,
+        pr: PullRequestNumber,
+        assigned_pr: AssignedPullRequest,
+    ) {
+        self.reviewers
+            .entry(user_id)
+            .or_default()
+            .insert(pr, assigned_pr);
+    }
+
+    /// Removes the pull request from the workqueue of the reviewer.
+    pub(crate) fn unassign(&mut self, user_id: UserId, pr: PullRequestNumber) {
+        delete_pr_from_user_queue(self, user_id, pr);
+    }
+
+    /// Returns the pull requests assigned to each reviewer.
+    pub fn assignments(
+        &self,
+    ) -> impl Iterator<Item = (UserId, &HashMap<PullRequestNumber, AssignedPullRequest>)> {
+        self.reviewers.iter().map(|(user_id, prs)| (*user_id, prs))
+    }
 }
 
 /// Stores per-repository reviewer workqueues.
@@ -191,6 +284,8 @@ pub(super) async fn handle_input(
 
     let assigned_pr = AssignedPullRequest {
         title: pr.title.clone(),
+        created_at: pr.created_at,
+        active: false,
     };
 
     match input {
@@ -235,6 +330,130 @@ pub(super) async fn handle_input(
     Ok(())
 }
 
+/// Marks the PR as active in the workqueue of the reviewer when they submit a
+/// review, as the PR is then progressing.
+pub(super) async fn handle_review(ctx: &Context, event: &IssueCommentEvent) {
+    if !matches!(
+        event,
+        IssueCommentEvent {
+            action: IssueCommentAction::Created,
+            issue: Issue {
+                pull_request: Some(_),
+                ..
+            },
+            comment: Comment {
+                pr_review_state: Some(state),
+                ..
+            },
+            ..
+        } if *state != PullRequestReviewState::Pending
+    ) {
+        return;
+    }
+
+    mark_reviewed(
+        ctx,
+        &event.repository.full_name,
+        event.issue.number,
+        &event.comment.user,
+    )
+    .await;
+}
+
+/// Marks `pr` as active in the workqueue of `reviewer`, if it is assigned to them.
+async fn mark_reviewed(ctx: &Context, repo: &str, pr: PullRequestNumber, reviewer: &GitHubUser) {
+    let Some(workqueue_arc) = ctx.workqueue_map.get(repo) else {
+        return;
+    };
+    if workqueue_arc.write().await.mark_active(reviewer.id, pr) {
+        log::info!(
+            "Marking PR {pr} as active in the workqueue of {} because they reviewed it.",
+            reviewer.login
+        );
+    }
+}
+
+/// Makes `reviewer` the only reviewer of `pr` in the workqueue, after they
+/// claimed it with `@rustbot claim`.
+pub(super) async fn claim_pr(ctx: &Context, repo: &str, pr: &Issue, reviewer: &GitHubUser) {
+    let Some(workqueue_arc) = ctx.workqueue_map.get(repo) else {
+        return;
+    };
+    let mut workqueue = workqueue_arc.write().await;
+
+    // Claiming the PR replaces all of its previous assignees
+    delete_pr_from_all_queues(&mut workqueue, pr.number);
+    if waits_for_a_review(
+        &pr.labels,
+        std::slice::from_ref(reviewer),
+        &pr.user,
+        pr.is_open(),
+        pr.draft,
+    ) {
+        log::info!(
+            "Adding PR {} to workqueue of {} because they claimed it.",
+            pr.number,
+            reviewer.login
+        );
+        upsert_pr_into_user_queue(
+            &mut workqueue,
+            reviewer.id,
+            pr.number,
+            AssignedPullRequest {
+                title: pr.title.clone(),
+                created_at: pr.created_at,
+                active: false,
+            },
+        );
+    }
+}
+
+/// Removes `pr` from the workqueue of a reviewer that released it with `@rustbot release`.
+pub(super) async fn release_pr(ctx: &Context, repo: &str, pr: PullRequestNumber, reviewer: UserId) {
+    let Some(workqueue_arc) = ctx.workqueue_map.get(repo) else {
+        return;
+    };
+    log::info!("Removing PR {pr} from workqueue of {reviewer} because they released it.");
+    delete_pr_from_user_queue(&mut *workqueue_arc.write().await, reviewer, pr);
+}
+
+/// Replaces the workqueue entries of `pr` with its current assignees, e.g. after
+/// a missed webhook made the workqueue drift from GitHub.
+///
+/// Returns the reviewers the PR is now assigned to in the workqueue, or `None` if
+/// the workqueue of `repo` isn't tracked.
+pub(crate) async fn resync_pr(ctx: &Context, repo: &str, pr: &Issue) -> Option<Vec<String>> {
+    let workqueue_arc = ctx.workqueue_map.get(repo)?;
+    let mut workqueue = workqueue_arc.write().await;
+
+    delete_pr_from_all_queues(&mut workqueue, pr.number);
+    if !waits_for_a_review(&pr.labels, &pr.assignees, &pr.user, pr.is_open(), pr.draft) {
+        log::info!(
+            "Resynced PR {} in {repo}: not waiting for a review.",
+            pr.number
+        );
+        return Some(Vec::new());
+    }
+    for assignee in &pr.assignees {
+        upsert_pr_into_user_queue(
+            &mut workqueue,
+            assignee.id,
+            pr.number,
+            AssignedPullRequest {
+                title: pr.title.clone(),
+                created_at: pr.created_at,
+                active: false,
+            },
+        );
+    }
+    let reviewers: Vec<String> = pr.assignees.iter().map(|a| a.login.clone()).collect();
+    log::info!(
+        "Resynced PR {} in {repo}: assigned to {reviewers:?}.",
+        pr.number
+    );
+    Some(reviewers)
+}
+
 /// Loads the workqueue (mapping of open PRs assigned to users) from GitHub
 pub async fn load_workqueue(
     client: &Octocrab,
@@ -314,6 +533,8 @@ pub async fn retrieve_pull_request_assignments(
                     pr.number,
                     AssignedPullRequest {
                         title: pr.title.clone().unwrap_or_default(),
+                        created_at: pr.created_at.unwrap_or_default(),
+                        active: false,
                     },
                 ));
             }
@@ -342,8 +563,28 @@ pub async fn get_assigned_prs(
         .unwrap_or_default()
 }
 
+/// Get pull request assignments for a team member across all tracked repositories,
+/// as `(repository full name, PR number, PR)` sorted by repository and PR number.
+pub async fn get_all_assigned_prs(
+    ctx: &Context,
+    user_id: UserId,
+) -> Vec<(String, PullRequestNumber, AssignedPullRequest)> {
+    let mut assigned = vec![];
+    for (repo, workqueue) in ctx.workqueue_map.tracked_repositories() {
+        if let Some(prs) = workqueue.read().await.reviewers.get(&user_id) {
+            assigned.extend(
+                prs.iter()
+                    .map(|(number, pr)| (repo.full_name(), *number, pr.clone())),
+            );
+        }
+    }
+    assigned.sort_by(|a, b| (&a.0, a.1).cmp(&(&b.0, b.1)));
+    assigned
+}
+
 /// Add a PR to the workqueue of a team member.
-/// Updates data of the pull request if it already was in the workqueue.
+/// Updates data of the pull request if it already was in the workqueue,
+/// keeping it active if it was.
 /// Ensures no accidental PR duplicates.
 ///
 /// Returns true if the PR was actually inserted.
@@ -351,14 +592,13 @@ fn upsert_pr_into_user_queue(
     workqueue: &mut RwLockWriteGuard<ReviewerWorkqueue>,
     user_id: UserId,
     pr: PullRequestNumber,
-    assigned_pr: AssignedPullRequest,
+    mut assigned_pr: AssignedPullRequest,
 ) -> bool {
-    workqueue
-        .reviewers
-        .entry(user_id)
-        .or_default()
-        .insert(pr, assigned_pr)
-        .is_none()
+    let queue = workqueue.reviewers.entry(user_id).or_default();
+    if let Some(previous) = queue.get(&pr) {
+        assigned_pr.active |= previous.active;
+    }
+    queue.insert(pr, assigned_pr).is_none()
 }
 
 /// Delete a PR from the workqueue of a team member.
@@ -418,10 +658,13 @@ mod tests {
     use crate::github::{GitHubUser, Issue, IssuesAction, IssuesEvent, Repository};
     use crate::github::{Label, PullRequestNumber};
     use crate::handlers::pr_tracking::{
-        AssignedPullRequest, handle_input, parse_input, upsert_pr_into_user_queue,
+        AssignedPullRequest, ReviewerWorkqueue, claim_pr, delete_pr_from_all_queues,
+        delete_pr_from_user_queue, get_assigned_prs, handle_input, mark_reviewed, parse_input,
+        release_pr, resync_pr, upsert_pr_into_user_queue,
     };
     use crate::tests::github::{default_test_user, issue, pull_request, user};
     use crate::tests::{TestContext, run_db_test};
+    use std::collections::{BTreeMap, BTreeSet, HashMap};
 
     #[tokio::test]
     async fn add_pr_to_workqueue_on_assign() {
@@ -495,6 +738,36 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn unassign_only_frees_the_unassigned_reviewer() {
+        run_db_test(|ctx| async move {
+            let martin = user("Martin", 2);
+            let diana = user("Diana", 3);
+            set_assigned_prs(&ctx, &martin, &[10, 11]).await;
+            set_assigned_prs(&ctx, &diana, &[10]).await;
+
+            // Martin was unassigned directly on GitHub, Diana still reviews the PR.
+            run_handler(
+                &ctx,
+                IssuesAction::Unassigned {
+                    assignee: martin.clone(),
+                },
+                pull_request()
+                    .number(10)
+                    .labels(vec!["S-waiting-on-review"])
+                    .assignees(vec![diana.clone()])
+                    .call(),
+            )
+            .await;
+
+            check_assigned_prs(&ctx, &martin, &[11]).await;
+            check_assigned_prs(&ctx, &diana, &[10]).await;
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
     #[tokio::test]
     async fn add_pr_to_workqueue_on_label() {
         run_db_test(|ctx| async move {
@@ -579,6 +852,41 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn draft_pr_does_not_consume_capacity() {
+        run_db_test(|ctx| async move {
+            let user = user("Martin", 2);
+            let pr = |draft| {
+                let mut pr = pull_request()
+                    .number(10)
+                    .labels(vec!["S-waiting-on-review"])
+                    .assignees(vec![user.clone()])
+                    .call();
+                pr.draft = draft;
+                pr
+            };
+
+            run_handler(
+                &ctx,
+                IssuesAction::Assigned {
+                    assignee: user.clone(),
+                },
+                pr(true),
+            )
+            .await;
+            check_assigned_prs(&ctx, &user, &[]).await;
+
+            run_handler(&ctx, IssuesAction::ReadyForReview, pr(false)).await;
+            check_assigned_prs(&ctx, &user, &[10]).await;
+
+            run_handler(&ctx, IssuesAction::ConvertedToDraft, pr(true)).await;
+            check_assigned_prs(&ctx, &user, &[]).await;
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
     // Make sure that we only consider pull requests, not issues.
     #[tokio::test]
     async fn ignore_issue_assignments() {
@@ -601,6 +909,174 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn claim_pr_replaces_previous_reviewers() {
+        run_db_test(|ctx| async move {
+            let previous = user("Martin", 2);
+            let claimer = user("Jana", 3);
+            set_assigned_prs(&ctx, &previous, &[10, 11]).await;
+
+            claim_pr(
+                &ctx.handler_ctx(),
+                TEST_REPO,
+                &pull_request()
+                    .number(10)
+                    .labels(vec!["S-waiting-on-review"])
+                    .call(),
+                &claimer,
+            )
+            .await;
+
+            check_assigned_prs(&ctx, &previous, &[11]).await;
+            check_assigned_prs(&ctx, &claimer, &[10]).await;
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn claim_pr_not_waiting_for_review() {
+        run_db_test(|ctx| async move {
+            let claimer = user("Jana", 3);
+
+            claim_pr(
+                &ctx.handler_ctx(),
+                TEST_REPO,
+                &pull_request().number(10).labels(vec!["S-blocked"]).call(),
+                &claimer,
+            )
+            .await;
+
+            check_assigned_prs(&ctx, &claimer, &[]).await;
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn release_pr_from_workqueue() {
+        run_db_test(|ctx| async move {
+            let reviewer = user("Martin", 2);
+            let other = user("Jana", 3);
+            set_assigned_prs(&ctx, &reviewer, &[10, 11]).await;
+            set_assigned_prs(&ctx, &other, &[10]).await;
+
+            release_pr(&ctx.handler_ctx(), TEST_REPO, 10, reviewer.id).await;
+
+            check_assigned_prs(&ctx, &reviewer, &[11]).await;
+            check_assigned_prs(&ctx, &other, &[10]).await;
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn resync_corrects_stale_assignment() {
+        run_db_test(|ctx| async move {
+            let stale = user("Martin", 2);
+            let reviewer = user("Jana", 3);
+            set_assigned_prs(&ctx, &stale, &[10, 11]).await;
+
+            let reviewers = resync_pr(
+                &ctx.handler_ctx(),
+                TEST_REPO,
+                &pull_request()
+                    .number(10)
+                    .assignees(vec![reviewer.clone()])
+                    .labels(vec!["S-waiting-on-review"])
+                    .call(),
+            )
+            .await;
+
+            assert_eq!(reviewers, Some(vec!["Jana".to_string()]));
+            check_assigned_prs(&ctx, &stale, &[11]).await;
+            check_assigned_prs(&ctx, &reviewer, &[10]).await;
+
+            assert_eq!(
+                resync_pr(
+                    &ctx.handler_ctx(),
+                    "rust-lang/untracked",
+                    &pull_request().number(10).call()
+                )
+                .await,
+                None
+            );
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn submitted_review_marks_pr_active() {
+        run_db_test(|ctx| async move {
+            let martin = user("Martin", 2);
+            let diana = user("Diana", 3);
+            set_assigned_prs(&ctx, &martin, &[10, 11]).await;
+
+            mark_reviewed(&ctx.handler_ctx(), TEST_REPO, 10, &martin).await;
+            // Reviews from users that aren't assigned don't change the workqueue
+            mark_reviewed(&ctx.handler_ctx(), TEST_REPO, 11, &diana).await;
+
+            check_active_prs(&ctx, &martin, &[10]).await;
+            check_assigned_prs(&ctx, &diana, &[]).await;
+
+            // The PR stays active when its assignment is refreshed
+            run_handler(
+                &ctx,
+                IssuesAction::Labeled {
+                    label: Label {
+                        name: "S-waiting-on-review".to_string(),
+                    },
+                },
+                pull_request()
+                    .number(10)
+                    .labels(vec!["S-waiting-on-review"])
+                    .assignees(vec![martin.clone()])
+                    .call(),
+            )
+            .await;
+            check_active_prs(&ctx, &martin, &[10]).await;
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[test]
+    fn workqueue_accessors() {
+        let workqueue = ReviewerWorkqueue::from_pr_numbers(HashMap::from([
+            (1, vec![10, 11, 12]),
+            (2, vec![20]),
+            (3, vec![]),
+        ]));
+
+        assert_eq!(
+            workqueue.assigned_pr_counts(),
+            BTreeMap::from([(1, 3), (2, 1)])
+        );
+        assert_eq!(workqueue.assigned_pr_count(1), 3);
+        assert_eq!(workqueue.assigned_pr_count(4), 0);
+        assert_eq!(workqueue.assigned_prs(1), BTreeSet::from([10, 11, 12]));
+        assert_eq!(workqueue.assigned_prs(3), BTreeSet::new());
+        assert_eq!(workqueue.assigned_prs(4), BTreeSet::new());
+    }
+
+    #[test]
+    fn workqueue_removals() {
+        let mut workqueue =
+            ReviewerWorkqueue::from_pr_numbers(HashMap::from([(1, vec![10, 11]), (2, vec![10])]));
+
+        delete_pr_from_user_queue(&mut workqueue, 1, 11);
+        assert_eq!(workqueue.assigned_prs(1), BTreeSet::from([10]));
+
+        delete_pr_from_all_queues(&mut workqueue, 10);
+        assert_eq!(workqueue.assigned_pr_counts(), BTreeMap::new());
+    }
+
     const TEST_REPO: &str = "rust-lang-test/triagebot-test";
 
     async fn check_assigned_prs(
@@ -613,17 +1089,23 @@ mod tests {
             .workqueue_map
             .get(TEST_REPO)
             .expect("test repo workqueue should exist");
-        let mut assigned = workqueue_arc
-            .read()
+        let assigned = workqueue_arc.read().await.assigned_prs(user.id);
+        assert_eq!(assigned.into_iter().collect::<Vec<_>>(), expected_prs);
+    }
+
+    async fn check_active_prs(
+        ctx: &TestContext,
+        user: &GitHubUser,
+        expected_prs: &[PullRequestNumber],
+    ) {
+        let mut active = get_assigned_prs(&ctx.handler_ctx(), TEST_REPO, user.id)
             .await
-            .reviewers
-            .get(&user.id)
-            .cloned()
-            .unwrap_or_default()
-            .into_keys()
+            .into_iter()
+            .filter(|(_, pr)| pr.active)
+            .map(|(number, _)| number)
             .collect::<Vec<_>>();
-        assigned.sort();
-        assert_eq!(assigned, expected_prs);
+        active.sort();
+        assert_eq!(active, expected_prs);
     }
 
     async fn set_assigned_prs(ctx: &TestContext, user: &GitHubUser, prs: &[PullRequestNumber]) {
@@ -641,6 +1123,8 @@ mod tests {
                     pr,
                     AssignedPullRequest {
                         title: format!("PR {pr}"),
+                        created_at: chrono::Utc::now(),
+                        active: false,
                     },
                 );
             }
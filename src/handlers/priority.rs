@@ -0,0 +1,138 @@
+//! Purpose: Allow setting the priority of an issue with `@rustbot priority <level>`,
+//! without having to know the exact names of the priority labels.
+//!
+//! The priority labels are exclusive: setting a priority removes the other
+//! priority labels from the issue. The labels are applied with the same
+//! authorization rules as the `relabel` command.
+//!
+//! Parsing is done in the `parser::command::priority` module.
+
+use crate::config::PriorityConfig;
+use crate::errors::user_error;
+use crate::github::{Event, Label};
+use crate::handlers::{Context, relabel};
+use anyhow::Context as _;
+use parser::command::priority::PriorityCommand;
+use parser::command::relabel::{self as relabel_parser, LabelDelta};
+
+pub(super) async fn handle_command(
+    ctx: &Context,
+    config: &PriorityConfig,
+    event: &Event,
+    cmd: PriorityCommand,
+) -> anyhow::Result<()> {
+    let Some(issue) = event.issue() else {
+        return user_error!("Can only set the priority of issues and pull requests");
+    };
+
+    let deltas = match priority_deltas(config, &cmd.level, &issue.labels) {
+        Ok(deltas) => deltas,
+        Err(err) => return user_error!(err),
+    };
+
+    let repo_config = crate::config::get(&ctx.github, event.repo())
+        .await
+        .context("failed to get triagebot configuration")?;
+    let Some(relabel_config) = &repo_config.relabel else {
+        return user_error!(
+            "The `priority` command requires the `relabel` feature to be enabled in this repository."
+        );
+    };
+
+    relabel::apply_deltas(ctx, relabel_config, event, issue, &deltas).await
+}
+
+/// Computes the label changes needed to set the priority of an issue
+/// currently having the `current` labels.
+fn priority_deltas(
+    config: &PriorityConfig,
+    level: &str,
+    current: &[Label],
+) -> Result<Vec<LabelDelta>, String> {
+    let Some(label) = config.label(level) else {
+        let mut levels: Vec<_> = config.levels.keys().map(|l| format!("`{l}`")).collect();
+        levels.sort();
+        return Err(format!(
+            "Unknown priority `{level}`, expected one of: {}.",
+            levels.join(", ")
+        ));
+    };
+
+    let mut deltas = vec![LabelDelta::Add(relabel_parser::Label(label.to_string()))];
+    deltas.extend(
+        current
+            .iter()
+            .filter(|l| l.name != label && config.levels.values().any(|p| *p == l.name))
+            .map(|l| LabelDelta::Remove(relabel_parser::Label(l.name.clone()))),
+    );
+    Ok(deltas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config() -> PriorityConfig {
+        PriorityConfig {
+            levels: HashMap::from([
+                ("critical".to_string(), "P-critical".to_string()),
+                ("high".to_string(), "P-high".to_string()),
+                ("medium".to_string(), "P-medium".to_string()),
+                ("low".to_string(), "P-low".to_string()),
+            ]),
+        }
+    }
+
+    fn labels(names: &[&str]) -> Vec<Label> {
+        names
+            .iter()
+            .map(|name| Label {
+                name: name.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn maps_level_to_label() {
+        assert_eq!(
+            priority_deltas(&config(), "high", &labels(&["T-compiler"])),
+            Ok(vec![LabelDelta::Add(relabel_parser::Label(
+                "P-high".to_string()
+            ))])
+        );
+        assert_eq!(
+            priority_deltas(&config(), "HIGH", &[]),
+            Ok(vec![LabelDelta::Add(relabel_parser::Label(
+                "P-high".to_string()
+            ))])
+        );
+    }
+
+    #[test]
+    fn removes_other_priorities() {
+        assert_eq!(
+            priority_deltas(
+                &config(),
+                "low",
+                &labels(&["P-high", "T-compiler", "P-medium", "P-low"])
+            ),
+            Ok(vec![
+                LabelDelta::Add(relabel_parser::Label("P-low".to_string())),
+                LabelDelta::Remove(relabel_parser::Label("P-high".to_string())),
+                LabelDelta::Remove(relabel_parser::Label("P-medium".to_string())),
+            ])
+        );
+    }
+
+    #[test]
+    fn unknown_level() {
+        assert_eq!(
+            priority_deltas(&config(), "urgent", &[]),
+            Err(
+                "Unknown priority `urgent`, expected one of: `critical`, `high`, `low`, `medium`."
+                    .to_string()
+            )
+        );
+    }
+}
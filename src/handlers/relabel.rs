@@ -6,20 +6,27 @@
 //! Parsing is done in the `parser::command::relabel` module.
 //!
 //! If the command was successful, there will be no feedback beyond the label change to reduce
-//! notification noise.
+//! notification noise, except with `--dry-run` where the bot only comments with the label
+//! changes it would have made, and with `explain` where it only comments with the patterns
+//! deciding whether the user can make each of the label changes. With `--if-open` or
+//! `--if-closed`, the bot comments instead when the issue is not in that state.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
 
+use crate::db::issue_data::IssueData;
 use crate::errors::user_error;
 use crate::github::Label;
 use crate::team_data::TeamClient;
 use crate::{
     config::RelabelConfig,
-    github::{self, Event},
+    github::{self, Event, GithubClient, IssueRepository},
     handlers::Context,
 };
 use anyhow::Context as _;
-use parser::command::relabel::{LabelDelta, RelabelCommand};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parser::command::relabel::{LabelDelta, LabelReason, RelabelCommand, StateCondition};
 
 use tracing as log;
 
@@ -33,14 +40,318 @@ pub(super) async fn handle_command(
         return user_error!("Can only add and remove labels on issues and pull requests");
     };
 
+    if let Some(condition) = input.if_state
+        && let Some(message) = state_mismatch_message(issue, condition)
+    {
+        issue
+            .post_comment(&ctx.github, &message)
+            .await
+            .context("failed to post the relabel skip comment")?;
+        return Ok(());
+    }
+
+    // Expand the requested preset (if any) into its deltas.
+    let input = match config.expand_preset(input) {
+        Ok(input) => input,
+        Err(err) => return user_error!(err),
+    };
+
+    if let Some(number) = input.copy_from {
+        return copy_labels(ctx, config, event, issue, number, input.dry_run).await;
+    }
+
     // If the input matches a valid alias, read the [relabel] config.
     // if any alias matches, extract the alias config (RelabelAliasConfig) and build a new RelabelCommand.
-    let new_input = config.retrieve_command_from_alias(input);
+    let new_input = match config.retrieve_command_from_alias(input) {
+        Ok(input) => input,
+        Err(err) => return user_error!(err),
+    };
+
+    if new_input.explain {
+        let membership = is_member(&event.user(), &ctx.team).await;
+        let is_issue_author = event.user().id == issue.user.id;
+        let message = match explain_message(
+            issue,
+            &new_input.deltas,
+            config,
+            membership,
+            is_issue_author,
+        ) {
+            Ok(message) => message,
+            Err(err) => return user_error!(err),
+        };
+        issue
+            .post_comment(&ctx.github, &message)
+            .await
+            .context("failed to post the relabel explanation comment")?;
+        return Ok(());
+    }
+
+    // Resolve the toggled labels against the current labels of the issue,
+    // before anything else is checked.
+    let new_input = RelabelCommand {
+        deltas: resolve_toggles(issue, new_input.deltas),
+        ..new_input
+    };
+
+    let deprecated = deprecated_labels(config, &new_input.deltas);
+    if let Some(deprecated) = &deprecated
+        && deprecated.block
+    {
+        return user_error!(deprecated.message.clone());
+    }
+
+    if new_input.dry_run {
+        authorize_deltas(ctx, config, event, issue, &new_input.deltas).await?;
+        let (to_add, to_remove) = compute_label_deltas(issue, &new_input.deltas);
+        let mut message = dry_run_message(issue_relabel(issue, &to_add, &to_remove));
+        if let Some(deprecated) = &deprecated {
+            message.push_str("\n\n");
+            message.push_str(&deprecated.message);
+        }
+        issue
+            .post_comment(&ctx.github, &message)
+            .await
+            .context("failed to post the relabel dry-run comment")?;
+        return Ok(());
+    }
+
+    apply_deltas(ctx, config, event, issue, &new_input.deltas).await?;
+
+    if let Some(deprecated) = deprecated {
+        issue
+            .post_comment(&ctx.github, &deprecated.message)
+            .await
+            .context("failed to post the deprecated labels warning")?;
+    }
+
+    if !new_input.reasons.is_empty() {
+        record_reasons(ctx, issue, &event.user().login, &new_input.reasons).await?;
+        if config.post_reasons {
+            issue
+                .post_comment(
+                    &ctx.github,
+                    &reasons_message(&event.user().login, &new_input.reasons),
+                )
+                .await
+                .context("failed to post the relabel reasons comment")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The note explaining why the label changes were skipped, `None` if the issue
+/// is in the state required by `condition`.
+fn state_mismatch_message(issue: &github::Issue, condition: StateCondition) -> Option<String> {
+    let (flag, state) = match condition {
+        StateCondition::Open if !issue.is_open() => ("--if-open", "closed"),
+        StateCondition::Closed if issue.is_open() => ("--if-closed", "open"),
+        _ => return None,
+    };
+    Some(format!(
+        "No labels were changed, as this {} is {state} (`{flag}`).",
+        if issue.is_pr() {
+            "pull request"
+        } else {
+            "issue"
+        }
+    ))
+}
+
+/// Resolves the `~label` toggles to the removal of the labels present on
+/// the issue, and to the addition of the missing ones.
+fn resolve_toggles(issue: &github::Issue, deltas: Vec<LabelDelta>) -> Vec<LabelDelta> {
+    deltas
+        .into_iter()
+        .map(|delta| resolve_toggle(issue, delta))
+        .collect()
+}
+
+fn resolve_toggle(issue: &github::Issue, delta: LabelDelta) -> LabelDelta {
+    match delta {
+        LabelDelta::Toggle(label) => {
+            let present = issue.contains_label(&Label {
+                name: label.0.clone(),
+            });
+            if present {
+                LabelDelta::Remove(label)
+            } else {
+                LabelDelta::Add(label)
+            }
+        }
+        delta => delta,
+    }
+}
+
+/// The deprecated labels added by a relabel command.
+#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
+struct DeprecatedLabels {
+    /// The warning (or error, when blocked) listing the labels and their replacement.
+    message: String,
+    /// Whether one of the labels can't be added anymore.
+    block: bool,
+}
+
+/// Checks the labels added by the deltas against the `deprecated` labels of the config.
+fn deprecated_labels(config: &RelabelConfig, deltas: &[LabelDelta]) -> Option<DeprecatedLabels> {
+    let mut lines = Vec::new();
+    let mut block = false;
+    for delta in deltas {
+        let LabelDelta::Add(label) = delta else {
+            continue;
+        };
+        let Some(deprecated) = config.deprecated.get(&label.0) else {
+            continue;
+        };
+        block |= deprecated.block;
+        lines.push(match &deprecated.replacement {
+            Some(replacement) => {
+                format!(
+                    "- `{}` is deprecated, use `{replacement}` instead.",
+                    label.0
+                )
+            }
+            None => format!("- `{}` is deprecated.", label.0),
+        });
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    let header = if block {
+        "No labels were changed, some of them can't be added anymore:"
+    } else {
+        "Some of the added labels are deprecated:"
+    };
+    Some(DeprecatedLabels {
+        message: format!("{header}\n\n{}", lines.join("\n")),
+        block,
+    })
+}
+
+/// Lists the reasons given for the added labels.
+fn reasons_message(user: &str, reasons: &[LabelReason]) -> String {
+    let mut message = format!("Labels added by `{user}` with a reason:\n");
+    for LabelReason { label, reason } in reasons {
+        // The reasons are free text, keep their mentions from pinging anyone.
+        let reason = reason.replace('@', "@\u{200B}");
+        message.push_str(&format!("\n- `{}`: {reason}", label.0));
+    }
+    message
+}
+
+const RELABEL_REASONS_KEY: &str = "relabel-reasons";
+
+/// The reasons given for the labels added to an issue, with who gave them.
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct RelabelReasons {
+    reasons: Vec<RecordedReason>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct RecordedReason {
+    label: String,
+    reason: String,
+    user: String,
+    added_at: DateTime<Utc>,
+}
+
+/// Logs the reasons given by `user` for the added labels, and records them
+/// in the issue data.
+async fn record_reasons(
+    ctx: &Context,
+    issue: &github::Issue,
+    user: &str,
+    reasons: &[LabelReason],
+) -> anyhow::Result<()> {
+    for LabelReason { label, reason } in reasons {
+        log::info!(
+            "{user} added the label `{}` to {}: {reason}",
+            label.0,
+            issue.global_id()
+        );
+    }
+
+    if crate::dry_run::skip(format_args!(
+        "recorded the label reasons of {} on {}",
+        user,
+        issue.global_id()
+    )) {
+        return Ok(());
+    }
+
+    let mut db = ctx.db.get().await;
+    let mut state: IssueData<'_, RelabelReasons> =
+        IssueData::load(&mut db, issue, RELABEL_REASONS_KEY).await?;
+    let added_at = Utc::now();
+    state.data.reasons.extend(
+        reasons
+            .iter()
+            .map(|LabelReason { label, reason }| RecordedReason {
+                label: label.0.clone(),
+                reason: reason.clone(),
+                user: user.to_string(),
+                added_at,
+            }),
+    );
+    state.save().await?;
+    Ok(())
+}
+
+/// Applies the label deltas to the issue, after checking that the user
+/// is allowed to set or remove each of the labels.
+pub(super) async fn apply_deltas(
+    ctx: &Context,
+    config: &RelabelConfig,
+    event: &Event,
+    issue: &github::Issue,
+    deltas: &[LabelDelta],
+) -> anyhow::Result<()> {
+    authorize_deltas(ctx, config, event, issue, deltas).await?;
+    apply_label_deltas(ctx, issue, deltas).await
+}
+
+/// Applies the label deltas to the issue, without any authorization check.
+pub(super) async fn apply_label_deltas(
+    ctx: &Context,
+    issue: &github::Issue,
+    deltas: &[LabelDelta],
+) -> anyhow::Result<()> {
+    // Compute the labels to add and remove
+    let (to_add, to_remove) = compute_label_deltas(issue, deltas);
+
+    // Add labels
+    issue
+        .add_labels(&ctx.github, to_add.clone())
+        .await
+        .context("failed to add labels to the issue")?;
+
+    // Remove labels
+    issue
+        .remove_labels(&ctx.github, to_remove.clone())
+        .await
+        .context("failed to remove labels from the issue")?;
+
+    Ok(())
+}
+
+/// Checks that the user is allowed to set or remove each of the labels.
+async fn authorize_deltas(
+    ctx: &Context,
+    config: &RelabelConfig,
+    event: &Event,
+    issue: &github::Issue,
+    deltas: &[LabelDelta],
+) -> anyhow::Result<()> {
+    let is_issue_author = event.user().id == issue.user.id;
+
+    let membership = is_member(&event.user(), &ctx.team).await;
+    check_account_age(ctx, config, event, membership).await?;
 
     // Check label authorization for the current user
-    for delta in &new_input.0 {
+    for delta in deltas {
         let name = delta.label() as &str;
-        let err = match check_filter(name, config, is_member(&event.user(), &ctx.team).await) {
+        let err = match check_filter(delta, config, membership, is_issue_author) {
             Ok(CheckFilterResult::Allow) => None,
             Ok(CheckFilterResult::Deny) => {
                 Some(format!("Label {name} can only be set by Rust team members"))
@@ -58,25 +369,397 @@ pub(super) async fn handle_command(
         }
     }
 
-    // Compute the labels to add and remove
-    let (to_add, to_remove) = compute_label_deltas(&new_input.0);
+    Ok(())
+}
 
-    // Add labels
-    issue
-        .add_labels(&ctx.github, to_add.clone())
+/// Declines the label commands of non-team members whose GitHub account is younger
+/// than `min_account_age_days`, unless they already interacted with the repository.
+async fn check_account_age(
+    ctx: &Context,
+    config: &RelabelConfig,
+    event: &Event,
+    membership: TeamMembership,
+) -> anyhow::Result<()> {
+    let Some(min_age_days) = config.min_account_age_days else {
+        return Ok(());
+    };
+    if membership == TeamMembership::Member || has_interacted(event) {
+        return Ok(());
+    }
+
+    let user = event.user();
+    // Fail closed: an account whose age can't be checked is treated as too new.
+    let created_at = match ctx.github.user_info(&user.login).await {
+        Ok(info) => Some(info.created_at),
+        Err(err) => {
+            log::warn!(
+                "failed to check the account age of {}, declining the label changes: {err:?}",
+                user.login
+            );
+            None
+        }
+    };
+    if let Some(message) = account_age_decline(created_at, Utc::now(), min_age_days) {
+        return user_error!(message);
+    }
+    Ok(())
+}
+
+/// Whether the author of the event already contributed to or is part of the repository.
+fn has_interacted(event: &Event) -> bool {
+    match event {
+        Event::IssueComment(e) => !e.comment.author_association.is_probably_first_timer(),
+        Event::Issue(e) => !e.issue.author_association.is_probably_first_timer(),
+        _ => false,
+    }
+}
+
+/// Returns the message declining the label changes of an account created less
+/// than `min_age_days` days before `now`, or whose creation date is unknown.
+fn account_age_decline(
+    created_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    min_age_days: u32,
+) -> Option<String> {
+    if let Some(created_at) = created_at
+        && now - created_at >= chrono::Duration::days(min_age_days.into())
+    {
+        return None;
+    }
+    Some(format!(
+        "Thanks for helping with the triage! Label changes from GitHub accounts \
+         created less than {min_age_days} days ago aren't applied automatically, \
+         a team member will take a look instead."
+    ))
+}
+
+/// Adds the labels of another issue of the repository, for `relabel copy-from #123`.
+///
+/// Labels the user isn't allowed to set are skipped, with a note.
+async fn copy_labels(
+    ctx: &Context,
+    config: &RelabelConfig,
+    event: &Event,
+    issue: &github::Issue,
+    number: u64,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    if number == issue.number {
+        return user_error!("Labels cannot be copied from the issue itself.");
+    }
+
+    let source = ctx
+        .github
+        .issue(issue.repository(), number)
         .await
-        .context("failed to add labels to the issue")?;
+        .context("failed to get the issue to copy the labels from")?;
+    let membership = is_member(&event.user(), &ctx.team).await;
+    check_account_age(ctx, config, event, membership).await?;
+    let is_issue_author = event.user().id == issue.user.id;
+    let (allowed, denied) =
+        match filter_copied_labels(&source.labels, config, membership, is_issue_author) {
+            Ok(filtered) => filtered,
+            Err(err) => return user_error!(err),
+        };
 
-    // Remove labels
-    issue
-        .remove_labels(&ctx.github, to_remove.clone())
+    let mut message = String::new();
+    if dry_run {
+        let (to_add, to_remove) = compute_label_deltas(issue, &allowed);
+        message = dry_run_message(issue_relabel(issue, &to_add, &to_remove));
+    } else {
+        apply_label_deltas(ctx, issue, &allowed).await?;
+    }
+    if !denied.is_empty() {
+        if !message.is_empty() {
+            message.push_str("\n\n");
+        }
+        message.push_str(&format!(
+            "Skipped copying {} from #{number}, only Rust team members can set them.",
+            denied
+                .iter()
+                .map(|label| format!("`{label}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    if !message.is_empty() {
+        issue
+            .post_comment(&ctx.github, &message)
+            .await
+            .context("failed to post the relabel copy comment")?;
+    }
+    Ok(())
+}
+
+/// Splits the labels to copy between the deltas the user is allowed to
+/// apply and the names of the denied labels.
+fn filter_copied_labels(
+    labels: &[Label],
+    config: &RelabelConfig,
+    membership: TeamMembership,
+    is_issue_author: bool,
+) -> Result<(Vec<LabelDelta>, Vec<String>), String> {
+    let mut allowed = Vec::new();
+    let mut denied = Vec::new();
+    for label in labels {
+        let delta = LabelDelta::Add(parser::command::relabel::Label(label.name.clone()));
+        match check_filter(&delta, config, membership, is_issue_author)? {
+            CheckFilterResult::Allow => allowed.push(delta),
+            CheckFilterResult::Deny | CheckFilterResult::DenyUnknown => {
+                denied.push(label.name.clone())
+            }
+        }
+    }
+    Ok((allowed, denied))
+}
+
+/// Describes the label changes a `--dry-run` relabel would have made.
+fn dry_run_message(relabel: Option<IssueRelabel>) -> String {
+    let Some(relabel) = relabel else {
+        return "Dry run: this would not change any label.".to_string();
+    };
+    let list = |labels: &[String]| {
+        labels
+            .iter()
+            .map(|label| format!("`{label}`"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut message = "Dry run: this would".to_string();
+    if !relabel.add.is_empty() {
+        message.push_str(&format!(" add {}", list(&relabel.add)));
+    }
+    if !relabel.remove.is_empty() {
+        if !relabel.add.is_empty() {
+            message.push_str(" and");
+        }
+        message.push_str(&format!(" remove {}", list(&relabel.remove)));
+    }
+    message.push('.');
+    message
+}
+
+/// Delay between the relabel of two issues by a bulk relabel, to stay well
+/// within the GitHub secondary rate limits.
+const BULK_RELABEL_DELAY: Duration = Duration::from_secs(1);
+
+/// The label changes of a single issue by a bulk relabel.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct IssueRelabel {
+    pub(crate) repository: String,
+    pub(crate) number: u64,
+    pub(crate) add: Vec<String>,
+    pub(crate) remove: Vec<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct BulkRelabelReport {
+    pub(crate) dry_run: bool,
+    /// Number of issues matching the search query.
+    pub(crate) matched: usize,
+    /// Issues which are being changed in the background (or would be, in dry-run mode).
+    pub(crate) changed: Vec<IssueRelabel>,
+}
+
+/// Applies the label deltas to all the issues matching a GitHub search query.
+///
+/// Issues where the deltas are a no-op are skipped. The issues are relabeled
+/// by a job, as relabeling them one by one can take a while; the failures are
+/// logged. In dry-run mode, nothing is changed on GitHub and the report only
+/// lists the changes that would be made.
+pub(crate) async fn bulk_relabel(
+    ctx: &Context,
+    query: &str,
+    deltas: &[LabelDelta],
+    dry_run: bool,
+) -> anyhow::Result<BulkRelabelReport> {
+    let issues = ctx.github.search_issues(query).await?;
+    let changed: Vec<_> = plan_bulk_relabel(&issues, deltas)
+        .into_iter()
+        .map(|(_, relabel)| relabel)
+        .collect();
+
+    if !dry_run && !changed.is_empty() {
+        let args = BulkRelabelJobArgs {
+            issues: changed.clone(),
+        };
+        crate::db::schedule_job(
+            &*ctx.db.get().await,
+            BULK_RELABEL_JOB_NAME,
+            serde_json::to_value(args)?,
+            Utc::now(),
+        )
         .await
-        .context("failed to remove labels from the issue")?;
+        .context("failed to schedule the bulk relabel")?;
+    }
+
+    log::info!(
+        "bulk relabel of `{query}` (dry run: {dry_run}): changing {} of {} issues",
+        changed.len(),
+        issues.len()
+    );
+    Ok(BulkRelabelReport {
+        dry_run,
+        matched: issues.len(),
+        changed,
+    })
+}
+
+/// Selects the issues actually changed by a bulk relabel, skipping the no-ops.
+fn plan_bulk_relabel<'a>(
+    issues: &'a [github::Issue],
+    deltas: &[LabelDelta],
+) -> Vec<(&'a github::Issue, IssueRelabel)> {
+    issues
+        .iter()
+        .filter_map(|issue| {
+            let (to_add, to_remove) = compute_label_deltas(issue, deltas);
+            Some((issue, issue_relabel(issue, &to_add, &to_remove)?))
+        })
+        .collect()
+}
+
+/// Computes the labels actually changed on the issue, or `None` if the relabel is a no-op.
+fn issue_relabel(
+    issue: &github::Issue,
+    to_add: &[Label],
+    to_remove: &[Label],
+) -> Option<IssueRelabel> {
+    let add: Vec<_> = to_add
+        .iter()
+        .filter(|label| !issue.contains_label(label))
+        .map(|label| label.name.clone())
+        .collect();
+    let remove: Vec<_> = to_remove
+        .iter()
+        .filter(|label| issue.contains_label(label))
+        .map(|label| label.name.clone())
+        .collect();
+    if add.is_empty() && remove.is_empty() {
+        return None;
+    }
+    Some(IssueRelabel {
+        repository: issue.repository().to_string(),
+        number: issue.number,
+        add,
+        remove,
+    })
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BulkRelabelJobArgs {
+    issues: Vec<IssueRelabel>,
+}
 
+pub(crate) struct BulkRelabelJob;
+
+const BULK_RELABEL_JOB_NAME: &str = "bulk_relabel";
+
+#[async_trait]
+impl crate::jobs::Job for BulkRelabelJob {
+    fn name(&self) -> &str {
+        BULK_RELABEL_JOB_NAME
+    }
+
+    async fn run(&self, ctx: &Context, metadata: &serde_json::Value) -> anyhow::Result<()> {
+        let args: BulkRelabelJobArgs =
+            serde_json::from_value(metadata.clone()).with_context(|| {
+                format!("failed to deserialize the metadata {metadata:?} into args")
+            })?;
+
+        // Each repository is relabeled in its own dry-run mode
+        let mut by_repository: BTreeMap<&str, Vec<IssueRelabel>> = BTreeMap::new();
+        for relabel in &args.issues {
+            by_repository
+                .entry(&relabel.repository)
+                .or_default()
+                .push(relabel.clone());
+        }
+        let mut failures = Vec::new();
+        for (repository, issues) in by_repository {
+            match crate::dry_run::is_enabled_for(&ctx.github, repository).await {
+                Ok(dry_run) => failures.extend(
+                    crate::dry_run::scope(dry_run, relabel_issues(&ctx.github, &issues)).await,
+                ),
+                Err(err) => failures.extend(issues.iter().map(|relabel| {
+                    anyhow::anyhow!(
+                        "failed to relabel {}#{}: {err:#}",
+                        relabel.repository,
+                        relabel.number
+                    )
+                })),
+            }
+        }
+        log::info!(
+            "bulk relabel: {} of {} issues relabeled",
+            args.issues.len() - failures.len(),
+            args.issues.len()
+        );
+        // The failures aren't retried, the relabel can be requested again as
+        // it skips the issues already relabeled.
+        for failure in failures {
+            log::error!("bulk relabel: {failure:?}");
+        }
+        Ok(())
+    }
+}
+
+/// Applies the planned label changes to the issues, returning the failures.
+///
+/// The changes are recomputed against the current labels of each issue, so
+/// the ones already made since they were planned (e.g. by a previous
+/// interrupted run) are skipped.
+async fn relabel_issues(github: &GithubClient, issues: &[IssueRelabel]) -> Vec<anyhow::Error> {
+    let mut failures = Vec::new();
+    for (idx, relabel) in issues.iter().enumerate() {
+        if idx > 0 {
+            tokio::time::sleep(BULK_RELABEL_DELAY).await;
+        }
+        if let Err(err) = relabel_issue(github, relabel).await {
+            failures.push(err.context(format!(
+                "failed to relabel {}#{}",
+                relabel.repository, relabel.number
+            )));
+        }
+    }
+    failures
+}
+
+async fn relabel_issue(github: &GithubClient, planned: &IssueRelabel) -> anyhow::Result<()> {
+    let (organization, repository) = planned
+        .repository
+        .split_once('/')
+        .with_context(|| format!("invalid repository `{}`", planned.repository))?;
+    let repo = IssueRepository {
+        organization: organization.to_string(),
+        repository: repository.to_string(),
+    };
+    let issue = github.issue(&repo, planned.number).await?;
+
+    let labels = |names: &[String]| -> Vec<Label> {
+        names
+            .iter()
+            .map(|name| Label { name: name.clone() })
+            .collect()
+    };
+    let Some(relabel) = issue_relabel(&issue, &labels(&planned.add), &labels(&planned.remove))
+    else {
+        log::info!("bulk relabel: {} is already relabeled", issue.global_id());
+        return Ok(());
+    };
+
+    issue
+        .add_labels(github, labels(&relabel.add))
+        .await
+        .context("failed to add labels")?;
+    issue
+        .remove_labels(github, labels(&relabel.remove))
+        .await
+        .context("failed to remove labels")?;
     Ok(())
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum TeamMembership {
     Member,
     Outsider,
@@ -102,23 +785,123 @@ enum CheckFilterResult {
 }
 
 /// Check if the team member is allowed to apply labels
-/// configured in `allow_unauthenticated`
+/// configured in `allow_unauthenticated`, or if the issue author
+/// is removing a label configured in `author_can_remove`
 fn check_filter(
-    label: &str,
+    delta: &LabelDelta,
     config: &RelabelConfig,
     is_member: TeamMembership,
+    is_issue_author: bool,
 ) -> Result<CheckFilterResult, String> {
     if is_member == TeamMembership::Member {
         return Ok(CheckFilterResult::Allow);
     }
-    let mut matched = false;
-    for pattern in &config.allow_unauthenticated {
+    let label = delta.label() as &str;
+    if is_issue_author
+        && matches!(delta, LabelDelta::Remove(_))
+        && matches_patterns(&config.author_can_remove, label)?
+    {
+        return Ok(CheckFilterResult::Allow);
+    }
+    let matched = matches_patterns(&config.allow_unauthenticated, label)?;
+    if matched {
+        Ok(CheckFilterResult::Allow)
+    } else if is_member == TeamMembership::Outsider {
+        Ok(CheckFilterResult::Deny)
+    } else {
+        Ok(CheckFilterResult::DenyUnknown)
+    }
+}
+
+/// Explains, for `relabel explain`, which patterns allow or deny each of the
+/// label changes and the resulting decision, without applying anything.
+fn explain_message(
+    issue: &github::Issue,
+    deltas: &[LabelDelta],
+    config: &RelabelConfig,
+    membership: TeamMembership,
+    is_issue_author: bool,
+) -> Result<String, String> {
+    let describe = |pattern: Option<(&str, bool)>, option: &str| match pattern {
+        Some((pattern, true)) => format!("allowed by `{pattern}` in `{option}`"),
+        Some((pattern, false)) => format!("denied by `{pattern}` in `{option}`"),
+        None => format!("no pattern of `{option}` matches"),
+    };
+
+    let mut message = "Explanation of the label changes, nothing was changed:\n".to_string();
+    for delta in deltas {
+        let label = delta.label() as &str;
+        let sign = match delta {
+            LabelDelta::Add(_) => '+',
+            LabelDelta::Remove(_) => '-',
+            LabelDelta::Toggle(_) => '~',
+        };
+        // The toggles are explained as the change they resolve to.
+        let resolved = resolve_toggle(issue, delta.clone());
+        let author_patterns = match resolved {
+            LabelDelta::Remove(_) if is_issue_author => {
+                Some(deciding_pattern(&config.author_can_remove, label)?)
+            }
+            _ => None,
+        };
+
+        let mut details = Vec::new();
+        if let LabelDelta::Toggle(_) = delta {
+            details.push(match resolved {
+                LabelDelta::Remove(_) => "removes the present label".to_string(),
+                _ => "adds the missing label".to_string(),
+            });
+        }
+        if let Some(pattern) = author_patterns {
+            details.push(describe(pattern, "author-can-remove"));
+        }
+        details.push(describe(
+            deciding_pattern(&config.allow_unauthenticated, label)?,
+            "allow-unauthenticated",
+        ));
+
+        let decision = match check_filter(&resolved, config, membership, is_issue_author)? {
+            CheckFilterResult::Allow if membership == TeamMembership::Member => {
+                "allowed, you are a team member"
+            }
+            CheckFilterResult::Allow => "allowed",
+            CheckFilterResult::Deny => "denied, only team members can set it",
+            CheckFilterResult::DenyUnknown => {
+                "denied, we were unable to check if you are a team member"
+            }
+        };
+        message.push_str(&format!(
+            "\n- `{sign}{label}`: {}; **{decision}**",
+            details.join(", ")
+        ));
+    }
+    Ok(message)
+}
+
+/// Whether the label is allowed by the patterns, an explicit deny
+/// (`!pattern`) overriding any allowed pattern.
+fn matches_patterns(patterns: &[String], label: &str) -> Result<bool, String> {
+    Ok(matches!(
+        deciding_pattern(patterns, label)?,
+        Some((_, true))
+    ))
+}
+
+/// The pattern deciding whether the label is allowed by the patterns (`true`)
+/// or denied by an explicit `!pattern` (`false`), if any pattern matches.
+fn deciding_pattern<'a>(
+    patterns: &'a [String],
+    label: &str,
+) -> Result<Option<(&'a str, bool)>, String> {
+    let mut decision = None;
+    for pattern in patterns {
         match match_pattern(pattern, label) {
-            Ok(MatchPatternResult::Allow) => matched = true,
+            Ok(MatchPatternResult::Allow) => {
+                decision.get_or_insert((pattern.as_str(), true));
+            }
             Ok(MatchPatternResult::Deny) => {
                 // An explicit deny overrides any allowed pattern
-                matched = false;
-                break;
+                return Ok(Some((pattern.as_str(), false)));
             }
             Ok(MatchPatternResult::NoMatch) => {}
             Err(err) => {
@@ -127,13 +910,7 @@ fn check_filter(
             }
         }
     }
-    if matched {
-        Ok(CheckFilterResult::Allow)
-    } else if is_member == TeamMembership::Outsider {
-        Ok(CheckFilterResult::Deny)
-    } else {
-        Ok(CheckFilterResult::DenyUnknown)
-    }
+    Ok(decision)
 }
 
 #[cfg_attr(test, derive(Debug, PartialEq, Eq))]
@@ -161,28 +938,28 @@ fn match_pattern(pattern: &str, label: &str) -> anyhow::Result<MatchPatternResul
     })
 }
 
-fn compute_label_deltas(deltas: &[LabelDelta]) -> (Vec<Label>, Vec<Label>) {
+/// Computes the labels to add to and remove from the issue, the toggles being
+/// resolved against its current labels.
+fn compute_label_deltas(issue: &github::Issue, deltas: &[LabelDelta]) -> (Vec<Label>, Vec<Label>) {
     let mut add = BTreeSet::new();
     let mut remove = BTreeSet::new();
 
     for delta in deltas {
-        match delta {
-            LabelDelta::Add(label) => {
-                let label = Label {
-                    name: label.to_string(),
-                };
-                if !remove.remove(&label) {
-                    add.insert(label);
-                }
-            }
-            LabelDelta::Remove(label) => {
-                let label = Label {
-                    name: label.to_string(),
-                };
-                if !add.remove(&label) {
-                    remove.insert(label);
-                }
-            }
+        let label = Label {
+            name: delta.label().to_string(),
+        };
+        let added = match delta {
+            LabelDelta::Add(_) => true,
+            LabelDelta::Remove(_) => false,
+            LabelDelta::Toggle(_) => !issue.contains_label(&label),
+        };
+        let (to, from) = if added {
+            (&mut add, &mut remove)
+        } else {
+            (&mut remove, &mut add)
+        };
+        if !from.remove(&label) {
+            to.insert(label);
         }
     }
 
@@ -191,16 +968,179 @@ fn compute_label_deltas(deltas: &[LabelDelta]) -> (Vec<Label>, Vec<Label>) {
 
 #[cfg(test)]
 mod tests {
-    use parser::command::relabel::{Label, LabelDelta};
+    use parser::command::relabel::{
+        Label, LabelDelta, LabelReason, RelabelCommand, StateCondition,
+    };
     use std::collections::HashMap;
 
     use super::{
-        CheckFilterResult, MatchPatternResult, TeamMembership, check_filter, compute_label_deltas,
-        match_pattern,
+        CheckFilterResult, DeprecatedLabels, IssueRelabel, MatchPatternResult, RELABEL_REASONS_KEY,
+        RelabelReasons, TeamMembership, account_age_decline, bulk_relabel, check_filter,
+        compute_label_deltas, deprecated_labels, dry_run_message, explain_message,
+        filter_copied_labels, handle_command, issue_relabel, match_pattern, plan_bulk_relabel,
+        reasons_message, record_reasons, relabel_issues, resolve_toggles, state_mismatch_message,
     };
-    use crate::config::RelabelConfig;
-    use crate::github::Label as GitHubLabel;
-    use crate::tests::github::issue;
+    use crate::config::{DeprecatedLabelConfig, RelabelConfig};
+    use crate::db::issue_data::IssueData;
+    use crate::github::{IssueState, Label as GitHubLabel};
+    use crate::tests::github::{comment_event, issue, issue_json};
+    use crate::tests::{mock_github_client, offline_context, run_db_test};
+    use axum::extract::Path;
+    use axum::http::StatusCode;
+    use axum::{Json, Router, routing::get};
+
+    #[tokio::test]
+    async fn dry_run_makes_no_external_calls() {
+        let github = crate::tests::unreachable_github_client();
+        let issue = issue().labels(vec!["A-old"]).call();
+        let label = |name: &str| GitHubLabel {
+            name: name.to_string(),
+        };
+
+        crate::dry_run::scope(true, async {
+            issue
+                .add_labels(&github, vec![label("A-new")])
+                .await
+                .unwrap();
+            issue
+                .remove_labels(&github, vec![label("A-old")])
+                .await
+                .unwrap();
+            issue.post_comment(&github, "reasons").await.unwrap();
+        })
+        .await;
+
+        // Outside of the dry-run, the same calls reach the (unreachable) GitHub API.
+        assert!(
+            issue
+                .add_labels(&github, vec![label("A-new")])
+                .await
+                .is_err()
+        );
+        assert!(
+            issue
+                .remove_labels(&github, vec![label("A-old")])
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn dry_run_handler_makes_no_external_calls() {
+        let ctx = crate::tests::offline_context();
+        let config = RelabelConfig {
+            allow_unauthenticated: vec!["A-*".to_string(), "C-*".to_string()],
+            ..deprecated_config(false)
+        };
+        let event = comment_event()
+            .issue(issue().labels(vec!["C-bug"]).call())
+            .call();
+        let input = RelabelCommand {
+            deltas: vec![
+                LabelDelta::Add(Label("A-new".to_string())),
+                LabelDelta::Remove(Label("C-bug".to_string())),
+            ],
+            ..Default::default()
+        };
+
+        let (result, actions) =
+            crate::dry_run::record(handle_command(&ctx, &config, &event, input)).await;
+        result.unwrap();
+        assert_eq!(
+            actions,
+            vec![
+                r#"added labels ["A-new"] to rust-lang/rust#1"#.to_string(),
+                r#"removed labels ["C-bug"] from rust-lang/rust#1"#.to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn cannot_copy_labels_from_itself() {
+        let ctx = crate::tests::offline_context();
+        let event = comment_event().issue(issue().call()).call();
+        let input = RelabelCommand {
+            copy_from: Some(1),
+            ..Default::default()
+        };
+
+        let err = handle_command(&ctx, &deprecated_config(false), &event, input)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Labels cannot be copied from the issue itself."
+        );
+    }
+
+    fn deprecated_config(block: bool) -> RelabelConfig {
+        RelabelConfig {
+            allow_unauthenticated: vec![],
+            author_can_remove: vec![],
+            presets: HashMap::new(),
+            post_reasons: false,
+            deprecated: HashMap::from([
+                (
+                    "A-old".to_string(),
+                    DeprecatedLabelConfig {
+                        replacement: Some("A-new".to_string()),
+                        block: false,
+                    },
+                ),
+                (
+                    "I-old".to_string(),
+                    DeprecatedLabelConfig {
+                        replacement: None,
+                        block,
+                    },
+                ),
+            ]),
+            min_account_age_days: None,
+            aliases: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn deprecated_labels_warn_and_apply() {
+        let deltas = vec![
+            LabelDelta::Add(Label("A-old".into())),
+            LabelDelta::Add(Label("I-old".into())),
+            LabelDelta::Add(Label("T-compiler".into())),
+            LabelDelta::Remove(Label("A-old".into())),
+        ];
+        assert_eq!(
+            deprecated_labels(&deprecated_config(false), &deltas),
+            Some(DeprecatedLabels {
+                message: "Some of the added labels are deprecated:\n\n\
+                    - `A-old` is deprecated, use `A-new` instead.\n\
+                    - `I-old` is deprecated."
+                    .to_string(),
+                block: false,
+            })
+        );
+
+        // Removing a deprecated label is always fine
+        let deltas = vec![LabelDelta::Remove(Label("I-old".into()))];
+        assert_eq!(deprecated_labels(&deprecated_config(true), &deltas), None);
+    }
+
+    #[test]
+    fn deprecated_labels_block() {
+        let deltas = vec![
+            LabelDelta::Add(Label("A-old".into())),
+            LabelDelta::Add(Label("I-old".into())),
+        ];
+        assert_eq!(
+            deprecated_labels(&deprecated_config(true), &deltas),
+            Some(DeprecatedLabels {
+                message: "No labels were changed, some of them can't be added anymore:\n\n\
+                    - `A-old` is deprecated, use `A-new` instead.\n\
+                    - `I-old` is deprecated."
+                    .to_string(),
+                block: true,
+            })
+        );
+    }
 
     #[test]
     fn test_match_pattern() -> anyhow::Result<()> {
@@ -233,10 +1173,20 @@ mod tests {
             ($($member:ident { $($label:expr => $res:ident,)* })*) => {
                 let config = RelabelConfig {
                     allow_unauthenticated: vec!["T-*".into(), "I-*".into(), "!I-*nominated".into()],
+                    author_can_remove: vec![],
+                    presets: HashMap::new(),
+                    post_reasons: false,
+                    deprecated: HashMap::new(),
+                    min_account_age_days: None,
                     aliases: HashMap::new()
                 };
                 $($(assert_eq!(
-                    check_filter($label, &config, TeamMembership::$member),
+                    check_filter(
+                        &LabelDelta::Add(Label($label.into())),
+                        &config,
+                        TeamMembership::$member,
+                        false,
+                    ),
                     Ok(CheckFilterResult::$res)
                 );)*)*
             }
@@ -267,6 +1217,375 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_account_age_decline() {
+        let config = RelabelConfig {
+            allow_unauthenticated: vec!["C-*".into()],
+            author_can_remove: vec![],
+            presets: HashMap::new(),
+            post_reasons: false,
+            deprecated: HashMap::new(),
+            min_account_age_days: Some(30),
+            aliases: HashMap::new(),
+        };
+        // The label is otherwise allowed for non-team members...
+        assert_eq!(
+            check_filter(
+                &LabelDelta::Add(Label("C-bug".into())),
+                &config,
+                TeamMembership::Outsider,
+                false
+            ),
+            Ok(CheckFilterResult::Allow)
+        );
+
+        // ...but the too new accounts are declined.
+        let now = chrono::Utc::now();
+        let decline = account_age_decline(Some(now - chrono::Duration::days(2)), now, 30);
+        assert!(decline.unwrap().contains("less than 30 days ago"));
+        assert_eq!(
+            account_age_decline(Some(now - chrono::Duration::days(30)), now, 30),
+            None
+        );
+        // Accounts of unknown age are declined too.
+        assert!(account_age_decline(None, now, 30).is_some());
+    }
+
+    #[tokio::test]
+    async fn handler_declines_young_and_unknown_accounts() {
+        use axum::{Json, Router, extract::Path, routing::get};
+
+        let router = Router::new().route(
+            "/users/{login}",
+            get(|Path(login): Path<String>| async move {
+                let mut user = serde_json::json!({
+                    "id": 2,
+                    "login": login,
+                    "public_repos": 0,
+                });
+                let created_at = match login.as_str() {
+                    "old" => Some(chrono::Utc::now() - chrono::Duration::days(365)),
+                    "young" => Some(chrono::Utc::now() - chrono::Duration::days(2)),
+                    _ => None,
+                };
+                if let Some(created_at) = created_at {
+                    user["created_at"] = created_at.to_rfc3339().into();
+                }
+                Json(user)
+            }),
+        );
+        let mut ctx = crate::tests::offline_context();
+        ctx.github = crate::tests::mock_github_client(router).await;
+        let config = RelabelConfig {
+            allow_unauthenticated: vec!["A-*".to_string()],
+            min_account_age_days: Some(30),
+            ..deprecated_config(false)
+        };
+        let relabel = |login: &str| {
+            let event = comment_event()
+                .issue(issue().call())
+                .author(crate::tests::github::user(login, 2))
+                .call();
+            let input = RelabelCommand {
+                deltas: vec![LabelDelta::Add(Label("A-new".to_string()))],
+                ..Default::default()
+            };
+            let (ctx, config) = (&ctx, &config);
+            async move { crate::dry_run::record(handle_command(ctx, config, &event, input)).await }
+        };
+
+        let (result, actions) = relabel("old").await;
+        result.unwrap();
+        assert_eq!(
+            actions,
+            vec![r#"added labels ["A-new"] to rust-lang/rust#1"#.to_string()]
+        );
+
+        // The account is too young, or its creation date is missing.
+        for login in ["young", "unknown"] {
+            let (result, actions) = relabel(login).await;
+            let err = result.unwrap_err();
+            assert!(
+                err.downcast_ref::<crate::errors::UserError>().is_some(),
+                "{err:?}"
+            );
+            assert!(err.to_string().contains("less than 30 days ago"), "{err}");
+            assert!(actions.is_empty(), "{actions:?}");
+        }
+    }
+
+    #[test]
+    fn test_check_filter_issue_author() {
+        let config = RelabelConfig {
+            allow_unauthenticated: vec!["T-*".into()],
+            author_can_remove: vec!["needs-*".into(), "!needs-fcp".into()],
+            presets: HashMap::new(),
+            post_reasons: false,
+            deprecated: HashMap::new(),
+            min_account_age_days: None,
+            aliases: HashMap::new(),
+        };
+        let remove = |label: &str| LabelDelta::Remove(Label(label.into()));
+        let add = |label: &str| LabelDelta::Add(Label(label.into()));
+
+        // The issue author can remove the configured labels...
+        assert_eq!(
+            check_filter(
+                &remove("needs-triage"),
+                &config,
+                TeamMembership::Outsider,
+                true
+            ),
+            Ok(CheckFilterResult::Allow)
+        );
+        assert_eq!(
+            check_filter(
+                &remove("needs-triage"),
+                &config,
+                TeamMembership::Unknown,
+                true
+            ),
+            Ok(CheckFilterResult::Allow)
+        );
+        // ...but not add them back, nor remove the denied ones
+        assert_eq!(
+            check_filter(
+                &add("needs-triage"),
+                &config,
+                TeamMembership::Outsider,
+                true
+            ),
+            Ok(CheckFilterResult::Deny)
+        );
+        assert_eq!(
+            check_filter(
+                &remove("needs-fcp"),
+                &config,
+                TeamMembership::Outsider,
+                true
+            ),
+            Ok(CheckFilterResult::Deny)
+        );
+        // Random outsiders can't remove them
+        assert_eq!(
+            check_filter(
+                &remove("needs-triage"),
+                &config,
+                TeamMembership::Outsider,
+                false
+            ),
+            Ok(CheckFilterResult::Deny)
+        );
+        // `allow_unauthenticated` still applies to everyone
+        assert_eq!(
+            check_filter(
+                &remove("T-compiler"),
+                &config,
+                TeamMembership::Outsider,
+                false
+            ),
+            Ok(CheckFilterResult::Allow)
+        );
+    }
+
+    #[test]
+    fn test_explain_message() {
+        let config = RelabelConfig {
+            allow_unauthenticated: vec!["T-*".into(), "I-*".into(), "!I-*nominated".into()],
+            author_can_remove: vec!["needs-*".into()],
+            presets: HashMap::new(),
+            post_reasons: false,
+            deprecated: HashMap::new(),
+            min_account_age_days: None,
+            aliases: HashMap::new(),
+        };
+        let issue = issue().labels(vec!["needs-triage"]).call();
+        let deltas = vec![
+            LabelDelta::Add(Label("I-slow".into())),
+            LabelDelta::Add(Label("I-lang-nominated".into())),
+            LabelDelta::Remove(Label("needs-triage".into())),
+            LabelDelta::Add(Label("A-spurious".into())),
+        ];
+
+        assert_eq!(
+            explain_message(&issue, &deltas, &config, TeamMembership::Outsider, true).unwrap(),
+            "Explanation of the label changes, nothing was changed:\n\
+             \n- `+I-slow`: allowed by `I-*` in `allow-unauthenticated`; **allowed**\
+             \n- `+I-lang-nominated`: denied by `!I-*nominated` in `allow-unauthenticated`; \
+             **denied, only team members can set it**\
+             \n- `-needs-triage`: allowed by `needs-*` in `author-can-remove`, \
+             no pattern of `allow-unauthenticated` matches; **allowed**\
+             \n- `+A-spurious`: no pattern of `allow-unauthenticated` matches; \
+             **denied, only team members can set it**"
+        );
+        assert_eq!(
+            explain_message(
+                &issue,
+                &deltas[1..2],
+                &config,
+                TeamMembership::Member,
+                false
+            )
+            .unwrap(),
+            "Explanation of the label changes, nothing was changed:\n\
+             \n- `+I-lang-nominated`: denied by `!I-*nominated` in `allow-unauthenticated`; \
+             **allowed, you are a team member**"
+        );
+        assert_eq!(
+            explain_message(
+                &issue,
+                &deltas[3..],
+                &config,
+                TeamMembership::Unknown,
+                false
+            )
+            .unwrap(),
+            "Explanation of the label changes, nothing was changed:\n\
+             \n- `+A-spurious`: no pattern of `allow-unauthenticated` matches; \
+             **denied, we were unable to check if you are a team member**"
+        );
+
+        // The toggles are explained as the change they resolve to.
+        let toggles = vec![
+            LabelDelta::Toggle(Label("needs-triage".into())),
+            LabelDelta::Toggle(Label("T-compiler".into())),
+        ];
+        assert_eq!(
+            explain_message(&issue, &toggles, &config, TeamMembership::Outsider, true).unwrap(),
+            "Explanation of the label changes, nothing was changed:\n\
+             \n- `~needs-triage`: removes the present label, allowed by `needs-*` in \
+             `author-can-remove`, no pattern of `allow-unauthenticated` matches; **allowed**\
+             \n- `~T-compiler`: adds the missing label, allowed by `T-*` in \
+             `allow-unauthenticated`; **allowed**"
+        );
+    }
+
+    #[test]
+    fn test_resolve_toggles() {
+        let issue = issue().labels(vec!["A-present", "C-bug"]).call();
+        let toggle = |label: &str| LabelDelta::Toggle(Label(label.into()));
+
+        assert_eq!(
+            resolve_toggles(
+                &issue,
+                vec![
+                    toggle("A-present"),
+                    toggle("a-PRESENT"),
+                    toggle("A-absent"),
+                    LabelDelta::Remove(Label("C-bug".into())),
+                ]
+            ),
+            vec![
+                LabelDelta::Remove(Label("A-present".into())),
+                LabelDelta::Remove(Label("a-PRESENT".into())),
+                LabelDelta::Add(Label("A-absent".into())),
+                LabelDelta::Remove(Label("C-bug".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_state_condition() {
+        let open = issue().call();
+        let closed = issue().state(IssueState::Closed).call();
+
+        assert_eq!(
+            state_mismatch_message(&closed, StateCondition::Open).as_deref(),
+            Some("No labels were changed, as this issue is closed (`--if-open`).")
+        );
+        assert_eq!(state_mismatch_message(&open, StateCondition::Open), None);
+
+        assert_eq!(
+            state_mismatch_message(&issue().pr(true).call(), StateCondition::Closed).as_deref(),
+            Some("No labels were changed, as this pull request is open (`--if-closed`).")
+        );
+        assert_eq!(
+            state_mismatch_message(&closed, StateCondition::Closed),
+            None
+        );
+    }
+
+    #[test]
+    fn test_preset_expansion() {
+        let config = RelabelConfig {
+            allow_unauthenticated: vec!["C-*".into(), "needs-triage".into()],
+            author_can_remove: vec![],
+            presets: HashMap::from([(
+                "new-bug".to_string(),
+                vec![
+                    "+C-bug".to_string(),
+                    "-needs-triage".to_string(),
+                    "I-prioritize".to_string(),
+                ],
+            )]),
+            post_reasons: false,
+            deprecated: HashMap::new(),
+            min_account_age_days: None,
+            aliases: HashMap::new(),
+        };
+
+        let command = config
+            .expand_preset(RelabelCommand {
+                deltas: vec![],
+                preset: Some("new-bug".to_string()),
+                dry_run: false,
+                reasons: vec![],
+                copy_from: None,
+                explain: false,
+                if_state: None,
+            })
+            .unwrap();
+        assert_eq!(
+            command,
+            RelabelCommand::from_deltas(vec![
+                LabelDelta::Add(Label("C-bug".to_string())),
+                LabelDelta::Remove(Label("needs-triage".to_string())),
+                LabelDelta::Add(Label("I-prioritize".to_string())),
+            ])
+        );
+
+        // The preset labels are still subject to the authorization checks
+        let results = command
+            .deltas
+            .iter()
+            .map(|delta| check_filter(delta, &config, TeamMembership::Outsider, false))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            vec![
+                Ok(CheckFilterResult::Allow),
+                Ok(CheckFilterResult::Allow),
+                Ok(CheckFilterResult::Deny),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_preset() {
+        let config = RelabelConfig {
+            allow_unauthenticated: vec![],
+            author_can_remove: vec![],
+            presets: HashMap::new(),
+            post_reasons: false,
+            deprecated: HashMap::new(),
+            min_account_age_days: None,
+            aliases: HashMap::new(),
+        };
+
+        assert_eq!(
+            config.expand_preset(RelabelCommand {
+                deltas: vec![],
+                preset: Some("new-bug".to_string()),
+                dry_run: false,
+                reasons: vec![],
+                copy_from: None,
+                explain: false,
+                if_state: None,
+            }),
+            Err("Unknown relabel preset `new-bug`.".to_string())
+        );
+    }
+
     #[test]
     fn test_compute_label_deltas() {
         let mut deltas = vec![
@@ -278,7 +1597,7 @@ mod tests {
         ];
 
         assert_eq!(
-            compute_label_deltas(&deltas),
+            compute_label_deltas(&issue().call(), &deltas),
             (
                 vec![
                     GitHubLabel {
@@ -296,7 +1615,7 @@ mod tests {
         deltas.push(LabelDelta::Add(Label("I-lang-nominated".to_string())));
 
         assert_eq!(
-            compute_label_deltas(&deltas),
+            compute_label_deltas(&issue().call(), &deltas),
             (
                 vec![
                     GitHubLabel {
@@ -316,6 +1635,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_compute_label_deltas_toggles() {
+        let issue = issue().labels(vec!["A-present"]).call();
+        let toggle = |label: &str| LabelDelta::Toggle(Label(label.into()));
+
+        assert_eq!(
+            compute_label_deltas(&issue, &[toggle("a-present"), toggle("A-absent")]),
+            (
+                vec![GitHubLabel {
+                    name: "A-absent".to_string()
+                }],
+                vec![GitHubLabel {
+                    name: "a-present".to_string()
+                }],
+            ),
+        );
+    }
+
     #[test]
     fn test_case_insensitive_label_lookup() {
         let issue = issue().labels(vec!["E-needs-mcve"]).call();
@@ -327,4 +1664,271 @@ mod tests {
             name: "e-needs-mcve".to_string(),
         }));
     }
+
+    #[test]
+    fn test_bulk_issue_relabel() {
+        let (to_add, to_remove) = compute_label_deltas(
+            &issue().call(),
+            &[
+                LabelDelta::Add(Label("A-diagnostics".to_string())),
+                LabelDelta::Remove(Label("needs-triage".to_string())),
+            ],
+        );
+
+        // Both changes apply
+        let untriaged = issue().number(1).labels(vec!["needs-triage"]).call();
+        assert_eq!(
+            issue_relabel(&untriaged, &to_add, &to_remove),
+            Some(IssueRelabel {
+                repository: untriaged.repository().to_string(),
+                number: 1,
+                add: vec!["A-diagnostics".to_string()],
+                remove: vec!["needs-triage".to_string()],
+            })
+        );
+
+        // Only the missing label is added
+        let triaged = issue().number(2).labels(vec![]).call();
+        assert_eq!(
+            issue_relabel(&triaged, &to_add, &to_remove).map(|r| (r.add, r.remove)),
+            Some((vec!["A-diagnostics".to_string()], vec![]))
+        );
+
+        // No-op relabels are skipped
+        let done = issue().number(3).labels(vec!["a-diagnostics"]).call();
+        assert_eq!(issue_relabel(&done, &to_add, &to_remove), None);
+    }
+
+    #[test]
+    fn test_bulk_relabel_plan() {
+        let deltas = [LabelDelta::Remove(Label("I-nominated".to_string()))];
+        let issues = vec![
+            issue().number(1).labels(vec!["I-nominated"]).call(),
+            issue().number(2).labels(vec!["T-compiler"]).call(),
+            issue()
+                .number(3)
+                .labels(vec!["I-nominated", "T-lang"])
+                .call(),
+        ];
+
+        let changes = plan_bulk_relabel(&issues, &deltas);
+        assert_eq!(
+            changes
+                .iter()
+                .map(|(issue, relabel)| (issue.number, relabel.remove.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, vec!["I-nominated".to_string()]),
+                (3, vec!["I-nominated".to_string()]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bulk_relabel_dry_run() {
+        // Only the search is served, and the database is unreachable: a dry run
+        // neither changes the issues nor schedules their relabel.
+        let router = Router::new().route(
+            "/search/issues",
+            get(|| async {
+                let mut nominated = issue_json("rust-lang/rust", 1);
+                nominated["labels"] = serde_json::json!([{ "name": "I-nominated" }]);
+                Json(serde_json::json!({
+                    "total_count": 2,
+                    "items": [nominated, issue_json("rust-lang/rust", 2)],
+                }))
+            }),
+        );
+        let mut ctx = offline_context();
+        ctx.github = mock_github_client(router).await;
+
+        let deltas = [LabelDelta::Remove(Label("I-nominated".to_string()))];
+        let report = bulk_relabel(&ctx, "label:I-nominated", &deltas, true)
+            .await
+            .unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.matched, 2);
+        assert_eq!(
+            report.changed,
+            vec![IssueRelabel {
+                repository: "rust-lang/rust".to_string(),
+                number: 1,
+                add: vec![],
+                remove: vec!["I-nominated".to_string()],
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bulk_relabel_reports_every_failure() {
+        let router = Router::new().route(
+            "/repos/rust-lang/rust/issues/{number}",
+            get(|Path(number): Path<u64>| async move {
+                let mut issue = issue_json("rust-lang/rust", number);
+                match number {
+                    2 => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+                    // Already relabeled since the relabel was planned.
+                    3 => {}
+                    _ => issue["labels"] = serde_json::json!([{ "name": "I-nominated" }]),
+                }
+                Ok(Json(issue))
+            }),
+        );
+        let github = mock_github_client(router).await;
+
+        let planned = |number| IssueRelabel {
+            repository: "rust-lang/rust".to_string(),
+            number,
+            add: vec![],
+            remove: vec!["I-nominated".to_string()],
+        };
+        let issues = [planned(1), planned(2), planned(3), planned(4)];
+        let (failures, actions) = crate::dry_run::record(relabel_issues(&github, &issues)).await;
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].to_string().contains("rust-lang/rust#2"));
+        assert_eq!(
+            actions,
+            vec![
+                "removed labels [\"I-nominated\"] from rust-lang/rust#1".to_string(),
+                "removed labels [\"I-nominated\"] from rust-lang/rust#4".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dry_run_message() {
+        let (to_add, to_remove) = compute_label_deltas(
+            &issue().call(),
+            &[
+                LabelDelta::Add(Label("A-foo".to_string())),
+                LabelDelta::Add(Label("A-baz".to_string())),
+                LabelDelta::Remove(Label("B-bar".to_string())),
+            ],
+        );
+
+        let labeled = issue().labels(vec!["B-bar"]).call();
+        assert_eq!(
+            dry_run_message(issue_relabel(&labeled, &to_add, &to_remove)),
+            "Dry run: this would add `A-baz`, `A-foo` and remove `B-bar`."
+        );
+
+        let unlabeled = issue().labels(vec!["A-foo"]).call();
+        assert_eq!(
+            dry_run_message(issue_relabel(&unlabeled, &to_add, &to_remove)),
+            "Dry run: this would add `A-baz`."
+        );
+
+        let done = issue().labels(vec!["A-foo", "A-baz"]).call();
+        assert_eq!(
+            dry_run_message(issue_relabel(&done, &to_add, &to_remove)),
+            "Dry run: this would not change any label."
+        );
+    }
+
+    #[test]
+    fn test_reasons_message() {
+        let reasons = [
+            LabelReason {
+                label: Label("S-blocked".to_string()),
+                reason: "waiting on RFC 1234".to_string(),
+            },
+            LabelReason {
+                label: Label("I-slow".to_string()),
+                reason: "regressed in 1.80".to_string(),
+            },
+        ];
+        assert_eq!(
+            reasons_message("octocat", &reasons),
+            "Labels added by `octocat` with a reason:\n\n- `S-blocked`: waiting on RFC 1234\n- `I-slow`: regressed in 1.80"
+        );
+
+        // The mentions in the reasons don't ping.
+        let reasons = [LabelReason {
+            label: Label("S-blocked".to_string()),
+            reason: "waiting on @rust-lang/lang".to_string(),
+        }];
+        assert_eq!(
+            reasons_message("octocat", &reasons),
+            "Labels added by `octocat` with a reason:\n\n- `S-blocked`: waiting on @\u{200B}rust-lang/lang"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reasons_are_recorded_with_their_author() {
+        run_db_test(|mut ctx| async move {
+            let issue = issue().number(1).call();
+            let reason = |label: &str, reason: &str| LabelReason {
+                label: Label(label.to_string()),
+                reason: reason.to_string(),
+            };
+            record_reasons(
+                ctx.handler_ctx(),
+                &issue,
+                "octocat",
+                &[reason("S-blocked", "waiting on RFC 1234")],
+            )
+            .await?;
+            record_reasons(
+                ctx.handler_ctx(),
+                &issue,
+                "ferris",
+                &[reason("I-slow", "regressed in 1.80")],
+            )
+            .await?;
+
+            let mut db = ctx.db_client_mut();
+            let state: IssueData<'_, RelabelReasons> =
+                IssueData::load(&mut db, &issue, RELABEL_REASONS_KEY).await?;
+            assert_eq!(
+                state
+                    .data
+                    .reasons
+                    .iter()
+                    .map(|r| (r.user.as_str(), r.label.as_str(), r.reason.as_str()))
+                    .collect::<Vec<_>>(),
+                vec![
+                    ("octocat", "S-blocked", "waiting on RFC 1234"),
+                    ("ferris", "I-slow", "regressed in 1.80"),
+                ]
+            );
+            drop(state);
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[test]
+    fn test_filter_copied_labels() {
+        let config = RelabelConfig {
+            allow_unauthenticated: vec!["C-*".into(), "!C-secret".into()],
+            author_can_remove: vec![],
+            presets: HashMap::new(),
+            post_reasons: false,
+            deprecated: HashMap::new(),
+            min_account_age_days: None,
+            aliases: HashMap::new(),
+        };
+        let labels = ["C-bug", "T-compiler", "C-secret"].map(|name| GitHubLabel {
+            name: name.to_string(),
+        });
+
+        assert_eq!(
+            filter_copied_labels(&labels, &config, TeamMembership::Outsider, false),
+            Ok((
+                vec![LabelDelta::Add(Label("C-bug".to_string()))],
+                vec!["T-compiler".to_string(), "C-secret".to_string()]
+            ))
+        );
+        assert_eq!(
+            filter_copied_labels(&labels, &config, TeamMembership::Member, false),
+            Ok((
+                vec![
+                    LabelDelta::Add(Label("C-bug".to_string())),
+                    LabelDelta::Add(Label("T-compiler".to_string())),
+                    LabelDelta::Add(Label("C-secret".to_string())),
+                ],
+                vec![]
+            ))
+        );
+    }
 }
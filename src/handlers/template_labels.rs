@@ -0,0 +1,141 @@
+//! Adds labels to newly opened issues based on the checkboxes checked in the
+//! issue template.
+//!
+//! Configured with the text of each checkbox and the labels to add when it
+//! is checked:
+//!
+//! ```toml
+//! [template-labels]
+//! "Regression" = ["regression-untriaged"]
+//! ```
+//!
+//! so that an issue body containing `- [x] Regression` gets the
+//! `regression-untriaged` label.
+
+use crate::{
+    config::TemplateLabelsConfig,
+    github::{IssuesAction, IssuesEvent},
+    handlers::{Context, relabel},
+};
+use parser::command::relabel::{Label, LabelDelta};
+
+pub(super) struct TemplateLabelsInput {
+    labels: Vec<String>,
+}
+
+pub(super) async fn parse_input(
+    _ctx: &Context,
+    event: &IssuesEvent,
+    config: Option<&TemplateLabelsConfig>,
+) -> Result<Option<TemplateLabelsInput>, String> {
+    let Some(config) = config else {
+        return Ok(None);
+    };
+    if event.action != IssuesAction::Opened || event.issue.is_pr() {
+        return Ok(None);
+    }
+
+    let labels = template_labels(config, &event.issue.body);
+    if labels.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(TemplateLabelsInput { labels }))
+}
+
+pub(super) async fn handle_input(
+    ctx: &Context,
+    _config: &TemplateLabelsConfig,
+    event: &IssuesEvent,
+    input: TemplateLabelsInput,
+) -> anyhow::Result<()> {
+    let deltas: Vec<_> = input
+        .labels
+        .into_iter()
+        .map(|label| LabelDelta::Add(Label(label)))
+        .collect();
+    relabel::apply_label_deltas(ctx, &event.issue, &deltas).await
+}
+
+/// Returns the labels of the configured checkboxes checked in the body, in
+/// the order they appear, without duplicates.
+fn template_labels(config: &TemplateLabelsConfig, body: &str) -> Vec<String> {
+    let mut labels = Vec::new();
+    for text in checked_boxes(body) {
+        let Some((_, checkbox_labels)) = config
+            .checkboxes
+            .iter()
+            .find(|(checkbox, _)| checkbox.eq_ignore_ascii_case(text))
+        else {
+            continue;
+        };
+        for label in checkbox_labels {
+            if !labels.contains(label) {
+                labels.push(label.clone());
+            }
+        }
+    }
+    labels
+}
+
+/// Returns the text of the checked Markdown task list items (`- [x] text`).
+fn checked_boxes(body: &str) -> impl Iterator<Item = &str> {
+    body.lines().filter_map(|line| {
+        let item = line.trim_start().strip_prefix(['-', '*'])?;
+        if !item.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let item = item.trim_start();
+        let text = item
+            .strip_prefix("[x]")
+            .or_else(|| item.strip_prefix("[X]"))?;
+        Some(text.trim())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{checked_boxes, template_labels};
+    use crate::config::TemplateLabelsConfig;
+
+    const BODY: &str = "\
+### Category
+
+- [x] Regression
+- [ ] Performance
+* [X] Crash
+- [x]   Documentation
+-[x] Not a list item
+
+### Description
+
+The compiler crashes when building the project with `[x] Regression`.
+";
+
+    #[test]
+    fn parse_checked_boxes() {
+        assert_eq!(
+            checked_boxes(BODY).collect::<Vec<_>>(),
+            vec!["Regression", "Crash", "Documentation"]
+        );
+    }
+
+    #[test]
+    fn labels_from_template() {
+        let config: TemplateLabelsConfig = toml::from_str(
+            r#"
+            "Regression" = ["regression-untriaged", "I-prioritize"]
+            "crash" = ["I-crash", "I-prioritize"]
+            "Performance" = ["I-slow"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            template_labels(&config, BODY),
+            vec!["regression-untriaged", "I-prioritize", "I-crash"]
+        );
+        assert_eq!(
+            template_labels(&config, "- [ ] Regression"),
+            Vec::<String>::new()
+        );
+    }
+}
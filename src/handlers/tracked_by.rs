@@ -0,0 +1,216 @@
+//! Links issues to their tracking issue.
+//!
+//! Team members can record that an issue is tracked by another one with:
+//!
+//! ```md
+//! @rustbot tracked-by #123
+//! ```
+//!
+//! The relationship is stored in the database, the optional configured label
+//! is added to the tracked issue and a comment is posted on both issues
+//! linking them together.
+
+use crate::{
+    config::TrackedByConfig,
+    db::tracking_issues,
+    errors::user_error,
+    github::{Event, Issue, Label},
+    handlers::Context,
+};
+use anyhow::Context as _;
+use parser::command::tracked_by::TrackedByCommand;
+
+pub(super) async fn handle_command(
+    ctx: &Context,
+    config: &TrackedByConfig,
+    event: &Event,
+    cmd: TrackedByCommand,
+) -> anyhow::Result<()> {
+    let TrackedByCommand(tracked_by) = cmd;
+    let Some(issue) = event.issue() else {
+        return user_error!(
+            "The `tracked-by` command can only be used on issues and pull requests."
+        );
+    };
+
+    if !ctx
+        .team
+        .is_team_member(&event.user().login)
+        .await
+        .context("failed to verify that the user is a team member")?
+    {
+        return user_error!("Only team members can link an issue to a tracking issue.");
+    }
+
+    if tracked_by == issue.number {
+        return user_error!("An issue cannot be tracked by itself.");
+    }
+
+    let tracking_issue = match ctx.github.issue(issue.repository(), tracked_by).await {
+        Ok(tracking_issue) => tracking_issue,
+        Err(err) if is_not_found(&err) => {
+            return user_error!(format!("Issue #{tracked_by} does not exist."));
+        }
+        Err(err) => return Err(err.context("failed to fetch the tracking issue")),
+    };
+
+    if !link(ctx, config, issue, &tracking_issue).await? {
+        return user_error!(format!("This issue is already tracked by #{tracked_by}."));
+    }
+    Ok(())
+}
+
+/// Whether the GitHub request failed because the resource doesn't exist.
+pub(super) fn is_not_found(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.status() == Some(reqwest::StatusCode::NOT_FOUND))
+}
+
+/// Labels `issue`, cross-links it with `tracking_issue` and only then records
+/// the relationship, so that a failure on GitHub can be retried.
+///
+/// Returns `false`, without touching GitHub, if the relationship was already
+/// recorded.
+async fn link(
+    ctx: &Context,
+    config: &TrackedByConfig,
+    issue: &Issue,
+    tracking_issue: &Issue,
+) -> anyhow::Result<bool> {
+    let repo = issue.repository().to_string();
+    let tracked_by =
+        tracking_issues::get_tracking_issues(&*ctx.db.get().await, &repo, issue.number)
+            .await
+            .context("unable to get the tracking issues")?;
+    if tracked_by.contains(&tracking_issue.number) {
+        return Ok(false);
+    }
+
+    let labels = tracked_labels(config);
+    if !labels.is_empty() {
+        issue
+            .add_labels(&ctx.github, labels)
+            .await
+            .context("failed to add the tracking label")?;
+    }
+
+    issue
+        .post_comment(
+            &ctx.github,
+            &format!("This issue is tracked by #{}.", tracking_issue.number),
+        )
+        .await
+        .context("failed to post the comment on the tracked issue")?;
+    tracking_issue
+        .post_comment(
+            &ctx.github,
+            &format!("#{} is now tracked by this issue.", issue.number),
+        )
+        .await
+        .context("failed to post the comment on the tracking issue")?;
+
+    tracking_issues::add_tracking_issue(
+        &*ctx.db.get().await,
+        &repo,
+        issue.number,
+        tracking_issue.number,
+    )
+    .await
+    .context("unable to record the tracking issue")
+}
+
+/// The labels to add to an issue linked to a tracking issue.
+fn tracked_labels(config: &TrackedByConfig) -> Vec<Label> {
+    config
+        .label
+        .iter()
+        .map(|name| Label { name: name.clone() })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::github::issue;
+    use crate::tests::{mock_github_client, run_db_test};
+
+    #[test]
+    fn configured_label() {
+        let config = TrackedByConfig {
+            label: Some("has-tracking-issue".to_string()),
+        };
+        assert_eq!(
+            tracked_labels(&config),
+            vec![Label {
+                name: "has-tracking-issue".to_string()
+            }]
+        );
+        assert!(tracked_labels(&TrackedByConfig { label: None }).is_empty());
+    }
+
+    #[tokio::test]
+    async fn link_records_relationship_once() {
+        run_db_test(|ctx| async move {
+            let config = TrackedByConfig {
+                label: Some("has-tracking-issue".to_string()),
+            };
+            let tracked = issue().number(10).call();
+            let tracking = issue().number(1).call();
+            let repo = tracked.repository().to_string();
+
+            let (linked, actions) =
+                crate::dry_run::record(link(ctx.handler_ctx(), &config, &tracked, &tracking)).await;
+            assert!(linked?);
+            assert_eq!(
+                actions,
+                vec![
+                    r#"added labels ["has-tracking-issue"] to rust-lang/rust#10"#.to_string(),
+                    r#"posted comment on rust-lang/rust#10: "This issue is tracked by #1.""#
+                        .to_string(),
+                    r#"posted comment on rust-lang/rust#1: "#10 is now tracked by this issue.""#
+                        .to_string(),
+                ]
+            );
+
+            // Linking them again doesn't touch GitHub.
+            let (linked, actions) =
+                crate::dry_run::record(link(ctx.handler_ctx(), &config, &tracked, &tracking)).await;
+            assert!(!linked?);
+            assert!(actions.is_empty());
+
+            let db = ctx.db_client();
+            assert_eq!(
+                tracking_issues::get_tracking_issues(&db, &repo, 10).await?,
+                vec![1]
+            );
+            assert_eq!(
+                tracking_issues::get_tracked_issues(&db, &repo, 1).await?,
+                vec![10]
+            );
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn only_missing_issues_do_not_exist() {
+        use axum::{Router, http::StatusCode, routing::get};
+
+        let router = Router::new()
+            .route(
+                "/repos/rust-lang/rust/issues/404",
+                get(|| async { StatusCode::NOT_FOUND }),
+            )
+            .route(
+                "/repos/rust-lang/rust/issues/500",
+                get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+            );
+        let github = mock_github_client(router).await;
+        let repo = issue().call().repository().clone();
+
+        let err = github.issue(&repo, 404).await.unwrap_err();
+        assert!(is_not_found(&err), "{err:?}");
+        let err = github.issue(&repo, 500).await.unwrap_err();
+        assert!(!is_not_found(&err), "{err:?}");
+    }
+}
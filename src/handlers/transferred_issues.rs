@@ -0,0 +1,132 @@
+//! Moves the data tracked for an issue (notes, concerns, holds, ...) to its new
+//! repository when it is transferred, so that it isn't orphaned under its old
+//! number.
+
+use crate::db::issue_transfers::transfer_issue;
+use crate::github::{Event, Issue, IssuesAction, IssuesEvent};
+use crate::handlers::Context;
+use tracing as log;
+
+pub(super) async fn handle(ctx: &Context, event: &Event) -> anyhow::Result<()> {
+    let Event::Issue(event) = event else {
+        return Ok(());
+    };
+    let Some(new_issue) = transferred_to(event) else {
+        return Ok(());
+    };
+
+    let old_repo = event.issue.repository().to_string();
+    let new_repo = new_issue.repository().to_string();
+    let mut db = ctx.db.get().await;
+    let rows = transfer_issue(
+        &mut db,
+        (&old_repo, event.issue.number),
+        (&new_repo, new_issue.number),
+    )
+    .await?;
+    log::info!(
+        "moved {} rows of the transferred issue {old_repo}#{} to {new_repo}#{}",
+        rows.moved,
+        event.issue.number,
+        new_issue.number
+    );
+    if !rows.dropped_edges.is_empty() {
+        log::warn!(
+            "dropped the edges {:?} (table, from, to) of the transferred issue {old_repo}#{}, \
+             as they can't cross repositories",
+            rows.dropped_edges,
+            event.issue.number
+        );
+    }
+    Ok(())
+}
+
+/// Returns the issue in its new repository, if the event is a transfer.
+fn transferred_to(event: &IssuesEvent) -> Option<&Issue> {
+    if event.action != IssuesAction::Transferred {
+        return None;
+    }
+    event.changes.as_ref()?.new_issue.as_ref()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::issue_blockers::{add_blocker, get_blocked_issues, get_blockers};
+    use crate::db::major_change_holds::{MajorChangeHold, get_hold, hold};
+    use crate::db::tracking_issues::{add_tracking_issue, get_tracking_issues};
+    use crate::tests::github::issue_json;
+    use crate::tests::run_db_test;
+
+    fn transfer_event() -> IssuesEvent {
+        serde_json::from_value(serde_json::json!({
+            "action": "transferred",
+            "issue": issue_json("rust-lang/rust", 10),
+            "changes": {
+                "new_issue": issue_json("rust-lang/cargo", 20),
+                "new_repository": { "full_name": "rust-lang/cargo" },
+            },
+            "repository": { "full_name": "rust-lang/rust", "default_branch": "main" },
+            "sender": { "login": "octocat", "id": 1, "type": "User" },
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn transfer_moves_issue_rows() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let held = MajorChangeHold {
+                held_by: "Urgau".to_string(),
+                held_at: "2025-01-01T10:00:00Z".parse().unwrap(),
+            };
+            hold(&db, "rust-lang/rust", 10, &held).await?;
+            // A stale hold of the destination number is replaced.
+            let stale = MajorChangeHold {
+                held_by: "someone".to_string(),
+                held_at: "2024-01-01T10:00:00Z".parse().unwrap(),
+            };
+            hold(&db, "rust-lang/cargo", 20, &stale).await?;
+            add_blocker(&db, "rust-lang/rust", 10, 5).await?;
+            add_blocker(&db, "rust-lang/rust", 12, 10).await?;
+            add_blocker(&db, "rust-lang/rust", 11, 5).await?;
+            add_tracking_issue(&db, "rust-lang/rust", 10, 1).await?;
+
+            handle(ctx.handler_ctx(), &Event::Issue(transfer_event())).await?;
+
+            let db = ctx.db_client();
+            assert_eq!(get_hold(&db, "rust-lang/rust", 10).await?, None);
+            assert_eq!(get_hold(&db, "rust-lang/cargo", 20).await?, Some(held));
+            // The blockers in both directions would now cross repositories:
+            // cargo#20 isn't blocked on cargo#5, nor rust#12 on rust#10.
+            assert_eq!(get_blockers(&db, "rust-lang/rust", 10).await?, vec![]);
+            assert_eq!(get_blockers(&db, "rust-lang/cargo", 20).await?, vec![]);
+            assert_eq!(get_blockers(&db, "rust-lang/rust", 12).await?, vec![]);
+            assert_eq!(
+                get_blocked_issues(&db, "rust-lang/cargo", 20).await?,
+                vec![]
+            );
+            assert_eq!(
+                get_tracking_issues(&db, "rust-lang/cargo", 20).await?,
+                vec![]
+            );
+            assert_eq!(
+                get_tracking_issues(&db, "rust-lang/rust", 10).await?,
+                vec![]
+            );
+            // The other edges are untouched.
+            assert_eq!(get_blockers(&db, "rust-lang/rust", 11).await?, vec![5]);
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[test]
+    fn only_transfers() {
+        let mut event = transfer_event();
+        assert_eq!(transferred_to(&event).map(|issue| issue.number), Some(20));
+        event.action = IssuesAction::Closed;
+        assert!(transferred_to(&event).is_none());
+    }
+}
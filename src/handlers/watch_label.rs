@@ -0,0 +1,273 @@
+//! Allows users to be notified on Zulip about label changes of an issue.
+//!
+//! Users can subscribe to the changes of a label with:
+//!
+//! ```md
+//! @rustbot watch-label S-waiting-on-review
+//! ```
+//!
+//! Each time the label is added or removed from the issue, the subscribers get
+//! a Zulip direct message, so only the users with a Zulip account linked in the
+//! team repo can subscribe. `@rustbot unwatch-label S-waiting-on-review` removes
+//! the subscription.
+
+use crate::{
+    config::WatchLabelConfig,
+    db::{label_subscriptions, users::DbUser},
+    errors::user_error,
+    github::{Event, IssuesAction, IssuesEvent},
+    handlers::Context,
+    zulip::{MessageApiRequest, api::Recipient},
+};
+use anyhow::Context as _;
+use parser::command::watch_label::WatchLabelCommand;
+use tokio_postgres::Client as DbClient;
+use tracing as log;
+
+pub(super) async fn handle_command(
+    ctx: &Context,
+    _config: &WatchLabelConfig,
+    event: &Event,
+    cmd: WatchLabelCommand,
+) -> anyhow::Result<()> {
+    let Some(issue) = event.issue() else {
+        return user_error!("Labels can only be watched on issues and pull requests.");
+    };
+    let repo = issue.repository().to_string();
+    let user = event.user();
+
+    match cmd {
+        WatchLabelCommand::Watch { label } => {
+            if ctx
+                .team
+                .github_to_zulip_id(user.id)
+                .await
+                .context("unable to get the Zulip ID of the user")?
+                .is_none()
+            {
+                return user_error!(
+                    "The label changes are sent as Zulip direct messages, but your GitHub account isn't linked to a Zulip account in the [team repo](https://github.com/rust-lang/team)."
+                );
+            }
+
+            label_subscriptions::subscribe(&*ctx.db.get().await, user, &repo, issue.number, &label)
+                .await
+                .context("unable to subscribe to the label")?;
+
+            issue
+                .post_comment(
+                    &ctx.github,
+                    &format!(
+                        "`{}` is now notified on Zulip when the `{label}` label is added to or removed from this issue.",
+                        user.login
+                    ),
+                )
+                .await
+                .context("failed to post the watch confirmation")?;
+        }
+        WatchLabelCommand::Unwatch { label } => {
+            let db = ctx.db.get().await;
+            if !label_subscriptions::unsubscribe(&db, user.id, &repo, issue.number, &label)
+                .await
+                .context("unable to unsubscribe from the label")?
+            {
+                return user_error!(format!("You are not watching the `{label}` label."));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) struct LabelChange {
+    label: String,
+    added: bool,
+}
+
+pub(super) async fn parse_input(
+    _ctx: &Context,
+    event: &IssuesEvent,
+    config: Option<&WatchLabelConfig>,
+) -> Result<Option<LabelChange>, String> {
+    if config.is_none() {
+        return Ok(None);
+    }
+
+    Ok(match &event.action {
+        IssuesAction::Labeled { label } => Some(LabelChange {
+            label: label.name.clone(),
+            added: true,
+        }),
+        IssuesAction::Unlabeled { label: Some(label) } => Some(LabelChange {
+            label: label.name.clone(),
+            added: false,
+        }),
+        _ => None,
+    })
+}
+
+pub(super) async fn handle_input(
+    ctx: &Context,
+    _config: &WatchLabelConfig,
+    event: &IssuesEvent,
+    input: LabelChange,
+) -> anyhow::Result<()> {
+    let subscribers = subscribers_to_notify(&*ctx.db.get().await, event, &input.label).await?;
+    if subscribers.is_empty() {
+        return Ok(());
+    }
+
+    let issue = &event.issue;
+    let message = format!(
+        "The `{}` label has been {} [{}#{}]({}) by {}.",
+        input.label,
+        if input.added {
+            "added to"
+        } else {
+            "removed from"
+        },
+        issue.repository(),
+        issue.number,
+        issue.html_url,
+        event.sender.login,
+    );
+
+    let zulip_users = ctx.zulip.get_zulip_users().await?;
+    for subscriber in subscribers {
+        let Some(zulip_id) = ctx.team.github_to_zulip_id(subscriber.id).await? else {
+            log::warn!(
+                "unable to notify {} about a label change, no Zulip ID",
+                subscriber.login
+            );
+            continue;
+        };
+        let Some(zulip_user) = zulip_users.iter().find(|u| u.user_id == zulip_id) else {
+            log::warn!("unable to find the Zulip user {zulip_id}");
+            continue;
+        };
+
+        if let Err(err) = (MessageApiRequest {
+            recipient: Recipient::Private {
+                id: zulip_user.user_id,
+                email: &zulip_user.email,
+            },
+            content: &message,
+        })
+        .send(&ctx.zulip)
+        .await
+        {
+            log::error!(
+                "failed to notify {} about a label change: {err:?}",
+                subscriber.login
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the subscribers of `label` on the event's issue, except the user who made the change.
+async fn subscribers_to_notify(
+    db: &DbClient,
+    event: &IssuesEvent,
+    label: &str,
+) -> anyhow::Result<Vec<DbUser>> {
+    let issue = &event.issue;
+    let mut subscribers = label_subscriptions::get_subscribers(
+        db,
+        &issue.repository().to_string(),
+        issue.number,
+        label,
+    )
+    .await
+    .context("unable to get the label subscribers")?;
+    subscribers.retain(|s| s.id != event.sender.id);
+    Ok(subscribers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{handle_command, subscribers_to_notify};
+    use crate::db::label_subscriptions::subscribe;
+    use crate::github::{IssuesAction, IssuesEvent, Label, Repository};
+    use crate::tests::github::{issue, user};
+    use crate::tests::run_db_test;
+
+    #[tokio::test]
+    async fn add_event_notifies_subscribers_of_the_label() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let repo = "rust-lang/rust";
+
+            let watcher = user("watcher", 1);
+            let other_label = user("other-label", 2);
+            let other_issue = user("other-issue", 3);
+            let sender = user("sender", 4);
+
+            subscribe(&db, &watcher, repo, 10, "S-waiting-on-review").await?;
+            subscribe(&db, &other_label, repo, 10, "S-blocked").await?;
+            subscribe(&db, &other_issue, repo, 11, "S-waiting-on-review").await?;
+            subscribe(&db, &sender, repo, 10, "S-waiting-on-review").await?;
+
+            let label = Label {
+                name: "S-waiting-on-review".to_string(),
+            };
+            let event = IssuesEvent {
+                action: IssuesAction::Labeled {
+                    label: label.clone(),
+                },
+                issue: issue().number(10).call(),
+                changes: None,
+                before: None,
+                after: None,
+                repository: Repository {
+                    full_name: repo.to_string(),
+                    default_branch: "main".to_string(),
+                    fork: false,
+                    parent: None,
+                },
+                sender,
+            };
+
+            let notified = subscribers_to_notify(&db, &event, &label.name).await?;
+            assert_eq!(
+                notified.into_iter().map(|u| u.login).collect::<Vec<_>>(),
+                vec!["watcher".to_string()]
+            );
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn watching_requires_a_zulip_account() {
+        use crate::tests::github::comment_event;
+        use axum::{Json, Router, routing::get};
+        use parser::command::watch_label::WatchLabelCommand;
+
+        let mut ctx = crate::tests::offline_context();
+        ctx.team = crate::tests::mock_team_client(Router::new().route(
+            "/zulip-map.json",
+            get(|| async { Json(serde_json::json!({ "users": {} })) }),
+        ))
+        .await;
+        let config = serde_json::from_value(serde_json::json!({})).unwrap();
+        let event = comment_event().issue(issue().call()).call();
+
+        let err = handle_command(
+            &ctx,
+            &config,
+            &event,
+            WatchLabelCommand::Watch {
+                label: "S-waiting-on-review".to_string(),
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("isn't linked to a Zulip account"),
+            "{err}"
+        );
+    }
+}
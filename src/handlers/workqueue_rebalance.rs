@@ -0,0 +1,442 @@
+//! Periodically rebalances the reviewer workqueues.
+//!
+//! Reviewers assigned more pull requests than their configured capacity get
+//! their newest pull requests, without any review yet, reassigned to reviewers
+//! that are on rotation and below their own capacity. The pull requests marked
+//! as active in the workqueue (reviewed since they were assigned) are never moved.
+//!
+//! A pull request is only moved to one of its eligible reviewers, resolved from
+//! the `[assign]` owners and adhoc groups like for a new pull request, including
+//! the reviewers with no pull request assigned yet.
+//!
+//! Only reviewers that have set a review capacity for the repository are taken
+//! into account, and the number of reassignments per run is bounded by the
+//! `[pr-tracking.rebalance]` configuration of the repository.
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::sync::RwLock;
+
+use crate::db::review_prefs::{RotationMode, get_review_prefs, get_review_prefs_batch};
+use crate::github::{Issue, IssueRepository, PullRequestNumber, UserId};
+use crate::handlers::Context;
+use crate::handlers::pr_tracking::{ReviewerWorkqueue, TrackedRepository};
+use crate::jobs::Job;
+
+pub struct WorkqueueRebalanceJob;
+
+#[async_trait]
+impl Job for WorkqueueRebalanceJob {
+    fn name(&self) -> &'static str {
+        "workqueue_rebalance"
+    }
+
+    async fn run(&self, ctx: &Context, _metadata: &serde_json::Value) -> anyhow::Result<()> {
+        for (repo, workqueue) in ctx.workqueue_map.tracked_repositories() {
+            if let Err(err) = rebalance_repository(ctx, repo, workqueue).await {
+                tracing::error!(
+                    "{}: failed to rebalance the workqueue of {}: {err:?}",
+                    self.name(),
+                    repo.full_name()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn rebalance_repository(
+    ctx: &Context,
+    repo: &TrackedRepository,
+    workqueue: &RwLock<ReviewerWorkqueue>,
+) -> anyhow::Result<()> {
+    let full_name = repo.full_name();
+    let repository = ctx
+        .github
+        .repository(&full_name)
+        .await
+        .context("failed retrieving the repository informations")?;
+    let config = crate::config::get(&ctx.github, &repository)
+        .await
+        .context("failed to get triagebot configuration")?;
+    let Some(rebalance) = config
+        .pr_tracking
+        .as_ref()
+        .and_then(|c| c.rebalance.as_ref())
+    else {
+        return Ok(());
+    };
+    let Some(assign) = &config.assign else {
+        return Ok(());
+    };
+
+    // Don't hold the workqueue lock while querying the database and GitHub
+    let mut started = HashSet::new();
+    let assignments: Vec<(UserId, Vec<(PullRequestNumber, DateTime<Utc>)>)> = workqueue
+        .read()
+        .await
+        .assignments()
+        .map(|(user_id, prs)| {
+            started.extend(prs.iter().filter(|(_, a)| a.active).map(|(pr, _)| *pr));
+            let prs = prs.iter().map(|(pr, a)| (*pr, a.created_at)).collect();
+            (user_id, prs)
+        })
+        .collect();
+
+    let mut reviewers = vec![];
+    {
+        let db = ctx.db.get().await;
+        for (user_id, prs) in assignments {
+            let prefs = get_review_prefs(&db, user_id).await?;
+            reviewers.push(ReviewerLoad {
+                user_id,
+                prs,
+                capacity: prefs
+                    .repo_review_prefs
+                    .get(&full_name)
+                    .and_then(|p| p.max_assigned_prs),
+                on_rotation: prefs.rotation_mode == RotationMode::OnRotation,
+            });
+        }
+    }
+
+    let issue_repo = IssueRepository {
+        organization: repo.owner().to_string(),
+        repository: repo.name().to_string(),
+    };
+
+    // Only the pull requests of overloaded reviewers can be moved, so only
+    // check the review activity of those (unless already known to be active)
+    // and resolve their eligible reviewers.
+    let mut pull_requests = HashMap::new();
+    let mut candidate_names: HashMap<PullRequestNumber, Vec<String>> = HashMap::new();
+    for reviewer in reviewers.iter().filter(|r| r.is_overloaded()) {
+        for (number, _) in &reviewer.prs {
+            if started.contains(number) {
+                continue;
+            }
+            let pr = ctx.github.pull_request(&issue_repo, *number).await?;
+            if !pr.get_reviews(&ctx.github).await?.is_empty() {
+                started.insert(*number);
+                continue;
+            }
+            match super::assign::rebalance_candidates(ctx, assign, &pr).await {
+                Ok(names) => {
+                    candidate_names.insert(*number, names);
+                }
+                Err(err) => tracing::warn!(
+                    "failed to resolve the eligible reviewers of {issue_repo}#{number}: {err:?}"
+                ),
+            }
+            pull_requests.insert(*number, pr);
+        }
+    }
+
+    // The eligible reviewers without any assigned pull request aren't in the
+    // workqueue yet
+    let mut candidates: HashMap<PullRequestNumber, Vec<UserId>> = HashMap::new();
+    {
+        let db = ctx.db.get().await;
+        let names: HashSet<&str> = candidate_names
+            .values()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        let prefs = get_review_prefs_batch(&db, &names.into_iter().collect::<Vec<_>>()).await?;
+        for (number, names) in &candidate_names {
+            // Reviewers without preferences have no capacity, so they can't be targets
+            let ids = names
+                .iter()
+                .filter_map(|name| prefs.get(name.as_str()))
+                .map(|prefs| prefs.user_id);
+            candidates.insert(*number, ids.collect());
+        }
+        for prefs in prefs.values() {
+            if !reviewers.iter().any(|r| r.user_id == prefs.user_id) {
+                reviewers.push(ReviewerLoad {
+                    user_id: prefs.user_id,
+                    prs: vec![],
+                    capacity: prefs
+                        .repo_review_prefs
+                        .get(&full_name)
+                        .and_then(|p| p.max_assigned_prs),
+                    on_rotation: prefs.rotation_mode == RotationMode::OnRotation,
+                });
+            }
+        }
+    }
+
+    for Move { pr, from, to } in
+        select_moves(&reviewers, &candidates, &started, rebalance.max_moves)
+    {
+        let reassigned = crate::dry_run::scope(
+            config.dry_run.is_some(),
+            reassign(ctx, &pull_requests[&pr], from, to),
+        );
+        if let Err(err) = reassigned.await {
+            tracing::error!("failed to reassign {issue_repo}#{pr} from {from} to {to}: {err:?}");
+        }
+    }
+
+    Ok(())
+}
+
+async fn reassign(ctx: &Context, pr: &Issue, from: UserId, to: UserId) -> anyhow::Result<()> {
+    let from = ctx
+        .team
+        .username_from_gh_id(from)
+        .await?
+        .context("unknown current reviewer")?;
+    let to = ctx
+        .team
+        .username_from_gh_id(to)
+        .await?
+        .context("unknown new reviewer")?;
+
+    if pr.user.login.eq_ignore_ascii_case(&to) {
+        return Ok(());
+    }
+
+    pr.set_assignee(&ctx.github, &to)
+        .await
+        .context("unable to reassign the pull request")?;
+    pr.post_comment(
+        &ctx.github,
+        &format!(
+            "This pull request has been reassigned from @{from} to @{to}, \
+             to balance the review workqueues."
+        ),
+    )
+    .await
+    .context("unable to post the reassignment comment")?;
+
+    Ok(())
+}
+
+#[derive(Debug)]
+struct ReviewerLoad {
+    user_id: UserId,
+    /// Assigned pull requests, with their creation date.
+    prs: Vec<(PullRequestNumber, DateTime<Utc>)>,
+    /// Maximum number of assigned pull requests, if configured.
+    capacity: Option<u32>,
+    on_rotation: bool,
+}
+
+impl ReviewerLoad {
+    fn is_overloaded(&self) -> bool {
+        self.capacity
+            .is_some_and(|capacity| self.prs.len() > capacity as usize)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Move {
+    pr: PullRequestNumber,
+    from: UserId,
+    to: UserId,
+}
+
+/// Selects at most `max_moves` pull requests to move from overloaded reviewers
+/// to the `candidates` of each pull request with the most spare capacity.
+///
+/// The newest pull requests are moved first, and `started` ones (with review
+/// activity) or without candidates are never moved.
+fn select_moves(
+    reviewers: &[ReviewerLoad],
+    candidates: &HashMap<PullRequestNumber, Vec<UserId>>,
+    started: &HashSet<PullRequestNumber>,
+    max_moves: usize,
+) -> Vec<Move> {
+    let mut load: HashMap<UserId, usize> =
+        reviewers.iter().map(|r| (r.user_id, r.prs.len())).collect();
+    let spare = |load: &HashMap<UserId, usize>, reviewer: &ReviewerLoad| {
+        reviewer
+            .capacity
+            .map(|capacity| capacity as isize - load[&reviewer.user_id] as isize)
+    };
+
+    // Start with the most overloaded reviewers
+    let mut overloaded: Vec<_> = reviewers.iter().filter(|r| r.is_overloaded()).collect();
+    overloaded.sort_by_key(|r| (spare(&load, r), r.user_id));
+
+    let mut moves = vec![];
+    for reviewer in overloaded {
+        let mut prs: Vec<_> = reviewer
+            .prs
+            .iter()
+            .filter(|(pr, _)| !started.contains(pr))
+            .collect();
+        prs.sort_by_key(|(pr, created_at)| Reverse((*created_at, *pr)));
+
+        for (pr, _) in prs {
+            if moves.len() >= max_moves {
+                return moves;
+            }
+            if spare(&load, reviewer).is_some_and(|spare| spare >= 0) {
+                break;
+            }
+
+            let Some(target) = reviewers
+                .iter()
+                .filter(|r| candidates.get(pr).is_some_and(|c| c.contains(&r.user_id)))
+                .filter(|r| r.on_rotation && r.user_id != reviewer.user_id)
+                .filter(|r| spare(&load, r).is_some_and(|spare| spare > 0))
+                .min_by_key(|r| (Reverse(spare(&load, r)), r.user_id))
+            else {
+                // None of its eligible reviewers can take more reviews
+                continue;
+            };
+
+            *load.get_mut(&reviewer.user_id).unwrap() -= 1;
+            *load.get_mut(&target.user_id).unwrap() += 1;
+            moves.push(Move {
+                pr: *pr,
+                from: reviewer.user_id,
+                to: target.user_id,
+            });
+        }
+    }
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn reviewer(
+        user_id: UserId,
+        prs: &[PullRequestNumber],
+        capacity: Option<u32>,
+        on_rotation: bool,
+    ) -> ReviewerLoad {
+        let epoch = DateTime::<Utc>::default();
+        ReviewerLoad {
+            user_id,
+            // Higher PR numbers are newer
+            prs: prs
+                .iter()
+                .map(|&pr| (pr, epoch + Duration::days(pr as i64)))
+                .collect(),
+            capacity,
+            on_rotation,
+        }
+    }
+
+    fn reviewers() -> Vec<ReviewerLoad> {
+        vec![
+            // Overloaded by 2
+            reviewer(1, &[10, 11, 12, 13], Some(2), true),
+            // Has room for 2 more PRs
+            reviewer(2, &[20], Some(3), true),
+            // Eligible reviewer without any PR yet, room for 2 PRs
+            reviewer(3, &[], Some(2), true),
+        ]
+    }
+
+    fn candidates(
+        candidates: &[(PullRequestNumber, &[UserId])],
+    ) -> HashMap<PullRequestNumber, Vec<UserId>> {
+        candidates
+            .iter()
+            .map(|(pr, users)| (*pr, users.to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn moves_newest_prs_to_their_eligible_reviewers() {
+        let candidates = candidates(&[(13, &[2, 3]), (12, &[3]), (11, &[2]), (10, &[2, 3])]);
+        assert_eq!(
+            select_moves(&reviewers(), &candidates, &HashSet::new(), 10),
+            vec![
+                Move {
+                    pr: 13,
+                    from: 1,
+                    to: 2
+                },
+                Move {
+                    pr: 12,
+                    from: 1,
+                    to: 3
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_started_prs() {
+        let candidates = candidates(&[(13, &[2, 3]), (12, &[3]), (11, &[2]), (10, &[2, 3])]);
+        assert_eq!(
+            select_moves(&reviewers(), &candidates, &HashSet::from([13]), 10),
+            vec![
+                Move {
+                    pr: 12,
+                    from: 1,
+                    to: 3
+                },
+                Move {
+                    pr: 11,
+                    from: 1,
+                    to: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_prs_without_available_eligible_reviewers() {
+        // PR 13 has no eligible reviewer, and the only one of PR 12 is its
+        // current reviewer
+        let candidates = candidates(&[(13, &[]), (12, &[1]), (11, &[3]), (10, &[2])]);
+        assert_eq!(
+            select_moves(&reviewers(), &candidates, &HashSet::new(), 10),
+            vec![
+                Move {
+                    pr: 11,
+                    from: 1,
+                    to: 3
+                },
+                Move {
+                    pr: 10,
+                    from: 1,
+                    to: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn limits_the_number_of_moves() {
+        let candidates = candidates(&[(13, &[2, 3]), (12, &[3])]);
+        assert_eq!(
+            select_moves(&reviewers(), &candidates, &HashSet::new(), 1),
+            vec![Move {
+                pr: 13,
+                from: 1,
+                to: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn no_moves_without_spare_capacity() {
+        let reviewers = vec![
+            reviewer(1, &[10, 11, 12], Some(1), true),
+            reviewer(2, &[20], Some(1), true),
+            // Off rotation
+            reviewer(3, &[], Some(4), false),
+            // Without a capacity
+            reviewer(4, &[], None, true),
+        ];
+        let candidates = candidates(&[(12, &[2, 3, 4]), (11, &[2, 3, 4]), (10, &[2, 3, 4])]);
+        assert_eq!(
+            select_moves(&reviewers, &candidates, &HashSet::new(), 10),
+            vec![]
+        );
+    }
+}
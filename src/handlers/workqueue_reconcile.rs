@@ -0,0 +1,249 @@
+//! Periodically reconciles the reviewer workqueues with the assignments on GitHub.
+//!
+//! Missed webhooks make the in-memory workqueues drift from GitHub. Rather than
+//! replacing the whole workqueues, only the entries that differ from GitHub are
+//! corrected, which keeps the state only known by triagebot (like the pull
+//! requests marked as active). Besides the missed assignments, this covers the
+//! pull requests which stopped waiting for a review (e.g. converted to drafts)
+//! and the ones whose title or creation date changed.
+//!
+//! The number of corrections is logged and exposed as the
+//! `workqueue_corrections_total` metric.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+
+use crate::github::{PullRequestNumber, UserId};
+use crate::handlers::Context;
+use crate::handlers::pr_tracking::{AssignedPullRequest, ReviewerWorkqueue, load_workqueue};
+use crate::jobs::Job;
+
+/// Number of workqueue entries corrected since triagebot started.
+pub static WORKQUEUE_CORRECTIONS: AtomicU64 = AtomicU64::new(0);
+
+pub struct WorkqueueReconcileJob;
+
+#[async_trait]
+impl Job for WorkqueueReconcileJob {
+    fn name(&self) -> &'static str {
+        "workqueue_reconcile"
+    }
+
+    async fn run(&self, ctx: &Context, _metadata: &serde_json::Value) -> anyhow::Result<()> {
+        for (repo, workqueue_arc) in ctx.workqueue_map.tracked_repositories() {
+            // Don't hold the workqueue lock while querying GitHub
+            let github = match load_workqueue(&ctx.octocrab, repo).await {
+                Ok(github) => github,
+                Err(err) => {
+                    tracing::error!(
+                        "{}: cannot load the assignments of {}: {err:?}",
+                        self.name(),
+                        repo.full_name()
+                    );
+                    continue;
+                }
+            };
+
+            let mut workqueue = workqueue_arc.write().await;
+            let corrections = corrections(&workqueue, &github);
+            if corrections.is_empty() {
+                continue;
+            }
+            tracing::info!(
+                "{}: correcting {} workqueue entries of {}: {corrections:?}",
+                self.name(),
+                corrections.len(),
+                repo.full_name()
+            );
+            WORKQUEUE_CORRECTIONS.fetch_add(corrections.len() as u64, Ordering::Relaxed);
+            apply(&mut workqueue, corrections);
+        }
+        Ok(())
+    }
+}
+
+/// An operation bringing a workqueue in line with GitHub.
+#[derive(Debug, PartialEq, Eq)]
+enum Correction {
+    /// The pull request is assigned to the reviewer on GitHub, but is missing
+    /// from their workqueue.
+    Assign {
+        reviewer: UserId,
+        pr: PullRequestNumber,
+        assigned_pr: AssignedPullRequest,
+    },
+    /// The pull request is in the workqueue of the reviewer, but its title or
+    /// creation date changed on GitHub. The entry stays active if it was.
+    Refresh {
+        reviewer: UserId,
+        pr: PullRequestNumber,
+        assigned_pr: AssignedPullRequest,
+    },
+    /// The pull request is in the workqueue of the reviewer, but isn't waiting
+    /// for their review on GitHub anymore.
+    Unassign {
+        reviewer: UserId,
+        pr: PullRequestNumber,
+    },
+}
+
+/// Returns the corrections turning the `current` workqueue into the `github` one,
+/// sorted by reviewer and pull request.
+fn corrections(current: &ReviewerWorkqueue, github: &ReviewerWorkqueue) -> Vec<Correction> {
+    let current: BTreeMap<_, _> = current.assignments().collect();
+    let github: BTreeMap<_, _> = github.assignments().collect();
+
+    let mut corrections = vec![];
+    for (&reviewer, prs) in &github {
+        let mut prs: Vec<_> = prs.iter().collect();
+        prs.sort_by_key(|(pr, _)| **pr);
+        for (&pr, assigned_pr) in prs {
+            match current.get(&reviewer).and_then(|c| c.get(&pr)) {
+                None => corrections.push(Correction::Assign {
+                    reviewer,
+                    pr,
+                    assigned_pr: assigned_pr.clone(),
+                }),
+                Some(known)
+                    if known.title != assigned_pr.title
+                        || known.created_at != assigned_pr.created_at =>
+                {
+                    corrections.push(Correction::Refresh {
+                        reviewer,
+                        pr,
+                        assigned_pr: AssignedPullRequest {
+                            active: known.active,
+                            ..assigned_pr.clone()
+                        },
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    for (&reviewer, prs) in &current {
+        let mut stale: Vec<_> = prs
+            .keys()
+            .filter(|pr| !github.get(&reviewer).is_some_and(|g| g.contains_key(pr)))
+            .collect();
+        stale.sort();
+        corrections.extend(
+            stale
+                .into_iter()
+                .map(|pr| Correction::Unassign { reviewer, pr: *pr }),
+        );
+    }
+    corrections
+}
+
+fn apply(workqueue: &mut ReviewerWorkqueue, corrections: Vec<Correction>) {
+    for correction in corrections {
+        match correction {
+            Correction::Assign {
+                reviewer,
+                pr,
+                assigned_pr,
+            }
+            | Correction::Refresh {
+                reviewer,
+                pr,
+                assigned_pr,
+            } => workqueue.assign(reviewer, pr, assigned_pr),
+            Correction::Unassign { reviewer, pr } => workqueue.unassign(reviewer, pr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeSet, HashMap};
+
+    #[test]
+    fn refreshes_renamed_entries() {
+        let mut current = ReviewerWorkqueue::from_pr_numbers(HashMap::from([(1, vec![10, 11])]));
+        let mut github = ReviewerWorkqueue::from_pr_numbers(HashMap::from([(1, vec![10, 11])]));
+        current.mark_active(1, 11);
+        let renamed = AssignedPullRequest {
+            title: "Renamed PR 11".to_string(),
+            created_at: chrono::DateTime::UNIX_EPOCH,
+            active: false,
+        };
+        github.assign(1, 11, renamed.clone());
+
+        let drift = corrections(&current, &github);
+        assert_eq!(
+            drift,
+            vec![Correction::Refresh {
+                reviewer: 1,
+                pr: 11,
+                assigned_pr: AssignedPullRequest {
+                    active: true,
+                    ..renamed
+                },
+            }]
+        );
+
+        apply(&mut current, drift);
+        // The active entries stay active
+        assert!(current.assignments().any(|(user_id, prs)| user_id == 1
+            && prs[&11].active
+            && prs[&11].title == "Renamed PR 11"));
+        assert_eq!(corrections(&current, &github), vec![]);
+    }
+
+    #[test]
+    fn corrects_drifted_entries() {
+        // Reviewer 1 missed an assignment of #12 and the unassignment of #10,
+        // reviewer 2 missed being assigned #20 and reviewer 3 missed the merge of #30.
+        let mut current =
+            ReviewerWorkqueue::from_pr_numbers(HashMap::from([(1, vec![10, 11]), (3, vec![30])]));
+        let github =
+            ReviewerWorkqueue::from_pr_numbers(HashMap::from([(1, vec![11, 12]), (2, vec![20])]));
+        current.mark_active(1, 11);
+
+        let pr = |number: PullRequestNumber| AssignedPullRequest {
+            title: format!("PR {number}"),
+            created_at: chrono::DateTime::UNIX_EPOCH,
+            active: false,
+        };
+        let drift = corrections(&current, &github);
+        assert_eq!(
+            drift,
+            vec![
+                Correction::Assign {
+                    reviewer: 1,
+                    pr: 12,
+                    assigned_pr: pr(12),
+                },
+                Correction::Assign {
+                    reviewer: 2,
+                    pr: 20,
+                    assigned_pr: pr(20),
+                },
+                Correction::Unassign {
+                    reviewer: 1,
+                    pr: 10
+                },
+                Correction::Unassign {
+                    reviewer: 3,
+                    pr: 30
+                },
+            ]
+        );
+
+        apply(&mut current, drift);
+        assert_eq!(current.assigned_prs(1), BTreeSet::from([11, 12]));
+        assert_eq!(current.assigned_prs(2), BTreeSet::from([20]));
+        assert_eq!(current.assigned_prs(3), BTreeSet::new());
+        // The entries that didn't drift are kept as is
+        assert!(
+            current
+                .assignments()
+                .any(|(user_id, prs)| user_id == 1 && prs[&11].active)
+        );
+        assert_eq!(corrections(&current, &github), vec![]);
+    }
+}
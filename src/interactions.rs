@@ -7,10 +7,19 @@ use crate::{
     github::{Comment, GithubClient, Issue},
 };
 use std::fmt::Write;
+use std::sync::LazyLock;
 
 pub const REPORT_TO: &str = "Please file an issue on GitHub at [triagebot](https://github.com/rust-lang/triagebot) if there's \
             a problem with this bot, or reach out on [#triagebot](https://rust-lang.zulipchat.com/#narrow/channel/224082-triagebot) on Zulip.";
 
+/// Optional footer appended to the error comments, from `TRIAGEBOT_ERROR_FOOTER`
+/// (e.g. a link to the documentation of a self-hosted instance).
+static ERROR_FOOTER: LazyLock<Option<String>> = LazyLock::new(|| {
+    std::env::var("TRIAGEBOT_ERROR_FOOTER")
+        .ok()
+        .filter(|footer| !footer.trim().is_empty())
+});
+
 pub struct ErrorComment<'a> {
     issue: &'a Issue,
     message: String,
@@ -28,10 +37,18 @@ impl<'a> ErrorComment<'a> {
     }
 
     pub fn markdown(message: &str) -> anyhow::Result<String> {
+        Self::markdown_with_footer(message, ERROR_FOOTER.as_deref())
+    }
+
+    fn markdown_with_footer(message: &str, footer: Option<&str>) -> anyhow::Result<String> {
         let mut body = String::new();
         writeln!(body, "**Error**: {message}")?;
         writeln!(body)?;
         writeln!(body, "*{REPORT_TO}*")?;
+        if let Some(footer) = footer {
+            writeln!(body)?;
+            writeln!(body, "{footer}")?;
+        }
         Ok(body)
     }
 
@@ -175,3 +192,26 @@ where
         format!("$$TRIAGEBOT_{}_DATA_END -->\n", self.id)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ErrorComment, REPORT_TO};
+
+    #[test]
+    fn error_comment_footer() {
+        assert_eq!(
+            ErrorComment::markdown_with_footer("Unknown label `A-foo`.", None).unwrap(),
+            format!("**Error**: Unknown label `A-foo`.\n\n*{REPORT_TO}*\n")
+        );
+        assert_eq!(
+            ErrorComment::markdown_with_footer(
+                "Unknown label `A-foo`.",
+                Some("— [triagebot docs](https://example.com/triagebot)")
+            )
+            .unwrap(),
+            format!(
+                "**Error**: Unknown label `A-foo`.\n\n*{REPORT_TO}*\n\n— [triagebot docs](https://example.com/triagebot)\n"
+            )
+        );
+    }
+}
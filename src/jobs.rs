@@ -55,8 +55,16 @@ use crate::{
     db::jobs::JobSchedule,
     github::client::GithubRateLimitLoggingJob,
     handlers::{
-        Context, docs_update::DocsUpdateJob, major_change::MajorChangeAcceptanceJob,
-        review_changes_since::AddReviewChangesSinceLinkJob, rustc_commits::RustcCommitsJob,
+        Context,
+        close::BulkCloseJob,
+        docs_update::DocsUpdateJob,
+        major_change::{MajorChangeAcceptanceJob, MajorChangeStaleJob},
+        milestone_prs::MilestoneSubmoduleJob,
+        relabel::BulkRelabelJob,
+        review_changes_since::AddReviewChangesSinceLinkJob,
+        rustc_commits::RustcCommitsJob,
+        workqueue_rebalance::WorkqueueRebalanceJob,
+        workqueue_reconcile::WorkqueueReconcileJob,
     },
 };
 
@@ -75,8 +83,14 @@ pub fn jobs() -> Vec<Box<dyn Job + Send + Sync>> {
         Box::new(RustcCommitsJob),
         Box::new(PullRequestAssignmentUpdate),
         Box::new(MajorChangeAcceptanceJob),
+        Box::new(MajorChangeStaleJob),
         Box::new(GithubRateLimitLoggingJob),
         Box::new(AddReviewChangesSinceLinkJob),
+        Box::new(WorkqueueRebalanceJob),
+        Box::new(WorkqueueReconcileJob),
+        Box::new(MilestoneSubmoduleJob),
+        Box::new(BulkCloseJob),
+        Box::new(BulkRelabelJob),
     ]
 }
 
@@ -96,8 +110,10 @@ pub fn default_jobs() -> Vec<JobSchedule> {
             metadata: serde_json::Value::Null,
         },
         JobSchedule {
-            name: PullRequestAssignmentUpdate.name(),
-            // Every 30 minutes
+            name: WorkqueueReconcileJob.name(),
+            // Every 30 minutes. It corrects the workqueues drifting from GitHub
+            // (assignments and pull request metadata), so the full reload of
+            // `PullRequestAssignmentUpdate` isn't scheduled.
             schedule: Schedule::from_str("* 0,30 * * * * *").unwrap(),
             metadata: serde_json::Value::Null,
         },
@@ -107,6 +123,20 @@ pub fn default_jobs() -> Vec<JobSchedule> {
             schedule: Schedule::from_str("* */15 * * * * *").unwrap(),
             metadata: serde_json::Value::Null,
         },
+        JobSchedule {
+            name: MajorChangeStaleJob.name(),
+            // Around 8am UTC every day. Only repositories with a
+            // `[major-change.stale]` configuration are affected.
+            schedule: Schedule::from_str("0 0 8 * * * *").unwrap(),
+            metadata: serde_json::Value::Null,
+        },
+        JobSchedule {
+            name: WorkqueueRebalanceJob.name(),
+            // Around 7am UTC every day. Only repositories with a
+            // `[pr-tracking.rebalance]` configuration are affected.
+            schedule: Schedule::from_str("0 0 7 * * * *").unwrap(),
+            metadata: serde_json::Value::Null,
+        },
     ]
 }
 
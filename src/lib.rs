@@ -2,11 +2,15 @@
 
 mod actions;
 pub mod agenda;
+pub mod api;
 pub mod bors;
 mod cache;
 mod changelogs;
 mod config;
 pub mod db;
+pub mod debug;
+mod diffstat;
+mod dry_run;
 mod errors;
 pub mod gh_changes_since;
 pub mod gh_comments;
@@ -17,10 +21,13 @@ pub mod handlers;
 pub mod http_client;
 mod interactions;
 pub mod jobs;
+pub mod metrics;
+pub mod milestone;
 mod rfcbot;
 pub mod team_data;
 pub mod triage;
 mod utils;
+pub mod workqueue;
 pub mod zulip;
 
 #[cfg(test)]
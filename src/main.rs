@@ -3,28 +3,36 @@
 use anyhow::Context as _;
 use axum::body::Body;
 use axum::error_handling::HandleErrorLayer;
+use axum::extract::DefaultBodyLimit;
 use axum::extract::State;
-use axum::http::HeaderName;
+use axum::http::header::RETRY_AFTER;
+use axum::http::{HeaderName, HeaderValue, Method};
 use axum::middleware::{self, Next};
 use axum::response::{Html, IntoResponse, Response};
-use axum::routing::{get, post};
+use axum::routing::{get, post, put};
 use axum::{BoxError, Router};
 use hyper::{Request, StatusCode};
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 use std::{env, net::SocketAddr, sync::Arc};
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, watch};
 use tokio::{task, time};
 use tower::ServiceBuilder;
 use tower::buffer::BufferLayer;
 use tower::limit::RateLimitLayer;
+use tower::load_shed::LoadShedLayer;
+use tower::load_shed::error::Overloaded;
 use tower_governor::GovernorLayer;
 use tower_governor::governor::GovernorConfigBuilder;
 use tower_governor::key_extractor::SmartIpKeyExtractor;
 use tower_http::catch_panic::CatchPanicLayer;
 use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
 use tower_http::trace::TraceLayer;
 use tracing::{self as log, info_span};
+use triagebot::agenda::AGENDA_RATE_LIMITED;
 use triagebot::gh_comments::{GH_COMMENTS_CACHE_CAPACITY_BYTES, GitHubCommentsCache};
 use triagebot::gha_logs::{GHA_LOGS_CACHE_CAPACITY_BYTES, GitHubActionLogsCache};
 use triagebot::handlers::Context;
@@ -40,14 +48,82 @@ use triagebot::{db, github};
 
 const BANNED_USER_AGENTS: &[&str] = &["meta-webindexer/1.1", "Nexus 5 Build/MRA58N"];
 
+/// Maximum time given to in-flight requests and jobs to finish once a shutdown
+/// signal is received, before exiting anyway.
+///
+/// This is kept below the 30s ECS waits for before sending a SIGKILL.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(25);
+
+/// Default maximum size of the GitHub webhook payloads, the same as axum's
+/// default limit. It can be raised with `WEBHOOK_BODY_LIMIT` (GitHub caps them at 25 MB).
+const DEFAULT_WEBHOOK_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Maximum size of the Zulip webhook payloads, which only contain a single
+/// message (Zulip caps them at 10000 characters).
+const ZULIP_WEBHOOK_BODY_LIMIT: usize = 128 * 1024;
+
+/// Rejects the requests with a body larger than `limit` bytes with a 413.
+fn with_body_limit<S: Clone + Send + Sync + 'static>(router: Router<S>, limit: usize) -> Router<S> {
+    router
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(limit))
+}
+
+/// Allows the browsers to read the responses of `router` from the `origins`,
+/// e.g. for dashboards using the JSON endpoints. No CORS headers are sent without
+/// any allowed origin.
+fn with_cors<S: Clone + Send + Sync + 'static>(
+    router: Router<S>,
+    origins: &[HeaderValue],
+) -> Router<S> {
+    if origins.is_empty() {
+        return router;
+    }
+    router.layer(
+        CorsLayer::new()
+            .allow_origin(AllowOrigin::list(origins.iter().cloned()))
+            .allow_methods([Method::GET]),
+    )
+}
+
+/// The agenda is expensive to generate, so its `router` is rate limited to `num`
+/// requests `per` period. Requests over the limit are rejected with a 429.
+fn rate_limit_agenda<S: Clone + Send + Sync + 'static>(
+    router: Router<S>,
+    num: u64,
+    per: Duration,
+) -> Router<S> {
+    router.layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(move |err: BoxError| async move {
+                if err.is::<Overloaded>() {
+                    let rejected = AGENDA_RATE_LIMITED.fetch_add(1, Ordering::Relaxed) + 1;
+                    log::warn!("agenda rate limit exceeded ({rejected} requests rejected)");
+                    return (
+                        StatusCode::TOO_MANY_REQUESTS,
+                        [(RETRY_AFTER, per.as_secs().to_string())],
+                        "Too many requests, try again later.",
+                    )
+                        .into_response();
+                }
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Unhandled error: {err}"),
+                )
+                    .into_response()
+            }))
+            .layer(BufferLayer::new(5))
+            // Reject the requests over the limit instead of queuing them.
+            .layer(LoadShedLayer::new())
+            .layer(RateLimitLayer::new(num, per)),
+    )
+}
+
 async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
     let gh = github::GithubClient::new_from_env();
     let zulip = ZulipClient::new_from_env();
     let team_api = TeamClient::new_from_env();
-    let oc = octocrab::OctocrabBuilder::new()
-        .personal_token(github::default_token_from_env())
-        .build()
-        .expect("Failed to build octocrab.");
+    let oc = github::default_octocrab_from_env().expect("Failed to build octocrab.");
 
     check_ongoing_service_maintenance();
 
@@ -128,11 +204,16 @@ async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
         zulip,
     });
 
+    // Set to `true` once a shutdown signal has been received.
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
     // Run all jobs that have a schedule (recurring jobs)
-    if !is_scheduled_jobs_disabled() {
+    let job_runner = if !is_scheduled_jobs_disabled() {
         spawn_job_scheduler(db_url);
-        spawn_job_runner(ctx.clone());
-    }
+        Some(spawn_job_runner(ctx.clone(), shutdown_rx))
+    } else {
+        None
+    };
 
     let ratelimit_config = if !std::env::var("DISABLE_RATE_LIMIT").is_ok_and(|value| value == "1") {
         // Allow bursts with up to 3 requests per IP address
@@ -172,7 +253,7 @@ async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
                     if let Some(request_id) = request_id {
                         info_span!(
                             "request",
-                            request_id = ?request_id,
+                            request_id = request_id.to_str().unwrap_or("<invalid>"),
                         )
                     } else {
                         tracing::error!("could not extract request_id");
@@ -191,23 +272,35 @@ async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
         .layer(CatchPanicLayer::new())
         .layer(middleware::from_fn(block_user_agents));
 
-    let agenda = Router::new()
-        .route("/", get(|| async { Html(triagebot::agenda::INDEX) }))
-        .route(
-            "/types/planning",
-            get(triagebot::agenda::types_planning_http),
-        )
-        .layer(
-            ServiceBuilder::new()
-                .layer(HandleErrorLayer::new(|err: BoxError| async move {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        format!("Unhandled error: {err}"),
-                    )
-                }))
-                .layer(BufferLayer::new(5))
-                .layer(RateLimitLayer::new(2, Duration::from_secs(60))),
-        );
+    let agenda = rate_limit_agenda(
+        Router::new()
+            .route("/", get(|| async { Html(triagebot::agenda::INDEX) }))
+            .route(
+                "/types/planning",
+                get(triagebot::agenda::types_planning_http),
+            ),
+        2,
+        Duration::from_secs(60),
+    )
+    // Archived agendas are cheap to retrieve
+    .route(
+        "/{team}/archive/{week}",
+        get(triagebot::agenda::archived_agenda_http),
+    );
+
+    // Origins allowed to read the read-only JSON endpoints from a browser,
+    // as a comma-separated list (e.g. `https://example.com,https://example.org`).
+    let cors_origins = env::var("CORS_ALLOWED_ORIGINS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|origin| !origin.is_empty())
+        .map(|origin| {
+            origin
+                .parse::<HeaderValue>()
+                .expect("CORS_ALLOWED_ORIGINS must contain valid origins")
+        })
+        .collect::<Vec<_>>();
 
     let protected = Router::new()
         .route(
@@ -238,20 +331,88 @@ async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
             "/gh-comments/{owner}/{repo}/pull/{pr}",
             get(triagebot::gh_comments::gh_comments),
         )
+        .merge(with_cors(
+            Router::new().route(
+                "/milestone/{owner}/{repo}/{version}",
+                get(triagebot::milestone::milestone),
+            ),
+            &cors_origins,
+        ))
         .layer(GovernorLayer::new(ratelimit_config))
         .layer(middleware::from_fn_with_state(
             ctx.clone(),
             block_if_github_ratelimit_too_low,
         ));
 
+    let api = Router::new()
+        .route("/config/{owner}/{repo}", get(triagebot::api::config))
+        .route(
+            "/milestone/recheck/{owner}/{repo}/{pr}",
+            post(triagebot::api::milestone_recheck),
+        )
+        .route("/close/bulk", post(triagebot::api::close_bulk))
+        .route("/relabel/bulk", post(triagebot::api::relabel_bulk))
+        .route(
+            "/workqueue/resync/{owner}/{repo}/{pr}",
+            post(triagebot::api::workqueue_resync),
+        )
+        .route(
+            "/handlers/{owner}/{repo}",
+            get(triagebot::api::disabled_handlers),
+        )
+        .route(
+            "/handlers/{owner}/{repo}/{handler}",
+            put(triagebot::api::disable_handler).delete(triagebot::api::enable_handler),
+        )
+        .layer(middleware::from_fn(triagebot::api::require_api_token));
+
+    let debug = if env::var_os("TRIAGEBOT_DEBUG_ENDPOINTS").is_some() {
+        Router::new()
+            .route("/debug/zulip-topic", get(triagebot::debug::zulip_topic))
+            .route("/debug/parse", post(triagebot::debug::parse))
+    } else {
+        Router::new()
+    };
+
+    let webhook_body_limit = env::var("WEBHOOK_BODY_LIMIT")
+        .ok()
+        .map(|limit| {
+            limit
+                .parse()
+                .expect("WEBHOOK_BODY_LIMIT must be a number of bytes")
+        })
+        .unwrap_or(DEFAULT_WEBHOOK_BODY_LIMIT);
+    let webhooks = with_body_limit(
+        Router::new().route("/github-hook", post(triagebot::github::webhook)),
+        webhook_body_limit,
+    )
+    .merge(with_body_limit(
+        Router::new().route("/zulip-hook", post(triagebot::zulip::webhook)),
+        ZULIP_WEBHOOK_BODY_LIMIT,
+    ));
+
     let app = Router::new()
         .route("/", get(|| async { "Triagebot is awaiting triage." }))
         .route(
             "/robots.txt",
             get(|| async { "User-Agent: *\nDisallow: /\n" }),
         )
+        .route("/metrics", get(triagebot::metrics::metrics))
         .route("/triage", get(triagebot::triage::index))
         .route("/triage/{owner}/{repo}", get(triagebot::triage::pulls))
+        .merge(with_cors(
+            Router::new()
+                .route("/workqueue", get(triagebot::workqueue::full_workqueue))
+                .route(
+                    "/workqueue/{user}",
+                    get(triagebot::workqueue::user_workqueue),
+                )
+                .route(
+                    "/workqueue/{user}/stale",
+                    get(triagebot::workqueue::stale_workqueue),
+                ),
+            &cors_origins,
+        ))
         .route(
             triagebot::gha_logs::ANSI_UP_URL,
             get(triagebot::gha_logs::ansi_up_min_js),
@@ -281,10 +442,11 @@ async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
             get(triagebot::gh_comments::relative_time_element_js),
         )
         .merge(protected)
+        .merge(api)
+        .merge(debug)
         .nest("/agenda", agenda)
         .route("/bors-commit-list", get(triagebot::bors::bors_commit_list))
-        .route("/zulip-hook", post(triagebot::zulip::webhook))
-        .route("/github-hook", post(triagebot::github::webhook))
+        .merge(webhooks)
         .layer(middleware)
         .with_state(ctx);
 
@@ -295,12 +457,52 @@ async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
         listener,
         app.into_make_service_with_connect_info::<SocketAddr>(),
     )
+    .with_graceful_shutdown(async move {
+        shutdown_signal().await;
+        log::info!("Shutting down, waiting for in-flight requests and jobs");
+        shutdown_tx.send_replace(true);
+
+        task::spawn(async {
+            time::sleep(SHUTDOWN_TIMEOUT).await;
+            log::error!("Graceful shutdown timed out after {SHUTDOWN_TIMEOUT:?}, exiting");
+            std::process::exit(1);
+        });
+    })
     .await
     .unwrap();
 
+    if let Some(job_runner) = job_runner {
+        job_runner.await.context("job runner failed to stop")?;
+    }
+    log::info!("Shutdown complete");
+
     Ok(())
 }
 
+/// Resolves once SIGINT (Ctrl+C) or SIGTERM has been received.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install the Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install the SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {},
+        () = terminate => {},
+    }
+}
+
 /// Check if the request is coming from one of our banned UA, reject the request if that's the case
 async fn block_user_agents(req: Request<axum::body::Body>, next: Next) -> Response {
     if let Some(user_agent_value) = req.headers().get(hyper::header::USER_AGENT)
@@ -382,17 +584,24 @@ fn spawn_job_scheduler(db_url: String) {
 /// The runner wakes up every `JOB_PROCESSING_CADENCE_IN_SECS` seconds to
 /// check if any jobs have been put into the queue by the scheduler. They
 /// will get popped off the queue and run if any are found.
-fn spawn_job_runner(ctx: Arc<Context>) {
+///
+/// Once `shutdown` is set, the runner finishes its current job and the
+/// returned task completes.
+fn spawn_job_runner(ctx: Arc<Context>, shutdown: watch::Receiver<bool>) -> task::JoinHandle<()> {
     task::spawn(async move {
         loop {
             let ctx = ctx.clone();
+            let mut shutdown = shutdown.clone();
             let res = task::spawn(async move {
                 let mut interval =
                     time::interval(time::Duration::from_secs(JOB_PROCESSING_CADENCE_IN_SECS));
 
                 loop {
-                    interval.tick().await;
-                    db::run_scheduled_jobs(&ctx)
+                    tokio::select! {
+                        _ = interval.tick() => {}
+                        _ = shutdown.wait_for(|shutdown| *shutdown) => return,
+                    }
+                    db::run_scheduled_jobs(&ctx, &shutdown)
                         .await
                         .context("run database scheduled jobs")
                         .unwrap();
@@ -400,6 +609,7 @@ fn spawn_job_runner(ctx: Arc<Context>) {
             });
 
             match res.await {
+                Ok(()) => return,
                 Err(err) if err.is_panic() => {
                     /* handle panic in above task, re-launching */
                     tracing::error!("run_scheduled_jobs task died (error={err})");
@@ -408,7 +618,7 @@ fn spawn_job_runner(ctx: Arc<Context>) {
                 _ => unreachable!(),
             }
         }
-    });
+    })
 }
 
 /// Determines whether or not background scheduled jobs should be disabled for
@@ -430,14 +640,35 @@ fn check_ongoing_service_maintenance() {
     }
 }
 
+/// Subscriber logging one JSON object per line, for ingestion by log aggregators.
+///
+/// The fields of the current span and its parents (like the `request_id`) are
+/// included in each event.
+fn json_subscriber<W>(make_writer: W) -> impl tracing::Subscriber + Send + Sync
+where
+    W: for<'w> tracing_subscriber::fmt::MakeWriter<'w> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt::Subscriber::builder()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .json()
+        .with_current_span(true)
+        .with_span_list(true)
+        .with_writer(make_writer)
+        .finish()
+}
+
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> anyhow::Result<()> {
     dotenvy::dotenv().ok();
-    tracing_subscriber::fmt::Subscriber::builder()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .with_ansi(std::env::var_os("DISABLE_COLOR").is_none())
-        .try_init()
-        .unwrap();
+    if env::var("LOG_FORMAT").is_ok_and(|format| format == "json") {
+        tracing::subscriber::set_global_default(json_subscriber(std::io::stdout)).unwrap();
+    } else {
+        tracing_subscriber::fmt::Subscriber::builder()
+            .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+            .with_ansi(std::env::var_os("DISABLE_COLOR").is_none())
+            .try_init()
+            .unwrap();
+    }
 
     let port = env::var("PORT")
         .ok()
@@ -447,3 +678,134 @@ async fn main() -> anyhow::Result<()> {
     run_server(addr).await.context("Failed to run the server")?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_logs_keep_request_id() {
+        let buffer = SharedBuffer::default();
+        let writer = buffer.clone();
+        tracing::subscriber::with_default(json_subscriber(move || writer.clone()), || {
+            let _span = info_span!("request", request_id = "0a1b2c").entered();
+            // Errors are always enabled, whatever the `RUST_LOG` filter is.
+            tracing::error!("something happened");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let line: serde_json::Value = serde_json::from_str(output.lines().next().unwrap()).unwrap();
+        assert_eq!(line["fields"]["message"], "something happened");
+        assert_eq!(line["span"]["request_id"], "0a1b2c");
+        assert_eq!(line["spans"][0]["request_id"], "0a1b2c");
+    }
+
+    #[tokio::test]
+    async fn agenda_rate_limit() {
+        use tower::ServiceExt;
+
+        let app = rate_limit_agenda(
+            Router::new().route("/", get(|| async { "agenda" })),
+            2,
+            Duration::from_secs(60),
+        );
+        let mut statuses = Vec::new();
+        for _ in 0..3 {
+            let response = app
+                .clone()
+                .oneshot(Request::get("/").body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            statuses.push((
+                response.status(),
+                response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .map(|value| value.to_str().unwrap().to_string()),
+            ));
+        }
+        assert_eq!(
+            statuses,
+            vec![
+                (StatusCode::OK, None),
+                (StatusCode::OK, None),
+                (StatusCode::TOO_MANY_REQUESTS, Some("60".to_string())),
+            ]
+        );
+        assert!(AGENDA_RATE_LIMITED.load(Ordering::Relaxed) >= 1);
+        assert!(
+            triagebot::metrics::metrics()
+                .await
+                .contains("\nagenda_rate_limited_total ")
+        );
+    }
+
+    #[tokio::test]
+    async fn webhook_body_limit() {
+        use tower::ServiceExt;
+
+        let app = with_body_limit(
+            Router::new().route("/hook", post(|body: String| async move { body })),
+            16,
+        );
+        let post = |body: &'static str| {
+            app.clone()
+                .oneshot(Request::post("/hook").body(Body::from(body)).unwrap())
+        };
+
+        assert_eq!(
+            post("small payload").await.unwrap().status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            post("a payload over the limit").await.unwrap().status(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+
+    #[tokio::test]
+    async fn cors_allowed_origins() {
+        use axum::http::header::{ACCESS_CONTROL_ALLOW_ORIGIN, ORIGIN};
+        use tower::ServiceExt;
+
+        let app = with_cors(
+            Router::new().route("/json", get(|| async { "[]" })),
+            &[HeaderValue::from_static("https://dashboard.example.com")],
+        );
+        let allow_origin = |origin: &'static str| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(
+                        Request::get("/json")
+                            .header(ORIGIN, origin)
+                            .body(Body::empty())
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap();
+                response.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).cloned()
+            }
+        };
+
+        assert_eq!(
+            allow_origin("https://dashboard.example.com").await,
+            Some(HeaderValue::from_static("https://dashboard.example.com"))
+        );
+        assert_eq!(allow_origin("https://evil.example.com").await, None);
+    }
+}
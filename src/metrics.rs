@@ -0,0 +1,63 @@
+//! Metrics of triagebot, in the Prometheus text exposition format.
+
+use crate::agenda::AGENDA_RATE_LIMITED;
+use crate::github::client::GITHUB_RATE_LIMITS;
+use crate::handlers::workqueue_reconcile::WORKQUEUE_CORRECTIONS;
+use std::fmt::Write as _;
+use std::sync::atomic::Ordering;
+
+/// `GET /metrics`
+pub async fn metrics() -> String {
+    let rate_limits = GITHUB_RATE_LIMITS.snapshot();
+    let mut out = String::new();
+
+    out.push_str(
+        "# HELP github_rate_limit_remaining Remaining GitHub API requests before the rate limit resets.\n\
+         # TYPE github_rate_limit_remaining gauge\n",
+    );
+    for (resource, limit) in &rate_limits {
+        writeln!(
+            out,
+            "github_rate_limit_remaining{{resource=\"{resource}\"}} {}",
+            limit.remaining
+        )
+        .unwrap();
+    }
+
+    out.push_str(
+        "# HELP github_rate_limit_reset Unix timestamp at which the GitHub rate limit resets.\n\
+         # TYPE github_rate_limit_reset gauge\n",
+    );
+    for (resource, limit) in &rate_limits {
+        writeln!(
+            out,
+            "github_rate_limit_reset{{resource=\"{resource}\"}} {}",
+            limit.reset
+        )
+        .unwrap();
+    }
+
+    out.push_str(
+        "# HELP workqueue_corrections_total Workqueue entries corrected because they drifted from GitHub.\n\
+         # TYPE workqueue_corrections_total counter\n",
+    );
+    writeln!(
+        out,
+        "workqueue_corrections_total {}",
+        WORKQUEUE_CORRECTIONS.load(Ordering::Relaxed)
+    )
+    .unwrap();
+
+    out.push_str(
+        "# HELP agenda_rate_limited_total Requests to the agenda rejected because of its rate limit.\n\
+         # TYPE agenda_rate_limited_total counter\n",
+    );
+    writeln!(
+        out,
+        "agenda_rate_limited_total {}",
+        AGENDA_RATE_LIMITED.load(Ordering::Relaxed)
+    )
+    .unwrap();
+
+    out
+}
@@ -0,0 +1,295 @@
+//! HTTP endpoint listing the pull requests of a milestone, e.g. to check what
+//! merged into a release after the PRs were milestoned.
+//!
+//! With `?format=changelog`, the pull requests are grouped in a Markdown
+//! changelog skeleton by the `changelog-sections` of the `[milestone-prs]` config.
+
+use std::fmt::Write;
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::{Html, IntoResponse, Response},
+};
+use hyper::StatusCode;
+use tracing as log;
+
+use crate::{config::ChangelogSectionConfig, errors::AppError, github::Issue, handlers::Context};
+
+#[derive(Debug, serde::Deserialize)]
+pub struct MilestoneParams {
+    /// `json` to get the listing as JSON, `changelog` to get a Markdown
+    /// changelog skeleton, HTML otherwise.
+    format: Option<String>,
+    /// Also list the issues and the unmerged pull requests, instead of only
+    /// the merged pull requests (which are the ones milestoned by triagebot).
+    #[serde(default)]
+    all: bool,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct MilestoneListing {
+    repository: String,
+    milestone: String,
+    items: Vec<MilestoneItem>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct MilestoneItem {
+    number: u64,
+    title: String,
+    url: String,
+    author: String,
+    is_pr: bool,
+    labels: Vec<String>,
+}
+
+/// Lists the pull requests of a milestone.
+pub async fn milestone(
+    Path((owner, repo, version)): Path<(String, String, String)>,
+    Query(params): Query<MilestoneParams>,
+    State(ctx): State<Arc<Context>>,
+) -> axum::response::Result<Response, AppError> {
+    let repository = format!("{owner}/{repo}");
+    let Some(milestone) = ctx.github.find_milestone(&repository, &version).await? else {
+        return Ok((
+            StatusCode::NOT_FOUND,
+            format!("milestone `{version}` doesn't exist in {repository}"),
+        )
+            .into_response());
+    };
+
+    let issues = ctx.github.milestone_issues(&repository, &milestone).await?;
+    let listing = MilestoneListing {
+        repository,
+        milestone: version,
+        items: milestone_items(issues, params.all),
+    };
+
+    match params.format.as_deref() {
+        Some("json") => Ok(Json(listing).into_response()),
+        Some("changelog") => {
+            let repository = ctx.github.repository(&listing.repository).await?;
+            let sections = match crate::config::get(&ctx.github, &repository).await {
+                Ok(config) => config
+                    .milestone_prs
+                    .as_ref()
+                    .map(|config| config.changelog_sections.clone())
+                    .unwrap_or_default(),
+                Err(err) => {
+                    log::warn!(
+                        "failed to get the configuration of {}: {err}",
+                        listing.repository
+                    );
+                    Vec::new()
+                }
+            };
+            Ok(render_changelog(&listing, &sections).into_response())
+        }
+        _ => Ok(Html(render_milestone(&listing)?).into_response()),
+    }
+}
+
+fn milestone_items(issues: Vec<Issue>, all: bool) -> Vec<MilestoneItem> {
+    issues
+        .into_iter()
+        .filter(|issue| {
+            let merged = issue.merged
+                || issue
+                    .pull_request
+                    .as_ref()
+                    .is_some_and(|pr| pr.merged_at.is_some());
+            all || merged
+        })
+        .map(|issue| MilestoneItem {
+            number: issue.number,
+            url: issue.html_url,
+            author: issue.user.login,
+            is_pr: issue.pull_request.is_some(),
+            labels: issue.labels.into_iter().map(|label| label.name).collect(),
+            title: issue.title,
+        })
+        .collect()
+}
+
+fn render_milestone(listing: &MilestoneListing) -> anyhow::Result<String> {
+    let mut title = String::new();
+    pulldown_cmark_escape::escape_html(
+        &mut title,
+        &format!("{} {}", listing.repository, listing.milestone),
+    )?;
+
+    let mut html = format!(
+        r#"<!DOCTYPE html>
+<title>Milestone {title}</title>
+<style>
+  :root {{ color-scheme: light dark; font-family: system-ui; margin: 2rem auto; max-width: 80ch; }}
+</style>
+<h1>Milestone {title}</h1>
+"#
+    );
+
+    if listing.items.is_empty() {
+        html.push_str("<p>Nothing was merged into this milestone.</p>\n");
+        return Ok(html);
+    }
+
+    html.push_str("<ul>\n");
+    for item in &listing.items {
+        let mut item_title = String::new();
+        pulldown_cmark_escape::escape_html(&mut item_title, &item.title)?;
+        writeln!(
+            html,
+            r#"<li><a href="{}">#{}</a> {item_title} by {}</li>"#,
+            item.url, item.number, item.author,
+        )?;
+    }
+    html.push_str("</ul>\n");
+
+    Ok(html)
+}
+
+/// Renders the pull requests of the milestone as a Markdown changelog, each in
+/// the first configured section matching one of its labels, or under "Other".
+fn render_changelog(listing: &MilestoneListing, sections: &[ChangelogSectionConfig]) -> String {
+    let mut grouped: Vec<Vec<&MilestoneItem>> = vec![Vec::new(); sections.len() + 1];
+    for item in listing.items.iter().filter(|item| item.is_pr) {
+        let section = sections
+            .iter()
+            .position(|section| item.labels.contains(&section.label))
+            .unwrap_or(sections.len());
+        grouped[section].push(item);
+    }
+
+    let titles = sections
+        .iter()
+        .map(|section| section.title.as_str())
+        .chain(["Other"]);
+    let mut changelog = format!("# {}\n", listing.milestone);
+    for (title, items) in titles.zip(grouped) {
+        if items.is_empty() {
+            continue;
+        }
+        changelog.push_str(&format!("\n## {title}\n\n"));
+        for item in items {
+            changelog.push_str(&format!(
+                "- [{}]({}) (#{})\n",
+                item.title, item.url, item.number
+            ));
+        }
+    }
+    changelog
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::github::{issue, pull_request};
+
+    #[test]
+    fn listing_serialization() {
+        let mut merged = pull_request().number(2).call();
+        merged.pull_request.as_mut().unwrap().merged_at = Some(chrono::Utc::now());
+        let issues = vec![
+            issue().number(1).call(),
+            merged,
+            pull_request().number(3).call(),
+        ];
+
+        let listing = MilestoneListing {
+            repository: "rust-lang/cargo".to_string(),
+            milestone: "1.90.0".to_string(),
+            items: milestone_items(issues, false),
+        };
+        assert_eq!(
+            serde_json::to_value(&listing).unwrap(),
+            serde_json::json!({
+                "repository": "rust-lang/cargo",
+                "milestone": "1.90.0",
+                "items": [{
+                    "number": 2,
+                    "title": listing.items[0].title,
+                    "url": listing.items[0].url,
+                    "author": listing.items[0].author,
+                    "is_pr": true,
+                    "labels": [],
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn listing_of_all_items() {
+        let issues = vec![issue().number(1).call(), pull_request().number(3).call()];
+        let items = milestone_items(issues, true);
+        assert_eq!(
+            items
+                .iter()
+                .map(|item| (item.number, item.is_pr))
+                .collect::<Vec<_>>(),
+            vec![(1, false), (3, true)]
+        );
+    }
+
+    #[test]
+    fn changelog_grouping() {
+        let item = |number, labels: &[&str]| MilestoneItem {
+            number,
+            title: format!("PR {number}"),
+            url: format!("https://github.com/rust-lang/rust/pull/{number}"),
+            author: "someone".to_string(),
+            is_pr: true,
+            labels: labels.iter().map(|label| label.to_string()).collect(),
+        };
+        let listing = MilestoneListing {
+            repository: "rust-lang/rust".to_string(),
+            milestone: "1.90.0".to_string(),
+            items: vec![
+                item(1, &["relnotes-compiler"]),
+                item(2, &["T-lang"]),
+                item(3, &["relnotes-language", "relnotes-compiler"]),
+                item(4, &["relnotes-language"]),
+                MilestoneItem {
+                    is_pr: false,
+                    ..item(5, &["relnotes-language"])
+                },
+            ],
+        };
+        let sections = [
+            ChangelogSectionConfig {
+                label: "relnotes-language".to_string(),
+                title: "Language".to_string(),
+            },
+            ChangelogSectionConfig {
+                label: "relnotes-compiler".to_string(),
+                title: "Compiler".to_string(),
+            },
+            ChangelogSectionConfig {
+                label: "relnotes-libs".to_string(),
+                title: "Libraries".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            render_changelog(&listing, &sections),
+            "\
+# 1.90.0
+
+## Language
+
+- [PR 3](https://github.com/rust-lang/rust/pull/3) (#3)
+- [PR 4](https://github.com/rust-lang/rust/pull/4) (#4)
+
+## Compiler
+
+- [PR 1](https://github.com/rust-lang/rust/pull/1) (#1)
+
+## Other
+
+- [PR 2](https://github.com/rust-lang/rust/pull/2) (#2)
+"
+        );
+        assert_eq!(render_changelog(&listing, &[]).matches("\n## ").count(), 1);
+    }
+}
@@ -1,5 +1,5 @@
 use reqwest::Client;
-use rust_team_data::v1::{BASE_URL, People, Repos, Teams, ZulipMapping};
+use rust_team_data::v1::{BASE_URL, People, Repos, Team, Teams, ZulipMapping};
 use serde::de::DeserializeOwned;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -130,6 +130,17 @@ impl TeamClient {
     }
 }
 
+/// Returns the GitHub logins of the members of the team, sorted and deduplicated.
+///
+/// The team comes from the team data cached by `TeamClient`, so expanding a
+/// team into its members doesn't query the team API again.
+pub fn member_logins(team: &Team) -> Vec<String> {
+    let mut logins: Vec<_> = team.members.iter().map(|m| m.github.clone()).collect();
+    logins.sort_by_key(|login| login.to_lowercase());
+    logins.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    logins
+}
+
 /// How long should downloaded team data items be cached in memory.
 const CACHE_DURATION: Duration = Duration::from_secs(2 * 60);
 
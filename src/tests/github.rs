@@ -1,4 +1,7 @@
-use crate::github::{GitHubUser, GitHubUserType, Issue, IssueState, Label, PullRequestDetails};
+use crate::github::{
+    Comment, Event, GitHubUser, GitHubUserType, Issue, IssueCommentAction, IssueCommentEvent,
+    IssueState, IssuesAction, IssuesEvent, Label, PullRequestDetails,
+};
 use bon::builder;
 use chrono::Utc;
 
@@ -97,3 +100,85 @@ pub fn pull_request(
         .pr(true)
         .call()
 }
+
+/// The JSON of an open issue, as returned by the GitHub API.
+pub fn issue_json(repo: &str, number: u64) -> serde_json::Value {
+    serde_json::json!({
+        "number": number,
+        "body": "",
+        "created_at": "2025-01-01T00:00:00Z",
+        "updated_at": "2025-01-01T00:00:00Z",
+        "title": format!("Issue #{number}"),
+        "html_url": format!("https://github.com/{repo}/issues/{number}"),
+        "user": { "login": "octocat", "id": 1, "type": "User" },
+        "labels": [],
+        "assignees": [],
+        "comments_url": format!("https://api.github.com/repos/{repo}/issues/{number}/comments"),
+        "state": "open",
+        "milestone": null,
+        "author_association": "NONE",
+    })
+}
+
+/// The JSON of a user, as returned by the GitHub REST API.
+pub fn user_json(login: &str, id: u64) -> serde_json::Value {
+    let url = format!("https://api.github.com/users/{login}");
+    serde_json::json!({
+        "login": login,
+        "id": id,
+        "node_id": format!("U_{id}"),
+        "avatar_url": format!("https://avatars.githubusercontent.com/u/{id}"),
+        "gravatar_id": "",
+        "url": url,
+        "html_url": format!("https://github.com/{login}"),
+        "followers_url": format!("{url}/followers"),
+        "following_url": format!("{url}/following{{/other_user}}"),
+        "gists_url": format!("{url}/gists{{/gist_id}}"),
+        "starred_url": format!("{url}/starred{{/owner}}{{/repo}}"),
+        "subscriptions_url": format!("{url}/subscriptions"),
+        "organizations_url": format!("{url}/orgs"),
+        "repos_url": format!("{url}/repos"),
+        "events_url": format!("{url}/events{{/privacy}}"),
+        "received_events_url": format!("{url}/received_events"),
+        "type": "User",
+        "site_admin": false,
+    })
+}
+
+/// An `issues` event of `action` on `issue`, sent by its author.
+pub fn issues_event(action: IssuesAction, issue: Issue) -> IssuesEvent {
+    IssuesEvent {
+        action,
+        sender: issue.user.clone(),
+        issue,
+        changes: None,
+        before: None,
+        after: None,
+        repository: Default::default(),
+    }
+}
+
+/// An event of `author` (the default test user otherwise) commenting `body` on `issue`.
+#[builder]
+pub fn comment_event(issue: Issue, author: Option<GitHubUser>, body: Option<&str>) -> Event {
+    let body = body.unwrap_or("").to_string();
+    Event::IssueComment(IssueCommentEvent {
+        action: IssueCommentAction::Created,
+        changes: None,
+        comment: Comment {
+            id: 1,
+            node_id: "IC_test".to_string(),
+            in_reply_to_id: None,
+            pull_request_review_id: None,
+            body,
+            html_url: format!("{}#issuecomment-1", issue.html_url),
+            user: author.unwrap_or(default_test_user()),
+            created_at: Some(Utc::now()),
+            updated_at: Some(Utc::now()),
+            pr_review_state: None,
+            author_association: octocrab::models::AuthorAssociation::None.into(),
+        },
+        issue,
+        repository: Default::default(),
+    })
+}
@@ -110,6 +110,18 @@ impl TestContext {
         &self.ctx
     }
 
+    pub(crate) fn handler_ctx_mut(&mut self) -> &mut Context {
+        &mut self.ctx
+    }
+
+    /// Makes the GitHub, Zulip and team services of the handler context
+    /// unreachable, for the handlers tested in dry-run mode.
+    pub(crate) fn use_unreachable_services(&mut self) {
+        self.ctx.github = unreachable_github_client();
+        self.ctx.zulip = unreachable_zulip_client();
+        self.ctx.team = TeamClient::new("http://127.0.0.1:1".to_string());
+    }
+
     pub(crate) fn db_client(&self) -> &PooledClient {
         &self.client
     }
@@ -141,6 +153,74 @@ impl TestContext {
     }
 }
 
+/// Returns a GitHub client pointing to an address where nothing listens,
+/// so that any request sent through it fails.
+pub(crate) fn unreachable_github_client() -> GithubClient {
+    GithubClient::new(
+        "gh-test-fake-token".into(),
+        "http://127.0.0.1:1".to_string(),
+        "http://127.0.0.1:1/graphql".to_string(),
+        "http://127.0.0.1:1".to_string(),
+    )
+}
+
+/// Returns a Zulip client pointing to an address where nothing listens.
+pub(crate) fn unreachable_zulip_client() -> ZulipClient {
+    ZulipClient::new(
+        "http://127.0.0.1:1".to_string(),
+        "test-bot@zulipchat.com".to_string(),
+    )
+}
+
+/// Returns a handler context where the GitHub, Zulip, team and database
+/// services are all unreachable, for the handlers tested in dry-run mode.
+pub(crate) fn offline_context() -> Context {
+    Context {
+        github: unreachable_github_client(),
+        zulip: unreachable_zulip_client(),
+        team: TeamClient::new("http://127.0.0.1:1".to_string()),
+        db: ClientPool::new("postgresql://127.0.0.1:1/unreachable".to_string()),
+        username: "triagebot-test".to_string(),
+        octocrab: Octocrab::builder().build().unwrap(),
+        workqueue_map: RepositoryWorkqueueMap::new(HashMap::new()),
+        gha_logs: Arc::new(RwLock::new(Default::default())),
+        gh_comments: Arc::new(RwLock::new(Default::default())),
+    }
+}
+
+/// Serves `router` on a local port, returning its URL.
+async fn serve_mock(router: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    url
+}
+
+/// Serves `router` as a fake GitHub API on a local port, returning a client
+/// sending its requests to it.
+pub(crate) async fn mock_github_client(router: axum::Router) -> GithubClient {
+    let url = serve_mock(router).await;
+    GithubClient::new(
+        "gh-test-fake-token".into(),
+        url.clone(),
+        format!("{url}/graphql"),
+        url,
+    )
+}
+
+/// Serves `router` as a fake team API on a local port, returning a client
+/// sending its requests to it.
+pub(crate) async fn mock_team_client(router: axum::Router) -> TeamClient {
+    TeamClient::new(serve_mock(router).await)
+}
+
+/// Serves `router` as a fake GitHub API on a local port, returning an octocrab
+/// client sending its requests to it.
+pub(crate) async fn mock_octocrab(router: axum::Router) -> Octocrab {
+    let url = serve_mock(router).await;
+    Octocrab::builder().base_uri(url).unwrap().build().unwrap()
+}
+
 pub(crate) async fn run_db_test<F, Fut, Ctx>(f: F)
 where
     F: FnOnce(TestContext) -> Fut,
@@ -0,0 +1,496 @@
+//! HTTP endpoints exposing the reviewer workqueues.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+
+use anyhow::Context as _;
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::{Html, IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use hyper::StatusCode;
+use tokio::sync::Mutex;
+
+use crate::github::{IssueRepository, UserId};
+use crate::handlers::pr_tracking::{ReviewerWorkqueue, get_all_assigned_prs};
+use crate::{errors::AppError, handlers::Context};
+
+#[derive(Debug, serde::Deserialize)]
+pub struct WorkqueueParams {
+    /// `json` to get the workqueue as JSON, HTML otherwise.
+    format: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct StaleWorkqueueParams {
+    /// Number of days without activity after which a pull request is stale,
+    /// 14 by default.
+    days: Option<i64>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct FullWorkqueueParams {
+    /// Only include the workqueue of this repository, e.g. `rust-lang/rust`.
+    repo: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct FullWorkqueue {
+    /// The assignments of each reviewer, keyed by their GitHub login (or their
+    /// GitHub ID if they are not in the team database). Reviewers without any
+    /// assignment are omitted.
+    reviewers: BTreeMap<String, Vec<Assignment>>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct UserWorkqueue {
+    user: String,
+    assignments: Vec<Assignment>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Assignment {
+    repository: String,
+    number: u64,
+    title: String,
+    url: String,
+    created_at: DateTime<Utc>,
+    age_days: i64,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct StaleWorkqueue {
+    user: String,
+    days: i64,
+    assignments: Vec<StaleAssignment>,
+    /// The assigned pull requests whose last activity couldn't be fetched, so
+    /// they can't be known to be stale or not.
+    unknown_activity: Vec<UnknownActivity>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct UnknownActivity {
+    repository: String,
+    number: u64,
+    url: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct StaleAssignment {
+    #[serde(flatten)]
+    assignment: Assignment,
+    last_activity: DateTime<Utc>,
+    inactive_days: i64,
+}
+
+/// Maximum number of last activities fetched from GitHub at once.
+const LAST_ACTIVITY_CONCURRENCY: usize = 5;
+
+/// How long the last activity of a pull request is cached, as fetching it for
+/// each assigned pull request is costly.
+const LAST_ACTIVITY_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// The last activity of the pull requests, keyed by repository and number,
+/// with when it was fetched.
+static LAST_ACTIVITY: LazyLock<Mutex<HashMap<(String, u64), (Instant, DateTime<Utc>)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Shows the pull requests a user is currently assigned to review, in all the
+/// tracked repositories.
+pub async fn user_workqueue(
+    Path(user): Path<String>,
+    Query(params): Query<WorkqueueParams>,
+    State(ctx): State<Arc<Context>>,
+) -> axum::response::Result<Response, AppError> {
+    let Some(user_id) = ctx
+        .team
+        .get_gh_id_from_username(&user)
+        .await
+        .context("unable to look up the user in the team database")?
+    else {
+        return Ok((
+            StatusCode::NOT_FOUND,
+            format!("user `{user}` is not part of the Rust Project team database"),
+        )
+            .into_response());
+    };
+
+    let now = Utc::now();
+    let workqueue = UserWorkqueue {
+        user,
+        assignments: get_all_assigned_prs(&ctx, user_id)
+            .await
+            .into_iter()
+            .map(|(repository, number, pr)| Assignment {
+                url: format!("https://github.com/{repository}/pull/{number}"),
+                repository,
+                number,
+                title: pr.title,
+                created_at: pr.created_at,
+                age_days: (now - pr.created_at).num_days(),
+            })
+            .collect(),
+    };
+
+    if params.format.as_deref() == Some("json") {
+        return Ok(Json(workqueue).into_response());
+    }
+    Ok(Html(render_workqueue(&workqueue)?).into_response())
+}
+
+/// Returns as JSON the pull requests assigned to a user without any activity for
+/// `?days=` days (14 by default), so that reviewers can triage their backlog.
+pub async fn stale_workqueue(
+    Path(user): Path<String>,
+    Query(params): Query<StaleWorkqueueParams>,
+    State(ctx): State<Arc<Context>>,
+) -> axum::response::Result<Response, AppError> {
+    let days = params.days.unwrap_or(14);
+    if days < 0 {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            format!("invalid number of days `{days}`"),
+        )
+            .into_response());
+    }
+    let Some(user_id) = ctx
+        .team
+        .get_gh_id_from_username(&user)
+        .await
+        .context("unable to look up the user in the team database")?
+    else {
+        return Ok((
+            StatusCode::NOT_FOUND,
+            format!("user `{user}` is not part of the Rust Project team database"),
+        )
+            .into_response());
+    };
+
+    let now = Utc::now();
+    let ctx = &ctx;
+    let assigned: Vec<_> = futures::stream::iter(get_all_assigned_prs(ctx, user_id).await)
+        .map(|(repository, number, pr)| async move {
+            let last_activity = last_activity(ctx, &repository, number).await;
+            (repository, number, pr, last_activity)
+        })
+        .buffer_unordered(LAST_ACTIVITY_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut assignments = Vec::new();
+    let mut unknown_activity = Vec::new();
+    for (repository, number, pr, last_activity) in assigned {
+        let url = format!("https://github.com/{repository}/pull/{number}");
+        match last_activity {
+            Ok(last_activity) => {
+                let assignment = Assignment {
+                    url,
+                    repository,
+                    number,
+                    title: pr.title,
+                    created_at: pr.created_at,
+                    age_days: (now - pr.created_at).num_days(),
+                };
+                assignments.push((assignment, last_activity));
+            }
+            Err(err) => {
+                tracing::warn!("unable to get the last activity of {repository}#{number}: {err:?}");
+                unknown_activity.push(UnknownActivity {
+                    repository,
+                    number,
+                    url,
+                });
+            }
+        }
+    }
+    unknown_activity.sort_by(|a, b| (&a.repository, a.number).cmp(&(&b.repository, b.number)));
+
+    Ok(Json(StaleWorkqueue {
+        user,
+        days,
+        assignments: stale_assignments(assignments.into_iter(), days, now),
+        unknown_activity,
+    })
+    .into_response())
+}
+
+/// Keeps the assignments without any activity for at least `days` days, the
+/// least recently active first.
+fn stale_assignments(
+    assignments: impl Iterator<Item = (Assignment, DateTime<Utc>)>,
+    days: i64,
+    now: DateTime<Utc>,
+) -> Vec<StaleAssignment> {
+    let mut stale: Vec<_> = assignments
+        .map(|(assignment, last_activity)| StaleAssignment {
+            assignment,
+            last_activity,
+            inactive_days: (now - last_activity).num_days(),
+        })
+        .filter(|a| a.inactive_days >= days)
+        .collect();
+    stale.sort_by_key(|a| a.last_activity);
+    stale
+}
+
+/// Returns when the pull request was last updated, cached for [`LAST_ACTIVITY_TTL`].
+async fn last_activity(
+    ctx: &Context,
+    repository: &str,
+    number: u64,
+) -> anyhow::Result<DateTime<Utc>> {
+    let key = (repository.to_string(), number);
+    if let Some((fetched_at, updated_at)) = LAST_ACTIVITY.lock().await.get(&key)
+        && fetched_at.elapsed() < LAST_ACTIVITY_TTL
+    {
+        return Ok(*updated_at);
+    }
+
+    let (organization, name) = repository
+        .split_once('/')
+        .with_context(|| format!("invalid repository `{repository}`"))?;
+    let pr = ctx
+        .github
+        .issue(
+            &IssueRepository {
+                organization: organization.to_string(),
+                repository: name.to_string(),
+            },
+            number,
+        )
+        .await
+        .context("unable to fetch the last activity of the pull request")?;
+
+    let mut cache = LAST_ACTIVITY.lock().await;
+    cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < LAST_ACTIVITY_TTL);
+    cache.insert(key, (Instant::now(), pr.updated_at));
+    Ok(pr.updated_at)
+}
+
+/// Returns the pull requests assigned to all the reviewers as JSON, e.g. for
+/// team dashboards, optionally only for the repository given with `?repo=`.
+pub async fn full_workqueue(
+    Query(params): Query<FullWorkqueueParams>,
+    State(ctx): State<Arc<Context>>,
+) -> axum::response::Result<Response, AppError> {
+    let logins: HashMap<UserId, String> = ctx
+        .team
+        .people()
+        .await
+        .context("unable to load the team database")?
+        .people
+        .into_iter()
+        .map(|(login, person)| (person.github_id, login))
+        .collect();
+
+    let mut workqueues = vec![];
+    for (repo, workqueue) in ctx.workqueue_map.tracked_repositories() {
+        let full_name = repo.full_name();
+        if params
+            .repo
+            .as_deref()
+            .is_some_and(|r| !r.eq_ignore_ascii_case(&full_name))
+        {
+            continue;
+        }
+        workqueues.push((full_name, workqueue.read().await));
+    }
+
+    let workqueues = workqueues.iter().map(|(repo, w)| (repo.as_str(), &**w));
+    Ok(Json(collect_full_workqueue(workqueues, &logins, Utc::now())).into_response())
+}
+
+fn collect_full_workqueue<'a>(
+    workqueues: impl Iterator<Item = (&'a str, &'a ReviewerWorkqueue)>,
+    logins: &HashMap<UserId, String>,
+    now: DateTime<Utc>,
+) -> FullWorkqueue {
+    let mut reviewers: BTreeMap<String, Vec<Assignment>> = BTreeMap::new();
+    for (repository, workqueue) in workqueues {
+        for (user_id, prs) in workqueue.assignments() {
+            if prs.is_empty() {
+                continue;
+            }
+            let reviewer = logins
+                .get(&user_id)
+                .cloned()
+                .unwrap_or_else(|| user_id.to_string());
+            reviewers
+                .entry(reviewer)
+                .or_default()
+                .extend(prs.iter().map(|(&number, pr)| Assignment {
+                    repository: repository.to_string(),
+                    number,
+                    title: pr.title.clone(),
+                    url: format!("https://github.com/{repository}/pull/{number}"),
+                    created_at: pr.created_at,
+                    age_days: (now - pr.created_at).num_days(),
+                }));
+        }
+    }
+    for assignments in reviewers.values_mut() {
+        assignments.sort_by(|a, b| (&a.repository, a.number).cmp(&(&b.repository, b.number)));
+    }
+    FullWorkqueue { reviewers }
+}
+
+fn render_workqueue(workqueue: &UserWorkqueue) -> anyhow::Result<String> {
+    let mut user = String::new();
+    pulldown_cmark_escape::escape_html(&mut user, &workqueue.user)?;
+
+    let mut html = format!(
+        r#"<!DOCTYPE html>
+<title>Review workqueue of {user}</title>
+<style>
+  :root {{ color-scheme: light dark; font-family: system-ui; margin: 2rem auto; max-width: 80ch; }}
+  td {{ padding: 0 0.5rem; }}
+</style>
+<h1>Review workqueue of {user}</h1>
+"#
+    );
+
+    if workqueue.assignments.is_empty() {
+        html.push_str("<p>No pull requests are waiting for a review from this user.</p>\n");
+        return Ok(html);
+    }
+
+    html.push_str("<table>\n<tr><th>Pull request</th><th>Title</th><th>Age</th></tr>\n");
+    for assignment in &workqueue.assignments {
+        let mut title = String::new();
+        pulldown_cmark_escape::escape_html(&mut title, &assignment.title)?;
+        writeln!(
+            html,
+            r#"<tr><td><a href="{}">{}#{}</a></td><td>{title}</td><td>{} days</td></tr>"#,
+            assignment.url, assignment.repository, assignment.number, assignment.age_days,
+        )?;
+    }
+    html.push_str("</table>\n");
+
+    Ok(html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_workqueue() {
+        let html = render_workqueue(&UserWorkqueue {
+            user: "ghost".to_string(),
+            assignments: vec![],
+        })
+        .unwrap();
+        assert!(html.contains("<h1>Review workqueue of ghost</h1>"));
+        assert!(html.contains("No pull requests are waiting for a review from this user."));
+        assert!(!html.contains("<table>"));
+    }
+
+    #[test]
+    fn workqueue_with_assignments() {
+        let html = render_workqueue(&UserWorkqueue {
+            user: "ghost".to_string(),
+            assignments: vec![Assignment {
+                repository: "rust-lang/rust".to_string(),
+                number: 123,
+                title: "Fix <script> handling".to_string(),
+                url: "https://github.com/rust-lang/rust/pull/123".to_string(),
+                created_at: Utc::now(),
+                age_days: 3,
+            }],
+        })
+        .unwrap();
+        assert!(html.contains(
+            r#"<tr><td><a href="https://github.com/rust-lang/rust/pull/123">rust-lang/rust#123</a></td><td>Fix &lt;script&gt; handling</td><td>3 days</td></tr>"#
+        ));
+    }
+
+    #[test]
+    fn full_workqueue_json() {
+        let rust =
+            ReviewerWorkqueue::from_pr_numbers(HashMap::from([(1, vec![11, 10]), (3, vec![])]));
+        let cargo = ReviewerWorkqueue::from_pr_numbers(HashMap::from([(1, vec![5]), (2, vec![6])]));
+        let logins = HashMap::from([(1, "ghost".to_string())]);
+        let now = DateTime::UNIX_EPOCH + chrono::Duration::days(2);
+
+        let workqueue = collect_full_workqueue(
+            [("rust-lang/rust", &rust), ("rust-lang/cargo", &cargo)].into_iter(),
+            &logins,
+            now,
+        );
+        let assignment = |repository: &str, number: u64| {
+            serde_json::json!({
+                "repository": repository,
+                "number": number,
+                "title": format!("PR {number}"),
+                "url": format!("https://github.com/{repository}/pull/{number}"),
+                "created_at": "1970-01-01T00:00:00Z",
+                "age_days": 2,
+            })
+        };
+        assert_eq!(
+            serde_json::to_value(workqueue).unwrap(),
+            serde_json::json!({
+                "reviewers": {
+                    "2": [assignment("rust-lang/cargo", 6)],
+                    "ghost": [
+                        assignment("rust-lang/cargo", 5),
+                        assignment("rust-lang/rust", 10),
+                        assignment("rust-lang/rust", 11),
+                    ],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn stale_assignments_filtering() {
+        let now = DateTime::UNIX_EPOCH + chrono::Duration::days(100);
+        let assignment = |number: u64| Assignment {
+            repository: "rust-lang/rust".to_string(),
+            number,
+            title: format!("PR {number}"),
+            url: format!("https://github.com/rust-lang/rust/pull/{number}"),
+            created_at: DateTime::UNIX_EPOCH,
+            age_days: 100,
+        };
+        let assignments = || {
+            [
+                (assignment(1), now - chrono::Duration::days(3)),
+                (assignment(2), now - chrono::Duration::days(30)),
+                (assignment(3), now - chrono::Duration::days(14)),
+                (assignment(4), now - chrono::Duration::hours(14 * 24 - 1)),
+            ]
+            .into_iter()
+        };
+
+        let stale = stale_assignments(assignments(), 14, now);
+        assert_eq!(
+            stale
+                .iter()
+                .map(|a| (a.assignment.number, a.inactive_days))
+                .collect::<Vec<_>>(),
+            vec![(2, 30), (3, 14)]
+        );
+        assert_eq!(stale_assignments(assignments(), 0, now).len(), 4);
+        assert!(stale_assignments(assignments(), 31, now).is_empty());
+
+        assert_eq!(
+            serde_json::to_value(&stale[1]).unwrap(),
+            serde_json::json!({
+                "repository": "rust-lang/rust",
+                "number": 3,
+                "title": "PR 3",
+                "url": "https://github.com/rust-lang/rust/pull/3",
+                "created_at": "1970-01-01T00:00:00Z",
+                "age_days": 100,
+                "last_activity": "1970-03-28T00:00:00Z",
+                "inactive_days": 14,
+            })
+        );
+    }
+}
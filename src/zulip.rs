@@ -1,6 +1,6 @@
 pub mod api;
 pub mod client;
-mod commands;
+pub(crate) mod commands;
 
 use crate::db::review_prefs::{
     ReviewPreferences, RotationMode, get_review_prefs, get_review_prefs_batch,
@@ -17,7 +17,7 @@ use crate::handlers::pr_tracking::{ReviewerWorkqueue, get_assigned_prs};
 use crate::handlers::project_goals::{self, ping_project_goals_owners};
 use crate::interactions::ErrorComment;
 use crate::utils::pluralize;
-use crate::zulip::api::{MessageApiResponse, Recipient};
+use crate::zulip::api::{AddReactionApiRequest, MessageApiResponse, Recipient};
 use crate::zulip::client::ZulipClient;
 use crate::zulip::commands::{
     BackportChannelArgs, BackportVerbArgs, ChatCommand, IssuePrio, LookupCmd, PingGoalsArgs,
@@ -1304,23 +1304,7 @@ async fn workqueue_commands(
         }
         WorkqueueCmd::SetPrLimit { limit, repo } => {
             let repo = normalize_repo(&ctx, repo).await?;
-            let max_assigned_prs = match limit {
-                WorkqueueLimit::Unlimited => None,
-                WorkqueueLimit::Limit(limit) => Some(*limit),
-            };
-            upsert_repo_review_prefs(&db_client, user.into(), &repo, max_assigned_prs)
-                .await
-                .context("Error occurred while setting review preferences.")?;
-            tracing::info!(
-                "Setting max assigned PRs of `{gh_username}` in `{repo}` to {max_assigned_prs:?}"
-            );
-            format!(
-                "Review capacity in `{repo}` set to {}",
-                match max_assigned_prs {
-                    Some(v) => v.to_string(),
-                    None => "unlimited".to_string(),
-                }
-            )
+            set_review_capacity(&db_client, user, &repo, limit).await?
         }
         WorkqueueCmd::SetRotationMode { rotation_mode } => {
             let rotation_mode = rotation_mode.0;
@@ -1370,6 +1354,34 @@ async fn workqueue_commands(
     Ok(Some(response))
 }
 
+/// Sets the review capacity of the user in the repository, which is used when
+/// selecting reviewers, and returns the confirmation message.
+pub(crate) async fn set_review_capacity(
+    db_client: &tokio_postgres::Client,
+    user: DbUser,
+    repo: &str,
+    limit: &WorkqueueLimit,
+) -> anyhow::Result<String> {
+    let max_assigned_prs = match limit {
+        WorkqueueLimit::Unlimited => None,
+        WorkqueueLimit::Limit(limit) => Some(*limit),
+    };
+    tracing::info!(
+        "Setting max assigned PRs of `{}` in `{repo}` to {max_assigned_prs:?}",
+        user.login
+    );
+    upsert_repo_review_prefs(db_client, user, repo, max_assigned_prs)
+        .await
+        .context("Error occurred while setting review preferences.")?;
+    Ok(format!(
+        "Review capacity in `{repo}` set to {}",
+        match max_assigned_prs {
+            Some(v) => v.to_string(),
+            None => "unlimited".to_string(),
+        }
+    ))
+}
+
 /// Checks if the given repository exists in the team database.
 /// Also adds `rust-lang` prefix to the repo name if it does not contain a slash.
 async fn normalize_repo(ctx: &Context, repo_name: &str) -> anyhow::Result<String> {
@@ -1612,7 +1624,11 @@ impl<'a> MessageApiRequest<'_> {
     }
 }
 
-#[derive(Debug)]
+/// The prefix of the resolved topics, see
+/// https://zulip.com/help/resolve-a-topic
+pub const RESOLVED_TOPIC_PREFIX: &str = "✔ ";
+
+#[derive(Debug, PartialEq, Eq)]
 pub struct UpdateMessageApiRequest<'a> {
     pub message_id: u64,
     pub topic: Option<&'a str>,
@@ -1620,7 +1636,17 @@ pub struct UpdateMessageApiRequest<'a> {
     pub content: Option<&'a str>,
 }
 
-impl UpdateMessageApiRequest<'_> {
+impl<'a> UpdateMessageApiRequest<'a> {
+    /// Moves the whole topic of the message to `topic`.
+    pub fn rename_topic(message_id: u64, topic: &'a str) -> Self {
+        UpdateMessageApiRequest {
+            message_id,
+            topic: Some(topic),
+            propagate_mode: Some("change_all"),
+            content: None,
+        }
+    }
+
     pub async fn send(&self, client: &ZulipClient) -> anyhow::Result<()> {
         client
             .update_message(
@@ -1638,18 +1664,6 @@ struct ResponseNotRequired {
     response_not_required: bool,
 }
 
-#[derive(serde::Serialize, Debug, Copy, Clone)]
-struct AddReaction<'a> {
-    message_id: u64,
-    emoji_name: &'a str,
-}
-
-impl AddReaction<'_> {
-    pub async fn send(self, client: &ZulipClient) -> anyhow::Result<()> {
-        client.add_reaction(self.message_id, self.emoji_name).await
-    }
-}
-
 struct WaitingMessage<'a> {
     primary: &'a str,
     emoji: &'a [&'a str],
@@ -1703,7 +1717,7 @@ async fn post_waiter(
     .await?;
 
     for reaction in waiting.emoji {
-        AddReaction {
+        AddReactionApiRequest {
             message_id: posted.message_id,
             emoji_name: reaction,
         }
@@ -35,6 +35,21 @@ impl ZulipUser {
     }
 }
 
+/// A collection of Zulip user groups, as returned from '/user_groups'
+#[derive(serde::Deserialize)]
+pub(crate) struct ZulipUserGroups {
+    pub(crate) user_groups: Vec<ZulipUserGroup>,
+}
+
+/// A single Zulip user group
+#[derive(Clone, serde::Deserialize, Debug, PartialEq, Eq)]
+pub(crate) struct ZulipUserGroup {
+    pub(crate) id: u64,
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) deactivated: bool,
+}
+
 #[derive(Debug, serde::Deserialize)]
 pub(crate) struct MessageApiResponse {
     #[serde(rename = "id")]
@@ -104,6 +119,21 @@ impl Recipient<'_> {
     }
 }
 
+/// Adds an emoji reaction to a message.
+/// Documentation: https://zulip.com/api/add-reaction
+#[derive(serde::Serialize, Debug, Copy, Clone)]
+pub(crate) struct AddReactionApiRequest<'a> {
+    pub(crate) message_id: u64,
+    /// The name of the emoji, e.g. `eyes`
+    pub(crate) emoji_name: &'a str,
+}
+
+impl AddReactionApiRequest<'_> {
+    pub(crate) async fn send(self, client: &ZulipClient) -> anyhow::Result<()> {
+        client.add_reaction(self.message_id, self.emoji_name).await
+    }
+}
+
 #[derive(serde::Deserialize)]
 pub struct ZulipChannel {
     pub stream: ZulipChannelData,
@@ -141,3 +171,18 @@ fn test_encode() {
     );
     check_encode("áé…", ".C3.A1.C3.A9.E2.80.A6");
 }
+
+#[test]
+fn test_add_reaction_payload() {
+    let request = AddReactionApiRequest {
+        message_id: 42,
+        emoji_name: "eyes",
+    };
+    assert_eq!(
+        serde_json::to_value(request).unwrap(),
+        serde_json::json!({
+            "message_id": 42,
+            "emoji_name": "eyes",
+        })
+    );
+}
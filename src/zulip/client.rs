@@ -3,7 +3,8 @@
 
 use crate::zulip::Recipient;
 use crate::zulip::api::{
-    MessageApiResponse, ZulipChannel, ZulipChannelData, ZulipUser, ZulipUsers,
+    AddReactionApiRequest, MessageApiResponse, ZulipChannel, ZulipChannelData, ZulipUser,
+    ZulipUserGroup, ZulipUserGroups, ZulipUsers,
 };
 use anyhow::Context;
 use reqwest::{Client, Method, RequestBuilder, Response};
@@ -56,6 +57,15 @@ impl ZulipClient {
             .map(|users| users.members)
     }
 
+    /// Retrieve the user groups of the organization
+    /// https://zulip.com/api/get-user-groups
+    pub(crate) async fn get_zulip_user_groups(&self) -> anyhow::Result<Vec<ZulipUserGroup>> {
+        let resp = self.make_request(Method::GET, "user_groups").send().await?;
+        deserialize_response::<ZulipUserGroups>(resp)
+            .await
+            .map(|groups| groups.user_groups)
+    }
+
     pub(crate) async fn send_message<'a>(
         &self,
         recipient: Recipient<'a>,
@@ -71,6 +81,14 @@ impl ZulipClient {
             content: &'a str,
         }
 
+        let to = match recipient {
+            Recipient::Stream { id, topic } => format!("stream {id} (topic {topic:?})"),
+            Recipient::Private { email, .. } => email.to_string(),
+        };
+        if crate::dry_run::skip(format_args!("sent Zulip message to {to}: {content:?}")) {
+            return Ok(MessageApiResponse { message_id: 0 });
+        }
+
         let response = self
             .make_request(Method::POST, "messages")
             .form(&SerializedApi {
@@ -112,6 +130,12 @@ impl ZulipClient {
             content: Option<&'a str>,
         }
 
+        if crate::dry_run::skip(format_args!(
+            "updated Zulip message {message_id} (topic: {topic:?}, content: {content:?})"
+        )) {
+            return Ok(());
+        }
+
         let resp = self
             .make_request(Method::PATCH, &format!("messages/{message_id}"))
             .form(&SerializedApi {
@@ -142,15 +166,15 @@ impl ZulipClient {
         message_id: u64,
         emoji_name: &str,
     ) -> anyhow::Result<()> {
-        #[derive(serde::Serialize)]
-        struct AddReaction<'a> {
-            message_id: u64,
-            emoji_name: &'a str,
+        if crate::dry_run::skip(format_args!(
+            "reacted with {emoji_name:?} to Zulip message {message_id}"
+        )) {
+            return Ok(());
         }
 
         let resp = self
             .make_request(Method::POST, &format!("messages/{message_id}/reactions"))
-            .form(&AddReaction {
+            .form(&AddReactionApiRequest {
                 message_id,
                 emoji_name,
             })
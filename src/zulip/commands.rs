@@ -78,6 +78,7 @@ pub enum WorkqueueCmd {
         repo: String,
     },
     /// Set the maximum capacity limit of your workqueue for a specific repository.
+    #[command(visible_alias = "capacity")]
     SetPrLimit {
         /// Workqueue capacity
         limit: WorkqueueLimit,
@@ -108,6 +109,10 @@ pub enum WorkqueueLimit {
     Limit(u32),
 }
 
+/// The highest workqueue capacity that can be set, larger values are most
+/// likely typos.
+const MAX_WORKQUEUE_LIMIT: u32 = 100;
+
 impl FromStr for WorkqueueLimit {
     type Err = String;
 
@@ -115,9 +120,14 @@ impl FromStr for WorkqueueLimit {
         match s {
             "unlimited" => Ok(Self::Unlimited),
             v => {
-                v.parse::<u32>()
-                    .map_err(|_| "Wrong parameter format. Must be a positive integer or `unlimited` to unset the limit.".to_string())
-                    .map(WorkqueueLimit::Limit)
+                let limit = v.parse::<u32>()
+                    .map_err(|_| "Wrong parameter format. Must be a positive integer or `unlimited` to unset the limit.".to_string())?;
+                if limit > MAX_WORKQUEUE_LIMIT {
+                    return Err(format!(
+                        "The capacity must be at most {MAX_WORKQUEUE_LIMIT}, or `unlimited` to unset the limit."
+                    ));
+                }
+                Ok(WorkqueueLimit::Limit(limit))
             }
         }
     }
@@ -364,6 +374,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn work_capacity_command() {
+        assert_eq!(
+            parse_chat(&["work", "capacity", "3"]),
+            ChatCommand::Work(WorkqueueCmd::SetPrLimit {
+                limit: WorkqueueLimit::Limit(3),
+                repo: "rust-lang/rust".to_string(),
+            })
+        );
+
+        assert!(parse_cli::<ChatCommand, _>(["work", "capacity", "100"].into_iter()).is_ok());
+        assert!(parse_cli::<ChatCommand, _>(["work", "capacity", "101"].into_iter()).is_err());
+        assert!(parse_cli::<ChatCommand, _>(["work", "capacity", "-1"].into_iter()).is_err());
+    }
+
     #[test]
     fn work_set_team_rotation_mode_command() {
         assert_eq!(